@@ -0,0 +1,98 @@
+//! Compara el costo de evaluar una clausula WHERE interpretandola en cada fila, separando la linea completa, con el de evaluarla ya compilada, separando solo los campos que usa.
+//! Genera una tabla de prueba con la cantidad de filas indicada (por defecto 2.000.000):
+//!
+//! ```text
+//! cargo bench --bench filtro_where [-- filas]
+//! ```
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::time::{Duration, Instant};
+use tp1::analizador::analizador_implementacion::{cumple_c_w, parsear_expresion};
+use tp1::analizador::predicado::Predicado;
+use tp1::ejecutor::ejecutor_implementacion::adaptar_where;
+use tp1::Database;
+
+const CLAUSULA: &str = "ciudad = 'rosario' AND saldo > 5000";
+
+fn main() {
+    let filas: usize = std::env::args()
+        .skip(1)
+        .find_map(|argumento| argumento.parse().ok())
+        .unwrap_or(2_000_000);
+    let directorio = std::env::temp_dir().join(format!("sql_rustico_bench_{}", std::process::id()));
+    fs::create_dir_all(&directorio).unwrap();
+    let ruta = directorio.join("cuentas.csv");
+    generar_tabla(&ruta.to_string_lossy(), filas);
+    println!("{} filas, WHERE {}", filas, CLAUSULA);
+
+    let columnas = ["id", "nombre", "ciudad", "saldo", "alta"];
+    let indice_columnas: HashMap<String, usize> = columnas
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.to_string(), i))
+        .collect();
+    let expresion = parsear_expresion(adaptar_where(CLAUSULA)).unwrap();
+
+    let (interpretada, cantidad) = medir(&ruta.to_string_lossy(), |linea| {
+        cumple_c_w(linea, &expresion, &indice_columnas).unwrap()
+    });
+    println!(
+        "interpretada:  {:>10.1} ms ({} filas cumplen)",
+        interpretada.as_secs_f64() * 1000.0,
+        cantidad
+    );
+    let predicado = Predicado::compilar(&expresion, &indice_columnas);
+    let (compilada, cantidad_compilada) = medir(&ruta.to_string_lossy(), |linea| {
+        predicado.cumple_linea(linea)
+    });
+    assert_eq!(cantidad, cantidad_compilada);
+    println!(
+        "compilada:     {:>10.1} ms ({:.1}x)",
+        compilada.as_secs_f64() * 1000.0,
+        interpretada.as_secs_f64() / compilada.as_secs_f64()
+    );
+
+    let db = Database::open(&directorio.to_string_lossy()).unwrap();
+    let inicio = Instant::now();
+    let resultado = db
+        .execute(&format!("SELECT COUNT(*) FROM cuentas WHERE {}", CLAUSULA))
+        .unwrap();
+    println!(
+        "SELECT COUNT(*): {:>8.1} ms ({:?})",
+        inicio.elapsed().as_secs_f64() * 1000.0,
+        resultado.filas_como_texto()[0][0]
+    );
+    let _ = fs::remove_dir_all(&directorio);
+}
+
+fn generar_tabla(ruta: &str, filas: usize) {
+    let ciudades = ["rosario", "cordoba", "mendoza", "salta", "neuquen"];
+    let mut archivo = BufWriter::new(File::create(ruta).unwrap());
+    writeln!(archivo, "id,nombre,ciudad,saldo,alta").unwrap();
+    for id in 0..filas {
+        writeln!(
+            archivo,
+            "{},cliente{},{},{},2024-{:02}-{:02}",
+            id,
+            id,
+            ciudades[id % ciudades.len()],
+            (id * 7919) % 10000,
+            id % 12 + 1,
+            id % 28 + 1
+        )
+        .unwrap();
+    }
+}
+
+/// Recorre la tabla evaluando la condicion en cada fila, y devuelve el tiempo que tardo y cuantas filas la cumplen.
+fn medir<F: Fn(&str) -> bool>(ruta: &str, cumple: F) -> (Duration, usize) {
+    let inicio = Instant::now();
+    let mut cantidad = 0;
+    for linea in BufReader::new(File::open(ruta).unwrap()).lines().skip(1) {
+        if cumple(&linea.unwrap()) {
+            cantidad += 1;
+        }
+    }
+    (inicio.elapsed(), cantidad)
+}
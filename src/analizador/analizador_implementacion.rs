@@ -1,22 +1,18 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
 use crate::analizador::condicional::Condicional;
 use crate::analizador::expresion::Expresion;
-use crate::errores::errores_implementacion::{imprimir_error, Errores};
+use crate::comandos::parametros::decodificar_parametro;
+use crate::errores::errores_implementacion::{Errores, TipoError};
 
 /// Parsea la cláusula WHERE y devuelve una expresión que la representa.
 pub fn parsear_expresion(clausula_where: Vec<String>) -> Result<Expresion, Errores> {
     if clausula_where.is_empty() {
         return Ok(Expresion::Unknown);
     }
-    let adaptada: Vec<Expresion> = match adaptar_where(clausula_where) {
-        Err(e) => return Err(e),
-        Ok(adap) => adap,
-    };
-    let a = match obtener_subexpresiones(adaptada) {
-        Ok(c_w) => c_w,
-        _ => return Err(Errores::InvalidSyntax),
-    };
+    let adaptada: Vec<Expresion> = adaptar_where(clausula_where)?;
+    let a = obtener_subexpresiones(adaptada)?;
     Ok(a)
 }
 
@@ -49,11 +45,11 @@ fn adaptar_where(clausula_where: Vec<String>) -> Result<Vec<Expresion>, Errores>
 /// Parsea una condicional y devuelve un enum que la representa.
 fn parsear_condicional(condicional: Vec<&str>) -> Result<Condicional, Errores> {
     if condicional.len() < 3 {
-        imprimir_error(
-            Errores::InvalidSyntax,
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
             "Error en la cláusula WHERE".to_string(),
-        );
-        return Err(Errores::InvalidSyntax);
+        )
+        .en_token(condicional.last().copied().unwrap_or_default()));
     }
 
     let miembro1 = condicional[0].to_string();
@@ -70,13 +66,11 @@ fn parsear_condicional(condicional: Vec<&str>) -> Result<Condicional, Errores> {
         "<" => Ok(Condicional::Menor { miembro1, miembro2 }),
         "<=" => Ok(Condicional::MenorIgual { miembro1, miembro2 }),
         "=" => Ok(Condicional::Igual { miembro1, miembro2 }),
-        _ => {
-            imprimir_error(
-                Errores::InvalidSyntax,
-                "Operador desconocido en la cláusula WHERE".to_string(),
-            );
-            Err(Errores::InvalidSyntax)
-        }
+        _ => Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Operador desconocido en la cláusula WHERE".to_string(),
+        )
+        .en_token(operador)),
     }
 }
 
@@ -92,29 +86,26 @@ fn obtener_subexpresiones(exp: Vec<Expresion>) -> Result<Expresion, Errores> {
             }
             Expresion::Dpar => {
                 if let Some(mut tope) = stack.pop() {
-                    let agrupada = match agrupar_expresion(&mut auxiliar) {
-                        Ok(a) => a,
-                        Err(e) => return Err(e),
-                    };
+                    let agrupada = agrupar_expresion(&mut auxiliar)?;
                     tope.push(agrupada);
                     auxiliar = tope;
                 } else {
-                    imprimir_error(
-                        Errores::InvalidSyntax,
+                    return Err(Errores::nuevo(
+                        TipoError::InvalidSyntax,
                         "Error en paréntesis cláusula WHERE".to_string(),
-                    );
-                    return Err(Errores::InvalidSyntax);
+                    )
+                    .en_token(")"));
                 }
             }
             _ => auxiliar.push(expresion),
         }
     }
     if !stack.is_empty() {
-        imprimir_error(
-            Errores::InvalidSyntax,
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
             "Error en sintaxis clausula where".to_string(),
-        );
-        return Err(Errores::InvalidSyntax);
+        )
+        .en_token("("));
     }
     agrupar_expresion(&mut auxiliar)
 }
@@ -130,11 +121,11 @@ fn agrupar_expresion(exp: &mut Vec<Expresion>) -> Result<Expresion, Errores> {
                     let derecha = Box::new(exp.remove(indice + 1));
                     exp[indice] = Expresion::Not { derecha };
                 } else {
-                    imprimir_error(
-                        Errores::InvalidSyntax,
+                    return Err(Errores::nuevo(
+                        TipoError::InvalidSyntax,
                         "Sintaxis incorrecta clausula where".to_string(),
-                    );
-                    return Err(Errores::InvalidSyntax);
+                    )
+                    .en_token("NOT"));
                 }
             }
         }
@@ -148,12 +139,14 @@ fn agrupar_expresion(exp: &mut Vec<Expresion>) -> Result<Expresion, Errores> {
                     let izquierda = Box::new(exp.remove(indice - 1));
                     let derecha = Box::new(exp.remove(indice));
                     exp[indice - 1] = Expresion::And { izquierda, derecha };
+                    // La expresion agrupada quedo antes del indice: el siguiente operador ocupa ahora su lugar.
+                    continue;
                 } else {
-                    imprimir_error(
-                        Errores::InvalidSyntax,
+                    return Err(Errores::nuevo(
+                        TipoError::InvalidSyntax,
                         "Sintaxis incorrecta clausula where".to_string(),
-                    );
-                    return Err(Errores::InvalidSyntax);
+                    )
+                    .en_token("AND"));
                 }
             }
         }
@@ -167,12 +160,14 @@ fn agrupar_expresion(exp: &mut Vec<Expresion>) -> Result<Expresion, Errores> {
                     let izquierda = Box::new(exp.remove(indice - 1));
                     let derecha = Box::new(exp.remove(indice));
                     exp[indice - 1] = Expresion::Or { izquierda, derecha };
+                    // La expresion agrupada quedo antes del indice: el siguiente operador ocupa ahora su lugar.
+                    continue;
                 } else {
-                    imprimir_error(
-                        Errores::InvalidSyntax,
+                    return Err(Errores::nuevo(
+                        TipoError::InvalidSyntax,
                         "Sintaxis incorrecta clausula where".to_string(),
-                    );
-                    return Err(Errores::InvalidSyntax);
+                    )
+                    .en_token("OR"));
                 }
             }
         }
@@ -182,11 +177,10 @@ fn agrupar_expresion(exp: &mut Vec<Expresion>) -> Result<Expresion, Errores> {
         let elemento = exp.remove(0);
         Ok(elemento)
     } else {
-        imprimir_error(
-            Errores::InvalidSyntax,
+        Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
             "Sintaxis incorrecta clausula where".to_string(),
-        );
-        Err(Errores::InvalidSyntax)
+        ))
     }
 }
 
@@ -207,7 +201,7 @@ pub fn cumple_c_w(
 /// Evalúa una expresión.
 fn evaluar_expresion(
     expresion: &Expresion,
-    linea: &Vec<String>,
+    linea: &[String],
     indice_columnas: &HashMap<String, usize>,
 ) -> bool {
     match expresion {
@@ -237,24 +231,25 @@ fn evaluar_condicional(
 
     match condicional {
         Mayor { miembro1, miembro2 } => {
-            comparar_valores(miembro1, miembro2, linea, indice_columnas, |a, b| a > b)
+            comparar_valores(miembro1, miembro2, linea, indice_columnas, |o| o.is_gt())
         }
         MayorIgual { miembro1, miembro2 } => {
-            comparar_valores(miembro1, miembro2, linea, indice_columnas, |a, b| a >= b)
+            comparar_valores(miembro1, miembro2, linea, indice_columnas, |o| o.is_ge())
         }
         Menor { miembro1, miembro2 } => {
-            comparar_valores(miembro1, miembro2, linea, indice_columnas, |a, b| a < b)
+            comparar_valores(miembro1, miembro2, linea, indice_columnas, |o| o.is_lt())
         }
         MenorIgual { miembro1, miembro2 } => {
-            comparar_valores(miembro1, miembro2, linea, indice_columnas, |a, b| a <= b)
+            comparar_valores(miembro1, miembro2, linea, indice_columnas, |o| o.is_le())
         }
         Igual { miembro1, miembro2 } => {
-            comparar_valores(miembro1, miembro2, linea, indice_columnas, |a, b| a == b)
+            comparar_valores(miembro1, miembro2, linea, indice_columnas, |o| o.is_eq())
         }
     }
 }
 
 /// Compara dos valores.
+/// Si ambos son numeros se comparan numericamente, de lo contrario se comparan como texto.
 fn comparar_valores<F>(
     miembro1: &str,
     miembro2: &str,
@@ -263,8 +258,9 @@ fn comparar_valores<F>(
     comparador: F,
 ) -> bool
 where
-    F: Fn(&str, &str) -> bool,
+    F: Fn(Ordering) -> bool,
 {
+    let comparador = |a: &str, b: &str| comparador(comparar_texto_o_numero(a, b));
     if indice_columnas.contains_key(miembro1) && indice_columnas.contains_key(miembro2) {
         let valor1 = obtener_valor(miembro1, linea, indice_columnas);
         let valor2 = obtener_valor(miembro2, linea, indice_columnas);
@@ -274,37 +270,39 @@ where
         }
     } else if !indice_columnas.contains_key(miembro1) && indice_columnas.contains_key(miembro2) {
         let valor2 = obtener_valor(miembro2, linea, indice_columnas);
-        if es_numero(miembro1) {
-            match (miembro1, valor2) {
-                (miembro1, Some(v2)) => return comparador(miembro1, v2.as_str()),
-                _ => return false,
-            }
-        } else if miembro1.starts_with('\'') && miembro1.ends_with('\'') {
-            let valor1 = miembro1.trim_start_matches('\'').trim_end_matches('\'');
-            match (valor1, valor2) {
-                (valor1, Some(valor2)) => return comparador(valor1, valor2.as_str()),
-                _ => return false,
-            }
+        match (valor_literal(miembro1), valor2) {
+            (Some(valor1), Some(valor2)) => return comparador(&valor1, &valor2),
+            _ => return false,
         }
-        return false;
     } else if indice_columnas.contains_key(miembro1) && !indice_columnas.contains_key(miembro2) {
         let valor1 = obtener_valor(miembro1, linea, indice_columnas);
-        if es_numero(miembro2) {
-            match (valor1, miembro2) {
-                (Some(v1), miembro2) => return comparador(v1.as_str(), miembro2),
-                _ => return false,
-            }
-        } else if miembro2.starts_with('\'') && miembro2.ends_with('\'') {
-            let valor2 = miembro2.trim_start_matches('\'').trim_end_matches('\'');
-            match (valor1, valor2) {
-                (Some(v1), valor2) => return comparador(v1.as_str(), valor2),
-                _ => return false,
-            }
+        match (valor1, valor_literal(miembro2)) {
+            (Some(valor1), Some(valor2)) => return comparador(&valor1, &valor2),
+            _ => return false,
         }
     }
     false
 }
 
+/// Devuelve el valor de un miembro que no es una columna: un numero, un texto entre comillas o el valor de un parametro.
+pub fn valor_literal(miembro: &str) -> Option<String> {
+    if let Some(valor) = decodificar_parametro(miembro) {
+        return Some(valor);
+    }
+    if es_numero(miembro) {
+        return Some(miembro.to_string());
+    }
+    if miembro.starts_with('\'') && miembro.ends_with('\'') {
+        return Some(
+            miembro
+                .trim_start_matches('\'')
+                .trim_end_matches('\'')
+                .to_string(),
+        );
+    }
+    None
+}
+
 /// Obtiene el valor de la columna en la linea actual.
 fn obtener_valor(
     miembro: &str,
@@ -319,7 +317,62 @@ fn obtener_valor(
     None
 }
 
-/// Verifica si una cadena es un número.
-fn es_numero(cadena: &str) -> bool {
-    cadena.chars().all(|c| c.is_ascii_digit())
+/// Compara dos valores numericamente si ambos son numeros, o como texto si no.
+pub fn comparar_texto_o_numero(a: &str, b: &str) -> Ordering {
+    if es_numero(a) && es_numero(b) {
+        if let (Ok(x), Ok(y)) = (a.parse::<f64>(), b.parse::<f64>()) {
+            return x.partial_cmp(&y).unwrap_or(Ordering::Equal);
+        }
+    }
+    a.cmp(b)
+}
+
+/// Compara dos valores con un orden total: los numeros se comparan numericamente y se ordenan antes que los textos, que se comparan como texto.
+/// A diferencia de comparar_texto_o_numero el resultado no depende del orden en que se comparan los valores, por lo que sirve para MIN, MAX y para ordenar muestras de una columna.
+pub fn comparar_orden_total(a: &str, b: &str) -> Ordering {
+    match (es_numero(a), es_numero(b)) {
+        (true, true) => comparar_texto_o_numero(a, b),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.cmp(b),
+    }
+}
+
+/// Verifica si una cadena es un número, con signo y parte decimal opcionales.
+pub fn es_numero(cadena: &str) -> bool {
+    let sin_signo = cadena.strip_prefix('-').unwrap_or(cadena);
+    let mut partes = sin_signo.splitn(2, '.');
+    let entera = partes.next().unwrap_or("");
+    let decimal = partes.next().unwrap_or("0");
+    !entera.is_empty()
+        && !decimal.is_empty()
+        && entera.chars().all(|c| c.is_ascii_digit())
+        && decimal.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Devuelve los miembros de las condiciones de la expresion que no son literales, es decir, las columnas que utiliza.
+pub fn columnas_de_expresion(expresion: &Expresion) -> Vec<String> {
+    let mut columnas: Vec<String> = Vec::new();
+    agregar_columnas(expresion, &mut columnas);
+    columnas
+}
+
+fn agregar_columnas(expresion: &Expresion, columnas: &mut Vec<String>) {
+    match expresion {
+        Expresion::Not { derecha } => agregar_columnas(derecha, columnas),
+        Expresion::And { izquierda, derecha } | Expresion::Or { izquierda, derecha } => {
+            agregar_columnas(izquierda, columnas);
+            agregar_columnas(derecha, columnas);
+        }
+        Expresion::Operacion { condicional } => {
+            let (miembro1, miembro2) = condicional.miembros();
+            for miembro in [miembro1, miembro2] {
+                let es_literal = valor_literal(miembro).is_some() || miembro.starts_with('\'');
+                if !es_literal && !columnas.contains(miembro) {
+                    columnas.push(miembro.to_string());
+                }
+            }
+        }
+        _ => {}
+    }
 }
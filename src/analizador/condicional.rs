@@ -1,4 +1,6 @@
-#[derive(Debug)]
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
 /// Representa los tipos de condiciones que se pueden utilizar en una consulta.
 pub enum Condicional {
     /// Representa la condición de mayor que.
@@ -12,3 +14,31 @@ pub enum Condicional {
     /// Representa la condición de igualdad.
     Igual { miembro1: String, miembro2: String },
 }
+
+impl Condicional {
+    /// Devuelve los dos miembros de la condicion.
+    pub fn miembros(&self) -> (&String, &String) {
+        match self {
+            Condicional::Mayor { miembro1, miembro2 }
+            | Condicional::MayorIgual { miembro1, miembro2 }
+            | Condicional::Menor { miembro1, miembro2 }
+            | Condicional::MenorIgual { miembro1, miembro2 }
+            | Condicional::Igual { miembro1, miembro2 } => (miembro1, miembro2),
+        }
+    }
+}
+
+impl fmt::Display for Condicional {
+    /// Muestra la condicion tal como se escribe en una clausula WHERE, por ejemplo "edad >= 18".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operador = match self {
+            Condicional::Mayor { .. } => ">",
+            Condicional::MayorIgual { .. } => ">=",
+            Condicional::Menor { .. } => "<",
+            Condicional::MenorIgual { .. } => "<=",
+            Condicional::Igual { .. } => "=",
+        };
+        let (miembro1, miembro2) = self.miembros();
+        write!(f, "{} {} {}", miembro1, operador, miembro2)
+    }
+}
@@ -1,5 +1,7 @@
 use crate::analizador::condicional::Condicional;
-#[derive(Debug)]
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
 /// Representa los tipos de expresiones booleanas que se pueden utilizar en una consulta.
 pub enum Expresion {
     /// Representa la negación de una expresión.
@@ -23,3 +25,32 @@ pub enum Expresion {
     /// Representa un parentesis derecho.
     Dpar,
 }
+
+impl fmt::Display for Expresion {
+    /// Muestra la expresion como se escribe en una clausula WHERE, agregando los parentesis necesarios para conservar la precedencia.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expresion::Not { derecha } => match derecha.as_ref() {
+                Expresion::Operacion { .. } => write!(f, "NOT {}", derecha),
+                _ => write!(f, "NOT ({})", derecha),
+            },
+            Expresion::And { izquierda, derecha } => {
+                for (i, miembro) in [izquierda, derecha].into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " AND ")?;
+                    }
+                    match miembro.as_ref() {
+                        Expresion::Or { .. } => write!(f, "({})", miembro)?,
+                        _ => write!(f, "{}", miembro)?,
+                    }
+                }
+                Ok(())
+            }
+            Expresion::Or { izquierda, derecha } => write!(f, "{} OR {}", izquierda, derecha),
+            Expresion::Operacion { condicional } => write!(f, "{}", condicional),
+            Expresion::Unknown => Ok(()),
+            Expresion::Ipar => write!(f, "("),
+            Expresion::Dpar => write!(f, ")"),
+        }
+    }
+}
@@ -1,3 +1,4 @@
 pub mod analizador_implementacion;
 pub mod condicional;
 pub mod expresion;
+pub mod predicado;
@@ -0,0 +1,241 @@
+use crate::analizador::analizador_implementacion::{
+    comparar_texto_o_numero, es_numero, valor_literal,
+};
+use crate::analizador::condicional::Condicional;
+use crate::analizador::expresion::Expresion;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Representa los valores de las columnas que usa el predicado en una fila, en el orden en que el predicado las usa. Un valor es None si la fila no tiene esa columna.
+type Valores<'a> = [Option<&'a str>];
+
+/// Representa una condicion ya compilada, que recibe los valores de las columnas que usa.
+type Evaluador = Box<dyn Fn(&Valores) -> bool + Send + Sync>;
+
+/// Representa un valor literal de la clausula WHERE, clasificado al compilar la condicion: si es un numero se guarda ademas su valor numerico.
+struct Constante {
+    texto: String,
+    numero: Option<f64>,
+}
+
+impl Constante {
+    fn new(texto: String) -> Constante {
+        let numero = if es_numero(&texto) {
+            texto.parse::<f64>().ok()
+        } else {
+            None
+        };
+        Constante { texto, numero }
+    }
+
+    /// Compara el valor de una columna con la constante, de la misma manera que comparar_texto_o_numero.
+    fn comparar(&self, valor: &str) -> Ordering {
+        if let Some(numero) = self.numero {
+            if es_numero(valor) {
+                if let Ok(valor) = valor.parse::<f64>() {
+                    return valor.partial_cmp(&numero).unwrap_or(Ordering::Equal);
+                }
+            }
+        }
+        valor.cmp(&self.texto)
+    }
+}
+
+/// Representa una clausula WHERE compilada para una tabla: los nombres de las columnas ya se reemplazaron por su posicion en la fila y los literales por constantes.
+/// Al evaluar una fila solo se separan los campos que la condicion usa, y sin copiarlos.
+pub struct Predicado {
+    /// La posicion dentro de los valores que usa el predicado de cada columna de la fila, o None si el predicado no la usa.
+    posiciones: Vec<Option<usize>>,
+    cantidad: usize,
+    evaluar: Evaluador,
+}
+
+impl Predicado {
+    /// Compila la expresion para las filas cuyas columnas se ubican segun indice_columnas.
+    /// Se evalua igual que la clausula WHERE: una expresion vacia se cumple siempre, y una comparacion con un miembro que no es una columna ni un literal, o con una columna que la fila no tiene, no se cumple.
+    pub fn compilar(expresion: &Expresion, indice_columnas: &HashMap<String, usize>) -> Predicado {
+        let mut columnas: Vec<usize> = Vec::new();
+        let evaluar: Evaluador = match expresion {
+            Expresion::Unknown => Box::new(|_| true),
+            _ => compilar_expresion(expresion, indice_columnas, &mut columnas),
+        };
+        let mut posiciones: Vec<Option<usize>> =
+            vec![None; columnas.iter().max().map_or(0, |m| m + 1)];
+        for (posicion, &columna) in columnas.iter().enumerate() {
+            posiciones[columna] = Some(posicion);
+        }
+        Predicado {
+            posiciones,
+            cantidad: columnas.len(),
+            evaluar,
+        }
+    }
+
+    /// Evalua el predicado sobre una linea de la tabla tal como esta en el archivo. Solo se recorre la linea hasta la ultima columna que se usa.
+    pub fn cumple_linea(&self, linea: &str) -> bool {
+        let mut valores: Vec<Option<&str>> = vec![None; self.cantidad];
+        for (campo, posicion) in linea.split(',').zip(&self.posiciones) {
+            if let Some(posicion) = posicion {
+                valores[*posicion] = Some(campo.trim());
+            }
+        }
+        (self.evaluar)(&valores)
+    }
+
+    /// Evalua el predicado sobre una fila cuyos valores ya fueron separados.
+    pub fn cumple_fila(&self, fila: &[String]) -> bool {
+        let mut valores: Vec<Option<&str>> = vec![None; self.cantidad];
+        for (campo, posicion) in fila.iter().zip(&self.posiciones) {
+            if let Some(posicion) = posicion {
+                valores[*posicion] = Some(campo.as_str());
+            }
+        }
+        (self.evaluar)(&valores)
+    }
+}
+
+fn compilar_expresion(
+    expresion: &Expresion,
+    indice_columnas: &HashMap<String, usize>,
+    columnas: &mut Vec<usize>,
+) -> Evaluador {
+    match expresion {
+        Expresion::Not { derecha } => {
+            let derecha = compilar_expresion(derecha, indice_columnas, columnas);
+            Box::new(move |valores| !derecha(valores))
+        }
+        Expresion::And { izquierda, derecha } => {
+            let izquierda = compilar_expresion(izquierda, indice_columnas, columnas);
+            let derecha = compilar_expresion(derecha, indice_columnas, columnas);
+            Box::new(move |valores| izquierda(valores) && derecha(valores))
+        }
+        Expresion::Or { izquierda, derecha } => {
+            let izquierda = compilar_expresion(izquierda, indice_columnas, columnas);
+            let derecha = compilar_expresion(derecha, indice_columnas, columnas);
+            Box::new(move |valores| izquierda(valores) || derecha(valores))
+        }
+        Expresion::Operacion { condicional } => {
+            compilar_condicional(condicional, indice_columnas, columnas)
+        }
+        _ => Box::new(|_| false),
+    }
+}
+
+/// Representa un miembro de una comparacion una vez resuelto.
+enum Miembro {
+    Columna(usize),
+    Constante(Constante),
+    Invalido,
+}
+
+fn compilar_condicional(
+    condicional: &Condicional,
+    indice_columnas: &HashMap<String, usize>,
+    columnas: &mut Vec<usize>,
+) -> Evaluador {
+    let comparador: fn(Ordering) -> bool = match condicional {
+        Condicional::Mayor { .. } => Ordering::is_gt,
+        Condicional::MayorIgual { .. } => Ordering::is_ge,
+        Condicional::Menor { .. } => Ordering::is_lt,
+        Condicional::MenorIgual { .. } => Ordering::is_le,
+        Condicional::Igual { .. } => Ordering::is_eq,
+    };
+    let (miembro1, miembro2) = condicional.miembros();
+    let mut resolver = |miembro: &str| -> Miembro {
+        if let Some(&indice) = indice_columnas.get(miembro) {
+            let posicion = match columnas.iter().position(|&c| c == indice) {
+                Some(posicion) => posicion,
+                None => {
+                    columnas.push(indice);
+                    columnas.len() - 1
+                }
+            };
+            return Miembro::Columna(posicion);
+        }
+        match valor_literal(miembro) {
+            Some(valor) => Miembro::Constante(Constante::new(valor)),
+            None => Miembro::Invalido,
+        }
+    };
+    let miembro1 = resolver(miembro1);
+    let miembro2 = resolver(miembro2);
+    match (miembro1, miembro2) {
+        (Miembro::Columna(a), Miembro::Columna(b)) => {
+            Box::new(move |valores| match (valores[a], valores[b]) {
+                (Some(a), Some(b)) => comparador(comparar_texto_o_numero(a, b)),
+                _ => false,
+            })
+        }
+        (Miembro::Columna(a), Miembro::Constante(constante)) => {
+            Box::new(move |valores| match valores[a] {
+                Some(a) => comparador(constante.comparar(a)),
+                None => false,
+            })
+        }
+        (Miembro::Constante(constante), Miembro::Columna(b)) => {
+            Box::new(move |valores| match valores[b] {
+                Some(b) => comparador(constante.comparar(b).reverse()),
+                None => false,
+            })
+        }
+        _ => Box::new(|_| false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analizador::analizador_implementacion::{cumple_c_w, parsear_expresion};
+    use crate::ejecutor::ejecutor_implementacion::adaptar_where;
+
+    #[test]
+    fn test_predicado_equivale_a_la_clausula_where() {
+        let indice_columnas: HashMap<String, usize> = [("id", 0), ("nombre", 1), ("saldo", 2)]
+            .iter()
+            .map(|(c, i)| (c.to_string(), *i))
+            .collect();
+        let lineas = [
+            "1,Ana,10",
+            "2, Juan ,-3.5",
+            "10,ana,",
+            "3,Zoe,abc",
+            "4,Luis",
+            "007,Eva,7.0",
+        ];
+        let clausulas = [
+            "",
+            "id = 1",
+            "1 = id",
+            "id > 2 AND saldo <= 7",
+            "nombre = 'Juan' OR NOT saldo < 0",
+            "10 > saldo",
+            "saldo >= id",
+            "nombre > 'B' AND (id = 3 OR id = 4)",
+            "inexistente = 1 OR id = 7",
+            "saldo = 'abc'",
+            "id = 7.0",
+        ];
+        for clausula in clausulas {
+            let expresion = parsear_expresion(adaptar_where(clausula)).unwrap();
+            let predicado = Predicado::compilar(&expresion, &indice_columnas);
+            for linea in lineas {
+                let esperado = cumple_c_w(linea, &expresion, &indice_columnas).unwrap();
+                let fila: Vec<String> = linea.split(',').map(|s| s.trim().to_string()).collect();
+                assert_eq!(
+                    predicado.cumple_linea(linea),
+                    esperado,
+                    "{} en {}",
+                    clausula,
+                    linea
+                );
+                assert_eq!(
+                    predicado.cumple_fila(&fila),
+                    esperado,
+                    "{} en {}",
+                    clausula,
+                    linea
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::comandos::parametros::{una_sentencia, SentenciaPreparada};
+use crate::ejecutor::bloqueos::{iniciar_sesion, Bloqueo};
+use crate::ejecutor::ejecutor_implementacion::{ejecutar_comando, ejecutar_consulta};
+use crate::ejecutor::resultado::{Resultado, Valor};
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use std::path::Path;
+
+/// Representa una base de datos abierta: un directorio con tablas .csv.
+/// Mientras exista, mantiene el bloqueo compartido del directorio, de la misma manera que una ejecucion del programa.
+pub struct Database {
+    directorio: String,
+    _sesion: Bloqueo,
+}
+
+impl Database {
+    /// Esta funcion abre la base de datos del directorio indicado. Si otro proceso termino abruptamente mientras la usaba, se recuperan sus cambios.
+    pub fn open(directorio: &str) -> Result<Database, Errores> {
+        if !Path::new(directorio).is_dir() {
+            return Err(Errores::nuevo(
+                TipoError::InvalidTable,
+                format!("{} no es un directorio", directorio),
+            ));
+        }
+        let directorio = directorio.to_string();
+        let sesion = iniciar_sesion(&directorio)?;
+        Ok(Database {
+            directorio,
+            _sesion: sesion,
+        })
+    }
+
+    /// Esta funcion ejecuta una sentencia. Los SELECT devuelven las columnas y filas obtenidas; el resto de las sentencias la cantidad de filas afectadas.
+    /// El ';' final es opcional, pero no se aceptan varias sentencias juntas.
+    pub fn execute(&self, sql: &str) -> Result<Resultado, Errores> {
+        una_sentencia(sql)
+            .and_then(|sentencia| ejecutar_consulta(&sentencia, &self.directorio))
+            .map_err(|error| error.ubicar(sql))
+    }
+
+    /// Esta funcion parsea una sentencia con parametros '?', '$n' o ':nombre', para ejecutarla una o mas veces asignandole valores con bind.
+    pub fn prepare(&self, sql: &str) -> Result<PreparedStatement<'_>, Errores> {
+        Ok(PreparedStatement {
+            base: self,
+            sentencia: SentenciaPreparada::preparar(sql).map_err(|error| error.ubicar(sql))?,
+        })
+    }
+
+    /// Devuelve el directorio de las tablas.
+    pub fn directorio(&self) -> &str {
+        &self.directorio
+    }
+}
+
+/// Representa una sentencia preparada sobre una base de datos abierta.
+pub struct PreparedStatement<'a> {
+    base: &'a Database,
+    sentencia: SentenciaPreparada,
+}
+
+impl PreparedStatement<'_> {
+    /// Esta funcion asigna el valor del parametro indicado, contando desde 1. Los parametros '?' se numeran en el orden en que aparecen.
+    pub fn bind(&mut self, posicion: usize, valor: impl Into<Valor>) -> Result<&mut Self, Errores> {
+        self.sentencia.asignar(posicion, valor.into())?;
+        Ok(self)
+    }
+
+    /// Esta funcion asigna el valor del parametro ':nombre' indicado.
+    pub fn bind_named(
+        &mut self,
+        nombre: &str,
+        valor: impl Into<Valor>,
+    ) -> Result<&mut Self, Errores> {
+        self.sentencia.asignar_por_nombre(nombre, valor.into())?;
+        Ok(self)
+    }
+
+    /// Esta funcion quita los valores asignados a los parametros.
+    pub fn clear_bindings(&mut self) {
+        self.sentencia.limpiar();
+    }
+
+    /// Devuelve la cantidad de parametros de la sentencia.
+    pub fn parameter_count(&self) -> usize {
+        self.sentencia.cantidad_parametros()
+    }
+
+    /// Esta funcion ejecuta la sentencia con los valores asignados. Falla si algun parametro no tiene valor.
+    pub fn execute(&self) -> Result<Resultado, Errores> {
+        ejecutar_comando(self.sentencia.comando()?, &self.base.directorio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Database;
+    use crate::ejecutor::ejecutor_implementacion::test::crear_directorio_prueba;
+    use crate::ejecutor::resultado::Valor;
+    use crate::errores::errores_implementacion::TipoError;
+
+    #[test]
+    fn test_database_execute() {
+        let directorio = crear_directorio_prueba("database", &[]);
+        let base = Database::open(&directorio).unwrap();
+        base.execute("CREATE TABLE personas (id INT PRIMARY KEY, nombre TEXT, altura REAL)")
+            .unwrap();
+        let insertadas = base
+            .execute("INSERT INTO personas (id, nombre, altura) VALUES (1, 'Ana', 1.65), (2, 'Luis', NULL);")
+            .unwrap();
+        assert_eq!(insertadas.filas_afectadas, 2);
+        assert!(!insertadas.es_consulta());
+
+        let consulta = base
+            .execute("SELECT id, nombre, altura FROM personas ORDER BY id")
+            .unwrap();
+        assert_eq!(consulta.columnas, vec!["id", "nombre", "altura"]);
+        assert_eq!(
+            consulta.filas,
+            vec![
+                vec![
+                    Valor::Entero(1),
+                    Valor::Texto("Ana".to_string()),
+                    Valor::Real(1.65)
+                ],
+                vec![
+                    Valor::Entero(2),
+                    Valor::Texto("Luis".to_string()),
+                    Valor::Nulo
+                ],
+            ]
+        );
+        let modificadas = base
+            .execute("UPDATE personas SET altura = 1.80 WHERE id = 2")
+            .unwrap();
+        assert_eq!(modificadas.filas_afectadas, 1);
+        let eliminadas = base.execute("DELETE FROM personas WHERE id > 0").unwrap();
+        assert_eq!(eliminadas.filas_afectadas, 2);
+
+        assert_eq!(
+            base.execute("SELECT * FROM personas; SELECT * FROM personas")
+                .unwrap_err()
+                .tipo,
+            TipoError::InvalidSyntax
+        );
+        assert_eq!(
+            base.execute("SELECT apodo FROM personas").unwrap_err().tipo,
+            TipoError::InvalidColumn
+        );
+        assert!(Database::open(&format!("{}/no_existe", directorio)).is_err());
+    }
+
+    #[test]
+    fn test_database_prepare() {
+        let directorio =
+            crear_directorio_prueba("database_prepare", &[("notas.csv", "alumno,nota\n")]);
+        let base = Database::open(&directorio).unwrap();
+        let mut insert = base
+            .prepare("INSERT INTO notas (alumno, nota) VALUES (?, ?)")
+            .unwrap();
+        assert_eq!(insert.parameter_count(), 2);
+        for (alumno, nota) in [("Ana", 9), ("Luis", 7), ("Sofía", 10)] {
+            insert.bind(1, alumno).unwrap().bind(2, nota).unwrap();
+            assert_eq!(insert.execute().unwrap().filas_afectadas, 1);
+        }
+        insert.clear_bindings();
+        assert!(insert.execute().is_err());
+
+        let mut select = base
+            .prepare("SELECT alumno FROM notas WHERE nota >= :minima ORDER BY alumno")
+            .unwrap();
+        select.bind_named("minima", 9).unwrap();
+        assert_eq!(
+            select.execute().unwrap().filas,
+            vec![
+                vec![Valor::Texto("Ana".to_string())],
+                vec![Valor::Texto("Sofía".to_string())]
+            ]
+        );
+    }
+}
@@ -0,0 +1 @@
+pub mod base_de_datos_implementacion;
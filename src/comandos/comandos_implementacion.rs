@@ -1,6 +1,10 @@
-use crate::errores::errores_implementacion::{imprimir_error, Errores};
+use crate::analizador::analizador_implementacion::parsear_expresion;
+use crate::ejecutor::ejecutor_implementacion::adaptar_where;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::errores::sugerencias::sugerir_palabra_clave;
+use crate::esquema::esquema_implementacion::separar_definiciones;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// Representa los diferentes tipos de comandos posibles que el programa soporta.
 pub enum Comandos {
     /// Comando Insert.
@@ -9,6 +13,14 @@ pub enum Comandos {
         into: Vec<String>,
         valores: Vec<String>,
     },
+    /// Comando Insert que inserta las filas de un SELECT: INSERT INTO tabla [(columnas)] SELECT ... Si no se indican columnas, se insertan en todas las de la tabla, en orden.
+    InsertSelect {
+        tabla: String,
+        into: Vec<String>,
+        consulta: Box<Comandos>,
+        /// Posicion en bytes del SELECT, medida desde la primera palabra de la consulta. Se usa para ubicar los errores que ocurren al ejecutarlo.
+        posicion_consulta: usize,
+    },
     /// Comando Update.
     Update {
         tabla: String,
@@ -24,29 +36,129 @@ pub enum Comandos {
     Select {
         campos: Vec<String>,
         tabla: String,
+        joins: Vec<Join>,
         clausula_where: String,
+        agrupar_por: Vec<String>,
         clausula_order: Vec<String>,
+        limite: Option<usize>,
+    },
+    /// Comando Explain, muestra el plan de ejecucion de un Select. Con ANALYZE ademas lo ejecuta y mide cada paso.
+    Explain {
+        analizar: bool,
+        consulta: Box<Comandos>,
+    },
+    /// Comando Create Table.
+    CreateTable {
+        tabla: String,
+        definiciones: Vec<String>,
+    },
+    /// Comando Create Table As, crea la tabla con las columnas y las filas de un SELECT.
+    CreateTableAs {
+        tabla: String,
+        consulta: Box<Comandos>,
+        /// Posicion en bytes del SELECT, medida desde la primera palabra de la consulta. Se usa para ubicar los errores que ocurren al ejecutarlo.
+        posicion_consulta: usize,
     },
+    /// Comando Create Sequence.
+    CreateSequence {
+        nombre: String,
+        inicio: i64,
+        incremento: i64,
+    },
+    /// Comando Create Index.
+    CreateIndex {
+        nombre: String,
+        tabla: String,
+        columna: String,
+        unico: bool,
+    },
+    /// Comando Create View. La definicion es el texto del SELECT, tal como se guarda, y la consulta es ese mismo SELECT parseado. Con OR REPLACE reemplaza la vista si ya existe.
+    CreateView {
+        nombre: String,
+        definicion: String,
+        consulta: Box<Comandos>,
+        reemplazar: bool,
+    },
+    /// Comando Drop View, elimina una vista.
+    DropView { nombre: String },
+    /// Comando Create Materialized View. Como en Create View, la definicion es el texto del SELECT y la consulta es ese mismo SELECT parseado.
+    CreateMaterializedView {
+        nombre: String,
+        definicion: String,
+        consulta: Box<Comandos>,
+    },
+    /// Comando Refresh Materialized View, vuelve a calcular las filas de una vista materializada.
+    RefreshMaterializedView { nombre: String },
+    /// Comando Reindex, reconstruye los indices de una tabla, un indice en particular o todos los indices si no se indica un nombre.
+    Reindex { nombre: Option<String> },
+    /// Comando Analyze, calcula las estadisticas de una tabla o, si no se indica ninguna, de todas las tablas.
+    Analyze { tabla: Option<String> },
+    /// Comando Begin, inicia una transaccion.
+    Begin,
+    /// Comando Commit, confirma la transaccion activa.
+    Commit,
+    /// Comando Rollback, deshace la transaccion activa.
+    Rollback,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa la union de otra tabla en un Select: la tabla y las dos columnas cuyos valores deben ser iguales.
+pub struct Join {
+    pub tabla: String,
+    pub columna_izquierda: String,
+    pub columna_derecha: String,
 }
 
 ///Esta funcion recibe el comando tal y como es ingresado para que, segun dependiendo de la primera palabra sea parseada de una u otra manera.
+/// Si la consulta esta mal escrita y contiene una palabra parecida a una palabra clave, el error la sugiere.
 pub fn parsear(comando: &str) -> Result<Comandos, Errores> {
     let token: Vec<&str> = comando.split_whitespace().collect();
+    parsear_comando(&token).map_err(|error| sugerir_en_error_de_sintaxis(error, comando))
+}
 
+fn parsear_comando(token: &[&str]) -> Result<Comandos, Errores> {
     if token.is_empty() {
-        imprimir_error(Errores::InvalidSyntax, "No se insertó nada".to_string());
-        return Err(Errores::InvalidSyntax);
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "No se insertó nada".to_string(),
+        ));
     }
 
-    match token[0].to_uppercase().as_str() {
-        "INSERT" => parser_insert(&token),
-        "UPDATE" => parser_update(&token),
-        "DELETE" => parser_delete(&token),
-        "SELECT" => parser_select(&token),
-        _ => {
-            imprimir_error(Errores::InvalidSyntax, "Comando inválido".to_string());
-            Err(Errores::InvalidSyntax)
+    match token[0].trim_end_matches(';').to_uppercase().as_str() {
+        "INSERT" => parser_insert(token),
+        "UPDATE" => parser_update(token),
+        "DELETE" => parser_delete(token),
+        "SELECT" => parser_select(token),
+        "CREATE" => parser_create(token),
+        "DROP" => parser_drop(token),
+        "REFRESH" => parser_refresh(token),
+        "REINDEX" => parser_reindex(token),
+        "ANALYZE" => parser_analyze(token),
+        "EXPLAIN" => parser_explain(token),
+        "BEGIN" | "START" | "COMMIT" | "ROLLBACK" => parser_transaccion(token),
+        _ => Err(en_token(
+            Errores::nuevo(TipoError::InvalidSyntax, "Comando inválido".to_string()),
+            token,
+            token[0],
+        )),
+    }
+}
+
+/// Agrega al error de sintaxis la palabra clave que probablemente quiso escribirse, si el error no indica otra palabra de la consulta.
+fn sugerir_en_error_de_sintaxis(error: Errores, comando: &str) -> Errores {
+    if error.tipo != TipoError::InvalidSyntax || error.sugerencia.is_some() {
+        return error;
+    }
+    match sugerir_palabra_clave(comando) {
+        Some((palabra, clave))
+            if error
+                .token
+                .as_ref()
+                .is_none_or(|token| token.eq_ignore_ascii_case(&palabra)) =>
+        {
+            error.en_token(&palabra).con_sugerencia(Some(clave))
         }
+        _ => error,
     }
 }
 
@@ -54,45 +166,75 @@ pub fn parsear(comando: &str) -> Result<Comandos, Errores> {
 fn parser_insert(token: &[&str]) -> Result<Comandos, Errores> {
     let indice_into: Option<usize> = obtener_indice(token, "INTO");
     let indice_values: Option<usize> = obtener_indice(token, "VALUES");
+    let indice_select: Option<usize> = obtener_indice(token, "SELECT");
 
+    if let (Some(indice_into), Some(indice_select)) = (indice_into, indice_select) {
+        if indice_values.is_none_or(|indice_values| indice_select < indice_values) {
+            return parser_insert_select(token, indice_into, indice_select);
+        }
+    }
     if let (Some(indice_into), Some(indice_values)) = (indice_into, indice_values) {
         if indice_into != 1 || indice_values <= indice_into + 2 {
-            imprimir_error(
-                Errores::InvalidSyntax,
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
                 "Los argumentos de la instrucción INSERT fueron escritos de manera incorrecta"
                     .to_string(),
-            );
-            return Err(Errores::InvalidSyntax);
+            ));
         }
         let tabla = token[indice_into + 1].to_string();
         let valores: Vec<String> = token[indice_values + 1..]
             .iter()
             .map(|&s| s.to_string())
             .collect();
-        let into: Vec<String> = token[indice_into + 2..indice_values]
-            .iter()
-            .map(|&s| s.to_string())
-            .collect();
-        let into_joined = into.join(" ");
-        let into_trimmed = into_joined.trim_start_matches("(").trim_end_matches(")");
-        let into_splitteada = into_trimmed.split(',').collect::<Vec<&str>>();
-        let into_final: Vec<String> = into_splitteada
-            .iter()
-            .map(|&s| s.trim_end_matches(", ").trim().to_string())
-            .collect();
         Ok(Comandos::Insert {
             tabla,
-            into: into_final,
+            into: separar_columnas_insert(&token[indice_into + 2..indice_values]),
             valores,
         })
     } else {
-        imprimir_error(
-            Errores::InvalidSyntax,
+        Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
             "Los argumentos de la instrucción INSERT fueron escritos de manera incorrecta"
                 .to_string(),
-        );
-        Err(Errores::InvalidSyntax)
+        ))
+    }
+}
+
+/// Esta funcion parsea a las consultas INSERT INTO tabla [(columnas)] SELECT ..., que insertan las filas de un SELECT.
+fn parser_insert_select(
+    token: &[&str],
+    indice_into: usize,
+    indice_select: usize,
+) -> Result<Comandos, Errores> {
+    if indice_into != 1 || indice_select <= indice_into + 1 {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Los argumentos de la instrucción INSERT fueron escritos de manera incorrecta"
+                .to_string(),
+        ));
     }
+    let consulta = parsear_subconsulta(token, indice_select)?;
+    let into = separar_columnas_insert(&token[indice_into + 2..indice_select])
+        .into_iter()
+        .filter(|columna| !columna.is_empty())
+        .collect();
+    Ok(Comandos::InsertSelect {
+        tabla: token[indice_into + 1].to_string(),
+        into,
+        consulta: Box::new(consulta),
+        posicion_consulta: desplazamiento(token, token[indice_select]).unwrap_or(0),
+    })
+}
+
+/// Esta funcion separa la lista de columnas de un INSERT, escrita entre parentesis y separada por comas.
+fn separar_columnas_insert(token: &[&str]) -> Vec<String> {
+    let into_joined = token.join(" ");
+    let into_trimmed = into_joined.trim_start_matches("(").trim_end_matches(")");
+    let into_splitteada = into_trimmed.split(',').collect::<Vec<&str>>();
+    into_splitteada
+        .iter()
+        .map(|&s| s.trim_end_matches(", ").trim().to_string())
+        .collect()
 }
 
 /// Esta funcion parsea a las consultas de tipo update
@@ -104,12 +246,11 @@ fn parser_update(token: &[&str]) -> Result<Comandos, Errores> {
     let indice_set = match indice_set {
         Some(index) => index,
         None => {
-            imprimir_error(
-                Errores::InvalidSyntax,
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
                 "Los argumentos de la instruccion UPDATE fueron escritos de manera incorrecta1"
                     .to_string(),
-            );
-            return Err(Errores::InvalidSyntax);
+            ));
         }
     };
     let indice_where = indice_where.unwrap_or_default();
@@ -122,18 +263,18 @@ fn parser_update(token: &[&str]) -> Result<Comandos, Errores> {
             || indice_set + 1 >= token.len()
             || indice_where + 1 >= token.len())
     {
-        imprimir_error(
-            Errores::InvalidSyntax,
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
             "Los argumentos de la instruccion UPDATE fueron escritos de manera incorrecta"
                 .to_string(),
-        );
-        return Err(Errores::InvalidSyntax);
+        ));
     }
     let tabla: String = token[1].to_string();
 
     if indice_where != 0 {
         clausula_where = token[indice_where + 1..].join(" ");
         clausula_set = token[indice_set + 1..indice_where].join(" ");
+        validar_where(token, indice_where + 1, &clausula_where)?;
     }
     Ok(Comandos::Update {
         tabla,
@@ -145,12 +286,11 @@ fn parser_update(token: &[&str]) -> Result<Comandos, Errores> {
 /// Esta funcion parsea a las consultas de tipo delete
 fn parser_delete(token: &[&str]) -> Result<Comandos, Errores> {
     if token.len() < 3 || token[1].to_uppercase() != "FROM" {
-        imprimir_error(
-            Errores::InvalidSyntax,
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
             "Los argumentos de la instruccion DELETE fueron escritos de manera incorrecta"
                 .to_string(),
-        );
-        return Err(Errores::InvalidSyntax);
+        ));
     }
     let indice_where = obtener_indice(token, "WHERE");
     let (indice_where, tiene_where) = match indice_where {
@@ -163,12 +303,11 @@ fn parser_delete(token: &[&str]) -> Result<Comandos, Errores> {
         .to_string();
     let clausula_where = if tiene_where {
         if indice_where + 1 > token.len() {
-            imprimir_error(
-                Errores::InvalidSyntax,
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
                 "Los argumentos de la instruccion DELETE fueron escritos de manera incorrecta"
                     .to_string(),
-            );
-            return Err(Errores::InvalidSyntax);
+            ));
         }
         token[indice_where + 1..]
             .iter()
@@ -178,6 +317,9 @@ fn parser_delete(token: &[&str]) -> Result<Comandos, Errores> {
     } else {
         String::new()
     };
+    if tiene_where {
+        validar_where(token, indice_where + 1, &clausula_where)?;
+    }
     Ok(Comandos::Delete {
         tabla,
         clausula_where,
@@ -185,20 +327,28 @@ fn parser_delete(token: &[&str]) -> Result<Comandos, Errores> {
 }
 
 /// Esta funcion parsea a las consultas de tipo select.
+/// Despues del FROM pueden unirse otras tablas con [INNER] JOIN tabla ON columna = columna, y las filas pueden agruparse con GROUP BY antes de ordenarse con ORDER BY. Al final, LIMIT indica la cantidad maxima de filas a devolver.
 fn parser_select(token: &[&str]) -> Result<Comandos, Errores> {
-    let indice_from: Option<usize> = obtener_indice(token, "FROM");
+    let error_select = || {
+        Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Los argumentos de la instrucción SELECT fueron escritos de manera incorrecta"
+                .to_string(),
+        )
+    };
+    let indice_from: usize = obtener_indice(token, "FROM").ok_or_else(error_select)?;
     let indice_where: Option<usize> = obtener_indice(token, "WHERE");
+    let indice_group_by: Option<usize> = obtener_indice(token, "GROUP");
     let indice_order_by: Option<usize> = obtener_indice(token, "ORDER");
-    let indice_from: usize = match indice_from {
-        Some(index) => index,
-        None => {
-            imprimir_error(
-                Errores::InvalidSyntax,
-                "Los argumentos de la instrucción SELECT fueron escritos de manera incorrecta"
-                    .to_string(),
-            );
-            return Err(Errores::InvalidSyntax);
-        }
+    let indice_limit: Option<usize> = obtener_indice(token, "LIMIT");
+    // Cada clausula termina donde comienza la siguiente que aparece en la consulta.
+    let fin_clausula = |inicio: usize| {
+        [indice_where, indice_group_by, indice_order_by, indice_limit]
+            .into_iter()
+            .flatten()
+            .filter(|&indice| indice > inicio)
+            .min()
+            .unwrap_or(token.len())
     };
     let mut campos: Vec<String> = token[1..indice_from]
         .iter()
@@ -206,70 +356,558 @@ fn parser_select(token: &[&str]) -> Result<Comandos, Errores> {
         .collect();
     campos.retain(|s| !s.trim().is_empty());
     if indice_from + 1 >= token.len() {
-        imprimir_error(
-            Errores::InvalidSyntax,
-            "Los argumentos de la instrucción SELECT fueron escritos de manera incorrecta"
-                .to_string(),
-        );
-        return Err(Errores::InvalidSyntax);
+        return Err(error_select());
     }
 
     let tabla = token[indice_from + 1].to_string();
+    let joins =
+        parser_joins(&token[indice_from + 2..fin_clausula(indice_from)]).map_err(|error| {
+            let posicion = token
+                .get(indice_from + 2)
+                .and_then(|t| desplazamiento(token, t));
+            error.desplazar(posicion.unwrap_or(0))
+        })?;
     let mut clausula_where = Vec::new();
+    let mut agrupar_por = Vec::new();
     let mut clausula_order = Vec::new();
 
     if let Some(indice_where) = indice_where {
-        if let Some(indice_order_by) = indice_order_by {
-            if indice_order_by + 2 <= token.len() {
-                clausula_where = token[indice_where + 1..indice_order_by]
-                    .iter()
-                    .map(|&s| s.to_string())
-                    .collect();
-                clausula_order = eliminar_punto_y_coma(
-                    token[indice_order_by + 2..]
-                        .iter()
-                        .map(|&s| s.to_string())
-                        .collect(),
-                );
-            } else {
-                imprimir_error(
-                    Errores::InvalidSyntax,
-                    "Los argumentos de la instrucción SELECT fueron escritos de manera incorrecta"
-                        .to_string(),
-                );
-                return Err(Errores::InvalidSyntax);
-            }
-        } else {
-            clausula_where = token[indice_where + 1..]
+        clausula_where = token[indice_where + 1..fin_clausula(indice_where)]
+            .iter()
+            .map(|&s| s.to_string())
+            .collect();
+    }
+    if let Some(indice_group_by) = indice_group_by {
+        if indice_group_by + 2 > token.len() {
+            return Err(error_select());
+        }
+        agrupar_por = eliminar_punto_y_coma(
+            token[indice_group_by + 2..fin_clausula(indice_group_by)]
                 .iter()
-                .map(|&s| s.to_string())
-                .collect();
+                .flat_map(|s| s.split(','))
+                .map(|s| s.trim_end_matches(';').to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+        );
+        if agrupar_por.is_empty() {
+            return Err(error_select());
+        }
+    }
+    if let Some(indice_order_by) = indice_order_by {
+        if indice_order_by + 2 > token.len() {
+            return Err(error_select());
         }
-    } else if let Some(indice_order_by) = indice_order_by {
-        if indice_order_by + 2 <= token.len() {
-            clausula_order = token[indice_order_by + 2..]
+        clausula_order = eliminar_punto_y_coma(
+            token[indice_order_by + 2..fin_clausula(indice_order_by)]
+                .iter()
+                .map(|&s| s.trim_end_matches(';').to_string())
+                .collect(),
+        );
+    }
+    let limite: Option<usize> = match indice_limit {
+        Some(indice_limit) => {
+            let cantidad: Vec<&str> = token[indice_limit + 1..]
                 .iter()
-                .map(|&s| s.to_string())
+                .map(|s| s.trim_end_matches(';'))
+                .filter(|s| !s.is_empty())
                 .collect();
-        } else {
-            imprimir_error(
-                Errores::InvalidSyntax,
-                "Los argumentos de la instrucción SELECT fueron escritos de manera incorrecta"
-                    .to_string(),
-            );
-            return Err(Errores::InvalidSyntax);
+            match cantidad.as_slice() {
+                [cantidad] => match cantidad.parse::<usize>() {
+                    Ok(cantidad) => Some(cantidad),
+                    Err(_) => {
+                        return Err(en_token(
+                            Errores::nuevo(
+                                TipoError::InvalidSyntax,
+                                "LIMIT debe indicar una cantidad de filas".to_string(),
+                            ),
+                            token,
+                            cantidad,
+                        ))
+                    }
+                },
+                _ => return Err(error_select()),
+            }
         }
-    }
+        None => None,
+    };
     let clausula_where: String = clausula_where.join(" ");
+    if let Some(indice_where) = indice_where {
+        validar_where(token, indice_where + 1, &clausula_where)?;
+    }
     Ok(Comandos::Select {
         campos,
         tabla,
+        joins,
         clausula_where,
+        agrupar_por,
         clausula_order,
+        limite,
+    })
+}
+
+/// Esta funcion parsea los JOIN que siguen a la tabla del FROM. Cada uno tiene la forma [INNER] JOIN tabla ON columna = columna.
+/// Si despues de la tabla no hay ningun JOIN, el resto de las palabras se ignora.
+fn parser_joins(token: &[&str]) -> Result<Vec<Join>, Errores> {
+    let mut joins: Vec<Join> = Vec::new();
+    if obtener_indice(token, "JOIN").is_none() {
+        return Ok(joins);
+    }
+    let mut resto = token;
+    while !resto.is_empty() {
+        if resto[0].to_uppercase() == "INNER" {
+            resto = &resto[1..];
+        }
+        if resto.len() < 4 || resto[0].to_uppercase() != "JOIN" || resto[2].to_uppercase() != "ON" {
+            let palabra = resto.first().copied().unwrap_or("JOIN");
+            return Err(en_token(
+                Errores::nuevo(
+                    TipoError::InvalidSyntax,
+                    "Los JOIN deben escribirse como JOIN tabla ON columna = columna".to_string(),
+                ),
+                token,
+                palabra,
+            ));
+        }
+        let fin = resto[3..]
+            .iter()
+            .position(|t| matches!(t.to_uppercase().as_str(), "JOIN" | "INNER"))
+            .map(|posicion| posicion + 3)
+            .unwrap_or(resto.len());
+        let condicion = resto[3..fin].join(" ");
+        let condicion = condicion.trim_end_matches(';');
+        let (columna_izquierda, columna_derecha) = match condicion.split_once('=') {
+            Some((izquierda, derecha))
+                if !izquierda.trim().is_empty()
+                    && !derecha.trim().is_empty()
+                    && !derecha.contains('=') =>
+            {
+                (izquierda.trim().to_string(), derecha.trim().to_string())
+            }
+            _ => {
+                return Err(en_token(
+                    Errores::nuevo(
+                        TipoError::InvalidSyntax,
+                        "La condicion de un JOIN debe ser la igualdad entre dos columnas"
+                            .to_string(),
+                    ),
+                    token,
+                    resto[2],
+                ));
+            }
+        };
+        joins.push(Join {
+            tabla: resto[1].to_string(),
+            columna_izquierda,
+            columna_derecha,
+        });
+        resto = &resto[fin..];
+    }
+    Ok(joins)
+}
+
+/// Esta funcion parsea a las consultas EXPLAIN [ANALYZE], que muestran el plan con el que se ejecuta un SELECT.
+fn parser_explain(token: &[&str]) -> Result<Comandos, Errores> {
+    let analizar = token.len() > 1 && token[1].to_uppercase() == "ANALYZE";
+    let inicio = if analizar { 2 } else { 1 };
+    if inicio >= token.len() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Falta la consulta a explicar".to_string(),
+        ));
+    }
+    let consulta = parsear_subconsulta(token, inicio)?;
+    if !matches!(consulta, Comandos::Select { .. }) {
+        return Err(en_token(
+            Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "EXPLAIN solo puede aplicarse a consultas SELECT".to_string(),
+            ),
+            token,
+            token[inicio],
+        ));
+    }
+    Ok(Comandos::Explain {
+        analizar,
+        consulta: Box::new(consulta),
+    })
+}
+
+/// Esta funcion parsea a las consultas de tipo create table, separando la definicion de cada columna o restriccion.
+fn parser_create(token: &[&str]) -> Result<Comandos, Errores> {
+    if token.len() >= 2
+        && (["VIEW", "MATERIALIZED"].contains(&token[1].to_uppercase().as_str())
+            || (token[1].to_uppercase() == "OR"
+                && token.get(2).is_some_and(|t| t.to_uppercase() == "REPLACE")))
+    {
+        return parser_create_view(token);
+    }
+    if token.len() >= 2 && token[1].to_uppercase() == "SEQUENCE" {
+        return parser_create_sequence(token);
+    }
+    if token.len() >= 2
+        && (token[1].to_uppercase() == "INDEX"
+            || (token[1].to_uppercase() == "UNIQUE"
+                && token.get(2).is_some_and(|t| t.to_uppercase() == "INDEX")))
+    {
+        return parser_create_index(token);
+    }
+    if token.len() < 3 || token[1].to_uppercase() != "TABLE" {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Los argumentos de la instruccion CREATE TABLE fueron escritos de manera incorrecta"
+                .to_string(),
+        ));
+    }
+    if token.len() >= 5 && token[3].to_uppercase() == "AS" && !token[2].contains('(') {
+        return parser_create_table_as(token);
+    }
+    let (tabla, resto) = match token[2].split_once('(') {
+        Some((tabla, resto)) => (
+            tabla.to_string(),
+            format!("({} {}", resto, token[3..].join(" ")),
+        ),
+        None => (token[2].to_string(), token[3..].join(" ")),
+    };
+    let definiciones = separar_definiciones(&resto)?;
+    Ok(Comandos::CreateTable {
+        tabla,
+        definiciones,
+    })
+}
+
+/// Esta funcion parsea a las consultas de tipo CREATE TABLE tabla AS SELECT ..., que crean la tabla con las filas del SELECT.
+fn parser_create_table_as(token: &[&str]) -> Result<Comandos, Errores> {
+    let consulta = parsear_subconsulta(token, 4)?;
+    if !matches!(consulta, Comandos::Select { .. }) {
+        return Err(en_token(
+            Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "CREATE TABLE AS solo puede usarse con una consulta SELECT".to_string(),
+            ),
+            token,
+            token[4],
+        ));
+    }
+    Ok(Comandos::CreateTableAs {
+        tabla: token[2].to_string(),
+        consulta: Box::new(consulta),
+        posicion_consulta: desplazamiento(token, token[4]).unwrap_or(0),
     })
 }
 
+/// Esta funcion parsea a las consultas de tipo create view: CREATE [OR REPLACE] VIEW nombre AS SELECT ... o CREATE MATERIALIZED VIEW nombre AS SELECT ...
+fn parser_create_view(token: &[&str]) -> Result<Comandos, Errores> {
+    let reemplazar = token[1].to_uppercase() == "OR";
+    let mut inicio = if reemplazar { 3 } else { 1 };
+    let materializada = token
+        .get(inicio)
+        .is_some_and(|t| t.to_uppercase() == "MATERIALIZED");
+    if materializada {
+        if reemplazar {
+            return Err(en_token(
+                Errores::nuevo(
+                    TipoError::InvalidSyntax,
+                    "Las vistas materializadas no pueden reemplazarse, sus filas se actualizan con REFRESH MATERIALIZED VIEW".to_string(),
+                ),
+                token,
+                token[inicio],
+            ));
+        }
+        inicio += 1;
+    }
+    if token.get(inicio).is_none_or(|t| t.to_uppercase() != "VIEW")
+        || token.len() < inicio + 4
+        || token[inicio + 2].to_uppercase() != "AS"
+    {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Los argumentos de la instruccion CREATE VIEW fueron escritos de manera incorrecta"
+                .to_string(),
+        ));
+    }
+    let definicion = token[inicio + 3..].join(" ");
+    let definicion = definicion.trim_end_matches(';').trim().to_string();
+    let consulta = parsear_subconsulta(token, inicio + 3)?;
+    if !matches!(consulta, Comandos::Select { .. }) {
+        return Err(en_token(
+            Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "Una vista solo puede definirse con una consulta SELECT".to_string(),
+            ),
+            token,
+            token[inicio + 3],
+        ));
+    }
+    if materializada {
+        return Ok(Comandos::CreateMaterializedView {
+            nombre: token[inicio + 1].to_string(),
+            definicion,
+            consulta: Box::new(consulta),
+        });
+    }
+    Ok(Comandos::CreateView {
+        nombre: token[inicio + 1].to_string(),
+        definicion,
+        consulta: Box::new(consulta),
+        reemplazar,
+    })
+}
+
+/// Esta funcion parsea a las consultas de tipo drop: DROP VIEW nombre.
+fn parser_drop(token: &[&str]) -> Result<Comandos, Errores> {
+    let token: Vec<&str> = token[1..]
+        .iter()
+        .map(|t| t.trim_end_matches(';'))
+        .filter(|t| !t.is_empty())
+        .collect();
+    match token.as_slice() {
+        [tipo, nombre] if tipo.to_uppercase() == "VIEW" => Ok(Comandos::DropView {
+            nombre: nombre.to_string(),
+        }),
+        _ => Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Los argumentos de la instruccion DROP fueron escritos de manera incorrecta, solo pueden eliminarse vistas con DROP VIEW nombre".to_string(),
+        )),
+    }
+}
+
+/// Esta funcion parsea a las consultas de tipo refresh: REFRESH MATERIALIZED VIEW nombre.
+fn parser_refresh(token: &[&str]) -> Result<Comandos, Errores> {
+    let token: Vec<&str> = token[1..]
+        .iter()
+        .map(|t| t.trim_end_matches(';'))
+        .filter(|t| !t.is_empty())
+        .collect();
+    match token.as_slice() {
+        [materializada, vista, nombre]
+            if materializada.to_uppercase() == "MATERIALIZED" && vista.to_uppercase() == "VIEW" =>
+        {
+            Ok(Comandos::RefreshMaterializedView {
+                nombre: nombre.to_string(),
+            })
+        }
+        _ => Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Los argumentos de la instruccion REFRESH MATERIALIZED VIEW fueron escritos de manera incorrecta".to_string(),
+        )),
+    }
+}
+
+/// Esta funcion parsea a las consultas de tipo create sequence: CREATE SEQUENCE nombre [START [WITH] n] [INCREMENT [BY] n].
+fn parser_create_sequence(consulta: &[&str]) -> Result<Comandos, Errores> {
+    let token: Vec<&str> = consulta
+        .iter()
+        .map(|t| t.trim_end_matches(';'))
+        .filter(|t| !t.is_empty())
+        .collect();
+    if token.len() < 3 {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Falta el nombre de la secuencia".to_string(),
+        ));
+    }
+    let mut inicio: i64 = 1;
+    let mut incremento: i64 = 1;
+    let mut indice = 3;
+    while indice < token.len() {
+        let opcion = token[indice];
+        let palabra = opcion.to_uppercase();
+        let (conector, destino) = match palabra.as_str() {
+            "START" => ("WITH", &mut inicio),
+            "INCREMENT" => ("BY", &mut incremento),
+            _ => {
+                return Err(en_token(
+                    Errores::nuevo(
+                        TipoError::InvalidSyntax,
+                        format!("Opcion de secuencia invalida: {}", token[indice]),
+                    ),
+                    consulta,
+                    token[indice],
+                ));
+            }
+        };
+        indice += 1;
+        if token
+            .get(indice)
+            .is_some_and(|t| t.to_uppercase() == conector)
+        {
+            indice += 1;
+        }
+        match token.get(indice).map(|t| t.parse::<i64>()) {
+            Some(Ok(valor)) => *destino = valor,
+            _ => {
+                return Err(en_token(
+                    Errores::nuevo(
+                        TipoError::InvalidSyntax,
+                        format!("Se esperaba un numero luego de {}", palabra),
+                    ),
+                    consulta,
+                    opcion,
+                ));
+            }
+        }
+        indice += 1;
+    }
+    Ok(Comandos::CreateSequence {
+        nombre: token[2].to_string(),
+        inicio,
+        incremento,
+    })
+}
+
+/// Esta funcion parsea a las consultas de tipo create index: CREATE [UNIQUE] INDEX nombre ON tabla (columna).
+fn parser_create_index(token: &[&str]) -> Result<Comandos, Errores> {
+    let unico = token[1].to_uppercase() == "UNIQUE";
+    let inicio = if unico { 3 } else { 2 };
+    let error = || {
+        Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Los argumentos de la instruccion CREATE INDEX fueron escritos de manera incorrecta"
+                .to_string(),
+        )
+    };
+    if token.len() < inicio + 3 || token[inicio + 1].to_uppercase() != "ON" {
+        return Err(error());
+    }
+    let destino = token[inicio + 2..].join(" ");
+    let destino = destino.trim().trim_end_matches(';').trim();
+    let (tabla, columna) = match destino.split_once('(') {
+        Some((tabla, resto)) => match resto.trim_end().strip_suffix(')') {
+            Some(columna) => (tabla.trim(), columna.trim()),
+            None => return Err(error()),
+        },
+        None => return Err(error()),
+    };
+    if tabla.is_empty() || columna.is_empty() || tabla.contains(' ') {
+        return Err(error());
+    }
+    if columna.contains(',') {
+        return Err(en_token(
+            Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "Los indices solo pueden crearse sobre una columna".to_string(),
+            ),
+            token,
+            columna,
+        ));
+    }
+    Ok(Comandos::CreateIndex {
+        nombre: token[inicio].to_string(),
+        tabla: tabla.to_string(),
+        columna: columna.to_string(),
+        unico,
+    })
+}
+
+/// Esta funcion parsea a las consultas de tipo reindex: REINDEX, REINDEX [INDEX] nombre o REINDEX [TABLE] tabla.
+fn parser_reindex(token: &[&str]) -> Result<Comandos, Errores> {
+    let token: Vec<String> = token[1..]
+        .iter()
+        .map(|t| t.trim_end_matches(';').to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let palabras: Vec<&str> = token.iter().map(|t| t.as_str()).collect();
+    match palabras.as_slice() {
+        [] => Ok(Comandos::Reindex { nombre: None }),
+        [tipo, nombre] if ["INDEX", "TABLE"].contains(&tipo.to_uppercase().as_str()) => {
+            Ok(Comandos::Reindex {
+                nombre: Some(nombre.to_string()),
+            })
+        }
+        [nombre] => Ok(Comandos::Reindex {
+            nombre: Some(nombre.to_string()),
+        }),
+        _ => Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Los argumentos de la instruccion REINDEX fueron escritos de manera incorrecta"
+                .to_string(),
+        )),
+    }
+}
+
+/// Esta funcion parsea a las consultas de tipo analyze: ANALYZE o ANALYZE tabla.
+fn parser_analyze(token: &[&str]) -> Result<Comandos, Errores> {
+    let token: Vec<&str> = token[1..]
+        .iter()
+        .map(|t| t.trim_end_matches(';'))
+        .filter(|t| !t.is_empty())
+        .collect();
+    match token.as_slice() {
+        [] => Ok(Comandos::Analyze { tabla: None }),
+        [tabla] => Ok(Comandos::Analyze {
+            tabla: Some(tabla.to_string()),
+        }),
+        _ => Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Los argumentos de la instruccion ANALYZE fueron escritos de manera incorrecta"
+                .to_string(),
+        )),
+    }
+}
+
+/// Esta funcion parsea a las instrucciones de control de transacciones: BEGIN [TRANSACTION], START TRANSACTION, COMMIT y ROLLBACK.
+fn parser_transaccion(token: &[&str]) -> Result<Comandos, Errores> {
+    let token: Vec<String> = token
+        .iter()
+        .map(|t| t.trim_end_matches(';').to_uppercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let palabras: Vec<&str> = token.iter().map(|t| t.as_str()).collect();
+    match palabras.as_slice() {
+        ["BEGIN"] | ["BEGIN", "TRANSACTION"] | ["START", "TRANSACTION"] => Ok(Comandos::Begin),
+        ["COMMIT"] | ["COMMIT", "TRANSACTION"] => Ok(Comandos::Commit),
+        ["ROLLBACK"] | ["ROLLBACK", "TRANSACTION"] => Ok(Comandos::Rollback),
+        _ => Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Instruccion de transaccion invalida".to_string(),
+        )),
+    }
+}
+
 /// Esta funcion sirve para que dado un arreglo y un elemento, se encuentre el indice del mismo.
+/// Esta funcion verifica la sintaxis de la clausula WHERE que comienza en el token indicado. Si es invalida, el error se ubica a partir del comienzo de la clausula.
+fn validar_where(token: &[&str], inicio: usize, clausula_where: &str) -> Result<(), Errores> {
+    parsear_expresion(adaptar_where(clausula_where.trim().trim_end_matches(';')))
+        .map(|_| ())
+        .map_err(|error| {
+            let posicion = token.get(inicio).and_then(|t| desplazamiento(token, t));
+            error.desplazar(posicion.unwrap_or(0))
+        })
+}
+
+/// Esta funcion parsea la consulta que comienza en el token indicado, como el SELECT de un INSERT o de un CREATE TABLE AS. Sus errores se ubican a partir de ese token.
+fn parsear_subconsulta(token: &[&str], inicio: usize) -> Result<Comandos, Errores> {
+    let mut subconsulta: Vec<&str> = token[inicio..].to_vec();
+    // El punto y coma final termina la consulta completa, no forma parte de la subconsulta.
+    if let Some(ultimo) = subconsulta.pop() {
+        let ultimo = ultimo.trim_end_matches(';');
+        if !ultimo.is_empty() {
+            subconsulta.push(ultimo);
+        }
+    }
+    let posicion = desplazamiento(token, token[inicio]).unwrap_or(0);
+    parsear_comando(&subconsulta).map_err(|error| error.desplazar(posicion))
+}
+
+/// Devuelve la posicion en bytes del fragmento, medida desde el comienzo del primer token. Los tokens son porciones del texto de la consulta, asi que la posicion es la distancia entre sus comienzos. Si el fragmento no es parte de los tokens, no se conoce.
+fn desplazamiento(token: &[&str], fragmento: &str) -> Option<usize> {
+    let inicio = token.first()?.as_ptr() as usize;
+    let ultimo = token.last()?;
+    let fin = ultimo.as_ptr() as usize + ultimo.len();
+    let posicion = fragmento.as_ptr() as usize;
+    (inicio..=fin)
+        .contains(&posicion)
+        .then(|| posicion - inicio)
+}
+
+/// Esta funcion indica en el error la palabra de la consulta que lo causo y, si es uno de los tokens, su posicion exacta.
+fn en_token(error: Errores, token: &[&str], palabra: &str) -> Errores {
+    match desplazamiento(token, palabra) {
+        Some(posicion) => error.en_token(palabra).en_desplazamiento(posicion),
+        None => error.en_token(palabra),
+    }
+}
+
 fn obtener_indice(token: &[&str], palabra: &str) -> Option<usize> {
     token.iter().position(|&t| t.to_uppercase() == palabra)
 }
@@ -288,13 +926,13 @@ fn eliminar_punto_y_coma(c_o: Vec<String>) -> Vec<String> {
 #[cfg(test)]
 mod tests {
     use crate::comandos::comandos_implementacion::parsear;
-    use crate::comandos::comandos_implementacion::Comandos;
-    use crate::errores::errores_implementacion::Errores;
+    use crate::comandos::comandos_implementacion::{Comandos, Join};
+    use crate::errores::errores_implementacion::TipoError;
     #[test]
     fn test_parser_insert_query_valida() {
         let comando =
             "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (111, 6, 'Laptop', 3)";
-        match parsear(&comando) {
+        match parsear(comando) {
             Ok(Comandos::Insert {
                 tabla,
                 into,
@@ -322,8 +960,8 @@ mod tests {
     #[test]
     fn test_parser_insert_query_invalida() {
         let comando = "INSERT INTO ordenes VALUES (111, 6, 'Laptop', 3)";
-        match parsear(&comando) {
-            Err(e) => assert_eq!(e, Errores::InvalidSyntax),
+        match parsear(comando) {
+            Err(e) => assert_eq!(e.tipo, TipoError::InvalidSyntax),
             _ => panic!("FALLO TEST INSERT"),
         }
     }
@@ -333,7 +971,7 @@ mod tests {
         let comando = "UPDATE clientes
         SET email = 'pitymartinez@912.com.es'
         WHERE id = 4";
-        match parsear(&comando) {
+        match parsear(comando) {
             Ok(Comandos::Update {
                 tabla,
                 clausula_set,
@@ -354,19 +992,42 @@ mod tests {
         let comando = "UPDATE clientes
         email = 'pitymartinez@912.com.es'
         WHERE id = 4";
-        match parsear(&comando) {
+        match parsear(comando) {
             Err(e) => {
-                assert_eq!(e, Errores::InvalidSyntax)
+                assert_eq!(e.tipo, TipoError::InvalidSyntax)
             }
             _ => panic!("FALLO TEST UPDATE"),
         }
     }
 
+    #[test]
+    fn test_parser_where_invalido_se_ubica_en_la_clausula() {
+        let comando = "UPDATE clientes SET nombre = 'x'\n  WHERE id = = 3";
+        let error = match parsear(comando) {
+            Err(error) => error.ubicar(comando),
+            _ => panic!("FALLO TEST UPDATE"),
+        };
+        assert_eq!(error.tipo, TipoError::InvalidSyntax);
+        let posicion = error.posicion.unwrap();
+        assert_eq!((posicion.linea, posicion.columna), (2, 12));
+    }
+
+    #[test]
+    fn test_parser_ubica_el_token_de_la_subconsulta() {
+        let comando = "INSERT INTO resumen (limite) SELECT id FROM ordenes LIMIT limite";
+        let error = match parsear(comando) {
+            Err(error) => error.ubicar(comando),
+            _ => panic!("FALLO TEST INSERT SELECT"),
+        };
+        assert_eq!(error.desplazamiento, Some(58));
+        assert_eq!(error.posicion.unwrap().columna, 59);
+    }
+
     #[test]
     fn test_parser_delete_query_valida() {
         let comando = "DELETE FROM clientes
         WHERE apellido = 'López'";
-        match parsear(&comando) {
+        match parsear(comando) {
             Ok(Comandos::Delete {
                 tabla,
                 clausula_where,
@@ -383,9 +1044,9 @@ mod tests {
     fn test_parser_delete_query_invalida() {
         let comando = "DELETE clientes
         WHERE apellido = 'López'";
-        match parsear(&comando) {
+        match parsear(comando) {
             Err(e) => {
-                assert_eq!(e, Errores::InvalidSyntax)
+                assert_eq!(e.tipo, TipoError::InvalidSyntax)
             }
             _ => panic!("FALLO TEST DELETE"),
         }
@@ -396,13 +1057,19 @@ mod tests {
         let comando = "SELECT id, nombre, email
         FROM clientes *
         ORDER BY email DESC";
-        match parsear(&comando) {
+        match parsear(comando) {
             Ok(Comandos::Select {
                 campos,
                 tabla,
+                joins,
                 clausula_where,
+                agrupar_por,
                 clausula_order,
+                limite,
             }) => {
+                assert!(joins.is_empty());
+                assert_eq!(limite, None);
+                assert!(agrupar_por.is_empty());
                 let campos_correcta =
                     vec!["id".to_string(), "nombre".to_string(), "email".to_string()];
                 let tabla_correcta: String = "clientes".to_string();
@@ -417,16 +1084,288 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parser_select_con_join_y_group_by() {
+        let comando = "SELECT ciudad, COUNT(*) FROM clientes JOIN ordenes ON clientes.id=ordenes.id_cliente INNER JOIN pagos ON pagos.id_orden = ordenes.id WHERE total > 10 GROUP BY ciudad ORDER BY ciudad LIMIT 3;";
+        match parsear(comando) {
+            Ok(Comandos::Select {
+                campos,
+                tabla,
+                joins,
+                clausula_where,
+                agrupar_por,
+                clausula_order,
+                limite,
+            }) => {
+                assert_eq!(campos, ["ciudad", "COUNT(*)"]);
+                assert_eq!(tabla, "clientes");
+                assert_eq!(
+                    joins,
+                    [
+                        Join {
+                            tabla: "ordenes".to_string(),
+                            columna_izquierda: "clientes.id".to_string(),
+                            columna_derecha: "ordenes.id_cliente".to_string(),
+                        },
+                        Join {
+                            tabla: "pagos".to_string(),
+                            columna_izquierda: "pagos.id_orden".to_string(),
+                            columna_derecha: "ordenes.id".to_string(),
+                        },
+                    ]
+                );
+                assert_eq!(clausula_where, "total > 10");
+                assert_eq!(agrupar_por, ["ciudad"]);
+                assert_eq!(clausula_order, ["ciudad"]);
+                assert_eq!(limite, Some(3));
+            }
+            _ => panic!("FALLO TEST SELECT"),
+        }
+        let error = parsear("SELECT * FROM clientes JOIN ordenes WHERE id = 1").unwrap_err();
+        assert_eq!(error.tipo, TipoError::InvalidSyntax);
+        let error = parsear("SELECT * FROM clientes JOIN ordenes ON id > id_cliente").unwrap_err();
+        assert_eq!(error.tipo, TipoError::InvalidSyntax);
+        let error = parsear("SELECT * FROM clientes LIMIT diez").unwrap_err();
+        assert_eq!(error.tipo, TipoError::InvalidSyntax);
+    }
+
+    #[test]
+    fn test_parser_explain() {
+        match parsear("EXPLAIN ANALYZE SELECT * FROM clientes") {
+            Ok(Comandos::Explain { analizar, consulta }) => {
+                assert!(analizar);
+                assert!(matches!(*consulta, Comandos::Select { .. }));
+            }
+            _ => panic!("FALLO TEST EXPLAIN"),
+        }
+        assert!(matches!(
+            parsear("EXPLAIN SELECT * FROM clientes"),
+            Ok(Comandos::Explain {
+                analizar: false,
+                ..
+            })
+        ));
+        assert!(parsear("EXPLAIN UPDATE clientes SET id = 1").is_err());
+        assert!(parsear("EXPLAIN").is_err());
+    }
+
     #[test]
     fn test_parser_select_query_invalida() {
         let comando = "SELECT id, nombre, email
         FROM clientes *
         ORDER";
-        match parsear(&comando) {
+        match parsear(comando) {
             Err(e) => {
-                assert_eq!(e, Errores::InvalidSyntax)
+                assert_eq!(e.tipo, TipoError::InvalidSyntax)
             }
             _ => panic!("FALLO TEST SELECT"),
         }
     }
+
+    #[test]
+    fn test_parser_create_table_valida() {
+        let comando = "CREATE TABLE ordenes (id INT PRIMARY KEY, producto VARCHAR(20) NOT NULL, UNIQUE (id, producto))";
+        match parsear(comando) {
+            Ok(Comandos::CreateTable {
+                tabla,
+                definiciones,
+            }) => {
+                let definiciones_correctas: Vec<String> = vec![
+                    "id INT PRIMARY KEY".to_string(),
+                    "producto VARCHAR(20) NOT NULL".to_string(),
+                    "UNIQUE (id, producto)".to_string(),
+                ];
+                assert_eq!(tabla, "ordenes".to_string());
+                assert_eq!(definiciones, definiciones_correctas);
+            }
+            _ => panic!("FALLO TEST CREATE TABLE"),
+        }
+    }
+
+    #[test]
+    fn test_parser_create_sequence() {
+        let comando = "CREATE SEQUENCE numeros START WITH 100 INCREMENT BY 10;";
+        match parsear(comando) {
+            Ok(Comandos::CreateSequence {
+                nombre,
+                inicio,
+                incremento,
+            }) => {
+                assert_eq!(nombre, "numeros".to_string());
+                assert_eq!(inicio, 100);
+                assert_eq!(incremento, 10);
+            }
+            _ => panic!("FALLO TEST CREATE SEQUENCE"),
+        }
+    }
+
+    #[test]
+    fn test_parser_create_index() {
+        match parsear("CREATE UNIQUE INDEX clientes_email ON clientes(email);") {
+            Ok(Comandos::CreateIndex {
+                nombre,
+                tabla,
+                columna,
+                unico,
+            }) => {
+                assert_eq!(nombre, "clientes_email".to_string());
+                assert_eq!(tabla, "clientes".to_string());
+                assert_eq!(columna, "email".to_string());
+                assert!(unico);
+            }
+            _ => panic!("FALLO TEST CREATE INDEX"),
+        }
+        assert!(matches!(
+            parsear("CREATE INDEX ordenes_id ON ordenes ( id )"),
+            Ok(Comandos::CreateIndex { unico: false, .. })
+        ));
+        assert!(matches!(
+            parsear("CREATE INDEX dos ON ordenes (id, producto)").map_err(|e| e.tipo),
+            Err(TipoError::InvalidSyntax)
+        ));
+        assert!(matches!(
+            parsear("CREATE INDEX ordenes_id ordenes (id)").map_err(|e| e.tipo),
+            Err(TipoError::InvalidSyntax)
+        ));
+        assert!(matches!(
+            parsear("REINDEX;"),
+            Ok(Comandos::Reindex { nombre: None })
+        ));
+        assert!(matches!(
+            parsear("REINDEX TABLE ordenes"),
+            Ok(Comandos::Reindex { nombre: Some(n) }) if n == "ordenes"
+        ));
+        assert!(matches!(
+            parsear("ANALYZE;"),
+            Ok(Comandos::Analyze { tabla: None })
+        ));
+        assert!(matches!(
+            parsear("analyze ordenes ;"),
+            Ok(Comandos::Analyze { tabla: Some(t) }) if t == "ordenes"
+        ));
+        assert!(matches!(
+            parsear("ANALYZE ordenes clientes").map_err(|e| e.tipo),
+            Err(TipoError::InvalidSyntax)
+        ));
+    }
+
+    #[test]
+    fn test_parser_create_y_drop_view() {
+        match parsear("CREATE OR REPLACE VIEW grandes AS SELECT id, cantidad FROM ordenes WHERE cantidad > 1;") {
+            Ok(Comandos::CreateView {
+                nombre,
+                definicion,
+                consulta,
+                reemplazar,
+            }) => {
+                assert_eq!(nombre, "grandes".to_string());
+                assert_eq!(
+                    definicion,
+                    "SELECT id, cantidad FROM ordenes WHERE cantidad > 1".to_string()
+                );
+                assert!(matches!(*consulta, Comandos::Select { .. }));
+                assert!(reemplazar);
+            }
+            _ => panic!("FALLO TEST CREATE VIEW"),
+        }
+        assert!(matches!(
+            parsear("CREATE VIEW todas AS SELECT * FROM ordenes"),
+            Ok(Comandos::CreateView {
+                reemplazar: false,
+                ..
+            })
+        ));
+        for consulta in [
+            "CREATE VIEW todas SELECT * FROM ordenes",
+            "CREATE VIEW todas AS DELETE FROM ordenes",
+            "CREATE OR VIEW todas AS SELECT * FROM ordenes",
+            "DROP TABLE ordenes",
+        ] {
+            assert!(matches!(
+                parsear(consulta).map_err(|e| e.tipo),
+                Err(TipoError::InvalidSyntax)
+            ));
+        }
+        assert!(matches!(
+            parsear("DROP VIEW grandes;"),
+            Ok(Comandos::DropView { nombre }) if nombre == "grandes"
+        ));
+        assert!(matches!(
+            parsear("CREATE MATERIALIZED VIEW totales AS SELECT id_cliente, COUNT(*) FROM ordenes GROUP BY id_cliente"),
+            Ok(Comandos::CreateMaterializedView { nombre, .. }) if nombre == "totales"
+        ));
+        assert!(matches!(
+            parsear("REFRESH MATERIALIZED VIEW totales;"),
+            Ok(Comandos::RefreshMaterializedView { nombre }) if nombre == "totales"
+        ));
+        for consulta in [
+            "CREATE OR REPLACE MATERIALIZED VIEW totales AS SELECT * FROM ordenes",
+            "REFRESH VIEW totales",
+        ] {
+            assert!(matches!(
+                parsear(consulta).map_err(|e| e.tipo),
+                Err(TipoError::InvalidSyntax)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parser_insert_select_y_create_table_as() {
+        match parsear("INSERT INTO resumen (id, cantidad) SELECT id, cantidad FROM ordenes WHERE producto = 'VALUES';") {
+            Ok(Comandos::InsertSelect {
+                tabla,
+                into,
+                consulta,
+                posicion_consulta,
+            }) => {
+                assert_eq!(tabla, "resumen".to_string());
+                assert_eq!(into, vec!["id".to_string(), "cantidad".to_string()]);
+                assert!(matches!(*consulta, Comandos::Select { .. }));
+                assert_eq!(posicion_consulta, 35);
+            }
+            _ => panic!("FALLO TEST INSERT SELECT"),
+        }
+        assert!(matches!(
+            parsear("INSERT INTO resumen SELECT * FROM ordenes"),
+            Ok(Comandos::InsertSelect { into, .. }) if into.is_empty()
+        ));
+        assert!(matches!(
+            parsear("INSERT INTO ordenes (id, producto) VALUES (1, 'SELECT')"),
+            Ok(Comandos::Insert { .. })
+        ));
+        assert!(matches!(
+            parsear("CREATE TABLE copia AS SELECT id, producto FROM ordenes;"),
+            Ok(Comandos::CreateTableAs { tabla, .. }) if tabla == "copia"
+        ));
+        for consulta in [
+            "INSERT resumen SELECT * FROM ordenes",
+            "CREATE TABLE copia AS DELETE FROM ordenes",
+        ] {
+            assert!(matches!(
+                parsear(consulta).map_err(|e| e.tipo),
+                Err(TipoError::InvalidSyntax)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parser_transacciones() {
+        assert!(matches!(parsear("BEGIN;"), Ok(Comandos::Begin)));
+        assert!(matches!(parsear("start transaction"), Ok(Comandos::Begin)));
+        assert!(matches!(parsear("COMMIT"), Ok(Comandos::Commit)));
+        assert!(matches!(parsear("ROLLBACK ;"), Ok(Comandos::Rollback)));
+        assert!(matches!(
+            parsear("ROLLBACK TO ahora").map_err(|e| e.tipo),
+            Err(TipoError::InvalidSyntax)
+        ));
+    }
+
+    #[test]
+    fn test_parser_create_table_invalida() {
+        let comando = "CREATE TABLE ordenes id INT PRIMARY KEY";
+        match parsear(comando) {
+            Err(e) => assert_eq!(e.tipo, TipoError::InvalidSyntax),
+            _ => panic!("FALLO TEST CREATE TABLE"),
+        }
+    }
 }
@@ -1 +1,3 @@
 pub mod comandos_implementacion;
+pub mod parametros;
+pub mod sentencias;
@@ -0,0 +1,462 @@
+use crate::comandos::comandos_implementacion::{parsear, Comandos};
+use crate::comandos::sentencias::separar_sentencias;
+use crate::ejecutor::resultado::Valor;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+
+/// Caracter que delimita los marcadores de parametros dentro de una consulta ya parseada. No puede aparecer en las consultas escritas por el usuario.
+const DELIMITADOR: char = '\u{1A}';
+
+/// Representa los estilos de parametros que pueden usarse en una consulta. Una misma consulta no puede mezclarlos.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Estilo {
+    /// Parametros '?', numerados en el orden en que aparecen.
+    Signo,
+    /// Parametros '$1', '$2', etc.
+    Numerado,
+    /// Parametros ':nombre'. Todas las apariciones del mismo nombre toman el mismo valor.
+    Nombrado,
+}
+
+/// Representa una consulta parseada una unica vez, con parametros a los que se les asignan valores antes de cada ejecucion.
+/// Los parametros se reemplazan en la consulta por marcadores que los separadores de la consulta tratan como una sola palabra. Al ejecutarla, cada marcador se reemplaza por otro que contiene el valor codificado en hexadecimal, que solo se decodifica donde se usa el valor (en la clausula WHERE, en los valores de un INSERT y en la clausula SET), por lo que el valor nunca se interpreta como parte de la consulta.
+#[derive(Debug, Clone)]
+pub struct SentenciaPreparada {
+    comando: Comandos,
+    /// Nombre de cada parametro, si la consulta usa parametros ':nombre'.
+    nombres: Vec<Option<String>>,
+    valores: Vec<Option<Valor>>,
+}
+
+impl SentenciaPreparada {
+    /// Esta funcion parsea la consulta, que puede contener parametros '?', '$n' o ':nombre' fuera de los textos entre comillas.
+    pub fn preparar(sql: &str) -> Result<SentenciaPreparada, Errores> {
+        let sentencia = una_sentencia(sql)?;
+        if sentencia.contains(DELIMITADOR) {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "La consulta contiene caracteres invalidos".to_string(),
+            ));
+        }
+        let (texto, nombres) = reemplazar_parametros(&sentencia)?;
+        let comando = parsear(&texto)?;
+        if !nombres.is_empty()
+            && !matches!(
+                comando,
+                Comandos::Insert { .. }
+                    | Comandos::InsertSelect { .. }
+                    | Comandos::Update { .. }
+                    | Comandos::Delete { .. }
+                    | Comandos::Select { .. }
+            )
+        {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "Solo pueden usarse parametros en INSERT, UPDATE, DELETE y SELECT".to_string(),
+            ));
+        }
+        let valores = vec![None; nombres.len()];
+        Ok(SentenciaPreparada {
+            comando,
+            nombres,
+            valores,
+        })
+    }
+
+    /// Devuelve la cantidad de parametros de la consulta.
+    pub fn cantidad_parametros(&self) -> usize {
+        self.valores.len()
+    }
+
+    /// Esta funcion asigna el valor del parametro indicado, contando desde 1.
+    /// Los textos no pueden contener comas ni saltos de linea, ya que no pueden guardarse en las tablas.
+    pub fn asignar(&mut self, posicion: usize, valor: Valor) -> Result<(), Errores> {
+        if posicion == 0 || posicion > self.valores.len() {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                format!(
+                    "La consulta no tiene un parametro {} (tiene {})",
+                    posicion,
+                    self.valores.len()
+                ),
+            ));
+        }
+        if let Valor::Texto(texto) = &valor {
+            if texto.contains([',', '\n', '\r']) {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Los valores de los parametros no pueden contener comas ni saltos de linea"
+                        .to_string(),
+                ));
+            }
+        }
+        self.valores[posicion - 1] = Some(valor);
+        Ok(())
+    }
+
+    /// Esta funcion asigna el valor del parametro con el nombre indicado, con o sin los ':' iniciales.
+    pub fn asignar_por_nombre(&mut self, nombre: &str, valor: Valor) -> Result<(), Errores> {
+        let nombre = nombre.trim_start_matches(':');
+        match self
+            .nombres
+            .iter()
+            .position(|n| n.as_deref() == Some(nombre))
+        {
+            Some(indice) => self.asignar(indice + 1, valor),
+            None => Err(Errores::nuevo(
+                TipoError::Error,
+                format!("La consulta no tiene un parametro :{}", nombre),
+            )),
+        }
+    }
+
+    /// Esta funcion quita los valores asignados a los parametros.
+    pub fn limpiar(&mut self) {
+        self.valores = vec![None; self.valores.len()];
+    }
+
+    /// Esta funcion devuelve la consulta con los valores asignados. Falla si algun parametro no tiene valor.
+    pub fn comando(&self) -> Result<Comandos, Errores> {
+        let mut valores: Vec<String> = Vec::new();
+        for (indice, valor) in self.valores.iter().enumerate() {
+            match valor {
+                Some(valor) => valores.push(marcador_valor(&valor.to_string())),
+                None => {
+                    let nombre = match &self.nombres[indice] {
+                        Some(nombre) => format!(":{}", nombre),
+                        None => (indice + 1).to_string(),
+                    };
+                    return Err(Errores::nuevo(
+                        TipoError::Error,
+                        format!("Falta asignar el valor del parametro {}", nombre),
+                    ));
+                }
+            }
+        }
+        Ok(sustituir(&self.comando, &|texto| {
+            reemplazar_marcadores(texto, &valores)
+        }))
+    }
+}
+
+/// Esta funcion devuelve la unica sentencia del texto, sin el ';' final ni comentarios. Falla si el texto contiene varias sentencias o ninguna.
+pub fn una_sentencia(sql: &str) -> Result<String, Errores> {
+    let (mut sentencias, resto) = separar_sentencias(sql);
+    if !resto.trim().is_empty() {
+        sentencias.push(resto.trim().to_string());
+    }
+    if sentencias.len() != 1 {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Debe indicarse exactamente una sentencia".to_string(),
+        ));
+    }
+    Ok(sentencias.remove(0))
+}
+
+/// Esta funcion devuelve el valor contenido en el marcador, si el texto es el marcador de un parametro con valor asignado.
+pub fn decodificar_parametro(texto: &str) -> Option<String> {
+    let hexadecimal = texto.strip_prefix(DELIMITADOR)?.strip_suffix(DELIMITADOR)?;
+    if hexadecimal.len() % 2 != 0 {
+        return None;
+    }
+    let bytes: Option<Vec<u8>> = (0..hexadecimal.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hexadecimal.get(i..i + 2)?, 16).ok())
+        .collect();
+    String::from_utf8(bytes?).ok()
+}
+
+/// Devuelve el marcador que reemplaza al parametro en la posicion indicada, contando desde 1.
+fn marcador_posicion(posicion: usize) -> String {
+    format!("{}#{}{}", DELIMITADOR, posicion, DELIMITADOR)
+}
+
+/// Devuelve el marcador que contiene el valor codificado en hexadecimal.
+fn marcador_valor(valor: &str) -> String {
+    let hexadecimal: String = valor.bytes().map(|b| format!("{:02x}", b)).collect();
+    format!("{}{}{}", DELIMITADOR, hexadecimal, DELIMITADOR)
+}
+
+/// Reemplaza los parametros de la consulta por marcadores de posicion. Devuelve la consulta y el nombre de cada parametro, si tiene.
+fn reemplazar_parametros(sentencia: &str) -> Result<(String, Vec<Option<String>>), Errores> {
+    let mut texto = String::new();
+    let mut nombres: Vec<Option<String>> = Vec::new();
+    let mut estilo: Option<Estilo> = None;
+    let mut entre_comillas = false;
+    let caracteres: Vec<char> = sentencia.chars().collect();
+    let mut i = 0;
+    while i < caracteres.len() {
+        let caracter = caracteres[i];
+        if caracter == '\'' {
+            entre_comillas = !entre_comillas;
+        }
+        let siguiente = caracteres.get(i + 1).copied().unwrap_or(' ');
+        let (estilo_actual, posicion, largo) = match caracter {
+            _ if entre_comillas => (None, 0, 0),
+            '?' => (Some(Estilo::Signo), nombres.len() + 1, 1),
+            '$' if siguiente.is_ascii_digit() => {
+                let digitos: String = caracteres[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect();
+                let posicion = digitos.parse::<usize>().unwrap_or(0);
+                (Some(Estilo::Numerado), posicion, digitos.len() + 1)
+            }
+            ':' if siguiente.is_alphabetic() || siguiente == '_' => {
+                let nombre: String = caracteres[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || **c == '_')
+                    .collect();
+                let largo = nombre.chars().count() + 1;
+                let posicion = match nombres.iter().position(|n| n.as_ref() == Some(&nombre)) {
+                    Some(indice) => indice + 1,
+                    None => {
+                        nombres.push(Some(nombre));
+                        nombres.len()
+                    }
+                };
+                (Some(Estilo::Nombrado), posicion, largo)
+            }
+            _ => (None, 0, 0),
+        };
+        let estilo_actual = match estilo_actual {
+            Some(estilo_actual) => estilo_actual,
+            None => {
+                texto.push(caracter);
+                i += 1;
+                continue;
+            }
+        };
+        if estilo.is_some_and(|e| e != estilo_actual) {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "No pueden mezclarse parametros '?', '$n' y ':nombre' en una consulta".to_string(),
+            ));
+        }
+        if posicion == 0 {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "Los parametros '$n' se numeran desde 1".to_string(),
+            ));
+        }
+        estilo = Some(estilo_actual);
+        while nombres.len() < posicion {
+            nombres.push(None);
+        }
+        texto.push_str(&marcador_posicion(posicion));
+        i += largo;
+    }
+    Ok((texto, nombres))
+}
+
+/// Reemplaza los marcadores de posicion del texto por los marcadores con los valores correspondientes.
+fn reemplazar_marcadores(texto: &str, valores: &[String]) -> String {
+    let mut resultado = String::new();
+    let mut partes = texto.split(DELIMITADOR);
+    if let Some(primera) = partes.next() {
+        resultado.push_str(primera);
+    }
+    // Las partes impares quedan entre dos delimitadores, por lo que son el contenido de un marcador.
+    let mut es_marcador = true;
+    for parte in partes {
+        let valor = parte
+            .strip_prefix('#')
+            .and_then(|p| p.parse::<usize>().ok())
+            .and_then(|posicion| valores.get(posicion.wrapping_sub(1)));
+        match (es_marcador, valor) {
+            (true, Some(valor)) => resultado.push_str(valor),
+            (true, None) => {
+                resultado.push(DELIMITADOR);
+                resultado.push_str(parte);
+                resultado.push(DELIMITADOR);
+            }
+            (false, _) => resultado.push_str(parte),
+        }
+        es_marcador = !es_marcador;
+    }
+    resultado
+}
+
+/// Devuelve una copia del comando aplicando la funcion a cada uno de sus textos.
+fn sustituir(comando: &Comandos, funcion: &dyn Fn(&str) -> String) -> Comandos {
+    let lista = |textos: &Vec<String>| textos.iter().map(|t| funcion(t)).collect();
+    match comando {
+        Comandos::Insert {
+            tabla,
+            into,
+            valores,
+        } => Comandos::Insert {
+            tabla: tabla.to_string(),
+            into: into.clone(),
+            valores: lista(valores),
+        },
+        Comandos::InsertSelect {
+            tabla,
+            into,
+            consulta,
+            posicion_consulta,
+        } => Comandos::InsertSelect {
+            tabla: tabla.to_string(),
+            into: into.clone(),
+            consulta: Box::new(sustituir(consulta, funcion)),
+            posicion_consulta: *posicion_consulta,
+        },
+        Comandos::Update {
+            tabla,
+            clausula_set,
+            clausula_where,
+        } => Comandos::Update {
+            tabla: tabla.to_string(),
+            clausula_set: funcion(clausula_set),
+            clausula_where: funcion(clausula_where),
+        },
+        Comandos::Delete {
+            tabla,
+            clausula_where,
+        } => Comandos::Delete {
+            tabla: tabla.to_string(),
+            clausula_where: funcion(clausula_where),
+        },
+        Comandos::Select {
+            campos,
+            tabla,
+            joins,
+            clausula_where,
+            agrupar_por,
+            clausula_order,
+            limite,
+        } => Comandos::Select {
+            campos: campos.clone(),
+            tabla: tabla.to_string(),
+            joins: joins.clone(),
+            clausula_where: funcion(clausula_where),
+            agrupar_por: agrupar_por.clone(),
+            clausula_order: clausula_order.clone(),
+            limite: *limite,
+        },
+        otro => otro.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decodificar_parametro, marcador_valor, SentenciaPreparada};
+    use crate::comandos::comandos_implementacion::Comandos;
+    use crate::ejecutor::ejecutor_implementacion::ejecutar_comando;
+    use crate::ejecutor::ejecutor_implementacion::test::crear_directorio_prueba;
+    use crate::ejecutor::resultado::Valor;
+    use crate::errores::errores_implementacion::{Errores, TipoError};
+
+    #[test]
+    fn test_marcadores() {
+        let marcador = marcador_valor("O'Brien ñ");
+        assert_eq!(
+            decodificar_parametro(&marcador),
+            Some("O'Brien ñ".to_string())
+        );
+        assert_eq!(marcador.split_whitespace().count(), 1);
+        assert_eq!(decodificar_parametro("nombre"), None);
+    }
+
+    #[test]
+    fn test_preparar_estilos() {
+        let sentencia =
+            SentenciaPreparada::preparar("SELECT * FROM t WHERE a = ? AND b = '?' OR c = ?;")
+                .unwrap();
+        assert_eq!(sentencia.cantidad_parametros(), 2);
+        let sentencia =
+            SentenciaPreparada::preparar("SELECT * FROM t WHERE a = $2 OR b = $1").unwrap();
+        assert_eq!(sentencia.cantidad_parametros(), 2);
+        let mut sentencia = SentenciaPreparada::preparar(
+            "SELECT * FROM t WHERE a = :valor OR b = :valor OR c = '10:30'",
+        )
+        .unwrap();
+        assert_eq!(sentencia.cantidad_parametros(), 1);
+        assert!(sentencia
+            .asignar_por_nombre(":valor", Valor::Entero(1))
+            .is_ok());
+        assert_eq!(
+            sentencia
+                .asignar_por_nombre("otro", Valor::Entero(1))
+                .map_err(|e| e.tipo),
+            Err(TipoError::Error)
+        );
+        assert!(matches!(
+            SentenciaPreparada::preparar("SELECT * FROM t WHERE a = ? OR b = $1"),
+            Err(Errores {
+                tipo: TipoError::InvalidSyntax,
+                ..
+            })
+        ));
+        assert!(matches!(
+            SentenciaPreparada::preparar("CREATE TABLE t (a INT DEFAULT ?)"),
+            Err(Errores {
+                tipo: TipoError::InvalidSyntax,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parametros_no_se_interpretan_como_sql() {
+        let path = crear_directorio_prueba(
+            "parametros",
+            &[(
+                "clientes.csv",
+                "id,nombre,apellido\n1,Juan,Pérez\n2,Ana,López\n",
+            )],
+        );
+        let mut insert = SentenciaPreparada::preparar(
+            "INSERT INTO clientes (id, nombre, apellido) VALUES ($1, $2, $3)",
+        )
+        .unwrap();
+        insert.asignar(1, Valor::Entero(3)).unwrap();
+        insert
+            .asignar(2, Valor::Texto("Ana' OR 'a' = 'a".to_string()))
+            .unwrap();
+        assert!(matches!(
+            insert.comando(),
+            Err(Errores {
+                tipo: TipoError::Error,
+                ..
+            })
+        ));
+        insert
+            .asignar(3, Valor::Texto("nombre".to_string()))
+            .unwrap();
+        let comando: Comandos = insert.comando().unwrap();
+        assert_eq!(ejecutar_comando(comando, &path).unwrap().filas_afectadas, 1);
+
+        let mut select =
+            SentenciaPreparada::preparar("SELECT id FROM clientes WHERE nombre = ?").unwrap();
+        select
+            .asignar(1, Valor::Texto("Ana' OR 'a' = 'a".to_string()))
+            .unwrap();
+        let resultado = ejecutar_comando(select.comando().unwrap(), &path).unwrap();
+        assert_eq!(resultado.filas, vec![vec![Valor::Entero(3)]]);
+
+        let mut update = SentenciaPreparada::preparar(
+            "UPDATE clientes SET nombre = :nuevo WHERE apellido = :apellido",
+        )
+        .unwrap();
+        update
+            .asignar_por_nombre("nuevo", Valor::Texto("Luz = Sol".to_string()))
+            .unwrap();
+        update
+            .asignar_por_nombre("apellido", Valor::Texto("nombre".to_string()))
+            .unwrap();
+        let resultado = ejecutar_comando(update.comando().unwrap(), &path).unwrap();
+        assert_eq!(resultado.filas_afectadas, 1);
+        let tabla = std::fs::read_to_string(format!("{}/clientes.csv", path)).unwrap();
+        assert_eq!(
+            tabla,
+            "id,nombre,apellido\n1,Juan,Pérez\n2,Ana,López\n3,Luz = Sol,nombre\n"
+        );
+        assert_eq!(
+            select
+                .asignar(1, Valor::Texto("a,b".to_string()))
+                .map_err(|e| e.tipo),
+            Err(TipoError::Error)
+        );
+    }
+}
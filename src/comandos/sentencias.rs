@@ -0,0 +1,129 @@
+/// Esta funcion separa el texto en sentencias terminadas en ';'. Los ';' dentro de textos entre comillas simples no separan sentencias.
+/// Los comentarios de linea ('--' hasta el final de la linea) y de bloque ('/* ... */') fuera de las comillas se descartan.
+/// Devuelve las sentencias completas, sin el ';' final, y el texto restante que todavia no fue terminado. Si el texto termina dentro de un comentario de bloque, el comentario se conserva en el resto para que pueda cerrarse con el texto siguiente.
+pub fn separar_sentencias(texto: &str) -> (Vec<String>, String) {
+    let (sentencias, (_, resto)) = separar_sentencias_con_lineas(texto);
+    (
+        sentencias
+            .into_iter()
+            .map(|(_, sentencia)| sentencia)
+            .collect(),
+        resto,
+    )
+}
+
+/// Esta funcion separa el texto en sentencias como `separar_sentencias`, e indica ademas la linea del texto en la que comienza cada una, y la del texto restante, contando desde 1.
+/// Los saltos de linea de los comentarios de bloque se conservan, para que las lineas dentro de cada sentencia sigan siendo las del texto.
+pub fn separar_sentencias_con_lineas(texto: &str) -> (Vec<(usize, String)>, (usize, String)) {
+    let mut sentencias: Vec<(usize, String)> = Vec::new();
+    let mut actual = String::new();
+    let mut entre_comillas = false;
+    let mut linea = 1;
+    let mut linea_inicio: Option<usize> = None;
+    let mut caracteres = texto.chars().peekable();
+    while let Some(caracter) = caracteres.next() {
+        if caracter == '\n' {
+            linea += 1;
+        }
+        match caracter {
+            '\'' => {
+                entre_comillas = !entre_comillas;
+                linea_inicio.get_or_insert(linea);
+                actual.push(caracter);
+            }
+            '-' if !entre_comillas && caracteres.peek() == Some(&'-') => {
+                for siguiente in caracteres.by_ref() {
+                    if siguiente == '\n' {
+                        linea += 1;
+                        actual.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if !entre_comillas && caracteres.peek() == Some(&'*') => {
+                caracteres.next();
+                let mut comentario = String::from("/*");
+                let mut cerrado = false;
+                while let Some(siguiente) = caracteres.next() {
+                    comentario.push(siguiente);
+                    if siguiente == '*' && caracteres.peek() == Some(&'/') {
+                        caracteres.next();
+                        cerrado = true;
+                        break;
+                    }
+                }
+                if !cerrado {
+                    actual.push_str(&comentario);
+                    return (sentencias, (linea_inicio.unwrap_or(linea), actual));
+                }
+                let saltos = comentario.chars().filter(|c| *c == '\n').count();
+                linea += saltos;
+                actual.push(' ');
+                actual.push_str(&"\n".repeat(saltos));
+            }
+            ';' if !entre_comillas => {
+                if !actual.trim().is_empty() {
+                    sentencias.push((linea_inicio.unwrap_or(linea), actual.trim().to_string()));
+                }
+                actual.clear();
+                linea_inicio = None;
+            }
+            _ => {
+                if !caracter.is_whitespace() {
+                    linea_inicio.get_or_insert(linea);
+                }
+                actual.push(caracter);
+            }
+        }
+    }
+    (sentencias, (linea_inicio.unwrap_or(linea), actual))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{separar_sentencias, separar_sentencias_con_lineas};
+
+    #[test]
+    fn test_separar_sentencias() {
+        let (sentencias, resto) = separar_sentencias(
+            "INSERT INTO t (a) VALUES ('x;y');\nSELECT *\nFROM t; ;DELETE FROM t",
+        );
+        assert_eq!(
+            sentencias,
+            vec![
+                "INSERT INTO t (a) VALUES ('x;y')".to_string(),
+                "SELECT *\nFROM t".to_string(),
+            ]
+        );
+        assert_eq!(resto, "DELETE FROM t");
+    }
+
+    #[test]
+    fn test_separar_sentencias_con_comentarios() {
+        let (sentencias, resto) = separar_sentencias(
+            "-- datos iniciales; no es una sentencia\nINSERT INTO t (a) VALUES ('--x', '/*y*/'); /* comentario\n; de bloque */ DELETE FROM t -- fin\n; SELECT * /* sin cerrar;",
+        );
+        assert_eq!(
+            sentencias,
+            vec![
+                "INSERT INTO t (a) VALUES ('--x', '/*y*/')".to_string(),
+                "DELETE FROM t".to_string(),
+            ]
+        );
+        assert_eq!(resto, " SELECT * /* sin cerrar;");
+        let (sentencias, resto) = separar_sentencias(&format!("{} */ FROM t;", resto));
+        assert_eq!(sentencias, vec!["SELECT *   FROM t".to_string()]);
+        assert!(resto.is_empty());
+    }
+
+    #[test]
+    fn test_separar_sentencias_con_lineas() {
+        let (sentencias, resto) = separar_sentencias_con_lineas(
+            "-- inicio\nSELECT * FROM t; /* varias\nlineas */\n\n  DELETE\nFROM t;\nSELECT 'a\nb' FROM t; UPDATE",
+        );
+        let lineas: Vec<usize> = sentencias.iter().map(|(linea, _)| *linea).collect();
+        assert_eq!(lineas, vec![2, 5, 7]);
+        assert_eq!(sentencias[1].1, "DELETE\nFROM t");
+        assert_eq!(resto, (8, " UPDATE".to_string()));
+    }
+}
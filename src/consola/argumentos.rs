@@ -0,0 +1,187 @@
+use crate::comandos::parametros::SentenciaPreparada;
+use crate::ejecutor::resultado::Valor;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::formato::formato_implementacion::{Formato, OpcionesFormato};
+
+/// Representa los argumentos con los que se ejecuto el programa.
+#[derive(Debug, PartialEq, Default)]
+pub struct Argumentos {
+    /// Directorio donde se encuentran las tablas.
+    pub directorio: String,
+    /// Consulta indicada directamente en la linea de comandos.
+    pub consulta: Option<String>,
+    /// Archivo con sentencias a ejecutar. "-" indica la entrada estandar.
+    pub archivo: Option<String>,
+    /// Indica si un script debe seguir ejecutandose despues de una sentencia fallida.
+    pub continuar_ante_error: bool,
+    /// Formato con el que se muestran los resultados de los SELECT.
+    pub formato: OpcionesFormato,
+    /// Valores de los parametros de la consulta, en orden o de la forma ":nombre=valor".
+    pub parametros: Vec<String>,
+}
+
+/// Esta funcion interpreta los argumentos del programa, sin incluir el nombre del ejecutable.
+/// El primer argumento que no es una opcion es el directorio de las tablas y el segundo, si existe, la consulta.
+pub fn parsear_argumentos(args: &[String]) -> Result<Argumentos, Errores> {
+    let mut argumentos = Argumentos::default();
+    let mut posicionales: Vec<String> = Vec::new();
+    let mut iterador = args.iter();
+    while let Some(argumento) = iterador.next() {
+        match argumento.as_str() {
+            "--file" | "-f" => match iterador.next() {
+                Some(archivo) => argumentos.archivo = Some(archivo.to_string()),
+                None => {
+                    return Err(Errores::nuevo(
+                        TipoError::Error,
+                        format!("Falta el archivo luego de {}", argumento),
+                    ));
+                }
+            },
+            "--format" => match iterador.next().and_then(|f| Formato::desde_nombre(f)) {
+                Some(formato) => argumentos.formato.formato = formato,
+                None => {
+                    return Err(Errores::nuevo(
+                        TipoError::Error,
+                        "--format debe ser table, csv, tsv, list, json, jsonl, markdown o html"
+                            .to_string(),
+                    ));
+                }
+            },
+            "--param" => match iterador.next() {
+                Some(valor) => argumentos.parametros.push(valor.to_string()),
+                None => {
+                    return Err(Errores::nuevo(
+                        TipoError::Error,
+                        "Falta el valor luego de --param".to_string(),
+                    ));
+                }
+            },
+            "--header" => argumentos.formato.encabezado = true,
+            "--no-header" => argumentos.formato.encabezado = false,
+            "--changes" => argumentos.formato.filas_afectadas = true,
+            "--continue-on-error" => argumentos.continuar_ante_error = true,
+            "--stop-on-error" => argumentos.continuar_ante_error = false,
+            opcion if opcion.starts_with("--") => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    format!("Opción desconocida: {}", opcion),
+                ));
+            }
+            _ => posicionales.push(argumento.to_string()),
+        }
+    }
+    let mut posicionales = posicionales.into_iter();
+    argumentos.directorio = match posicionales.next() {
+        Some(directorio) => directorio,
+        None => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Los argumentos del programa fueron ingresados de manera incorrecta".to_string(),
+            ));
+        }
+    };
+    argumentos.consulta = posicionales.next();
+    if posicionales.next().is_some()
+        || (argumentos.consulta.is_some() && argumentos.archivo.is_some())
+        || (argumentos.consulta.is_none() && !argumentos.parametros.is_empty())
+    {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Los argumentos del programa fueron ingresados de manera incorrecta".to_string(),
+        ));
+    }
+    Ok(argumentos)
+}
+
+/// Esta funcion asigna a la sentencia los valores indicados con --param. Los de la forma ":nombre=valor" se asignan al parametro con ese nombre y el resto en orden.
+pub fn asignar_parametros(
+    sentencia: &mut SentenciaPreparada,
+    parametros: &[String],
+) -> Result<(), Errores> {
+    let mut posicion = 1;
+    for parametro in parametros {
+        match parametro.strip_prefix(':').and_then(|p| p.split_once('=')) {
+            Some((nombre, valor)) => sentencia.asignar_por_nombre(nombre, Valor::from(valor))?,
+            None => {
+                sentencia.asignar(posicion, Valor::from(parametro.as_str()))?;
+                posicion += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parsear_argumentos, Argumentos};
+    use crate::errores::errores_implementacion::{Errores, TipoError};
+    use crate::formato::formato_implementacion::{Formato, OpcionesFormato};
+
+    fn argumentos(args: &[&str]) -> Result<Argumentos, Errores> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        parsear_argumentos(&args)
+    }
+
+    #[test]
+    fn test_parsear_argumentos() {
+        assert_eq!(
+            argumentos(&["tablas", "SELECT * FROM t"]),
+            Ok(Argumentos {
+                directorio: "tablas".to_string(),
+                consulta: Some("SELECT * FROM t".to_string()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            argumentos(&["--continue-on-error", "tablas", "--file", "datos.sql"]),
+            Ok(Argumentos {
+                directorio: "tablas".to_string(),
+                archivo: Some("datos.sql".to_string()),
+                continuar_ante_error: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            argumentos(&["tablas", "--format", "JSON", "--no-header"]),
+            Ok(Argumentos {
+                directorio: "tablas".to_string(),
+                formato: OpcionesFormato {
+                    formato: Formato::Json,
+                    encabezado: false,
+                    filas_afectadas: false
+                },
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            argumentos(&["tablas", "--format", "xml"]).map_err(|e| e.tipo),
+            Err(TipoError::Error)
+        );
+        assert_eq!(
+            argumentos(&["tablas", "SELECT * FROM t WHERE a = ?", "--param", "1"]),
+            Ok(Argumentos {
+                directorio: "tablas".to_string(),
+                consulta: Some("SELECT * FROM t WHERE a = ?".to_string()),
+                parametros: vec!["1".to_string()],
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            argumentos(&["tablas", "--param", "1"]).map_err(|e| e.tipo),
+            Err(TipoError::Error)
+        );
+        assert_eq!(argumentos(&[]).map_err(|e| e.tipo), Err(TipoError::Error));
+        assert_eq!(
+            argumentos(&["tablas", "--file"]).map_err(|e| e.tipo),
+            Err(TipoError::Error)
+        );
+        assert_eq!(
+            argumentos(&["tablas", "--otra"]).map_err(|e| e.tipo),
+            Err(TipoError::Error)
+        );
+        assert_eq!(
+            argumentos(&["tablas", "SELECT * FROM t", "-f", "datos.sql"]).map_err(|e| e.tipo),
+            Err(TipoError::Error)
+        );
+    }
+}
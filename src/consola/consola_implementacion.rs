@@ -0,0 +1,199 @@
+use super::editor_linea::{EditorLinea, Lectura};
+use crate::comandos::sentencias::separar_sentencias;
+use crate::ejecutor::ejecutor_implementacion::{
+    ejecutar_consulta, listar_tablas, obtener_primera_linea,
+};
+use crate::ejecutor::transaccion::ruta_tabla;
+use crate::errores::errores_implementacion::{imprimir_error, Errores, TipoError};
+use crate::esquema::esquema_implementacion::cargar_esquema;
+use crate::formato::formato_implementacion::{
+    configurar_formato, imprimir_resultado, opciones_formato, Formato, OpcionesFormato,
+};
+use std::time::Instant;
+
+const PROMPT: &str = "sql> ";
+const PROMPT_CONTINUACION: &str = "...> ";
+
+const AYUDA: &str = "\
+.tables                 Lista las tablas de la base de datos
+.schema [tabla]         Muestra la sentencia CREATE TABLE (y sus indices) de una tabla, o de todas
+.mode FORMATO           Cambia el formato de los resultados: table, csv, tsv, list, json, jsonl, markdown o html
+.headers on|off         Muestra u oculta los nombres de las columnas en los resultados
+.changes on|off         Informa la cantidad de filas afectadas por cada sentencia
+.timer on|off           Muestra el tiempo que tarda cada sentencia
+.help                   Muestra esta ayuda
+.quit                   Termina la sesion (tambien .exit o Ctrl-D)";
+
+/// Representa el estado de una sesion de la consola.
+struct Consola<'a> {
+    path: &'a String,
+    medir_tiempo: bool,
+}
+
+/// Indica si la consola debe seguir leyendo despues de un meta-comando.
+#[derive(Debug, PartialEq)]
+enum Continuar {
+    Si,
+    No,
+}
+
+/// Esta funcion inicia la consola interactiva sobre el directorio indicado.
+/// Las sentencias pueden ocupar varias lineas y se ejecutan al terminar con ';'. Las lineas que comienzan con '.' son meta-comandos.
+/// Un error en una sentencia se informa pero no termina la sesion.
+pub fn iniciar_consola(path: &String) {
+    let mut editor = EditorLinea::new();
+    let mut consola = Consola {
+        path,
+        medir_tiempo: false,
+    };
+    if editor.es_terminal() {
+        println!("SQL Rustico. Escriba .help para ver los meta-comandos.");
+    }
+    let mut pendiente = String::new();
+    loop {
+        let prompt = if pendiente.trim().is_empty() {
+            PROMPT
+        } else {
+            PROMPT_CONTINUACION
+        };
+        let linea = match editor.leer_linea(prompt) {
+            Lectura::Linea(linea) => linea,
+            Lectura::Interrumpida => {
+                pendiente.clear();
+                continue;
+            }
+            Lectura::Fin => break,
+        };
+        if pendiente.trim().is_empty() && linea.trim_start().starts_with('.') {
+            editor.agregar_historial(&linea);
+            if consola.ejecutar_meta_comando(linea.trim()) == Continuar::No {
+                break;
+            }
+            continue;
+        }
+        pendiente.push_str(&linea);
+        pendiente.push('\n');
+        let (sentencias, resto) = separar_sentencias(&pendiente);
+        if !sentencias.is_empty() {
+            editor.agregar_historial(&pendiente);
+        }
+        for sentencia in sentencias {
+            consola.ejecutar_sentencia(&sentencia);
+        }
+        pendiente = resto;
+    }
+}
+
+impl Consola<'_> {
+    /// Parsea y ejecuta una sentencia, mostrando su resultado o el error.
+    fn ejecutar_sentencia(&self, sentencia: &str) {
+        let inicio = Instant::now();
+        match ejecutar_consulta(sentencia, self.path) {
+            Ok(resultado) => imprimir_resultado(&resultado),
+            Err(error) => imprimir_error(&error),
+        }
+        if self.medir_tiempo {
+            println!("Tiempo: {:.3} ms", inicio.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+
+    /// Ejecuta un meta-comando de la consola.
+    fn ejecutar_meta_comando(&mut self, linea: &str) -> Continuar {
+        let partes: Vec<&str> = linea.split_whitespace().collect();
+        match (partes[0].to_lowercase().as_str(), &partes[1..]) {
+            (".quit" | ".exit", []) => return Continuar::No,
+            (".help", []) => println!("{}", AYUDA),
+            (".tables", []) => match listar_tablas(self.path) {
+                Ok(tablas) => {
+                    for tabla in tablas {
+                        println!("{}", tabla);
+                    }
+                }
+                Err(error) => imprimir_error(&error),
+            },
+            (".schema", []) => match listar_tablas(self.path) {
+                Ok(tablas) => {
+                    for tabla in tablas {
+                        self.mostrar_esquema(&tabla);
+                    }
+                }
+                Err(error) => imprimir_error(&error),
+            },
+            (".schema", [tabla]) => self.mostrar_esquema(tabla),
+            (".mode", [modo]) => match Formato::desde_nombre(modo) {
+                Some(formato) => configurar_formato(OpcionesFormato {
+                    formato,
+                    ..opciones_formato()
+                }),
+                None => error_meta_comando(format!(
+                    "Formato desconocido: {}. Use table, csv, tsv, list, json, jsonl, markdown o html",
+                    modo
+                )),
+            },
+            (".headers", [valor]) => match valor.to_lowercase().as_str() {
+                "on" | "off" => configurar_formato(OpcionesFormato {
+                    encabezado: valor.eq_ignore_ascii_case("on"),
+                    ..opciones_formato()
+                }),
+                _ => error_meta_comando("Use .headers on o .headers off".to_string()),
+            },
+            (".changes", [valor]) => match valor.to_lowercase().as_str() {
+                "on" | "off" => configurar_formato(OpcionesFormato {
+                    filas_afectadas: valor.eq_ignore_ascii_case("on"),
+                    ..opciones_formato()
+                }),
+                _ => error_meta_comando("Use .changes on o .changes off".to_string()),
+            },
+            (".timer", [valor]) => match valor.to_lowercase().as_str() {
+                "on" => self.medir_tiempo = true,
+                "off" => self.medir_tiempo = false,
+                _ => error_meta_comando("Use .timer on o .timer off".to_string()),
+            },
+            _ => error_meta_comando(format!(
+                "Meta-comando desconocido: {}. Escriba .help para ver la ayuda",
+                linea
+            )),
+        }
+        Continuar::Si
+    }
+
+    /// Muestra la sentencia CREATE TABLE equivalente a la tabla indicada.
+    fn mostrar_esquema(&self, tabla: &str) {
+        let tabla = tabla.to_string();
+        let sentencia =
+            obtener_primera_linea(&ruta_tabla(self.path, &tabla)).and_then(|encabezado| {
+                cargar_esquema(self.path, &tabla, &encabezado)
+                    .map(|esquema| esquema.sentencia_create(&tabla, &encabezado))
+            });
+        match sentencia {
+            Ok(sentencia) => println!("{}", sentencia),
+            Err(error) => imprimir_error(&error),
+        }
+    }
+}
+
+/// Informa un error en un meta-comando.
+fn error_meta_comando(mensaje: String) {
+    imprimir_error(&Errores::nuevo(TipoError::Error, mensaje));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Consola, Continuar};
+
+    #[test]
+    fn test_meta_comandos() {
+        let path = "src".to_string();
+        let mut consola = Consola {
+            path: &path,
+            medir_tiempo: false,
+        };
+        assert_eq!(consola.ejecutar_meta_comando(".timer on"), Continuar::Si);
+        assert!(consola.medir_tiempo);
+        assert_eq!(consola.ejecutar_meta_comando(".TIMER off"), Continuar::Si);
+        assert!(!consola.medir_tiempo);
+        assert_eq!(consola.ejecutar_meta_comando(".desconocido"), Continuar::Si);
+        assert_eq!(consola.ejecutar_meta_comando(".quit"), Continuar::No);
+        assert_eq!(consola.ejecutar_meta_comando(".exit"), Continuar::No);
+    }
+}
@@ -0,0 +1,408 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+/// Nombre del archivo, dentro del directorio personal del usuario, donde se guarda el historial de la consola.
+const ARCHIVO_HISTORIAL: &str = ".sql_rustico_historial";
+/// Cantidad maxima de entradas que se conservan en el historial.
+const MAXIMO_HISTORIAL: usize = 1000;
+
+/// Representa el resultado de leer una linea de la consola.
+#[derive(Debug, PartialEq)]
+pub enum Lectura {
+    /// Una linea completa, sin el salto de linea final.
+    Linea(String),
+    /// El usuario presiono Ctrl-C: se descarta lo escrito hasta el momento.
+    Interrumpida,
+    /// No hay mas entrada (Ctrl-D con la linea vacia, o fin de la entrada estandar).
+    Fin,
+}
+
+/// Representa las teclas que el editor reconoce.
+#[derive(Debug, PartialEq)]
+enum Tecla {
+    Caracter(char),
+    Enter,
+    Borrar,
+    Suprimir,
+    Izquierda,
+    Derecha,
+    Arriba,
+    Abajo,
+    Inicio,
+    Final,
+    BorrarHastaElFinal,
+    BorrarHastaElInicio,
+    BorrarPalabra,
+    Interrumpir,
+    FinDeEntrada,
+    Ignorada,
+}
+
+/// Lee lineas de la entrada estandar. Si la entrada es una terminal, permite editar la linea (flechas, Inicio, Fin, Ctrl-A, Ctrl-E, Ctrl-K, Ctrl-U, Ctrl-W) y recorrer el historial con las flechas arriba y abajo.
+/// El historial se guarda en ~/.sql_rustico_historial para conservarlo entre sesiones.
+pub struct EditorLinea {
+    historial: Vec<String>,
+    ruta_historial: Option<String>,
+    es_terminal: bool,
+}
+
+impl EditorLinea {
+    pub fn new() -> EditorLinea {
+        let es_terminal = io::stdin().is_terminal() && io::stdout().is_terminal();
+        let ruta_historial = match env::var("HOME") {
+            Ok(directorio) if es_terminal => Some(format!("{}/{}", directorio, ARCHIVO_HISTORIAL)),
+            _ => None,
+        };
+        let historial: Vec<String> = match &ruta_historial {
+            Some(ruta) => cargar_historial(ruta),
+            None => Vec::new(),
+        };
+        EditorLinea {
+            historial,
+            ruta_historial,
+            es_terminal,
+        }
+    }
+
+    /// Indica si la entrada es una terminal, en cuyo caso se muestran los prompts.
+    pub fn es_terminal(&self) -> bool {
+        self.es_terminal
+    }
+
+    /// Agrega una entrada al historial y la guarda en el archivo de historial. Las entradas repetidas consecutivas se guardan una sola vez.
+    /// Mientras el historial no supera el maximo la entrada se agrega al final del archivo; si lo supera, el archivo se reescribe con las ultimas entradas para que no crezca indefinidamente.
+    pub fn agregar_historial(&mut self, entrada: &str) {
+        let entrada = entrada.trim().replace('\n', " ");
+        if entrada.is_empty() || self.historial.last() == Some(&entrada) {
+            return;
+        }
+        self.historial.push(entrada.to_string());
+        let recortado = self.historial.len() > MAXIMO_HISTORIAL;
+        if recortado {
+            self.historial.remove(0);
+        }
+        if let Some(ruta) = &self.ruta_historial {
+            if recortado {
+                guardar_historial(ruta, &self.historial);
+            } else if let Ok(mut archivo) = OpenOptions::new().create(true).append(true).open(ruta)
+            {
+                let _ = writeln!(archivo, "{}", entrada);
+            }
+        }
+    }
+
+    /// Lee una linea mostrando el prompt indicado.
+    pub fn leer_linea(&mut self, prompt: &str) -> Lectura {
+        if !self.es_terminal {
+            return leer_linea_simple();
+        }
+        let modo_crudo = match ModoCrudo::activar() {
+            Some(modo) => modo,
+            None => {
+                print!("{}", prompt);
+                let _ = io::stdout().flush();
+                return leer_linea_simple();
+            }
+        };
+        let lectura = self.editar(prompt);
+        drop(modo_crudo);
+        lectura
+    }
+
+    /// Edita una linea en modo crudo hasta que el usuario presiona Enter.
+    fn editar(&mut self, prompt: &str) -> Lectura {
+        let mut linea: Vec<char> = Vec::new();
+        let mut cursor: usize = 0;
+        // La posicion actual en el historial; la linea que se estaba escribiendo se conserva al recorrerlo.
+        let mut posicion_historial = self.historial.len();
+        let mut borrador: Vec<char> = Vec::new();
+        redibujar(prompt, &linea, cursor);
+        loop {
+            match leer_tecla() {
+                Tecla::Caracter(caracter) => {
+                    linea.insert(cursor, caracter);
+                    cursor += 1;
+                }
+                Tecla::Enter => {
+                    print!("\r\n");
+                    let _ = io::stdout().flush();
+                    return Lectura::Linea(linea.iter().collect());
+                }
+                Tecla::Borrar if cursor > 0 => {
+                    cursor -= 1;
+                    linea.remove(cursor);
+                }
+                Tecla::Suprimir if cursor < linea.len() => {
+                    linea.remove(cursor);
+                }
+                Tecla::Izquierda if cursor > 0 => cursor -= 1,
+                Tecla::Derecha if cursor < linea.len() => cursor += 1,
+                Tecla::Inicio => cursor = 0,
+                Tecla::Final => cursor = linea.len(),
+                Tecla::BorrarHastaElFinal => linea.truncate(cursor),
+                Tecla::BorrarHastaElInicio => {
+                    linea.drain(..cursor);
+                    cursor = 0;
+                }
+                Tecla::BorrarPalabra => {
+                    let mut inicio = cursor;
+                    while inicio > 0 && linea[inicio - 1] == ' ' {
+                        inicio -= 1;
+                    }
+                    while inicio > 0 && linea[inicio - 1] != ' ' {
+                        inicio -= 1;
+                    }
+                    linea.drain(inicio..cursor);
+                    cursor = inicio;
+                }
+                Tecla::Arriba if posicion_historial > 0 => {
+                    if posicion_historial == self.historial.len() {
+                        borrador = linea.clone();
+                    }
+                    posicion_historial -= 1;
+                    linea = self.historial[posicion_historial].chars().collect();
+                    cursor = linea.len();
+                }
+                Tecla::Abajo if posicion_historial < self.historial.len() => {
+                    posicion_historial += 1;
+                    linea = match self.historial.get(posicion_historial) {
+                        Some(entrada) => entrada.chars().collect(),
+                        None => borrador.clone(),
+                    };
+                    cursor = linea.len();
+                }
+                Tecla::Interrumpir => {
+                    print!("^C\r\n");
+                    let _ = io::stdout().flush();
+                    return Lectura::Interrumpida;
+                }
+                Tecla::FinDeEntrada if linea.is_empty() => {
+                    print!("\r\n");
+                    let _ = io::stdout().flush();
+                    return Lectura::Fin;
+                }
+                _ => continue,
+            }
+            redibujar(prompt, &linea, cursor);
+        }
+    }
+}
+
+impl Default for EditorLinea {
+    fn default() -> EditorLinea {
+        EditorLinea::new()
+    }
+}
+
+/// Representa la terminal configurada en modo crudo, en el que cada tecla se recibe apenas se presiona y sin eco. Al descartarse se restaura la configuracion anterior.
+/// La configuracion se cambia con el comando stty, disponible en cualquier sistema Linux.
+struct ModoCrudo {
+    configuracion_anterior: String,
+}
+
+impl ModoCrudo {
+    fn activar() -> Option<ModoCrudo> {
+        let salida = Command::new("stty")
+            .arg("-g")
+            .stdin(Stdio::inherit())
+            .output()
+            .ok()?;
+        if !salida.status.success() {
+            return None;
+        }
+        let configuracion_anterior = String::from_utf8_lossy(&salida.stdout).trim().to_string();
+        let estado = Command::new("stty")
+            .args(["raw", "-echo"])
+            .stdin(Stdio::inherit())
+            .status()
+            .ok()?;
+        if !estado.success() {
+            return None;
+        }
+        Some(ModoCrudo {
+            configuracion_anterior,
+        })
+    }
+}
+
+impl Drop for ModoCrudo {
+    fn drop(&mut self) {
+        let _ = Command::new("stty")
+            .arg(&self.configuracion_anterior)
+            .stdin(Stdio::inherit())
+            .status();
+    }
+}
+
+/// Lee el historial guardado en el archivo y conserva sus ultimas MAXIMO_HISTORIAL entradas. Si el archivo tenia mas, se reescribe solo con ellas.
+fn cargar_historial(ruta: &str) -> Vec<String> {
+    let mut historial: Vec<String> = fs::read_to_string(ruta)
+        .unwrap_or_default()
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.to_string())
+        .collect();
+    if historial.len() > MAXIMO_HISTORIAL {
+        historial.drain(..historial.len() - MAXIMO_HISTORIAL);
+        guardar_historial(ruta, &historial);
+    }
+    historial
+}
+
+/// Reescribe el archivo de historial con las entradas indicadas. Si no puede escribirse, el historial se conserva solo en memoria.
+fn guardar_historial(ruta: &str, historial: &[String]) {
+    let contenido: String = historial
+        .iter()
+        .map(|entrada| format!("{}\n", entrada))
+        .collect();
+    let _ = fs::write(ruta, contenido);
+}
+
+/// Lee una linea sin edicion, para cuando la entrada no es una terminal.
+fn leer_linea_simple() -> Lectura {
+    let mut linea = String::new();
+    match io::stdin().lock().read_line(&mut linea) {
+        Ok(0) | Err(_) => Lectura::Fin,
+        Ok(_) => Lectura::Linea(linea.trim_end_matches(['\n', '\r']).to_string()),
+    }
+}
+
+/// Vuelve a escribir la linea completa y ubica el cursor en su posicion.
+fn redibujar(prompt: &str, linea: &[char], cursor: usize) {
+    let texto: String = linea.iter().collect();
+    print!("\r{}{}\x1b[K", prompt, texto);
+    let atras = linea.len() - cursor;
+    if atras > 0 {
+        print!("\x1b[{}D", atras);
+    }
+    let _ = io::stdout().flush();
+}
+
+fn leer_byte() -> Option<u8> {
+    let mut byte = [0u8; 1];
+    match io::stdin().lock().read(&mut byte) {
+        Ok(1) => Some(byte[0]),
+        _ => None,
+    }
+}
+
+/// Lee una tecla de la entrada, interpretando las secuencias de escape de las flechas y los caracteres de varios bytes.
+fn leer_tecla() -> Tecla {
+    let byte = match leer_byte() {
+        Some(byte) => byte,
+        None => return Tecla::FinDeEntrada,
+    };
+    match byte {
+        b'\r' | b'\n' => Tecla::Enter,
+        127 | 8 => Tecla::Borrar,
+        1 => Tecla::Inicio,
+        2 => Tecla::Izquierda,
+        3 => Tecla::Interrumpir,
+        4 => Tecla::FinDeEntrada,
+        5 => Tecla::Final,
+        6 => Tecla::Derecha,
+        11 => Tecla::BorrarHastaElFinal,
+        14 => Tecla::Abajo,
+        16 => Tecla::Arriba,
+        21 => Tecla::BorrarHastaElInicio,
+        23 => Tecla::BorrarPalabra,
+        27 => leer_secuencia_de_escape(),
+        byte if byte < 32 => Tecla::Ignorada,
+        byte => leer_caracter(byte),
+    }
+}
+
+fn leer_secuencia_de_escape() -> Tecla {
+    let tipo = leer_byte();
+    if tipo != Some(b'[') && tipo != Some(b'O') {
+        return Tecla::Ignorada;
+    }
+    match leer_byte() {
+        Some(b'A') => Tecla::Arriba,
+        Some(b'B') => Tecla::Abajo,
+        Some(b'C') => Tecla::Derecha,
+        Some(b'D') => Tecla::Izquierda,
+        Some(b'H') => Tecla::Inicio,
+        Some(b'F') => Tecla::Final,
+        Some(numero) if numero.is_ascii_digit() => {
+            // Secuencias de la forma ESC [ n ~
+            let mut codigo = vec![numero];
+            while let Some(byte) = leer_byte() {
+                if byte == b'~' {
+                    break;
+                }
+                codigo.push(byte);
+            }
+            match codigo.as_slice() {
+                b"1" | b"7" => Tecla::Inicio,
+                b"3" => Tecla::Suprimir,
+                b"4" | b"8" => Tecla::Final,
+                _ => Tecla::Ignorada,
+            }
+        }
+        _ => Tecla::Ignorada,
+    }
+}
+
+/// Lee el resto de los bytes de un caracter UTF-8 que comienza con el byte indicado.
+fn leer_caracter(primero: u8) -> Tecla {
+    let largo = match primero {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => 1,
+    };
+    let mut bytes = vec![primero];
+    for _ in 1..largo {
+        match leer_byte() {
+            Some(byte) => bytes.push(byte),
+            None => return Tecla::FinDeEntrada,
+        }
+    }
+    match String::from_utf8(bytes) {
+        Ok(texto) => match texto.chars().next() {
+            Some(caracter) => Tecla::Caracter(caracter),
+            None => Tecla::Ignorada,
+        },
+        Err(_) => Tecla::Ignorada,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cargar_historial, EditorLinea, MAXIMO_HISTORIAL};
+    use std::fs;
+
+    #[test]
+    fn test_historial_no_supera_el_maximo() {
+        let ruta = std::env::temp_dir()
+            .join(format!("sql_rustico_historial_{}", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let entradas: Vec<String> = (0..MAXIMO_HISTORIAL + 5)
+            .map(|i| format!("SELECT {}", i))
+            .collect();
+        fs::write(&ruta, entradas.join("\n")).unwrap();
+        let historial = cargar_historial(&ruta);
+        assert_eq!(historial.len(), MAXIMO_HISTORIAL);
+        assert_eq!(historial[0], "SELECT 5");
+        assert_eq!(
+            fs::read_to_string(&ruta).unwrap().lines().count(),
+            MAXIMO_HISTORIAL
+        );
+
+        let mut editor = EditorLinea {
+            historial,
+            ruta_historial: Some(ruta.clone()),
+            es_terminal: false,
+        };
+        editor.agregar_historial("SELECT 'nueva'");
+        let guardado = fs::read_to_string(&ruta).unwrap();
+        assert_eq!(guardado.lines().count(), MAXIMO_HISTORIAL);
+        assert_eq!(guardado.lines().next(), Some("SELECT 6"));
+        assert_eq!(guardado.lines().last(), Some("SELECT 'nueva'"));
+        let _ = fs::remove_file(&ruta);
+    }
+}
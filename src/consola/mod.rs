@@ -0,0 +1,4 @@
+pub mod argumentos;
+pub mod consola_implementacion;
+pub mod editor_linea;
+pub mod script;
@@ -0,0 +1,133 @@
+use crate::comandos::sentencias::separar_sentencias_con_lineas;
+use crate::ejecutor::ejecutor_implementacion::ejecutar_consulta;
+use crate::errores::errores_implementacion::{imprimir_error, Errores, TipoError};
+use crate::formato::formato_implementacion::imprimir_resultado;
+use std::fs;
+use std::io::{self, Read};
+
+/// Representa el resultado de ejecutar un script.
+#[derive(Debug, PartialEq, Default)]
+pub struct ResumenScript {
+    /// Cantidad de sentencias ejecutadas correctamente.
+    pub exitosas: usize,
+    /// Cantidad de sentencias que fallaron.
+    pub fallidas: usize,
+    /// Cantidad de sentencias que no se ejecutaron por haberse detenido el script.
+    pub omitidas: usize,
+    /// Tipo de error de la primera sentencia que fallo, que determina el codigo de salida del programa.
+    pub primer_error: Option<TipoError>,
+}
+
+impl ResumenScript {
+    pub fn descripcion(&self) -> String {
+        format!(
+            "{} sentencias ejecutadas, {} fallidas, {} omitidas",
+            self.exitosas, self.fallidas, self.omitidas
+        )
+    }
+}
+
+/// Esta funcion lee el contenido de un script. Si la ruta es "-" se lee la entrada estandar.
+pub fn leer_script(ruta: &str) -> Result<String, Errores> {
+    let mut contenido = String::new();
+    let lectura = if ruta == "-" {
+        io::stdin().read_to_string(&mut contenido).map(|_| ())
+    } else {
+        fs::read_to_string(ruta).map(|texto| contenido = texto)
+    };
+    match lectura {
+        Ok(()) => Ok(contenido),
+        _ => Err(Errores::nuevo(
+            TipoError::Error,
+            format!("No se pudo leer el script {}", ruta),
+        )),
+    }
+}
+
+/// Esta funcion ejecuta en orden las sentencias del script sobre las tablas del directorio indicado.
+/// Si continuar_ante_error es falso, la ejecucion se detiene en la primera sentencia que falla y las restantes se cuentan como omitidas.
+/// Una sentencia final sin ';' tambien se ejecuta. Los errores se ubican en la linea del script, no en la de la sentencia.
+pub fn ejecutar_script(path: &String, script: &str, continuar_ante_error: bool) -> ResumenScript {
+    let (mut sentencias, (linea_resto, resto)) = separar_sentencias_con_lineas(script);
+    if !resto.trim().is_empty() {
+        sentencias.push((linea_resto, resto.trim().to_string()));
+    }
+    let mut resumen = ResumenScript::default();
+    for (numero, (linea, sentencia)) in sentencias.iter().enumerate() {
+        match ejecutar_consulta(sentencia, path).map_err(|error| error.desplazar_lineas(linea - 1))
+        {
+            Ok(resultado) => {
+                imprimir_resultado(&resultado);
+                resumen.exitosas += 1;
+                continue;
+            }
+            Err(error) => {
+                imprimir_error(&error);
+                resumen.primer_error.get_or_insert(error.tipo);
+            }
+        }
+        resumen.fallidas += 1;
+        if !continuar_ante_error {
+            resumen.omitidas = sentencias.len() - numero - 1;
+            imprimir_error(&Errores::nuevo(
+                TipoError::Error,
+                format!(
+                    "Falló la sentencia {}, se detiene la ejecución del script",
+                    numero + 1
+                ),
+            ));
+            break;
+        }
+    }
+    resumen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ejecutar_script, ResumenScript};
+    use crate::ejecutor::ejecutor_implementacion::test::crear_directorio_prueba;
+    use crate::errores::errores_implementacion::TipoError;
+    use std::fs;
+
+    const SCRIPT: &str = "
+        -- carga inicial
+        INSERT INTO clientes (id, nombre) VALUES (3, 'Luis');
+        INSERT INTO inexistente (id) VALUES (1);
+        /* esta sentencia se omite
+           si el script se detiene */
+        INSERT INTO clientes (id, nombre) VALUES (4, 'Sofía')";
+
+    #[test]
+    fn test_script_se_detiene_ante_error() {
+        let path = crear_directorio_prueba("script_detener", &[("clientes.csv", "id,nombre\n")]);
+        let resumen = ejecutar_script(&path, SCRIPT, false);
+        assert_eq!(
+            resumen,
+            ResumenScript {
+                exitosas: 1,
+                fallidas: 1,
+                omitidas: 1,
+                primer_error: Some(TipoError::InvalidTable)
+            }
+        );
+        let clientes = fs::read_to_string(format!("{}/clientes.csv", path)).unwrap();
+        assert_eq!(clientes, "id,nombre\n3,Luis\n");
+    }
+
+    #[test]
+    fn test_script_continua_ante_error() {
+        let path = crear_directorio_prueba("script_continuar", &[("clientes.csv", "id,nombre\n")]);
+        let resumen = ejecutar_script(&path, SCRIPT, true);
+        assert_eq!(
+            resumen,
+            ResumenScript {
+                exitosas: 2,
+                fallidas: 1,
+                omitidas: 0,
+                primer_error: Some(TipoError::InvalidTable)
+            }
+        );
+        let clientes = fs::read_to_string(format!("{}/clientes.csv", path)).unwrap();
+        assert_eq!(clientes, "id,nombre\n3,Luis\n4,Sofía\n");
+    }
+}
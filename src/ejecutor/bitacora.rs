@@ -0,0 +1,660 @@
+use crate::ejecutor::transaccion::descartar_sombras_huerfanas;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Prefijo de los archivos de bitacora (write-ahead log). Cada publicacion usa su propia bitacora, llamada bitacora_<proceso>_<numero>.
+const PREFIJO_BITACORA: &str = "bitacora_";
+/// Prefijo de los archivos auxiliares, llamados archivo_temporal_<proceso>_<numero>_<descripcion>. Los que quedan en el directorio de un proceso que termino pertenecen a cambios que no llegaron a registrarse en la bitacora.
+const PREFIJO_TEMPORAL: &str = "archivo_temporal_";
+/// Ultima linea de una bitacora completa.
+const FIN_BITACORA: &str = "fin";
+
+/// Contador que distingue los archivos auxiliares creados por el proceso, incluso desde distintos hilos.
+static CONTADOR: AtomicUsize = AtomicUsize::new(0);
+
+/// Esta funcion devuelve la ruta a un archivo auxiliar nuevo. El nombre incluye el identificador del proceso y un numero que no se repite, de manera que dos consultas nunca usan el mismo archivo.
+pub fn nombre_temporal(path: &String, descripcion: &str) -> String {
+    format!(
+        "{}/{}{}_{}_{}",
+        path,
+        PREFIJO_TEMPORAL,
+        process::id(),
+        CONTADOR.fetch_add(1, Ordering::SeqCst),
+        descripcion
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa un cambio que se aplica sobre los archivos del directorio. Todas las operaciones pueden repetirse sin cambiar el resultado, de manera que la recuperacion puede volver a aplicarlas aunque ya se hayan aplicado en parte.
+pub enum Operacion {
+    /// Reemplaza el destino por el archivo de origen, cuya longitud se registra. Si el origen ya no existe y el destino tiene esa longitud, la operacion ya fue aplicada.
+    Renombrar {
+        origen: String,
+        destino: String,
+        longitud: u64,
+    },
+    /// Agrega al final del destino el contenido del archivo de datos, despues de truncar el destino a su longitud original.
+    Anexar {
+        datos: String,
+        destino: String,
+        longitud: u64,
+    },
+    /// Elimina el archivo indicado.
+    Eliminar { ruta: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Representa los pasos en los que puede interrumpirse la confirmacion de una bitacora. Se usa para simular fallas en los tests.
+pub enum Paso {
+    /// Antes de que la bitacora quede guardada.
+    EscribirBitacora,
+    /// Antes de aplicar la operacion con el indice indicado.
+    AplicarOperacion(usize),
+    /// Antes de eliminar la bitacora, con todas las operaciones ya aplicadas.
+    EliminarBitacora,
+}
+
+/// Representa el conjunto de cambios que una consulta publica de una sola vez.
+/// Antes de tocar las tablas se sincronizan los archivos auxiliares y se guarda la lista de operaciones en la bitacora del directorio; si el programa se interrumpe mientras se aplican, la recuperacion las vuelve a aplicar al iniciar.
+pub struct Bitacora {
+    ruta_directorio: String,
+    ruta: String,
+    operaciones: Vec<Operacion>,
+    registrada: bool,
+    falla: Option<Paso>,
+}
+
+impl Bitacora {
+    pub fn new(ruta_directorio: &String) -> Bitacora {
+        Bitacora {
+            ruta_directorio: ruta_directorio.to_string(),
+            ruta: format!(
+                "{}/{}{}_{}",
+                ruta_directorio,
+                PREFIJO_BITACORA,
+                process::id(),
+                CONTADOR.fetch_add(1, Ordering::SeqCst)
+            ),
+            operaciones: Vec::new(),
+            registrada: false,
+            falla: None,
+        }
+    }
+
+    /// Hace que la confirmacion se interrumpa en el paso indicado, como si el programa hubiera terminado abruptamente.
+    #[cfg(test)]
+    pub fn fallar_en(&mut self, paso: Paso) {
+        self.falla = Some(paso);
+    }
+
+    /// Registra que el archivo de origen, ya escrito, debe reemplazar al destino.
+    pub fn renombrar(&mut self, origen: &str, destino: &str) -> Result<(), Errores> {
+        let longitud = match fs::metadata(origen) {
+            Ok(metadatos) => metadatos.len(),
+            Err(_) => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error leyendo el archivo".to_string(),
+                ));
+            }
+        };
+        self.operaciones.push(Operacion::Renombrar {
+            origen: origen.to_string(),
+            destino: destino.to_string(),
+            longitud,
+        });
+        Ok(())
+    }
+
+    /// Registra que el contenido debe agregarse al final del destino. El contenido se guarda en un archivo auxiliar para poder volver a agregarlo durante la recuperacion.
+    pub fn anexar(&mut self, destino: &str, contenido: &str) -> Result<(), Errores> {
+        let datos = nombre_temporal(&self.ruta_directorio, "anexo");
+        if fs::write(&datos, contenido).is_err() {
+            let _ = fs::remove_file(&datos);
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error escribiendo el archivo".to_string(),
+            ));
+        }
+        self.anexar_archivo(destino, datos)
+    }
+
+    /// Registra que el contenido del archivo auxiliar, ya escrito, debe agregarse al final del destino. A partir de este momento el auxiliar pertenece a la bitacora.
+    /// Un auxiliar vacio no se registra: se elimina sin cambiar el destino.
+    pub fn anexar_archivo(&mut self, destino: &str, datos: String) -> Result<(), Errores> {
+        if fs::metadata(&datos).is_ok_and(|metadatos| metadatos.len() == 0) {
+            let _ = fs::remove_file(&datos);
+            return Ok(());
+        }
+        let longitud = match fs::metadata(destino) {
+            Ok(metadatos) => metadatos.len(),
+            Err(_) => {
+                let _ = fs::remove_file(&datos);
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error leyendo el archivo".to_string(),
+                ));
+            }
+        };
+        self.operaciones.push(Operacion::Anexar {
+            datos,
+            destino: destino.to_string(),
+            longitud,
+        });
+        Ok(())
+    }
+
+    /// Registra que el archivo indicado debe eliminarse.
+    pub fn eliminar(&mut self, ruta: &str) {
+        self.operaciones.push(Operacion::Eliminar {
+            ruta: ruta.to_string(),
+        });
+    }
+
+    /// Indica si la bitacora ya fue guardada. A partir de ese momento los archivos auxiliares pertenecen a la bitacora y no deben eliminarse aunque la confirmacion falle, ya que la recuperacion los necesita.
+    pub fn fue_registrada(&self) -> bool {
+        self.registrada
+    }
+
+    /// Confirma los cambios: sincroniza los archivos auxiliares, guarda la bitacora, aplica las operaciones y por ultimo elimina la bitacora.
+    pub fn confirmar(&mut self) -> Result<(), Errores> {
+        if self.operaciones.is_empty() {
+            return Ok(());
+        }
+        for operacion in &self.operaciones {
+            let auxiliar = match operacion {
+                Operacion::Renombrar { origen, .. } => origen,
+                Operacion::Anexar { datos, .. } => datos,
+                Operacion::Eliminar { .. } => continue,
+            };
+            if sincronizar_archivo(auxiliar).is_err() {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error guardando los cambios".to_string(),
+                ));
+            }
+        }
+        self.simular_falla(Paso::EscribirBitacora)?;
+        self.escribir()?;
+        self.registrada = true;
+        for (indice, operacion) in self.operaciones.iter().enumerate() {
+            self.simular_falla(Paso::AplicarOperacion(indice))?;
+            aplicar(operacion)?;
+        }
+        let _ = sincronizar_directorio(&self.ruta_directorio);
+        self.simular_falla(Paso::EliminarBitacora)?;
+        if fs::remove_file(&self.ruta).is_err() {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error eliminando la bitacora".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn simular_falla(&self, paso: Paso) -> Result<(), Errores> {
+        if self.falla == Some(paso) {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                format!("Falla simulada en el paso {:?}", paso),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Guarda la bitacora en un archivo auxiliar que, una vez sincronizado, la reemplaza. De esta manera la bitacora se encuentra completa o no se encuentra.
+    fn escribir(&self) -> Result<(), Errores> {
+        let mut contenido = String::new();
+        for operacion in &self.operaciones {
+            contenido.push_str(&serializar(operacion, &self.ruta_directorio));
+            contenido.push('\n');
+        }
+        contenido.push_str(FIN_BITACORA);
+        contenido.push('\n');
+        if escribir_archivo(&self.ruta, &contenido).is_err()
+            || sincronizar_directorio(&self.ruta_directorio).is_err()
+        {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error escribiendo la bitacora".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Bitacora {
+    /// Elimina los archivos de datos de una bitacora que no llego a guardarse.
+    fn drop(&mut self) {
+        if self.registrada {
+            return;
+        }
+        for operacion in &self.operaciones {
+            if let Operacion::Anexar { datos, .. } = operacion {
+                let _ = fs::remove_file(datos);
+            }
+        }
+    }
+}
+
+/// Esta funcion deja el directorio en un estado consistente cuando ningun otro proceso esta trabajando sobre el.
+/// Las bitacoras que quedaron se vuelven a aplicar y luego se eliminan los archivos auxiliares de los cambios que no llegaron a registrarse.
+pub fn recuperar(path: &String) -> Result<(), Errores> {
+    recuperar_archivos(path, "")?;
+    descartar_sombras_huerfanas(path);
+    Ok(())
+}
+
+/// Esta funcion recupera los cambios que dejo a medio publicar un proceso que termino sin liberar sus bloqueos: vuelve a aplicar sus bitacoras y elimina sus archivos auxiliares.
+pub fn recuperar_proceso(path: &String, proceso: u32) -> Result<(), Errores> {
+    recuperar_archivos(path, &format!("{}_", proceso))
+}
+
+/// Vuelve a aplicar las bitacoras y elimina los archivos auxiliares cuyo nombre, luego del prefijo, comienza con el texto indicado.
+fn recuperar_archivos(path: &String, origen: &str) -> Result<(), Errores> {
+    let archivos = listar_archivos(path);
+    let mut bitacoras: Vec<(Vec<u64>, String)> = archivos
+        .iter()
+        .filter_map(|nombre| {
+            let resto = nombre.strip_prefix(PREFIJO_BITACORA)?;
+            if !resto.starts_with(origen) {
+                return None;
+            }
+            let numeros: Vec<u64> = resto.split('_').filter_map(|n| n.parse().ok()).collect();
+            Some((numeros, nombre.to_string()))
+        })
+        .collect();
+    bitacoras.sort();
+    for (_, nombre) in bitacoras {
+        let ruta = format!("{}/{}", path, nombre);
+        if let Ok(contenido) = fs::read_to_string(&ruta) {
+            if let Some(operaciones) = deserializar(&contenido, path) {
+                for operacion in &operaciones {
+                    aplicar(operacion)?;
+                }
+                let _ = sincronizar_directorio(path);
+            }
+        }
+        if fs::remove_file(&ruta).is_err() {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error eliminando la bitacora".to_string(),
+            ));
+        }
+    }
+    let prefijo = format!("{}{}", PREFIJO_TEMPORAL, origen);
+    for nombre in archivos.iter().filter(|n| n.starts_with(&prefijo)) {
+        let _ = fs::remove_file(format!("{}/{}", path, nombre));
+    }
+    Ok(())
+}
+
+fn listar_archivos(path: &String) -> Vec<String> {
+    match fs::read_dir(path) {
+        Ok(entradas) => entradas
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Escribe el contenido en un archivo auxiliar que, una vez sincronizado, reemplaza al archivo indicado.
+/// El auxiliar se crea en el mismo directorio con un nombre que no se repite, de manera que dos escrituras simultaneas del mismo archivo no usan el mismo auxiliar.
+pub fn escribir_archivo(ruta: &str, contenido: &str) -> std::io::Result<()> {
+    let directorio = match Path::new(ruta).parent() {
+        Some(directorio) if !directorio.as_os_str().is_empty() => {
+            directorio.to_string_lossy().to_string()
+        }
+        _ => ".".to_string(),
+    };
+    let temporal = nombre_temporal(&directorio, "escritura");
+    let resultado = File::create(&temporal)
+        .and_then(|mut archivo| {
+            archivo.write_all(contenido.as_bytes())?;
+            archivo.sync_all()
+        })
+        .and_then(|_| fs::rename(&temporal, ruta));
+    if resultado.is_err() {
+        let _ = fs::remove_file(&temporal);
+    }
+    resultado
+}
+
+/// Fuerza la escritura a disco del contenido del archivo.
+pub fn sincronizar_archivo(ruta: &str) -> std::io::Result<()> {
+    File::open(ruta)?.sync_all()
+}
+
+/// Fuerza la escritura a disco de las entradas del directorio, para que los renombres y eliminaciones no se pierdan.
+fn sincronizar_directorio(path: &str) -> std::io::Result<()> {
+    File::open(path)?.sync_all()
+}
+
+/// Aplica la operacion. Si ya habia sido aplicada, el resultado no cambia.
+/// Si falta el archivo auxiliar de una operacion que no fue aplicada, devuelve error: los cambios no pueden completarse y la bitacora debe conservarse.
+fn aplicar(operacion: &Operacion) -> Result<(), Errores> {
+    let resultado = match operacion {
+        Operacion::Renombrar {
+            origen,
+            destino,
+            longitud,
+        } => {
+            if !Path::new(origen).exists() {
+                return match fs::metadata(destino) {
+                    Ok(metadatos) if metadatos.len() == *longitud => Ok(()),
+                    _ => Err(auxiliar_faltante(origen)),
+                };
+            }
+            fs::rename(origen, destino)
+        }
+        Operacion::Anexar {
+            datos,
+            destino,
+            longitud,
+        } => {
+            let contenido = match fs::read(datos) {
+                Ok(contenido) => contenido,
+                Err(_) => {
+                    return match fs::metadata(destino) {
+                        Ok(metadatos) if metadatos.len() > *longitud => Ok(()),
+                        _ => Err(auxiliar_faltante(datos)),
+                    };
+                }
+            };
+            OpenOptions::new()
+                .append(true)
+                .open(destino)
+                .and_then(|mut archivo| {
+                    archivo.set_len(*longitud)?;
+                    archivo.write_all(&contenido)?;
+                    archivo.sync_all()
+                })
+                .and_then(|_| fs::remove_file(datos))
+        }
+        Operacion::Eliminar { ruta } => match fs::remove_file(ruta) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            resultado => resultado,
+        },
+    };
+    if resultado.is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error aplicando los cambios".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Devuelve el error para cuando falta el archivo auxiliar de una operacion que todavia no fue aplicada.
+fn auxiliar_faltante(ruta: &str) -> Errores {
+    Errores::nuevo(
+        TipoError::Error,
+        format!(
+            "No se pueden completar los cambios de la bitacora: falta el archivo {}",
+            ruta
+        ),
+    )
+}
+
+/// Devuelve la linea de la bitacora que representa a la operacion. Los archivos se guardan por su nombre dentro del directorio, de manera que la bitacora no depende de como se escribio la ruta al directorio ni del directorio de trabajo.
+fn serializar(operacion: &Operacion, path: &str) -> String {
+    let nombre = |ruta: &str| nombre_en_directorio(ruta, path);
+    match operacion {
+        Operacion::Renombrar {
+            origen,
+            destino,
+            longitud,
+        } => format!(
+            "renombrar\t{}\t{}\t{}",
+            nombre(origen),
+            nombre(destino),
+            longitud
+        ),
+        Operacion::Anexar {
+            datos,
+            destino,
+            longitud,
+        } => format!(
+            "anexar\t{}\t{}\t{}",
+            nombre(datos),
+            nombre(destino),
+            longitud
+        ),
+        Operacion::Eliminar { ruta } => format!("eliminar\t{}", nombre(ruta)),
+    }
+}
+
+/// Devuelve la ruta del archivo relativa al directorio.
+fn nombre_en_directorio(ruta: &str, path: &str) -> String {
+    let prefijo = format!("{}/", path.trim_end_matches('/'));
+    match ruta.strip_prefix(&prefijo) {
+        Some(nombre) => nombre.to_string(),
+        None => Path::new(ruta)
+            .file_name()
+            .map(|nombre| nombre.to_string_lossy().to_string())
+            .unwrap_or_else(|| ruta.to_string()),
+    }
+}
+
+/// Devuelve las operaciones de la bitacora, con los archivos ubicados en el directorio indicado, o None si esta incompleta o es invalida.
+fn deserializar(contenido: &str, path: &str) -> Option<Vec<Operacion>> {
+    let ruta = |nombre: &str| format!("{}/{}", path.trim_end_matches('/'), nombre);
+    let lineas: Vec<&str> = contenido.lines().collect();
+    if lineas.last() != Some(&FIN_BITACORA) {
+        return None;
+    }
+    let mut operaciones: Vec<Operacion> = Vec::new();
+    for linea in &lineas[..lineas.len() - 1] {
+        let partes: Vec<&str> = linea.split('\t').collect();
+        let operacion = match partes.as_slice() {
+            ["renombrar", origen, destino, longitud] => Operacion::Renombrar {
+                origen: ruta(origen),
+                destino: ruta(destino),
+                longitud: longitud.parse().ok()?,
+            },
+            ["anexar", datos, destino, longitud] => Operacion::Anexar {
+                datos: ruta(datos),
+                destino: ruta(destino),
+                longitud: longitud.parse().ok()?,
+            },
+            ["eliminar", nombre] => Operacion::Eliminar { ruta: ruta(nombre) },
+            _ => return None,
+        };
+        operaciones.push(operacion);
+    }
+    Some(operaciones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        deserializar, escribir_archivo, nombre_temporal, recuperar, serializar, Bitacora,
+        Operacion, Paso,
+    };
+    use std::fs;
+
+    const ORIGINAL_A: &str = "id\n1\n";
+    const ORIGINAL_B: &str = "id\n2\n";
+    const NUEVO_A: &str = "id\n1\n3\n";
+
+    fn crear_directorio(nombre: &str) -> String {
+        let directorio =
+            std::env::temp_dir().join(format!("sql_rustico_{}_{}", nombre, std::process::id()));
+        let _ = fs::remove_dir_all(&directorio);
+        fs::create_dir_all(&directorio).unwrap();
+        let ruta = directorio.to_string_lossy().to_string();
+        fs::write(format!("{}/a.csv", ruta), ORIGINAL_A).unwrap();
+        fs::write(format!("{}/b.csv", ruta), ORIGINAL_B).unwrap();
+        ruta
+    }
+
+    /// Reemplaza a.csv y agrega una fila a b.csv, interrumpiendo la confirmacion en el paso indicado. Devuelve la ruta al archivo con las filas agregadas.
+    fn confirmar_con_falla(ruta: &String, paso: Option<Paso>) -> String {
+        let temporal = nombre_temporal(ruta, "a");
+        fs::write(&temporal, NUEVO_A).unwrap();
+        let mut bitacora = Bitacora::new(ruta);
+        bitacora
+            .renombrar(&temporal, &format!("{}/a.csv", ruta))
+            .unwrap();
+        bitacora.anexar(&format!("{}/b.csv", ruta), "4\n").unwrap();
+        if let Some(paso) = paso {
+            bitacora.fallar_en(paso);
+        }
+        let resultado = bitacora.confirmar();
+        assert_eq!(resultado.is_ok(), paso.is_none());
+        let datos = match &bitacora.operaciones[1] {
+            Operacion::Anexar { datos, .. } => datos.to_string(),
+            _ => panic!("FALLO TEST BITACORA"),
+        };
+        // Al simular la falla el programa termina sin limpiar los archivos auxiliares.
+        std::mem::forget(bitacora);
+        datos
+    }
+
+    #[test]
+    fn test_recuperacion_en_cada_paso() {
+        let pasos = [
+            (Some(Paso::EscribirBitacora), false),
+            (Some(Paso::AplicarOperacion(0)), true),
+            (Some(Paso::AplicarOperacion(1)), true),
+            (Some(Paso::EliminarBitacora), true),
+            (None, true),
+        ];
+        for (paso, aplicado) in pasos {
+            let ruta = crear_directorio("bitacora");
+            confirmar_con_falla(&ruta, paso);
+            recuperar(&ruta).unwrap();
+            let a = fs::read_to_string(format!("{}/a.csv", ruta)).unwrap();
+            let b = fs::read_to_string(format!("{}/b.csv", ruta)).unwrap();
+            if aplicado {
+                assert_eq!(
+                    (a.as_str(), b.as_str()),
+                    (NUEVO_A, "id\n2\n4\n"),
+                    "{:?}",
+                    paso
+                );
+            } else {
+                assert_eq!(
+                    (a.as_str(), b.as_str()),
+                    (ORIGINAL_A, ORIGINAL_B),
+                    "{:?}",
+                    paso
+                );
+            }
+            let mut restantes: Vec<String> = fs::read_dir(&ruta)
+                .unwrap()
+                .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+                .collect();
+            restantes.sort();
+            assert_eq!(restantes, ["a.csv", "b.csv"], "{:?}", paso);
+        }
+    }
+
+    #[test]
+    fn test_anexar_dos_veces_no_duplica_filas() {
+        let ruta = crear_directorio("bitacora_anexar");
+        let datos = confirmar_con_falla(&ruta, Some(Paso::EliminarBitacora));
+        // Simula que el programa termino luego de agregar las filas pero antes de eliminar el archivo de datos; la recuperacion no debe repetirlas.
+        fs::write(datos, "4\n").unwrap();
+        recuperar(&ruta).unwrap();
+        let b = fs::read_to_string(format!("{}/b.csv", ruta)).unwrap();
+        assert_eq!(b, "id\n2\n4\n");
+    }
+
+    #[test]
+    fn test_escrituras_simultaneas_del_mismo_archivo() {
+        let ruta = crear_directorio("bitacora_escrituras");
+        let archivo = format!("{}/x.secuencia", ruta);
+        let hilos: Vec<_> = (0..8)
+            .map(|i| {
+                let archivo = archivo.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..20 {
+                        escribir_archivo(&archivo, &format!("valor {}\n", i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for hilo in hilos {
+            hilo.join().unwrap();
+        }
+        assert!(fs::read_to_string(&archivo).unwrap().starts_with("valor "));
+        let mut restantes: Vec<String> = fs::read_dir(&ruta)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        restantes.sort();
+        assert_eq!(restantes, ["a.csv", "b.csv", "x.secuencia"]);
+    }
+
+    #[test]
+    fn test_bitacora_incompleta_se_descarta() {
+        let operacion = Operacion::Eliminar {
+            ruta: "datos/x".to_string(),
+        };
+        let completa = format!("{}\nfin\n", serializar(&operacion, "datos"));
+        assert_eq!(completa, "eliminar\tx\nfin\n");
+        assert_eq!(
+            deserializar(&completa, "datos"),
+            Some(vec![operacion.clone()])
+        );
+        assert_eq!(
+            deserializar(&serializar(&operacion, "datos"), "datos"),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_recuperacion_desde_otro_directorio_de_trabajo() {
+        let ruta = crear_directorio("bitacora_relativa");
+        // Los cambios se confirman con una ruta relativa al directorio de trabajo que pasa por un enlace, y la recuperacion usa otra ruta: la bitacora no debe depender de como se escribio la ruta.
+        let enlace = format!("{}_enlace", ruta);
+        let _ = fs::remove_file(&enlace);
+        std::os::unix::fs::symlink(&ruta, &enlace).unwrap();
+        let actual = std::env::current_dir().unwrap();
+        let relativa = format!(
+            "{}{}",
+            "../".repeat(actual.components().count() - 1),
+            enlace.trim_start_matches('/')
+        );
+        confirmar_con_falla(&relativa, Some(Paso::AplicarOperacion(0)));
+        fs::remove_file(&enlace).unwrap();
+        recuperar(&ruta).unwrap();
+        let a = fs::read_to_string(format!("{}/a.csv", ruta)).unwrap();
+        let b = fs::read_to_string(format!("{}/b.csv", ruta)).unwrap();
+        assert_eq!((a.as_str(), b.as_str()), (NUEVO_A, "id\n2\n4\n"));
+    }
+
+    #[test]
+    fn test_auxiliar_faltante_conserva_la_bitacora() {
+        let ruta = crear_directorio("bitacora_auxiliar_faltante");
+        confirmar_con_falla(&ruta, Some(Paso::AplicarOperacion(0)));
+        let auxiliares: Vec<String> = fs::read_dir(&ruta)
+            .unwrap()
+            .map(|e| e.unwrap().path().to_string_lossy().to_string())
+            .filter(|nombre| nombre.ends_with("_a"))
+            .collect();
+        assert_eq!(auxiliares.len(), 1);
+        fs::remove_file(&auxiliares[0]).unwrap();
+        assert!(recuperar(&ruta).is_err());
+        let a = fs::read_to_string(format!("{}/a.csv", ruta)).unwrap();
+        assert_eq!(a, ORIGINAL_A);
+        let bitacoras = fs::read_dir(&ruta)
+            .unwrap()
+            .filter(|e| {
+                e.as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("bitacora_")
+            })
+            .count();
+        assert_eq!(bitacoras, 1);
+    }
+}
@@ -0,0 +1,304 @@
+use crate::ejecutor::bitacora::{recuperar, recuperar_proceso};
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use std::env;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Variable de entorno con el tiempo maximo, en milisegundos, que se espera para obtener un bloqueo.
+const VARIABLE_TIEMPO_DE_ESPERA: &str = "SQL_RUSTICO_TIMEOUT_BLOQUEO";
+/// Tiempo de espera por defecto para obtener un bloqueo.
+const ESPERA_POR_DEFECTO: Duration = Duration::from_millis(5000);
+/// Tiempo entre cada intento de obtener un bloqueo ocupado.
+const INTERVALO_DE_REINTENTO: Duration = Duration::from_millis(10);
+/// Nombre del bloqueo que toman todos los procesos que trabajan sobre el directorio.
+const BLOQUEO_DIRECTORIO: &str = "_directorio";
+/// Nombre del bloqueo que protege el estado de la transaccion activa.
+pub const BLOQUEO_TRANSACCION: &str = "_transaccion";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// Representa los modos en los que puede bloquearse una tabla.
+pub enum ModoBloqueo {
+    /// Lo toman las consultas que solo leen la tabla. Varios procesos pueden tenerlo a la vez.
+    Compartido,
+    /// Lo toman las consultas que modifican la tabla. Solo un proceso puede tenerlo, y mientras lo tiene nadie puede tener el compartido.
+    Exclusivo,
+}
+
+/// Representa un bloqueo obtenido sobre el archivo <nombre>.lock del directorio. Se libera al descartarse.
+/// Los bloqueos son bloqueos consultivos del sistema operativo, que los libera automaticamente si el proceso termina. El proceso que tiene el bloqueo exclusivo escribe su identificador en el archivo y lo borra al liberarlo; si al obtener un bloqueo el archivo todavia tiene un identificador, el proceso anterior termino sin liberarlo y sus cambios a medio publicar se recuperan.
+pub struct Bloqueo {
+    archivo: File,
+    modo: ModoBloqueo,
+}
+
+impl Bloqueo {
+    /// Devuelve el identificador del proceso que tuvo el bloqueo exclusivo y termino sin liberarlo, si lo hay.
+    fn propietario_abandonado(&mut self) -> Option<u32> {
+        let mut contenido = String::new();
+        let _ = self.archivo.seek(SeekFrom::Start(0));
+        let _ = self.archivo.read_to_string(&mut contenido);
+        contenido
+            .trim()
+            .parse()
+            .ok()
+            .filter(|&pid| pid != std::process::id())
+    }
+
+    fn registrar_propietario(&mut self) -> Result<(), Errores> {
+        let resultado = self
+            .archivo
+            .set_len(0)
+            .and_then(|_| self.archivo.seek(SeekFrom::Start(0)))
+            .and_then(|_| write!(self.archivo, "{}", std::process::id()))
+            .and_then(|_| self.archivo.sync_all());
+        if resultado.is_err() {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error registrando el bloqueo".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Bloqueo {
+    /// Borra el identificador del proceso antes de liberar el bloqueo exclusivo.
+    fn drop(&mut self) {
+        if self.modo == ModoBloqueo::Exclusivo {
+            let _ = self.archivo.set_len(0);
+        }
+        let _ = self.archivo.unlock();
+    }
+}
+
+/// Esta funcion devuelve el tiempo maximo que se espera para obtener un bloqueo, configurable mediante la variable de entorno SQL_RUSTICO_TIMEOUT_BLOQUEO.
+pub fn tiempo_de_espera() -> Duration {
+    match env::var(VARIABLE_TIEMPO_DE_ESPERA)
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+    {
+        Some(milisegundos) => Duration::from_millis(milisegundos),
+        None => ESPERA_POR_DEFECTO,
+    }
+}
+
+fn crear_ruta_bloqueo(path: &String, nombre: &str) -> String {
+    format!("{}/{}.lock", path, nombre)
+}
+
+/// Esta funcion obtiene un bloqueo sobre el nombre indicado, esperando como maximo el tiempo indicado. Si no lo consigue devuelve el error LockTimeout.
+/// Si el bloqueo habia quedado abandonado por un proceso que termino, antes de devolverlo se recuperan los cambios que ese proceso dejo a medio publicar.
+pub fn bloquear(
+    path: &String,
+    nombre: &str,
+    modo: ModoBloqueo,
+    espera: Duration,
+) -> Result<Bloqueo, Errores> {
+    let inicio = Instant::now();
+    loop {
+        let restante = espera.saturating_sub(inicio.elapsed());
+        let mut bloqueo = adquirir(path, nombre, modo, restante)?;
+        let abandonado = bloqueo.propietario_abandonado();
+        match (abandonado, modo) {
+            (None, ModoBloqueo::Compartido) => return Ok(bloqueo),
+            (None, ModoBloqueo::Exclusivo) => {
+                bloqueo.registrar_propietario()?;
+                return Ok(bloqueo);
+            }
+            (Some(pid), ModoBloqueo::Exclusivo) => {
+                recuperar_proceso(path, pid)?;
+                bloqueo.registrar_propietario()?;
+                return Ok(bloqueo);
+            }
+            (Some(_), ModoBloqueo::Compartido) => {
+                // Para recuperar hace falta el bloqueo exclusivo; una vez recuperado se vuelve a pedir el compartido.
+                drop(bloqueo);
+                let restante = espera.saturating_sub(inicio.elapsed());
+                drop(bloquear(path, nombre, ModoBloqueo::Exclusivo, restante)?);
+            }
+        }
+    }
+}
+
+/// Intenta obtener el bloqueo hasta que se agote el tiempo de espera.
+fn adquirir(
+    path: &String,
+    nombre: &str,
+    modo: ModoBloqueo,
+    espera: Duration,
+) -> Result<Bloqueo, Errores> {
+    let archivo = match OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(crear_ruta_bloqueo(path, nombre))
+    {
+        Ok(archivo) => archivo,
+        Err(_) => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                format!("No se pudo crear el bloqueo de {}", nombre),
+            ));
+        }
+    };
+    let inicio = Instant::now();
+    loop {
+        let intento = match modo {
+            ModoBloqueo::Compartido => archivo.try_lock_shared(),
+            ModoBloqueo::Exclusivo => archivo.try_lock(),
+        };
+        match intento {
+            Ok(()) => return Ok(Bloqueo { archivo, modo }),
+            Err(TryLockError::WouldBlock) if inicio.elapsed() < espera => {
+                thread::sleep(INTERVALO_DE_REINTENTO)
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(Errores::nuevo(
+                    TipoError::LockTimeout,
+                    format!(
+                        "No se pudo bloquear {} antes de que se agotara el tiempo de espera",
+                        nombre
+                    ),
+                ));
+            }
+            Err(TryLockError::Error(_)) => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    format!("Error obteniendo el bloqueo de {}", nombre),
+                ));
+            }
+        }
+    }
+}
+
+/// Esta funcion obtiene los bloqueos indicados. Se piden siempre en orden alfabetico para que dos procesos no puedan esperarse mutuamente; si un nombre aparece mas de una vez se pide el modo mas fuerte.
+pub fn bloquear_varios(
+    path: &String,
+    pedidos: &[(String, ModoBloqueo)],
+) -> Result<Vec<Bloqueo>, Errores> {
+    let mut ordenados: Vec<(String, ModoBloqueo)> = pedidos.to_vec();
+    ordenados.sort();
+    let mut unicos: Vec<(String, ModoBloqueo)> = Vec::new();
+    for (nombre, modo) in ordenados {
+        match unicos.last_mut() {
+            Some((ultimo, modo_ultimo)) if *ultimo == nombre => {
+                *modo_ultimo = (*modo_ultimo).max(modo)
+            }
+            _ => unicos.push((nombre, modo)),
+        }
+    }
+    let espera = tiempo_de_espera();
+    let mut bloqueos: Vec<Bloqueo> = Vec::new();
+    for (nombre, modo) in unicos {
+        bloqueos.push(bloquear(path, &nombre, modo, espera)?);
+    }
+    Ok(bloqueos)
+}
+
+/// Esta funcion se ejecuta al iniciar el programa. Si ningun otro proceso esta trabajando sobre el directorio, todos los archivos auxiliares y bitacoras que encuentre quedaron de procesos que terminaron abruptamente, y se recuperan.
+/// Devuelve el bloqueo compartido del directorio, que debe mantenerse mientras dure el programa.
+pub fn iniciar_sesion(path: &String) -> Result<Bloqueo, Errores> {
+    if let Ok(exclusivo) = adquirir(
+        path,
+        BLOQUEO_DIRECTORIO,
+        ModoBloqueo::Exclusivo,
+        Duration::ZERO,
+    ) {
+        recuperar(path)?;
+        drop(exclusivo);
+    }
+    bloquear(
+        path,
+        BLOQUEO_DIRECTORIO,
+        ModoBloqueo::Compartido,
+        tiempo_de_espera(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bloquear, bloquear_varios, ModoBloqueo};
+    use crate::errores::errores_implementacion::TipoError;
+    use std::fs;
+    use std::time::Duration;
+
+    fn crear_directorio(nombre: &str) -> String {
+        let directorio =
+            std::env::temp_dir().join(format!("sql_rustico_{}_{}", nombre, std::process::id()));
+        let _ = fs::remove_dir_all(&directorio);
+        fs::create_dir_all(&directorio).unwrap();
+        directorio.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_bloqueo_exclusivo_excluye_a_los_demas() {
+        let ruta = crear_directorio("bloqueos");
+        let espera = Duration::from_millis(50);
+        let lectura_1 = bloquear(&ruta, "clientes", ModoBloqueo::Compartido, espera).unwrap();
+        let lectura_2 = bloquear(&ruta, "clientes", ModoBloqueo::Compartido, espera).unwrap();
+        match bloquear(&ruta, "clientes", ModoBloqueo::Exclusivo, espera) {
+            Err(e) => assert_eq!(e.tipo, TipoError::LockTimeout),
+            Ok(_) => panic!("FALLO TEST BLOQUEO"),
+        }
+        drop(lectura_1);
+        drop(lectura_2);
+        let escritura = bloquear(&ruta, "clientes", ModoBloqueo::Exclusivo, espera).unwrap();
+        match bloquear(&ruta, "clientes", ModoBloqueo::Compartido, espera) {
+            Err(e) => assert_eq!(e.tipo, TipoError::LockTimeout),
+            Ok(_) => panic!("FALLO TEST BLOQUEO"),
+        }
+        drop(escritura);
+        assert_eq!(
+            fs::read_to_string(format!("{}/clientes.lock", ruta)).unwrap(),
+            ""
+        );
+        let bloqueos = bloquear_varios(
+            &ruta,
+            &[
+                ("ordenes".to_string(), ModoBloqueo::Compartido),
+                ("clientes".to_string(), ModoBloqueo::Exclusivo),
+                ("ordenes".to_string(), ModoBloqueo::Exclusivo),
+            ],
+        )
+        .unwrap();
+        assert_eq!(bloqueos.len(), 2);
+    }
+
+    #[test]
+    fn test_bloqueo_abandonado_recupera_los_cambios() {
+        let ruta = crear_directorio("bloqueo_abandonado");
+        // Un proceso inexistente dejo el bloqueo de la tabla, un archivo auxiliar y una bitacora completa.
+        let pid_abandonado: u32 = u32::MAX - 1;
+        fs::write(format!("{}/t.csv", ruta), "id\n1\n").unwrap();
+        fs::write(format!("{}/t.lock", ruta), pid_abandonado.to_string()).unwrap();
+        let temporal = format!("archivo_temporal_{}_0_t", pid_abandonado);
+        fs::write(format!("{}/{}", ruta, temporal), "id\n1\n2\n").unwrap();
+        fs::write(
+            format!("{}/bitacora_{}_1", ruta, pid_abandonado),
+            format!("renombrar\t{}\tt.csv\t7\nfin\n", temporal),
+        )
+        .unwrap();
+        let lectura = bloquear(
+            &ruta,
+            "t",
+            ModoBloqueo::Compartido,
+            Duration::from_millis(50),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(format!("{}/t.csv", ruta)).unwrap(),
+            "id\n1\n2\n"
+        );
+        assert_eq!(fs::read_to_string(format!("{}/t.lock", ruta)).unwrap(), "");
+        drop(lectura);
+        let mut restantes: Vec<String> = fs::read_dir(&ruta)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        restantes.sort();
+        assert_eq!(restantes, ["t.csv", "t.lock"]);
+    }
+}
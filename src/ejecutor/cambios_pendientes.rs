@@ -0,0 +1,148 @@
+use crate::ejecutor::bitacora::{nombre_temporal, Bitacora};
+use crate::ejecutor::transaccion::{ruta_escritura, ruta_tabla};
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::indices::sincronizar_indices;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+
+/// Representa el conjunto de tablas modificadas por una consulta cuyos cambios todavia no fueron publicados.
+/// Cada tabla modificada se escribe completa en un archivo auxiliar; recien cuando todas las tablas involucradas fueron escritas y validadas, los auxiliares reemplazan a las tablas originales.
+/// Si los cambios no se publican (por ejemplo, porque la consulta fallo a mitad de camino), los archivos auxiliares se eliminan y las tablas quedan sin cambios.
+pub struct CambiosPendientes {
+    ruta_directorio: String,
+    archivos: Vec<(String, String)>,
+}
+
+impl CambiosPendientes {
+    pub fn new(ruta_directorio: &String) -> CambiosPendientes {
+        CambiosPendientes {
+            ruta_directorio: ruta_directorio.to_string(),
+            archivos: Vec::new(),
+        }
+    }
+
+    /// Devuelve la ruta a un archivo auxiliar nuevo para la tabla indicada.
+    pub fn nuevo_temporal(&self, tabla: &str) -> String {
+        nombre_temporal(&self.ruta_directorio, tabla)
+    }
+
+    /// Registra el archivo auxiliar que contiene la nueva version de la tabla. Si la tabla ya habia sido modificada, se descarta el auxiliar anterior.
+    pub fn registrar(&mut self, tabla: &String, temporal: String) {
+        if let Some(posicion) = self.archivos.iter().position(|(t, _)| t == tabla) {
+            let (_, anterior) = self.archivos.remove(posicion);
+            let _ = fs::remove_file(anterior);
+        }
+        self.archivos.push((tabla.to_string(), temporal));
+    }
+
+    /// Devuelve la ruta a la version actual de la tabla: el auxiliar si fue modificada en esta consulta o la tabla original si no.
+    pub fn ruta_actual(&self, tabla: &str) -> String {
+        match self.archivos.iter().find(|(t, _)| t == tabla) {
+            Some((_, temporal)) => temporal.to_string(),
+            None => ruta_tabla(&self.ruta_directorio, tabla),
+        }
+    }
+
+    /// Reescribe la version actual de la tabla en un nuevo archivo auxiliar, aplicando la funcion recibida a cada fila.
+    /// La funcion devuelve la fila (posiblemente modificada) para conservarla, o None para eliminarla.
+    pub fn reescribir_tabla<F>(&mut self, tabla: &String, mut funcion: F) -> Result<(), Errores>
+    where
+        F: FnMut(Vec<String>) -> Result<Option<Vec<String>>, Errores>,
+    {
+        let origen = match File::open(self.ruta_actual(tabla)) {
+            Ok(f) => f,
+            _ => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "No se pudo abrir el archivo".to_string(),
+                ));
+            }
+        };
+        let temporal = self.nuevo_temporal(tabla);
+        let mut destino = match File::create(&temporal) {
+            Ok(f) => f,
+            _ => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error actualizando la tabla".to_string(),
+                ));
+            }
+        };
+        let resultado = copiar_filas(origen, &mut destino, &mut funcion);
+        if resultado.is_err() {
+            let _ = fs::remove_file(&temporal);
+            return resultado;
+        }
+        self.registrar(tabla, temporal);
+        Ok(())
+    }
+
+    /// Reemplaza cada tabla modificada por su archivo auxiliar. Si hay una transaccion activa, se reemplaza el archivo sombra de la tabla en lugar del original.
+    /// Los reemplazos se registran en la bitacora del directorio, de manera que si el programa se interrumpe se completan al volver a iniciarlo.
+    /// Una vez publicados los cambios se actualizan los indices de las tablas modificadas.
+    pub fn publicar(mut self) -> Result<(), Errores> {
+        let mut bitacora = Bitacora::new(&self.ruta_directorio);
+        for (tabla, temporal) in &self.archivos {
+            let destino = ruta_escritura(&self.ruta_directorio, tabla)?;
+            bitacora.renombrar(temporal, &destino)?;
+        }
+        let resultado = bitacora.confirmar();
+        if resultado.is_ok() {
+            for (tabla, _) in &self.archivos {
+                sincronizar_indices(&self.ruta_directorio, tabla, None);
+            }
+        }
+        if resultado.is_ok() || bitacora.fue_registrada() {
+            self.archivos.clear();
+        }
+        resultado
+    }
+}
+
+impl Drop for CambiosPendientes {
+    /// Elimina los archivos auxiliares de los cambios que no llegaron a publicarse.
+    fn drop(&mut self) {
+        for (_, temporal) in &self.archivos {
+            let _ = fs::remove_file(temporal);
+        }
+    }
+}
+
+/// Copia el encabezado y las filas del origen al destino, aplicando la funcion a cada fila.
+fn copiar_filas<F>(origen: File, destino: &mut File, funcion: &mut F) -> Result<(), Errores>
+where
+    F: FnMut(Vec<String>) -> Result<Option<Vec<String>>, Errores>,
+{
+    let mut primera_linea: bool = true;
+    for linea in BufReader::new(origen).lines() {
+        let linea = match linea {
+            Ok(l) => l,
+            _ => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error leyendo la tabla".to_string(),
+                ));
+            }
+        };
+        if primera_linea {
+            if let Err(_e) = writeln!(destino, "{}", linea) {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error escribiendo el archivo".to_string(),
+                ));
+            }
+            primera_linea = false;
+            continue;
+        }
+        let fila: Vec<String> = linea.split(',').map(|s| s.to_string()).collect();
+        if let Some(fila) = funcion(fila)? {
+            if let Err(_e) = writeln!(destino, "{}", fila.join(",")) {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error escribiendo el archivo".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
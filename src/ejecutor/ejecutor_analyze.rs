@@ -0,0 +1,39 @@
+use crate::comandos::comandos_implementacion::Comandos;
+use crate::ejecutor::ejecutor_implementacion::{crear_ruta, listar_tablas, obtener_primera_linea};
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{cargar_esquema, guardar_esquema};
+use crate::esquema::estadisticas::calcular_estadisticas;
+use std::path::Path;
+
+/// Esta funcion se encarga de ejecutar la consulta. Calcula las estadisticas de la tabla indicada, o de todas las tablas del directorio, y las guarda en su esquema reemplazando las anteriores.
+/// Las estadisticas describen el contenido confirmado de cada tabla, por lo que dentro de una transaccion no incluyen sus cambios.
+pub fn ejecutar_analyze(comando: &Comandos, path: &String) -> Result<(), Errores> {
+    let tabla = match comando {
+        Comandos::Analyze { tabla } => tabla,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
+        }
+    };
+    for tabla in tablas_a_analizar(path, tabla)? {
+        let ruta = crear_ruta(path, &tabla);
+        let columnas = obtener_primera_linea(&ruta)?;
+        let mut esquema = cargar_esquema(path, &tabla, &columnas)?;
+        calcular_estadisticas(&ruta)?.guardar_en(&mut esquema);
+        guardar_esquema(path, &tabla, &esquema)?;
+    }
+    Ok(())
+}
+
+/// Esta funcion devuelve las tablas que analiza la instruccion ANALYZE: la tabla indicada o todas las tablas confirmadas del directorio.
+pub fn tablas_a_analizar(path: &String, tabla: &Option<String>) -> Result<Vec<String>, Errores> {
+    match tabla {
+        Some(tabla) => Ok(vec![tabla.to_string()]),
+        None => Ok(listar_tablas(path)?
+            .into_iter()
+            .filter(|tabla| Path::new(&crear_ruta(path, tabla)).exists())
+            .collect()),
+    }
+}
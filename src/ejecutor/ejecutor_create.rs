@@ -0,0 +1,164 @@
+use crate::comandos::comandos_implementacion::Comandos;
+use crate::ejecutor::ejecutor_implementacion::obtener_primera_linea;
+use crate::ejecutor::ejecutor_vista::{esquema_de_columnas, materializar};
+use crate::ejecutor::transaccion::{ruta_escritura, ruta_tabla};
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{guardar_esquema, Esquema, Restriccion};
+use crate::esquema::secuencias::crear_secuencia;
+use crate::esquema::vistas::definicion_de_vista;
+use std::fs;
+use std::path::Path;
+
+/// Esta funcion se encarga de ejecutar la consulta. Crea el archivo .csv con el encabezado de la tabla y el archivo de esquema con sus restricciones.
+pub fn ejecutar_create_table(comando: &Comandos, path: &String) -> Result<(), Errores> {
+    let (tabla, definiciones) = match comando {
+        Comandos::CreateTable {
+            tabla,
+            definiciones,
+        } => (tabla, definiciones),
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
+        }
+    };
+    let (esquema, encabezado) = validar_create_table(path, tabla, definiciones)?;
+    let destino = ruta_escritura(path, tabla)?;
+    guardar_esquema(path, tabla, &esquema)?;
+    if fs::write(&destino, format!("{}\n", encabezado.join(","))).is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error creando la tabla".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Esta funcion se encarga de ejecutar la consulta CREATE TABLE AS. Crea la tabla con las columnas del SELECT y sus tipos, sin restricciones, y le agrega sus filas.
+/// Las filas se escriben a medida que se producen en un archivo auxiliar que se convierte en la tabla una vez completo. Devuelve la cantidad de filas de la tabla.
+pub fn ejecutar_create_table_as(comando: &Comandos, path: &String) -> Result<usize, Errores> {
+    let (tabla, consulta, posicion_consulta) = match comando {
+        Comandos::CreateTableAs {
+            tabla,
+            consulta,
+            posicion_consulta,
+        } => (tabla, consulta, *posicion_consulta),
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
+        }
+    };
+    verificar_nombre_libre(path, tabla)?;
+    let (columnas, filas, cambios) =
+        materializar(path, tabla, consulta).map_err(|error| error.desplazar(posicion_consulta))?;
+    let esquema = esquema_de_columnas(columnas);
+    cambios.publicar()?;
+    guardar_esquema(path, tabla, &esquema)?;
+    Ok(filas)
+}
+
+/// Esta funcion se encarga de ejecutar la consulta. Crea el archivo de la secuencia, cuyo primer valor sera el de inicio.
+pub fn ejecutar_create_sequence(comando: &Comandos, path: &String) -> Result<(), Errores> {
+    match comando {
+        Comandos::CreateSequence {
+            nombre,
+            inicio,
+            incremento,
+        } => crear_secuencia(path, nombre, *inicio, *incremento),
+        _ => Err(Errores::nuevo(
+            TipoError::Error,
+            "Error procesando la consulta".to_string(),
+        )),
+    }
+}
+
+/// Verifica que la consulta CREATE TABLE sea valida: que tenga columnas, que sus restricciones sean correctas, que no exista una tabla ni una vista con su nombre y que sus claves foraneas referencien tablas y columnas existentes.
+/// Devuelve el esquema de la tabla y su encabezado.
+pub fn validar_create_table(
+    path: &String,
+    tabla: &String,
+    definiciones: &[String],
+) -> Result<(Esquema, Vec<String>), Errores> {
+    let esquema = Esquema::desde_definiciones(definiciones)?;
+    if esquema.columnas.is_empty() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "La tabla debe tener al menos una columna".to_string(),
+        ));
+    }
+    let encabezado: Vec<String> = esquema
+        .columnas
+        .iter()
+        .map(|c| c.nombre.to_string())
+        .collect();
+    esquema.validar_columnas(&encabezado)?;
+    verificar_nombre_libre(path, tabla)?;
+    validar_referencias(path, tabla, &esquema, &encabezado)?;
+    Ok((esquema, encabezado))
+}
+
+/// Devuelve error si ya existe una tabla o una vista con el nombre de la tabla que se quiere crear.
+pub fn verificar_nombre_libre(path: &String, tabla: &String) -> Result<(), Errores> {
+    if Path::new(&ruta_tabla(path, tabla)).exists() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidTable,
+            format!("La tabla {} ya existe", tabla),
+        )
+        .en_token(tabla));
+    }
+    if definicion_de_vista(path, tabla).is_some() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidTable,
+            format!("Ya existe una vista llamada {}", tabla),
+        )
+        .en_token(tabla));
+    }
+    Ok(())
+}
+
+/// Verifica que las tablas y columnas referenciadas por las claves foraneas existan. Una tabla puede referenciarse a si misma.
+fn validar_referencias(
+    path: &String,
+    tabla: &String,
+    esquema: &Esquema,
+    encabezado: &[String],
+) -> Result<(), Errores> {
+    for restriccion in &esquema.restricciones {
+        if let Restriccion::ForeignKey {
+            tabla_referenciada,
+            columnas_referenciadas,
+            ..
+        } = restriccion
+        {
+            let columnas_padre: Vec<String> = if tabla_referenciada == tabla {
+                encabezado.to_vec()
+            } else {
+                let ruta_padre = ruta_tabla(path, tabla_referenciada);
+                if !Path::new(&ruta_padre).exists() {
+                    return Err(Errores::nuevo(
+                        TipoError::InvalidTable,
+                        format!("No existe la tabla referenciada {}", tabla_referenciada),
+                    )
+                    .en_token(tabla_referenciada));
+                }
+                obtener_primera_linea(&ruta_padre)?
+            };
+            for columna in columnas_referenciadas {
+                if !columnas_padre.contains(columna) {
+                    return Err(Errores::nuevo(
+                        TipoError::InvalidColumn,
+                        format!(
+                            "No existe la columna {} en la tabla referenciada {}",
+                            columna, tabla_referenciada
+                        ),
+                    )
+                    .en_token(columna));
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,23 +1,72 @@
-use crate::comandos::comandos_implementacion::Comandos;
+use crate::comandos::comandos_implementacion::{parsear, Comandos};
+use crate::ejecutor::bloqueos::{bloquear_varios, ModoBloqueo, BLOQUEO_TRANSACCION};
+use crate::ejecutor::ejecutor_analyze::{ejecutar_analyze, tablas_a_analizar};
+use crate::ejecutor::ejecutor_create::{
+    ejecutar_create_sequence, ejecutar_create_table, ejecutar_create_table_as,
+    validar_create_table, verificar_nombre_libre,
+};
 use crate::ejecutor::ejecutor_delete::ejecutar_delete;
-use crate::ejecutor::ejecutor_insert::ejecutar_insert;
-use crate::ejecutor::ejecutor_select::ejecutar_select;
+use crate::ejecutor::ejecutor_indice::{
+    ejecutar_create_index, ejecutar_reindex, tablas_a_reindexar,
+};
+use crate::ejecutor::ejecutor_insert::{ejecutar_insert, ejecutar_insert_select};
+use crate::ejecutor::ejecutor_select::{ejecutar_explain, ejecutar_select, tablas_de_consulta};
 use crate::ejecutor::ejecutor_update::ejecutar_update;
-use crate::errores::errores_implementacion::{imprimir_error, Errores};
+use crate::ejecutor::ejecutor_vista::{
+    ejecutar_create_materialized_view, ejecutar_create_view, ejecutar_drop_view,
+    ejecutar_refresh_materialized_view, reescribir_sobre_vista, validar_create_materialized_view,
+    validar_select,
+};
+use crate::ejecutor::integridad_referencial::{tablas_dependientes, tablas_referenciadas};
+use crate::ejecutor::resultado::Resultado;
+use crate::ejecutor::transaccion::{
+    confirmar_transaccion, deshacer_transaccion, hay_transaccion_activa, iniciar_transaccion,
+    ruta_tabla, tablas_de_transaccion,
+};
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::errores::sugerencias::{limpiar_identificador, sugerir};
+use crate::esquema::vistas::{expandir_vistas, leer_vista_materializada};
+use crate::planificador::catalogo::{verificar_tabla_modificable, TablaCatalogo};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::path::Path;
 use std::vec;
 
 /// Esta funcion se encarga de que una vez recibido la consulta y la ruta al directorio donde se encuentra la tabla, procesar la misma.
 /// Si la query es delete o update, la estrategia elegida para evitar cargar el archivo en memoria es ir escribiendo los cambios (ya sean con actualizaciones o con eliminaciones) en otro archivo nuevo, el cual despues reemplezara al anterior.
-pub fn ejecutar_comando(comando: Comandos, path: &String) -> Result<(), Errores> {
+/// Antes de procesarla se bloquean las tablas involucradas, que se liberan al terminar.
+/// Devuelve las filas obtenidas si es un SELECT, o la cantidad de filas afectadas si modifica una tabla.
+pub fn ejecutar_comando(comando: Comandos, path: &String) -> Result<Resultado, Errores> {
     match &comando {
+        Comandos::Insert { tabla, .. }
+        | Comandos::InsertSelect { tabla, .. }
+        | Comandos::Update { tabla, .. }
+        | Comandos::Delete { tabla, .. }
+        | Comandos::CreateTable { tabla, .. }
+        | Comandos::CreateTableAs { tabla, .. }
+        | Comandos::CreateIndex { tabla, .. }
+        | Comandos::CreateView { nombre: tabla, .. }
+        | Comandos::CreateMaterializedView { nombre: tabla, .. }
+        | Comandos::RefreshMaterializedView { nombre: tabla }
+        | Comandos::Analyze { tabla: Some(tabla) } => verificar_tabla_modificable(tabla)?,
+        _ => {}
+    }
+    let comando = reescribir_sobre_vista(comando, path)?;
+    validar_creacion(&comando, path)?;
+    let _bloqueos = bloquear_varios(path, &bloqueos_necesarios(&comando, path)?)?;
+    let filas_afectadas = match &comando {
         Comandos::Insert {
             tabla: _,
             into: _,
             valores: _,
         } => ejecutar_insert(&comando, path),
+        Comandos::InsertSelect {
+            tabla: _,
+            into: _,
+            consulta: _,
+            posicion_consulta: _,
+        } => ejecutar_insert_select(&comando, path),
         Comandos::Update {
             tabla: _,
             clausula_set: _,
@@ -30,10 +79,247 @@ pub fn ejecutar_comando(comando: Comandos, path: &String) -> Result<(), Errores>
         Comandos::Select {
             campos: _,
             tabla: _,
+            joins: _,
             clausula_where: _,
+            agrupar_por: _,
             clausula_order: _,
-        } => ejecutar_select(&comando, path),
+            limite: _,
+        } => return ejecutar_select(&comando, path),
+        Comandos::Explain {
+            analizar: _,
+            consulta: _,
+        } => return ejecutar_explain(&comando, path),
+        Comandos::CreateTable {
+            tabla: _,
+            definiciones: _,
+        } => ejecutar_create_table(&comando, path).map(|()| 0),
+        Comandos::CreateTableAs {
+            tabla: _,
+            consulta: _,
+            posicion_consulta: _,
+        } => ejecutar_create_table_as(&comando, path),
+        Comandos::CreateSequence {
+            nombre: _,
+            inicio: _,
+            incremento: _,
+        } => ejecutar_create_sequence(&comando, path).map(|()| 0),
+        Comandos::CreateIndex {
+            nombre: _,
+            tabla: _,
+            columna: _,
+            unico: _,
+        } => ejecutar_create_index(&comando, path).map(|()| 0),
+        Comandos::CreateView {
+            nombre: _,
+            definicion: _,
+            consulta: _,
+            reemplazar: _,
+        } => ejecutar_create_view(&comando, path).map(|()| 0),
+        Comandos::DropView { nombre: _ } => ejecutar_drop_view(&comando, path).map(|()| 0),
+        Comandos::CreateMaterializedView {
+            nombre: _,
+            definicion: _,
+            consulta: _,
+        } => ejecutar_create_materialized_view(&comando, path),
+        Comandos::RefreshMaterializedView { nombre: _ } => {
+            ejecutar_refresh_materialized_view(&comando, path)
+        }
+        Comandos::Reindex { nombre: _ } => ejecutar_reindex(&comando, path).map(|()| 0),
+        Comandos::Analyze { tabla: _ } => ejecutar_analyze(&comando, path).map(|()| 0),
+        Comandos::Begin => iniciar_transaccion(path).map(|()| 0),
+        Comandos::Commit => confirmar_transaccion(path).map(|()| 0),
+        Comandos::Rollback => deshacer_transaccion(path).map(|()| 0),
+    }?;
+    Ok(Resultado::con_filas_afectadas(filas_afectadas))
+}
+
+/// Esta funcion parsea y ejecuta una consulta escrita por el usuario. Si falla, el error se ubica en el texto de la consulta para poder mostrar donde ocurrio.
+pub fn ejecutar_consulta(consulta: &str, path: &String) -> Result<Resultado, Errores> {
+    parsear(consulta)
+        .and_then(|comando| ejecutar_comando(comando, path))
+        .map_err(|error| error.ubicar(consulta))
+}
+
+/// Verifica, antes de bloquear la tabla que crea la consulta, que la creacion sea valida, para que una creacion que falla no deje el archivo de bloqueo de una tabla que no existe.
+/// Al ejecutar la consulta las verificaciones se repiten con la tabla ya bloqueada.
+fn validar_creacion(comando: &Comandos, path: &String) -> Result<(), Errores> {
+    match comando {
+        Comandos::CreateTable {
+            tabla,
+            definiciones,
+        } => validar_create_table(path, tabla, definiciones).map(|_| ()),
+        Comandos::CreateTableAs {
+            tabla,
+            consulta,
+            posicion_consulta,
+        } => {
+            verificar_nombre_libre(path, tabla)?;
+            validar_select(path, tabla, consulta)
+                .map_err(|error| error.desplazar(*posicion_consulta))
+        }
+        Comandos::CreateMaterializedView {
+            nombre, consulta, ..
+        } => validar_create_materialized_view(path, nombre, consulta),
+        _ => Ok(()),
+    }
+}
+
+/// Esta funcion devuelve los bloqueos que necesita la consulta: compartido sobre las tablas que solo lee y exclusivo sobre las que puede modificar, incluidas aquellas a las que se propagan los cambios por claves foraneas.
+fn bloqueos_necesarios(
+    comando: &Comandos,
+    path: &String,
+) -> Result<Vec<(String, ModoBloqueo)>, Errores> {
+    let mut bloqueos: Vec<(String, ModoBloqueo)> = Vec::new();
+    let mut modifica = true;
+    match comando {
+        Comandos::Select { .. } | Comandos::Explain { .. } => {
+            bloqueos.extend(bloqueos_de_lectura(path, tablas_de_consulta(comando))?);
+            modifica = false;
+        }
+        Comandos::Insert { tabla, .. } => {
+            bloqueos.push((tabla_existente(path, tabla)?, ModoBloqueo::Exclusivo));
+            for padre in tablas_referenciadas(path, tabla)? {
+                bloqueos.push((padre, ModoBloqueo::Compartido));
+            }
+        }
+        Comandos::InsertSelect {
+            tabla, consulta, ..
+        } => {
+            bloqueos.extend(bloqueos_de_lectura(path, tablas_de_consulta(consulta))?);
+            bloqueos.push((tabla_existente(path, tabla)?, ModoBloqueo::Exclusivo));
+            for padre in tablas_referenciadas(path, tabla)? {
+                bloqueos.push((padre, ModoBloqueo::Compartido));
+            }
+        }
+        Comandos::Update { tabla, .. } | Comandos::Delete { tabla, .. } => {
+            bloqueos.push((tabla_existente(path, tabla)?, ModoBloqueo::Exclusivo));
+            for padre in tablas_referenciadas(path, tabla)? {
+                bloqueos.push((padre, ModoBloqueo::Compartido));
+            }
+            for hija in tablas_dependientes(path, tabla)? {
+                bloqueos.push((hija, ModoBloqueo::Exclusivo));
+            }
+        }
+        Comandos::CreateTable { tabla, .. } => {
+            bloqueos.push((tabla.to_string(), ModoBloqueo::Exclusivo));
+        }
+        Comandos::CreateTableAs {
+            tabla, consulta, ..
+        } => {
+            bloqueos.push((tabla.to_string(), ModoBloqueo::Exclusivo));
+            bloqueos.extend(bloqueos_de_lectura(path, tablas_de_consulta(consulta))?);
+        }
+        Comandos::CreateSequence { nombre, .. } => {
+            bloqueos.push((format!("{}.secuencia", nombre), ModoBloqueo::Exclusivo));
+            modifica = false;
+        }
+        Comandos::CreateIndex { tabla, .. } => {
+            bloqueos.push((tabla_existente(path, tabla)?, ModoBloqueo::Exclusivo));
+        }
+        Comandos::CreateView { nombre, .. } | Comandos::DropView { nombre } => {
+            bloqueos.push((format!("{}.vista", nombre), ModoBloqueo::Exclusivo));
+            modifica = false;
+        }
+        Comandos::CreateMaterializedView {
+            nombre, consulta, ..
+        } => {
+            bloqueos.push((nombre.to_string(), ModoBloqueo::Exclusivo));
+            bloqueos.extend(bloqueos_de_lectura(path, tablas_de_consulta(consulta))?);
+        }
+        Comandos::RefreshMaterializedView { nombre } => {
+            bloqueos.push((tabla_existente(path, nombre)?, ModoBloqueo::Exclusivo));
+            // Si la tabla no es una vista materializada, la instruccion falla sin leer otras tablas.
+            if let Some(Ok(consulta)) =
+                leer_vista_materializada(path, nombre)?.map(|vista| parsear(&vista.definicion))
+            {
+                bloqueos.extend(bloqueos_de_lectura(path, tablas_de_consulta(&consulta))?);
+            }
+        }
+        Comandos::Reindex { nombre } => {
+            for tabla in tablas_a_reindexar(path, nombre)? {
+                bloqueos.push((tabla, ModoBloqueo::Exclusivo));
+            }
+        }
+        Comandos::Analyze { tabla } => {
+            // ANALYZE reescribe el esquema de cada tabla, por lo que necesita un bloqueo exclusivo.
+            for tabla in tablas_a_analizar(path, tabla)? {
+                bloqueos.push((tabla_existente(path, &tabla)?, ModoBloqueo::Exclusivo));
+            }
+            modifica = false;
+        }
+        Comandos::Begin => {}
+        Comandos::Commit | Comandos::Rollback => {
+            for tabla in tablas_de_transaccion(path) {
+                bloqueos.push((tabla, ModoBloqueo::Exclusivo));
+            }
+        }
+    }
+    let es_transaccion = matches!(
+        comando,
+        Comandos::Begin | Comandos::Commit | Comandos::Rollback
+    );
+    if es_transaccion || (modifica && hay_transaccion_activa(path)) {
+        bloqueos.push((BLOQUEO_TRANSACCION.to_string(), ModoBloqueo::Exclusivo));
+    }
+    Ok(bloqueos)
+}
+
+/// Devuelve los bloqueos compartidos sobre las tablas que lee un Select. Las vistas se bloquean a traves de las tablas que leen, y las tablas del catalogo no tienen un archivo que bloquear.
+fn bloqueos_de_lectura(
+    path: &String,
+    tablas: Vec<String>,
+) -> Result<Vec<(String, ModoBloqueo)>, Errores> {
+    expandir_vistas(path, tablas)?
+        .into_iter()
+        .filter(|tabla| TablaCatalogo::desde_nombre(tabla).is_none())
+        .map(|tabla| Ok((tabla_existente(path, &tabla)?, ModoBloqueo::Compartido)))
+        .collect()
+}
+
+/// Devuelve el nombre de la tabla si existe, incluidas las creadas dentro de la transaccion activa. Se verifica antes de bloquearla para no dejar archivos de bloqueo de tablas inexistentes.
+fn tabla_existente(path: &String, tabla: &str) -> Result<String, Errores> {
+    let ruta = ruta_tabla(path, tabla);
+    if !Path::new(&ruta).exists() {
+        return Err(tabla_inexistente(&ruta, tabla));
+    }
+    Ok(tabla.to_string())
+}
+
+/// Devuelve el error de una tabla que no existe, con la sugerencia de la tabla mas parecida del directorio.
+fn tabla_inexistente(ruta: &str, tabla: &str) -> Errores {
+    Errores::nuevo(
+        TipoError::InvalidTable,
+        format!("La tabla {} no existe", tabla),
+    )
+    .con_sugerencia(sugerir_tabla(ruta, tabla))
+    .en_token(tabla)
+}
+
+/// Esta funcion devuelve, ordenados, los nombres de las tablas del directorio, incluidas las creadas dentro de la transaccion activa.
+pub fn listar_tablas(path: &String) -> Result<Vec<String>, Errores> {
+    let entradas = match fs::read_dir(path) {
+        Ok(entradas) => entradas,
+        Err(_) => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "No se pudo leer el directorio de las tablas".to_string(),
+            ));
+        }
+    };
+    let mut tablas: Vec<String> = entradas
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let nombre = e.file_name().to_string_lossy().to_string();
+            nombre.strip_suffix(".csv").map(|t| t.to_string())
+        })
+        .collect();
+    for tabla in tablas_de_transaccion(path) {
+        if !tablas.contains(&tabla) {
+            tablas.push(tabla);
+        }
     }
+    tablas.sort();
+    Ok(tablas)
 }
 
 /// Esta funcion recibe la ruta al directorio y el nombre de la tabla incluida en la query, y devuelve la ruta al archivo .csv que va a ser procesado.
@@ -45,9 +331,19 @@ pub fn crear_ruta(path: &String, nombre_archivo: &String) -> String {
 pub fn obtener_primera_linea(path: &String) -> Result<Vec<String>, Errores> {
     let archivo = match File::open(path) {
         Ok(file) => file,
-        Err(_e) => {
-            imprimir_error(Errores::Error, "Error procesando el archivo".to_string());
-            return Err(Errores::Error);
+        Err(error) => {
+            let tabla = Path::new(path)
+                .file_stem()
+                .map(|nombre| nombre.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if error.kind() == ErrorKind::NotFound {
+                return Err(tabla_inexistente(path, &tabla));
+            }
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando el archivo".to_string(),
+            )
+            .en_token(&tabla));
         }
     };
     let lector = BufReader::new(archivo);
@@ -55,17 +351,40 @@ pub fn obtener_primera_linea(path: &String) -> Result<Vec<String>, Errores> {
     let primera_linea = match lineas.next() {
         Some(Ok(line)) => line,
         _ => {
-            imprimir_error(
-                Errores::Error,
-                "Error procesando el archivo: {}".to_string(),
-            );
-            return Err(Errores::Error);
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                format!("Error procesando el archivo {}", path),
+            ));
         }
     };
     let columnas: Vec<String> = primera_linea.split(',').map(|s| s.to_string()).collect();
     Ok(columnas)
 }
 
+/// Esta funcion devuelve el error para cuando la consulta usa una columna que no esta entre las columnas de la tabla, sugiriendo la mas parecida.
+pub fn columna_inexistente<'a, I>(columna: &str, columnas: I) -> Errores
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    let columna = limpiar_identificador(columna);
+    Errores::nuevo(
+        TipoError::InvalidColumn,
+        format!("La columna {} no existe", columna),
+    )
+    .en_token(columna)
+    .con_sugerencia(sugerir(columna, columnas.into_iter().map(|c| c.as_str())))
+}
+
+/// Devuelve la tabla del mismo directorio cuyo nombre se parece al de la tabla que no existe.
+fn sugerir_tabla(ruta_archivo: &str, tabla: &str) -> Option<String> {
+    let directorio = Path::new(ruta_archivo)
+        .parent()?
+        .to_string_lossy()
+        .to_string();
+    let tablas = listar_tablas(&directorio).ok()?;
+    sugerir(tabla, tablas.iter().map(|t| t.as_str()))
+}
+
 /// Esta funcion se encarga de eliminar los caracteres no deseados de los parametros que puede tener la query ingresada.
 /// Ejemplo:
 /// Teniendo estos valores: "(111,", "6,", "'Laptop',", "3)".
@@ -78,7 +397,8 @@ pub fn limpiar_lista(linea: &str) -> Vec<String> {
         .trim_end_matches(';')
         .trim_end_matches(',')
         .trim_end_matches('\'')
-        .trim_end_matches(')');
+        .trim_end_matches(')')
+        .trim_end_matches('\'');
     let elementos: Vec<String> = linea_limpia
         .split(',')
         .map(|s| s.trim().to_string())
@@ -186,14 +506,13 @@ fn agrupar_condicionales(tokens: Vec<String>) -> Vec<String> {
     let mut resultado = Vec::new();
     let mut i = 0;
 
+    let es_operador = |token: &str| matches!(token, ">" | "<" | "=" | ">=" | "<=");
     while i < tokens.len() {
-        if i + 2 < tokens.len()
-            && (tokens[i + 1] == ">"
-                || tokens[i + 1] == "<"
-                || tokens[i + 1] == "="
-                || tokens[i + 1] == ">="
-                || tokens[i + 1] == "<=")
-        {
+        if i + 2 < tokens.len() && es_operador(&tokens[i + 1]) && es_operador(&tokens[i + 2]) {
+            // Falta el segundo miembro: la condicion queda incompleta y el operador siguiente empieza otra.
+            resultado.push(format!("{} {}", tokens[i], tokens[i + 1]));
+            i += 2;
+        } else if i + 2 < tokens.len() && es_operador(&tokens[i + 1]) {
             let condicional = format!("{} {} {}", tokens[i], tokens[i + 1], tokens[i + 2]);
             resultado.push(condicional);
             i += 3;
@@ -206,16 +525,24 @@ fn agrupar_condicionales(tokens: Vec<String>) -> Vec<String> {
     resultado
 }
 #[cfg(test)]
-mod test {
-    use crate::comandos::comandos_implementacion::Comandos;
-    use crate::ejecutor::ejecutor_implementacion::ejecutar_comando;
-    use crate::errores::errores_implementacion::Errores;
-    use std::fs::File;
+pub mod test {
+    use crate::analizador::analizador_implementacion::parsear_expresion;
+    use crate::comandos::comandos_implementacion::{parsear, Comandos};
+    use crate::ejecutor::ejecutor_implementacion::{
+        adaptar_where, crear_ruta, ejecutar_comando, ejecutar_consulta, obtener_indices_columnas,
+        obtener_primera_linea,
+    };
+    use crate::errores::errores_implementacion::{Errores, TipoError};
+    use crate::esquema::esquema_implementacion::cargar_esquema;
+    use crate::esquema::indices::buscar_posiciones;
+    use std::fs::{self, File};
     use std::io::{BufRead, BufReader};
+    use std::path::Path;
 
     #[test]
     fn test_insert_valida() {
-        let ruta_tabla: String = "src".to_string();
+        let ruta_tabla =
+            crear_directorio_prueba("insert_valida", &[("ordenes.csv", ORDENES_EJEMPLO)]);
         let query: Comandos = Comandos::Insert {
             tabla: "ordenes".to_string(),
             into: vec![
@@ -234,15 +561,19 @@ mod test {
         if let Err(_e) = ejecutar_comando(query, &ruta_tabla) {
             panic!("FALLO TEST INSERT")
         }
-        let linea_esta = buscar_linea("111,6,Laptop,3".to_string(), "src/ordenes.csv".to_string());
+        let linea_esta = buscar_linea(
+            "111,6,Laptop,3".to_string(),
+            crear_ruta(&ruta_tabla, &"ordenes".to_string()),
+        );
         match linea_esta {
-            Ok(bool) => assert_eq!(bool, true),
+            Ok(bool) => assert!(bool),
             _ => panic!("FALLO TEST INSERT"),
         }
     }
     #[test]
     fn test_insert_invalida() {
-        let ruta_tabla: String = "src".to_string();
+        let ruta_tabla =
+            crear_directorio_prueba("insert_invalida", &[("ordenes.csv", ORDENES_EJEMPLO)]);
         let query: Comandos = Comandos::Insert {
             tabla: "ordenes".to_string(),
             into: vec![
@@ -259,14 +590,15 @@ mod test {
             ],
         };
         match ejecutar_comando(query, &ruta_tabla) {
-            Err(e) => assert_eq!(e, Errores::Error),
+            Err(e) => assert_eq!(e.tipo, TipoError::InvalidColumn),
             _ => panic!("FALLO TEST INSERT"),
         }
     }
 
     #[test]
     fn test_delete_valida() {
-        let ruta_tabla: String = "src".to_string();
+        let ruta_tabla =
+            crear_directorio_prueba("delete_valida", &[("clientes.csv", CLIENTES_EJEMPLO)]);
         let query: Comandos = Comandos::Delete {
             tabla: "clientes".to_string(),
             clausula_where: "apellido = 'López' and email = 'ana.lopez@email.com'".to_string(),
@@ -276,28 +608,90 @@ mod test {
         }
         let linea_esta = buscar_linea(
             "2,Ana,López,ana.lopez@email.com".to_string(),
-            "src/clientes.csv".to_string(),
+            crear_ruta(&ruta_tabla, &"clientes".to_string()),
         );
         match linea_esta {
-            Ok(bool) => assert_eq!(bool, false),
+            Ok(bool) => assert!(!bool),
             _ => panic!("FALLO TEST DELETE"),
         }
     }
+    #[test]
+    fn test_sugerencias_de_tablas_y_columnas() {
+        let ruta_tabla =
+            crear_directorio_prueba("sugerencias", &[("clientes.csv", "id,nombre\n1,Ana\n")]);
+        let error =
+            ejecutar_comando(parsear("SELECT * FROM cliente").unwrap(), &ruta_tabla).unwrap_err();
+        assert_eq!(error.tipo, TipoError::InvalidTable);
+        assert_eq!(error.sugerencia, Some("clientes".to_string()));
+        let error = ejecutar_comando(
+            parsear("UPDATE clientes SET nombr = 'Luis' WHERE id = 1").unwrap(),
+            &ruta_tabla,
+        )
+        .unwrap_err();
+        assert_eq!(error.tipo, TipoError::InvalidColumn);
+        assert_eq!(error.sugerencia, Some("nombre".to_string()));
+        let error = parsear("SELECT * FORM clientes").unwrap_err();
+        assert_eq!(error.sugerencia, Some("FROM".to_string()));
+    }
+
+    #[test]
+    fn test_tabla_inexistente_no_deja_archivo_de_bloqueo() {
+        let ruta_tabla =
+            crear_directorio_prueba("bloqueo_inexistente", &[("clientes.csv", CLIENTES)]);
+        for consulta in [
+            "SELECT * FROM inexistente",
+            "INSERT INTO inexistente (id) VALUES (1)",
+            "UPDATE inexistente SET id = 2",
+            "DELETE FROM inexistente",
+            "INSERT INTO clientes (id, nombre) SELECT id, nombre FROM inexistente",
+        ] {
+            let error = ejecutar_consulta(consulta, &ruta_tabla).unwrap_err();
+            assert_eq!(error.tipo, TipoError::InvalidTable);
+            assert_eq!(error.mensaje, "La tabla inexistente no existe");
+        }
+        assert!(!Path::new(&ruta_tabla).join("inexistente.lock").exists());
+    }
+
+    #[test]
+    fn test_create_invalido_no_deja_archivo_de_bloqueo() {
+        let ruta_tabla = crear_directorio_prueba("bloqueo_create", &[("clientes.csv", CLIENTES)]);
+        for (consulta, tabla) in [
+            ("CREATE TABLE g (a INT, a TEXT)", "g"),
+            ("CREATE TABLE h (a INT CHECK (a > ))", "h"),
+            (
+                "CREATE TABLE copia AS SELECT id, apellido FROM clientes",
+                "copia",
+            ),
+            (
+                "CREATE MATERIALIZED VIEW resumen AS SELECT id, id FROM clientes",
+                "resumen",
+            ),
+        ] {
+            if ejecutar_consulta(consulta, &ruta_tabla).is_ok() {
+                panic!("FALLO TEST CREATE: {}", consulta)
+            }
+            let bloqueo = Path::new(&ruta_tabla).join(format!("{}.lock", tabla));
+            assert!(!bloqueo.exists(), "{}", consulta);
+        }
+    }
+
     #[test]
     fn test_delete_invalida() {
-        let ruta_tabla: String = "src".to_string();
+        let ruta_tabla =
+            crear_directorio_prueba("delete_invalida", &[("clientes.csv", CLIENTES_EJEMPLO)]);
         let query: Comandos = Comandos::Delete {
             tabla: "cliente".to_string(),
             clausula_where: "apellido = 'López' and email = 'ana.lopez@email.com'".to_string(),
         };
         match ejecutar_comando(query, &ruta_tabla) {
-            Err(e) => assert_eq!(e, Errores::Error),
+            Err(e) => assert_eq!(e.tipo, TipoError::InvalidTable),
             _ => panic!("FALLO TEST DELETE"),
         }
     }
     #[test]
     fn test_update_valida() {
-        let ruta_tabla: String = "src".to_string();
+        let ruta_tabla =
+            crear_directorio_prueba("update_valida", &[("clientes.csv", CLIENTES_EJEMPLO)]);
         let query: Comandos = Comandos::Update {
             tabla: "clientes".to_string(),
             clausula_set: "email = 'mrodriguez@hotmail.com'".to_string(),
@@ -308,18 +702,1076 @@ mod test {
         }
         let linea_esta = buscar_linea(
             "4,María,Rodríguez,mrodriguez@hotmail.com".to_string(),
-            "src/clientes.csv".to_string(),
+            crear_ruta(&ruta_tabla, &"clientes".to_string()),
         );
         match linea_esta {
-            Ok(bool) => assert_eq!(bool, true),
+            Ok(bool) => assert!(bool),
+            _ => panic!("FALLO TEST UPDATE"),
+        }
+    }
+
+    #[test]
+    fn test_create_table_valida() {
+        let ruta_tabla = crear_directorio_prueba("create_table", &[]);
+        let query = parsear(
+            "CREATE TABLE productos (id INT PRIMARY KEY, nombre TEXT NOT NULL, codigo TEXT UNIQUE)",
+        )
+        .unwrap();
+        if let Err(_e) = ejecutar_comando(query, &ruta_tabla) {
+            panic!("FALLO TEST CREATE TABLE")
+        }
+        let tabla = fs::read_to_string(format!("{}/productos.csv", ruta_tabla)).unwrap();
+        let esquema = fs::read_to_string(format!("{}/productos.esquema", ruta_tabla)).unwrap();
+        assert_eq!(tabla, "id,nombre,codigo\n");
+        assert_eq!(
+            esquema,
+            "id INT\nnombre TEXT NOT NULL\ncodigo TEXT\nPRIMARY KEY (id)\nUNIQUE (codigo)\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_primary_key_duplicada() {
+        let ruta_tabla = crear_directorio_prueba(
+            "insert_pk",
+            &[
+                ("ordenes.csv", ORDENES),
+                ("ordenes.esquema", "id INT PRIMARY KEY\n"),
+            ],
+        );
+        let query = parsear(
+            "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (101, 6, 'Laptop', 3)",
+        )
+        .unwrap();
+        match ejecutar_comando(query, &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::ConstraintViolation),
+            _ => panic!("FALLO TEST INSERT"),
+        }
+        let tabla = fs::read_to_string(format!("{}/ordenes.csv", ruta_tabla)).unwrap();
+        assert_eq!(tabla, ORDENES);
+    }
+
+    #[test]
+    fn test_insert_varias_filas_es_todo_o_nada() {
+        let ruta_tabla = crear_directorio_prueba(
+            "insert_varias",
+            &[
+                ("ordenes.csv", ORDENES),
+                (
+                    "ordenes.esquema",
+                    "id INT PRIMARY KEY\nproducto TEXT NOT NULL\n",
+                ),
+            ],
+        );
+        let duplicada =
+            parsear("INSERT INTO ordenes (id, producto) VALUES (120, 'Mouse'), (120, 'Monitor')")
+                .unwrap();
+        match ejecutar_comando(duplicada, &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::ConstraintViolation),
+            _ => panic!("FALLO TEST INSERT"),
+        }
+        let nula = parsear("INSERT INTO ordenes (id, producto) VALUES (121, 'Mouse'), (122, NULL)")
+            .unwrap();
+        match ejecutar_comando(nula, &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::ConstraintViolation),
+            _ => panic!("FALLO TEST INSERT"),
+        }
+        let tabla = fs::read_to_string(format!("{}/ordenes.csv", ruta_tabla)).unwrap();
+        assert_eq!(tabla, ORDENES);
+    }
+
+    #[test]
+    fn test_update_set_vacio_es_error_de_sintaxis() {
+        let ruta_tabla = crear_directorio_prueba("update_set_vacio", &[("ordenes.csv", ORDENES)]);
+        let query = parsear("UPDATE ordenes SET WHERE id = 101").unwrap();
+        match ejecutar_comando(query, &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::InvalidSyntax),
+            _ => panic!("FALLO TEST UPDATE"),
+        }
+    }
+
+    #[test]
+    fn test_select_con_varias_condiciones_encadenadas() {
+        let ruta_tabla =
+            crear_directorio_prueba("condiciones_encadenadas", &[("ordenes.csv", ORDENES)]);
+        let query = parsear(
+            "SELECT id FROM ordenes WHERE id > 100 AND cantidad = 1 AND producto = 'Monitor'",
+        )
+        .unwrap();
+        let filas = ejecutar_comando(query, &ruta_tabla)
+            .unwrap()
+            .filas_como_texto();
+        assert_eq!(filas, vec![vec!["102".to_string()]]);
+    }
+
+    #[test]
+    fn test_columna_inexistente_sin_separadores() {
+        let ruta_tabla = crear_directorio_prueba("columna_con_coma", &[("ordenes.csv", ORDENES)]);
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        let columnas = ["id".to_string(), "producto".to_string()];
+        let error = super::columna_inexistente("productos,", &columnas);
+        assert_eq!(error.mensaje, "La columna productos no existe");
+        assert_eq!(error.sugerencia, Some("producto".to_string()));
+        assert_eq!(
+            super::columna_inexistente("producto;", &columnas).sugerencia,
+            None
+        );
+        let error = ejecutar("SELECT * FROM ordenes ORDER BY producto, cantidad DESC").unwrap_err();
+        assert_eq!(error.tipo, TipoError::InvalidSyntax);
+        assert_eq!(error.sugerencia, None);
+    }
+
+    #[test]
+    fn test_update_unique_duplicado() {
+        let ruta_tabla = crear_directorio_prueba(
+            "update_unique",
+            &[
+                ("ordenes.csv", ORDENES),
+                ("ordenes.esquema", "id INT PRIMARY KEY\n"),
+            ],
+        );
+        let query = parsear("UPDATE ordenes SET id = 102 WHERE producto = 'Laptop'").unwrap();
+        match ejecutar_comando(query, &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::ConstraintViolation),
+            _ => panic!("FALLO TEST UPDATE"),
+        }
+        let tabla = fs::read_to_string(format!("{}/ordenes.csv", ruta_tabla)).unwrap();
+        assert_eq!(tabla, ORDENES);
+    }
+
+    #[test]
+    fn test_insert_clave_foranea_inexistente() {
+        let ruta_tabla = crear_directorio_prueba(
+            "insert_fk",
+            &[
+                ("clientes.csv", CLIENTES),
+                ("ordenes.csv", ORDENES),
+                (
+                    "ordenes.esquema",
+                    "id_cliente INT REFERENCES clientes(id)\n",
+                ),
+            ],
+        );
+        let query = parsear(
+            "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (103, 9, 'Mouse', 1)",
+        )
+        .unwrap();
+        match ejecutar_comando(query, &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::ConstraintViolation),
+            _ => panic!("FALLO TEST INSERT"),
+        }
+        let query = parsear(
+            "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (103, 2, 'Mouse', 1)",
+        )
+        .unwrap();
+        if let Err(_e) = ejecutar_comando(query, &ruta_tabla) {
+            panic!("FALLO TEST INSERT")
+        }
+    }
+
+    #[test]
+    fn test_delete_clave_foranea_restrict() {
+        let ruta_tabla = crear_directorio_prueba(
+            "delete_restrict",
+            &[
+                ("clientes.csv", CLIENTES),
+                ("ordenes.csv", ORDENES),
+                (
+                    "ordenes.esquema",
+                    "FOREIGN KEY (id_cliente) REFERENCES clientes (id)\n",
+                ),
+            ],
+        );
+        let query = parsear("DELETE FROM clientes WHERE id = 1").unwrap();
+        match ejecutar_comando(query, &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::ConstraintViolation),
+            _ => panic!("FALLO TEST DELETE"),
+        }
+        let clientes = fs::read_to_string(format!("{}/clientes.csv", ruta_tabla)).unwrap();
+        assert_eq!(clientes, CLIENTES);
+        let archivos = fs::read_dir(&ruta_tabla)
+            .unwrap()
+            .filter(|e| {
+                let nombre = e.as_ref().unwrap().file_name();
+                !nombre.to_string_lossy().ends_with(".lock")
+            })
+            .count();
+        assert_eq!(archivos, 3);
+    }
+
+    #[test]
+    fn test_delete_clave_foranea_cascade_y_set_null() {
+        let ruta_tabla = crear_directorio_prueba(
+            "delete_cascade",
+            &[
+                ("clientes.csv", CLIENTES),
+                ("ordenes.csv", ORDENES),
+                (
+                    "ordenes.esquema",
+                    "id_cliente INT REFERENCES clientes(id) ON DELETE CASCADE\n",
+                ),
+                ("envios.csv", "id,id_orden\n1,101\n2,102\n"),
+                (
+                    "envios.esquema",
+                    "id_orden INT REFERENCES ordenes(id) ON DELETE SET NULL\n",
+                ),
+            ],
+        );
+        let query = parsear("DELETE FROM clientes WHERE id = 1").unwrap();
+        if let Err(_e) = ejecutar_comando(query, &ruta_tabla) {
+            panic!("FALLO TEST DELETE")
+        }
+        let clientes = fs::read_to_string(format!("{}/clientes.csv", ruta_tabla)).unwrap();
+        let ordenes = fs::read_to_string(format!("{}/ordenes.csv", ruta_tabla)).unwrap();
+        let envios = fs::read_to_string(format!("{}/envios.csv", ruta_tabla)).unwrap();
+        assert_eq!(clientes, "id,nombre\n2,Ana\n");
+        assert_eq!(
+            ordenes,
+            "id,id_cliente,producto,cantidad\n102,2,Monitor,1\n"
+        );
+        assert_eq!(envios, "id,id_orden\n1,\n2,102\n");
+    }
+
+    #[test]
+    fn test_update_clave_foranea_cascade() {
+        let ruta_tabla = crear_directorio_prueba(
+            "update_cascade",
+            &[
+                ("clientes.csv", CLIENTES),
+                ("ordenes.csv", ORDENES),
+                (
+                    "ordenes.esquema",
+                    "id_cliente INT REFERENCES clientes(id) ON UPDATE CASCADE\n",
+                ),
+            ],
+        );
+        let query = parsear("UPDATE clientes SET id = 7 WHERE id = 2").unwrap();
+        if let Err(_e) = ejecutar_comando(query, &ruta_tabla) {
+            panic!("FALLO TEST UPDATE")
+        }
+        let ordenes = fs::read_to_string(format!("{}/ordenes.csv", ruta_tabla)).unwrap();
+        assert_eq!(
+            ordenes,
+            "id,id_cliente,producto,cantidad\n101,1,Laptop,1\n102,7,Monitor,1\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_valores_por_defecto_y_check() {
+        let ruta_tabla = crear_directorio_prueba("defaults_check", &[]);
+        let create = parsear(
+            "CREATE TABLE pedidos (id INT, cantidad INT CHECK (cantidad > 0 AND cantidad <= 100), estado TEXT DEFAULT 'pendiente', fecha TEXT DEFAULT CURRENT_DATE)",
+        )
+        .unwrap();
+        if let Err(_e) = ejecutar_comando(create, &ruta_tabla) {
+            panic!("FALLO TEST CREATE TABLE")
+        }
+        let insert = parsear("INSERT INTO pedidos (id, cantidad) VALUES (1, 20)").unwrap();
+        if let Err(_e) = ejecutar_comando(insert, &ruta_tabla) {
+            panic!("FALLO TEST INSERT")
+        }
+        let tabla = fs::read_to_string(format!("{}/pedidos.csv", ruta_tabla)).unwrap();
+        let fila: Vec<&str> = tabla.lines().nth(1).unwrap().split(',').collect();
+        assert_eq!(fila[..3], ["1", "20", "pendiente"]);
+        assert_eq!(fila[3].len(), "AAAA-MM-DD".len());
+
+        let insert = parsear("INSERT INTO pedidos (id, cantidad) VALUES (2, 0)").unwrap();
+        match ejecutar_comando(insert, &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::ConstraintViolation),
+            _ => panic!("FALLO TEST INSERT"),
+        }
+        let update = parsear("UPDATE pedidos SET cantidad = 101 WHERE id = 1").unwrap();
+        match ejecutar_comando(update, &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::ConstraintViolation),
             _ => panic!("FALLO TEST UPDATE"),
         }
+        let final_tabla = fs::read_to_string(format!("{}/pedidos.csv", ruta_tabla)).unwrap();
+        assert_eq!(final_tabla, tabla);
+    }
+
+    #[test]
+    fn test_insert_identidad_no_reutiliza_valores() {
+        let ruta_tabla = crear_directorio_prueba("identidad", &[]);
+        for consulta in [
+            "CREATE TABLE notas (id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY, texto TEXT)",
+            "INSERT INTO notas (texto) VALUES ('a'), ('b')",
+            "DELETE FROM notas WHERE id = 2",
+            "INSERT INTO notas (texto) VALUES ('c')",
+        ] {
+            if let Err(_e) = ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla) {
+                panic!("FALLO TEST IDENTIDAD: {}", consulta)
+            }
+        }
+        let tabla = fs::read_to_string(format!("{}/notas.csv", ruta_tabla)).unwrap();
+        assert_eq!(tabla, "id,texto\n1,a\n3,c\n");
+        let esquema = fs::read_to_string(format!("{}/notas.esquema", ruta_tabla)).unwrap();
+        assert!(esquema.contains("@identidad.id 3"));
+
+        let insert = parsear("INSERT INTO notas (id, texto) VALUES (7, 'd')").unwrap();
+        match ejecutar_comando(insert, &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::ConstraintViolation),
+            _ => panic!("FALLO TEST IDENTIDAD"),
+        }
+    }
+
+    #[test]
+    fn test_insert_con_secuencia() {
+        let ruta_tabla = crear_directorio_prueba("secuencia", &[]);
+        for consulta in [
+            "CREATE SEQUENCE numeros START WITH 100 INCREMENT BY 10",
+            "CREATE TABLE facturas (numero INT DEFAULT NEXTVAL('numeros'), monto INT)",
+            "INSERT INTO facturas (monto) VALUES (5), (6)",
+            "INSERT INTO facturas (numero, monto) VALUES (NEXTVAL('numeros'), 7)",
+        ] {
+            if let Err(_e) = ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla) {
+                panic!("FALLO TEST SECUENCIA: {}", consulta)
+            }
+        }
+        let tabla = fs::read_to_string(format!("{}/facturas.csv", ruta_tabla)).unwrap();
+        assert_eq!(tabla, "numero,monto\n100,5\n110,6\n120,7\n");
+    }
+
+    #[test]
+    fn test_insert_texto_con_caracteres_multibyte() {
+        let ruta_tabla = crear_directorio_prueba("multibyte", &[("t.csv", "id,v\n")]);
+        let consulta = parsear("INSERT INTO t (id, v) VALUES (7, 'ééééé')").unwrap();
+        if let Err(_e) = ejecutar_comando(consulta, &ruta_tabla) {
+            panic!("FALLO TEST MULTIBYTE")
+        }
+        let tabla = fs::read_to_string(format!("{}/t.csv", ruta_tabla)).unwrap();
+        assert_eq!(tabla, "id,v\n7,ééééé\n");
+    }
+
+    #[test]
+    fn test_transaccion_commit() {
+        let ruta_tabla = crear_directorio_prueba(
+            "transaccion_commit",
+            &[
+                ("clientes.csv", CLIENTES),
+                ("ordenes.csv", ORDENES),
+                (
+                    "ordenes.esquema",
+                    "id INT PRIMARY KEY\nid_cliente INT REFERENCES clientes(id)\n",
+                ),
+            ],
+        );
+        for consulta in [
+            "BEGIN TRANSACTION",
+            "INSERT INTO clientes (id, nombre) VALUES (3, 'Luis')",
+            "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (103, 3, 'Mouse', 2)",
+            "DELETE FROM ordenes WHERE id_cliente = 1",
+            "DELETE FROM clientes WHERE id = 1",
+        ] {
+            if let Err(_e) = ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla) {
+                panic!("FALLO TEST TRANSACCION: {}", consulta)
+            }
+        }
+        let clientes = fs::read_to_string(format!("{}/clientes.csv", ruta_tabla)).unwrap();
+        assert_eq!(clientes, CLIENTES);
+        if let Err(_e) = ejecutar_comando(parsear("COMMIT").unwrap(), &ruta_tabla) {
+            panic!("FALLO TEST COMMIT")
+        }
+        let clientes = fs::read_to_string(format!("{}/clientes.csv", ruta_tabla)).unwrap();
+        let ordenes = fs::read_to_string(format!("{}/ordenes.csv", ruta_tabla)).unwrap();
+        assert_eq!(clientes, "id,nombre\n2,Ana\n3,Luis\n");
+        assert_eq!(
+            ordenes,
+            "id,id_cliente,producto,cantidad\n102,2,Monitor,1\n103,3,Mouse,2\n"
+        );
+        assert!(!Path::new(&format!("{}/clientes.sombra", ruta_tabla)).exists());
+    }
+
+    #[test]
+    fn test_transaccion_rollback() {
+        let ruta_tabla =
+            crear_directorio_prueba("transaccion_rollback", &[("clientes.csv", CLIENTES)]);
+        for consulta in [
+            "BEGIN",
+            "UPDATE clientes SET nombre = 'Pedro' WHERE id = 1",
+            "CREATE TABLE notas (id INT, texto TEXT)",
+            "INSERT INTO notas (id, texto) VALUES (1, 'hola')",
+            "ROLLBACK",
+        ] {
+            if let Err(_e) = ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla) {
+                panic!("FALLO TEST TRANSACCION: {}", consulta)
+            }
+        }
+        let clientes = fs::read_to_string(format!("{}/clientes.csv", ruta_tabla)).unwrap();
+        assert_eq!(clientes, CLIENTES);
+        assert!(!Path::new(&format!("{}/notas.csv", ruta_tabla)).exists());
+        assert!(!Path::new(&format!("{}/notas.esquema", ruta_tabla)).exists());
+        match ejecutar_comando(parsear("COMMIT").unwrap(), &ruta_tabla) {
+            Err(e) => assert_eq!(e.tipo, TipoError::Error),
+            _ => panic!("FALLO TEST COMMIT SIN TRANSACCION"),
+        }
+    }
+
+    #[test]
+    fn test_indices() {
+        let ruta_tabla = crear_directorio_prueba("indices", &[("ordenes.csv", ORDENES)]);
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        let primera_columna = |consulta: &str| -> Vec<String> {
+            ejecutar(consulta)
+                .unwrap()
+                .filas_como_texto()
+                .into_iter()
+                .map(|fila| fila[0].to_string())
+                .collect()
+        };
+        let usa_indice = |clausula: &str| {
+            let columnas =
+                obtener_primera_linea(&crear_ruta(&ruta_tabla, &"ordenes".to_string())).unwrap();
+            let esquema = cargar_esquema(&ruta_tabla, &"ordenes".to_string(), &columnas).unwrap();
+            let expresion = parsear_expresion(adaptar_where(clausula)).unwrap();
+            buscar_posiciones(
+                &ruta_tabla,
+                "ordenes",
+                &esquema,
+                &obtener_indices_columnas(&columnas),
+                &expresion,
+            )
+            .is_some()
+        };
+        ejecutar("CREATE UNIQUE INDEX ordenes_id ON ordenes (id)").unwrap();
+        ejecutar("CREATE INDEX ordenes_producto ON ordenes (producto)").unwrap();
+        for id in 103..=110 {
+            ejecutar(&format!(
+                "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES ({}, 1, 'Mouse', 1)",
+                id
+            ))
+            .unwrap();
+        }
+        assert!(usa_indice("id = 105"));
+        assert!(usa_indice("cantidad = 1 AND 107 <= id"));
+        assert!(!usa_indice("id = 105 OR cantidad = 1"));
+        assert_eq!(
+            primera_columna("SELECT id FROM ordenes WHERE id = 105"),
+            ["105"]
+        );
+        assert_eq!(
+            primera_columna("SELECT id FROM ordenes WHERE cantidad = 1 AND 107 <= id"),
+            ["107", "108", "109", "110"]
+        );
+        assert_eq!(
+            primera_columna("SELECT id FROM ordenes WHERE producto = 'Monitor'"),
+            ["102"]
+        );
+        let error = ejecutar(
+            "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (105, 1, 'Mouse', 1)",
+        )
+        .unwrap_err();
+        assert_eq!(error.tipo, TipoError::ConstraintViolation);
+        assert_eq!(
+            ejecutar("DELETE FROM ordenes WHERE id = 999")
+                .unwrap()
+                .filas_afectadas,
+            0
+        );
+        ejecutar("UPDATE ordenes SET producto = 'Teclado' WHERE id > 108").unwrap();
+        ejecutar("DELETE FROM ordenes WHERE id < 104").unwrap();
+        assert!(usa_indice("producto = 'Teclado'"));
+        assert_eq!(
+            primera_columna("SELECT id FROM ordenes WHERE producto = 'Teclado'"),
+            ["109", "110"]
+        );
+        assert_eq!(
+            primera_columna("SELECT id FROM ordenes WHERE id <= 105"),
+            ["104", "105"]
+        );
+        let tabla = crear_ruta(&ruta_tabla, &"ordenes".to_string());
+        let mut contenido = fs::read_to_string(&tabla).unwrap();
+        contenido.push_str("200,1,Mouse,1\n");
+        fs::write(&tabla, contenido).unwrap();
+        assert!(!usa_indice("id = 200"));
+        assert_eq!(
+            primera_columna("SELECT id FROM ordenes WHERE id = 200"),
+            ["200"]
+        );
+        ejecutar("REINDEX ordenes_id").unwrap();
+        assert!(usa_indice("id = 200"));
+        assert!(!usa_indice("producto = 'Mouse'"));
+        ejecutar("REINDEX").unwrap();
+        assert!(usa_indice("producto = 'Mouse'"));
+        assert_eq!(
+            ejecutar("CREATE UNIQUE INDEX repetido ON ordenes (producto)")
+                .unwrap_err()
+                .tipo,
+            TipoError::ConstraintViolation
+        );
+        assert_eq!(
+            ejecutar("REINDEX inexistente").unwrap_err().tipo,
+            TipoError::InvalidTable
+        );
+    }
+
+    #[test]
+    fn test_join_y_group_by() {
+        let ruta_tabla = crear_directorio_prueba(
+            "join",
+            &[
+                ("clientes.csv", CLIENTES),
+                (
+                    "ordenes.csv",
+                    "id,id_cliente,producto,cantidad\n101,1,Laptop,1\n102,2,Monitor,1\n103,1,Mouse,3\n104,3,Mouse,2\n",
+                ),
+            ],
+        );
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        let filas = |consulta: &str| ejecutar(consulta).unwrap().filas_como_texto();
+        assert_eq!(
+            filas("SELECT nombre, producto FROM ordenes JOIN clientes ON id_cliente = clientes.id WHERE cantidad < 3 ORDER BY producto"),
+            [["Juan", "Laptop"], ["Ana", "Monitor"]]
+        );
+        assert_eq!(
+            filas("SELECT * FROM clientes INNER JOIN ordenes ON clientes.id = ordenes.id_cliente WHERE ordenes.id = 103"),
+            [["1", "Juan", "103", "1", "Mouse", "3"]]
+        );
+        assert_eq!(
+            filas("SELECT nombre, COUNT(*), SUM(cantidad), MAX(producto) FROM clientes JOIN ordenes ON clientes.id = id_cliente GROUP BY nombre ORDER BY nombre"),
+            [["Ana", "1", "1", "Monitor"], ["Juan", "2", "4", "Mouse"]]
+        );
+        assert_eq!(
+            filas("SELECT COUNT(*), AVG(cantidad), MIN(id) FROM ordenes WHERE id > 200"),
+            [["0", "", ""]]
+        );
+        assert_eq!(
+            filas(
+                "SELECT producto, COUNT(id) FROM ordenes GROUP BY producto ORDER BY COUNT(id) DESC"
+            ),
+            [["Mouse", "2"], ["Laptop", "1"], ["Monitor", "1"]]
+        );
+        let error = ejecutar("SELECT id FROM ordenes JOIN clientes ON id_cliente = clientes.id")
+            .unwrap_err();
+        assert_eq!(error.tipo, TipoError::InvalidColumn);
+        let error =
+            ejecutar("SELECT producto, cantidad FROM ordenes GROUP BY producto").unwrap_err();
+        assert_eq!(error.tipo, TipoError::InvalidSyntax);
+        let error =
+            ejecutar("SELECT * FROM ordenes JOIN cliente ON id_cliente = cliente.id").unwrap_err();
+        assert_eq!(error.tipo, TipoError::InvalidTable);
+        assert_eq!(error.sugerencia, Some("clientes".to_string()));
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut ordenes = ORDENES.to_string();
+        for id in 103..=140 {
+            ordenes.push_str(&format!("{},1,Mouse,1\n", id));
+        }
+        let ruta_tabla = crear_directorio_prueba(
+            "explain",
+            &[("clientes.csv", CLIENTES), ("ordenes.csv", &ordenes)],
+        );
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        let plan = |consulta: &str| -> Vec<String> {
+            ejecutar(consulta)
+                .unwrap()
+                .filas_como_texto()
+                .into_iter()
+                .map(|fila| fila[0].to_string())
+                .collect()
+        };
+        ejecutar("CREATE INDEX ordenes_id ON ordenes (id)").unwrap();
+        assert_eq!(
+            plan("EXPLAIN SELECT producto FROM ordenes WHERE id = 101 ORDER BY producto"),
+            [
+                "Project (producto) (filas estimadas: 1)",
+                "-> Sort (producto ASC) (filas estimadas: 1)",
+                "   -> Filter (id = 101) (filas estimadas: 1)",
+                "      -> Index Scan ordenes usando ordenes_id (id = 101) (filas estimadas: 1)",
+            ]
+        );
+        assert_eq!(
+            plan("EXPLAIN SELECT nombre FROM ordenes JOIN clientes ON id_cliente = clientes.id WHERE nombre = 'Ana' AND cantidad >= 1"),
+            [
+                "Project (nombre) (filas estimadas: 14)",
+                "-> Hash Join (id_cliente = clientes.id) (filas estimadas: 14)",
+                "   -> Filter (cantidad >= 1) (filas estimadas: 14)",
+                "      -> Scan ordenes (filas estimadas: 40)",
+                "   -> Filter (nombre = 'Ana') (filas estimadas: 1)",
+                "      -> Scan clientes (filas estimadas: 2)",
+            ]
+        );
+        let analizado = plan("EXPLAIN ANALYZE SELECT nombre FROM clientes WHERE id > 1");
+        assert_eq!(analizado.len(), 3);
+        assert!(
+            analizado[0].starts_with("Project (nombre) (filas estimadas: 1, filas: 1, tiempo: ")
+        );
+        assert!(
+            analizado[2].starts_with("   -> Scan clientes (filas estimadas: 2, filas: 2, tiempo: ")
+        );
+        assert_eq!(
+            parsear("EXPLAIN DELETE FROM clientes").unwrap_err().tipo,
+            TipoError::InvalidSyntax
+        );
+    }
+
+    #[test]
+    fn test_limit_y_modificaciones_por_operadores() {
+        let mut ordenes = ORDENES.to_string();
+        for id in 103..=140 {
+            ordenes.push_str(&format!("{},1,Mouse,1\n", id));
+        }
+        let ruta_tabla = crear_directorio_prueba("limit", &[("ordenes.csv", &ordenes)]);
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        let resultado = ejecutar("SELECT id FROM ordenes ORDER BY id DESC LIMIT 2").unwrap();
+        assert_eq!(resultado.filas_como_texto(), [["140"], ["139"]]);
+        let analizado: Vec<String> = ejecutar("EXPLAIN ANALYZE SELECT id FROM ordenes LIMIT 3")
+            .unwrap()
+            .filas_como_texto()
+            .into_iter()
+            .map(|fila| fila[0].to_string())
+            .collect();
+        assert!(analizado[1].starts_with("-> Limit (3) (filas estimadas: 3, filas: 3, tiempo: "));
+        // Sin ORDER BY, el Limit deja de pedir filas y la tabla no se lee completa.
+        assert!(
+            analizado[2].starts_with("   -> Scan ordenes (filas estimadas: 40, filas: 3, tiempo: ")
+        );
+        ejecutar("CREATE INDEX ordenes_id ON ordenes (id)").unwrap();
+        assert_eq!(
+            ejecutar("UPDATE ordenes SET cantidad = 5 WHERE id >= 139")
+                .unwrap()
+                .filas_afectadas,
+            2
+        );
+        assert_eq!(
+            ejecutar("DELETE FROM ordenes WHERE cantidad = 5 OR id = 101")
+                .unwrap()
+                .filas_afectadas,
+            3
+        );
+        assert_eq!(
+            ejecutar("DELETE FROM ordenes WHERE id = 500")
+                .unwrap()
+                .filas_afectadas,
+            0
+        );
+        let resultado = ejecutar("SELECT COUNT(*), MAX(id) FROM ordenes").unwrap();
+        assert_eq!(resultado.filas_como_texto(), [["37", "138"]]);
+    }
+
+    #[test]
+    fn test_analyze_y_estadisticas() {
+        let mut ordenes = ORDENES.to_string();
+        for id in 103..=140 {
+            ordenes.push_str(&format!("{},1,Mouse,1\n", id));
+        }
+        let ruta_tabla = crear_directorio_prueba(
+            "analyze",
+            &[("clientes.csv", CLIENTES), ("ordenes.csv", &ordenes)],
+        );
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        let plan = |consulta: &str| -> Vec<String> {
+            ejecutar(consulta)
+                .unwrap()
+                .filas_como_texto()
+                .into_iter()
+                .map(|fila| fila[0].to_string())
+                .collect()
+        };
+        let consulta =
+            "EXPLAIN SELECT producto, COUNT(*) FROM ordenes WHERE cantidad >= 1 GROUP BY producto";
+        assert_eq!(
+            plan(consulta),
+            [
+                "Project (producto, COUNT(*)) (filas estimadas: 1)",
+                "-> Aggregate (COUNT(*)) GROUP BY producto (filas estimadas: 1)",
+                "   -> Filter (cantidad >= 1) (filas estimadas: 14)",
+                "      -> Scan ordenes (filas estimadas: 40)",
+            ]
+        );
+        ejecutar("ANALYZE").unwrap();
+        assert_eq!(
+            plan(consulta),
+            [
+                "Project (producto, COUNT(*)) (filas estimadas: 3)",
+                "-> Aggregate (COUNT(*)) GROUP BY producto (filas estimadas: 3)",
+                "   -> Filter (cantidad >= 1) (filas estimadas: 40)",
+                "      -> Scan ordenes (filas estimadas: 40)",
+            ]
+        );
+        let estadisticas = ejecutar(
+            "SELECT columna, filas, distintos, fraccion_nulos, minimo, maximo, vigentes FROM sqlrustico_stats WHERE tabla = 'ordenes' ORDER BY columna",
+        )
+        .unwrap();
+        assert_eq!(
+            estadisticas.filas_como_texto(),
+            [
+                ["cantidad", "40", "1", "0", "1", "1", "true"],
+                ["id", "40", "40", "0", "101", "140", "true"],
+                ["id_cliente", "40", "2", "0", "1", "2", "true"],
+                ["producto", "40", "3", "0", "Laptop", "Mouse", "true"],
+            ]
+        );
+        assert_eq!(
+            plan("EXPLAIN SELECT tabla FROM sqlrustico_stats WHERE filas > 2"),
+            [
+                "Project (tabla) (filas estimadas: 1)",
+                "-> Filter (filas > 2) (filas estimadas: 1)",
+                "   -> Catalog Scan sqlrustico_stats (filas estimadas: 2)",
+            ]
+        );
+        let esquema = fs::read_to_string(format!("{}/ordenes.esquema", ruta_tabla)).unwrap();
+        assert!(esquema.contains("@estadisticas.producto 3 0 Laptop,"));
+
+        // Al modificar la tabla sus estadisticas dejan de estar vigentes, hasta volver a analizarla.
+        ejecutar("INSERT INTO ordenes (id, id_cliente, producto) VALUES (141, 2, Teclado)")
+            .unwrap();
+        let vigentes = |tabla: &str| {
+            ejecutar(&format!(
+                "SELECT vigentes FROM sqlrustico_stats WHERE tabla = '{}' AND columna = 'id'",
+                tabla
+            ))
+            .unwrap()
+            .filas_como_texto()
+        };
+        assert_eq!(vigentes("ordenes"), [["false"]]);
+        assert_eq!(vigentes("clientes"), [["true"]]);
+        assert_eq!(
+            plan(consulta)[2],
+            "   -> Filter (cantidad >= 1) (filas estimadas: 14)"
+        );
+        ejecutar("ANALYZE ordenes").unwrap();
+        assert_eq!(vigentes("ordenes"), [["true"]]);
+        let nulos = ejecutar(
+            "SELECT fraccion_nulos FROM sqlrustico_stats WHERE tabla = 'ordenes' AND columna = 'cantidad'",
+        )
+        .unwrap();
+        assert_eq!(nulos.filas_como_texto(), [["0.0244"]]);
+        assert_eq!(
+            ejecutar("ANALYZE inexistente").unwrap_err().tipo,
+            TipoError::InvalidTable
+        );
+    }
+
+    #[test]
+    fn test_tablas_del_catalogo() {
+        let ruta_tabla = crear_directorio_prueba(
+            "catalogo",
+            &[("clientes.csv", CLIENTES), ("ordenes.csv", ORDENES)],
+        );
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        let consultar = |consulta: &str| ejecutar(consulta).unwrap().filas_como_texto();
+        ejecutar("CREATE TABLE productos (id INT PRIMARY KEY, nombre TEXT NOT NULL, precio REAL DEFAULT 0)").unwrap();
+        ejecutar("CREATE UNIQUE INDEX productos_nombre ON productos (nombre)").unwrap();
+        ejecutar("CREATE INDEX ordenes_cliente ON ordenes (id_cliente)").unwrap();
+        assert_eq!(
+            consultar("SELECT table_name FROM information_schema.tables WHERE table_type = 'BASE TABLE' ORDER BY table_name"),
+            [["clientes"], ["ordenes"], ["productos"]]
+        );
+        assert_eq!(
+            consultar("SELECT column_name, ordinal_position, data_type, is_nullable, column_default FROM information_schema.columns WHERE information_schema.columns.table_name = 'productos'"),
+            [
+                ["id", "1", "INT", "NO", ""],
+                ["nombre", "2", "TEXT", "NO", ""],
+                ["precio", "3", "REAL", "YES", "0"],
+            ]
+        );
+        assert_eq!(
+            consultar("SELECT table_name, COUNT(*) FROM information_schema.columns WHERE data_type = 'INT' GROUP BY table_name ORDER BY table_name"),
+            [["information_schema.columns", "1"], ["productos", "1"], ["sqlrustico_stats", "2"]]
+        );
+        assert_eq!(
+            consultar("SELECT indice, data_type, is_nullable FROM sqlrustico_indexes JOIN information_schema.columns ON tabla = table_name WHERE columna = column_name ORDER BY indice"),
+            [["ordenes_cliente", "", "YES"], ["productos_nombre", "TEXT", "NO"]]
+        );
+        assert_eq!(
+            consultar(
+                "SELECT unico, vigente FROM sqlrustico_indexes WHERE indice = 'productos_nombre'"
+            ),
+            [["true", "true"]]
+        );
+        for consulta in [
+            "INSERT INTO information_schema.tables (table_name) VALUES (x)",
+            "DELETE FROM sqlrustico_indexes",
+            "UPDATE sqlrustico_stats SET filas = 0",
+            "CREATE TABLE sqlrustico_indexes (id INT)",
+        ] {
+            assert_eq!(
+                ejecutar(consulta).unwrap_err().tipo,
+                TipoError::InvalidTable
+            );
+        }
+    }
+
+    #[test]
+    fn test_vistas() {
+        let ruta_tabla = crear_directorio_prueba(
+            "vistas",
+            &[("clientes.csv", CLIENTES), ("ordenes.csv", ORDENES)],
+        );
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        let consultar = |consulta: &str| ejecutar(consulta).unwrap().filas_como_texto();
+        let error = |consulta: &str| ejecutar(consulta).unwrap_err().tipo;
+        ejecutar("CREATE VIEW compras AS SELECT clientes.nombre, producto, cantidad FROM ordenes JOIN clientes ON id_cliente = clientes.id").unwrap();
+        assert_eq!(
+            consultar(
+                "SELECT compras.nombre, producto FROM compras WHERE cantidad = 1 ORDER BY nombre"
+            ),
+            [["Ana", "Monitor"], ["Juan", "Laptop"]]
+        );
+        // Una vista puede usarse en un JOIN y en la definicion de otra vista.
+        ejecutar("CREATE VIEW resumen AS SELECT nombre, COUNT(*) FROM compras GROUP BY nombre")
+            .unwrap();
+        assert_eq!(
+            consultar("SELECT clientes.id, resumen.COUNT(*) FROM resumen JOIN clientes ON resumen.nombre = clientes.nombre ORDER BY clientes.id"),
+            [["1", "1"], ["2", "1"]]
+        );
+        assert!(consultar("EXPLAIN SELECT * FROM resumen")
+            .iter()
+            .any(|fila| fila[0].contains("View compras")));
+        // Las vistas no pueden repetirse, reemplazar tablas ni usarse a si mismas.
+        assert_eq!(
+            error("CREATE VIEW compras AS SELECT * FROM ordenes"),
+            TipoError::InvalidTable
+        );
+        assert_eq!(
+            error("CREATE VIEW clientes AS SELECT * FROM ordenes"),
+            TipoError::InvalidTable
+        );
+        assert_eq!(
+            error("CREATE TABLE compras (id INT)"),
+            TipoError::InvalidTable
+        );
+        assert_eq!(
+            error("CREATE OR REPLACE VIEW compras AS SELECT * FROM resumen"),
+            TipoError::InvalidTable
+        );
+        assert_eq!(error("CREATE VIEW duplicadas AS SELECT * FROM ordenes JOIN clientes ON id_cliente = clientes.id"), TipoError::InvalidColumn);
+        assert_eq!(error("DROP VIEW compras"), TipoError::Error);
+        assert_eq!(
+            error("UPDATE compras SET cantidad = 2"),
+            TipoError::InvalidTable
+        );
+        // Las vistas simples se modifican a traves de su tabla, solo en las filas que cumplen su condicion.
+        ejecutar("CREATE OR REPLACE VIEW unitarias AS SELECT id, producto, cantidad FROM ordenes WHERE cantidad = 1").unwrap();
+        ejecutar("INSERT INTO unitarias (id, producto, cantidad) VALUES (103, Mouse, 3)").unwrap();
+        assert_eq!(
+            consultar("SELECT id FROM unitarias ORDER BY id"),
+            [["101"], ["102"]]
+        );
+        let resultado =
+            ejecutar("UPDATE unitarias SET cantidad = 5 WHERE producto = 'Laptop'").unwrap();
+        assert_eq!(resultado.filas_afectadas, 1);
+        let resultado = ejecutar("DELETE FROM unitarias").unwrap();
+        assert_eq!(resultado.filas_afectadas, 1);
+        assert_eq!(
+            consultar("SELECT id, cantidad FROM ordenes ORDER BY id"),
+            [["101", "5"], ["103", "3"]]
+        );
+        assert_eq!(
+            error("UPDATE unitarias SET id_cliente = 1"),
+            TipoError::InvalidColumn
+        );
+        assert_eq!(
+            consultar("SELECT table_name, table_type FROM information_schema.tables WHERE table_type = 'VIEW'"),
+            [["compras", "VIEW"], ["resumen", "VIEW"], ["unitarias", "VIEW"]]
+        );
+        assert_eq!(
+            consultar(
+                "SELECT column_name FROM information_schema.columns WHERE table_name = 'resumen'"
+            ),
+            [["nombre"], ["COUNT(*)"]]
+        );
+        ejecutar("DROP VIEW resumen").unwrap();
+        ejecutar("DROP VIEW compras").unwrap();
+        assert_eq!(
+            consultar("SELECT * FROM information_schema.views"),
+            [[
+                "unitarias",
+                "SELECT id, producto, cantidad FROM ordenes WHERE cantidad = 1"
+            ]]
+        );
+        assert_eq!(error("SELECT * FROM compras"), TipoError::InvalidTable);
+    }
+
+    #[test]
+    fn test_vistas_materializadas() {
+        let ruta_tabla =
+            crear_directorio_prueba("vistas_materializadas", &[("ordenes.csv", ORDENES)]);
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        let consultar = |consulta: &str| ejecutar(consulta).unwrap().filas_como_texto();
+        let error = |consulta: &str| ejecutar(consulta).unwrap_err().tipo;
+        let resultado = ejecutar("CREATE MATERIALIZED VIEW por_cliente AS SELECT id_cliente, COUNT(*) FROM ordenes GROUP BY id_cliente").unwrap();
+        assert_eq!(resultado.filas_afectadas, 2);
+        assert_eq!(
+            consultar(
+                "SELECT id_cliente, por_cliente.COUNT(*) FROM por_cliente ORDER BY id_cliente"
+            ),
+            [["1", "1"], ["2", "1"]]
+        );
+        // Las filas no cambian hasta que la vista se actualiza.
+        ejecutar(
+            "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (103, 1, Mouse, 2)",
+        )
+        .unwrap();
+        assert_eq!(
+            consultar("SELECT * FROM por_cliente WHERE id_cliente = 1"),
+            [["1", "1"]]
+        );
+        ejecutar("CREATE INDEX por_cliente_id ON por_cliente (id_cliente)").unwrap();
+        let resultado = ejecutar("REFRESH MATERIALIZED VIEW por_cliente").unwrap();
+        assert_eq!(resultado.filas_afectadas, 2);
+        assert_eq!(
+            consultar("SELECT * FROM por_cliente WHERE id_cliente = 1"),
+            [["1", "2"]]
+        );
+        assert_eq!(
+            consultar("SELECT vigente FROM sqlrustico_indexes WHERE indice = 'por_cliente_id'"),
+            [["true"]]
+        );
+        let vistas = consultar("SELECT vista, actualizada FROM sqlrustico_matviews");
+        assert_eq!(vistas.len(), 1);
+        assert_eq!(vistas[0][0], "por_cliente");
+        assert_eq!(vistas[0][1].len(), "AAAA-MM-DD HH:MM:SS".len());
+        assert_eq!(
+            consultar(
+                "SELECT table_type FROM information_schema.tables WHERE table_name = 'por_cliente'"
+            ),
+            [["MATERIALIZED VIEW"]]
+        );
+        for consulta in [
+            "DELETE FROM por_cliente",
+            "UPDATE por_cliente SET id_cliente = 3",
+            "REFRESH MATERIALIZED VIEW ordenes",
+            "CREATE MATERIALIZED VIEW por_cliente AS SELECT * FROM ordenes",
+        ] {
+            assert_eq!(error(consulta), TipoError::InvalidTable);
+        }
+        ejecutar("BEGIN").unwrap();
+        assert_eq!(
+            error("REFRESH MATERIALIZED VIEW por_cliente"),
+            TipoError::Error
+        );
+        ejecutar("ROLLBACK").unwrap();
+    }
+
+    #[test]
+    fn test_insert_select_y_create_table_as() {
+        let ruta_tabla = crear_directorio_prueba(
+            "insert_select",
+            &[("clientes.csv", CLIENTES), ("ordenes.csv", ORDENES)],
+        );
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        let consultar = |consulta: &str| ejecutar(consulta).unwrap().filas_como_texto();
+        let error = |consulta: &str| ejecutar(consulta).unwrap_err().tipo;
+        ejecutar("CREATE TABLE pedidos (id INT GENERATED ALWAYS AS IDENTITY PRIMARY KEY, cliente INT REFERENCES clientes(id), producto TEXT NOT NULL, cantidad INT)").unwrap();
+        let resultado = ejecutar(
+            "INSERT INTO pedidos (cliente, producto, cantidad) SELECT id_cliente, producto, cantidad FROM ordenes",
+        )
+        .unwrap();
+        assert_eq!(resultado.filas_afectadas, 2);
+        assert_eq!(
+            consultar("SELECT * FROM pedidos"),
+            [["1", "1", "Laptop", "1"], ["2", "2", "Monitor", "1"]]
+        );
+        // Los errores se detectan antes de escribir: la tabla no cambia.
+        for (consulta, tipo) in [
+            (
+                "INSERT INTO pedidos (cliente, producto) SELECT id_cliente FROM ordenes",
+                TipoError::InvalidColumn,
+            ),
+            (
+                "INSERT INTO pedidos (cantidad, producto) SELECT producto, producto FROM pedidos",
+                TipoError::InvalidColumn,
+            ),
+            (
+                "INSERT INTO pedidos (cliente, producto) SELECT id_cliente, precio FROM ordenes",
+                TipoError::InvalidColumn,
+            ),
+            (
+                "INSERT INTO pedidos (cliente, producto) SELECT id, producto FROM ordenes",
+                TipoError::ConstraintViolation,
+            ),
+            (
+                "INSERT INTO pedidos (id, producto) SELECT id, producto FROM ordenes",
+                TipoError::ConstraintViolation,
+            ),
+        ] {
+            assert_eq!(error(consulta), tipo, "{}", consulta);
+        }
+        // El error del SELECT se marca en su columna, no en la columna del mismo nombre de la lista a insertar.
+        let consulta =
+            "INSERT INTO pedidos (cliente, producto) SELECT cliente, producto FROM ordenes";
+        let posicion = ejecutar_consulta(consulta, &ruta_tabla)
+            .unwrap_err()
+            .posicion
+            .unwrap();
+        assert_eq!(posicion.columna, consulta.rfind("cliente").unwrap() + 1);
+        assert_eq!(consultar("SELECT COUNT(*) FROM pedidos"), [["2"]]);
+        ejecutar("INSERT INTO pedidos (producto) SELECT producto FROM pedidos").unwrap();
+        assert_eq!(
+            consultar("SELECT id, producto FROM pedidos WHERE id > 2"),
+            [["3", "Laptop"], ["4", "Monitor"]]
+        );
+
+        let resultado = ejecutar(
+            "CREATE TABLE por_producto AS SELECT producto, COUNT(*) FROM pedidos GROUP BY producto ORDER BY producto",
+        )
+        .unwrap();
+        assert_eq!(resultado.filas_afectadas, 2);
+        assert_eq!(
+            consultar("SELECT * FROM por_producto"),
+            [["Laptop", "2"], ["Monitor", "2"]]
+        );
+        assert_eq!(
+            consultar("SELECT data_type FROM information_schema.columns WHERE table_name = 'por_producto'"),
+            [["TEXT"], [""]]
+        );
+        ejecutar("INSERT INTO por_producto (producto) VALUES ('Mouse')").unwrap();
+        assert_eq!(
+            error("CREATE TABLE por_producto AS SELECT * FROM pedidos"),
+            TipoError::InvalidTable
+        );
+    }
+
+    #[test]
+    fn test_insert_select_desde_columna_sin_tipo() {
+        let ruta_tabla =
+            crear_directorio_prueba("insert_select_sin_tipo", &[("ordenes.csv", ORDENES)]);
+        let ejecutar = |consulta: &str| ejecutar_comando(parsear(consulta).unwrap(), &ruta_tabla);
+        ejecutar("CREATE TABLE cantidades (producto TEXT, cantidad INT)").unwrap();
+        // Las columnas de ordenes no declaran tipo: cada valor se verifica al insertarlo.
+        match ejecutar(
+            "INSERT INTO cantidades (producto, cantidad) SELECT producto, producto FROM ordenes",
+        ) {
+            Err(e) => assert_eq!(e.tipo, TipoError::InvalidColumn),
+            _ => panic!("FALLO TEST INSERT SELECT"),
+        }
+        let tabla = fs::read_to_string(format!("{}/cantidades.csv", ruta_tabla)).unwrap();
+        assert_eq!(tabla, "producto,cantidad\n");
+        let resultado = ejecutar(
+            "INSERT INTO cantidades (producto, cantidad) SELECT producto, cantidad FROM ordenes",
+        )
+        .unwrap();
+        assert_eq!(resultado.filas_afectadas, 2);
+    }
+
+    const CLIENTES: &str = "id,nombre\n1,Juan\n2,Ana\n";
+    const ORDENES: &str = "id,id_cliente,producto,cantidad\n101,1,Laptop,1\n102,2,Monitor,1\n";
+    const CLIENTES_EJEMPLO: &str = include_str!("../clientes.csv");
+    const ORDENES_EJEMPLO: &str = include_str!("../ordenes.csv");
+
+    /// Crea un directorio temporal con las tablas indicadas, de manera que cada test trabaje sobre sus propios archivos.
+    pub fn crear_directorio_prueba(nombre: &str, archivos: &[(&str, &str)]) -> String {
+        let directorio =
+            std::env::temp_dir().join(format!("sql_rustico_{}_{}", nombre, std::process::id()));
+        let _ = fs::remove_dir_all(&directorio);
+        fs::create_dir_all(&directorio).unwrap();
+        for (archivo, contenido) in archivos {
+            fs::write(directorio.join(archivo), contenido).unwrap();
+        }
+        directorio.to_string_lossy().to_string()
     }
 
     pub fn buscar_linea(buscada: String, ruta: String) -> Result<bool, Errores> {
         let tabla = match File::open(ruta) {
             Ok(f) => f,
-            _ => return Err(Errores::Error),
+            _ => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "No se pudo abrir el archivo".to_string(),
+                ))
+            }
         };
         let reader = BufReader::new(tabla);
         let mut encontrada: bool = false;
@@ -327,7 +1779,10 @@ mod test {
             let linea = match linea {
                 Ok(l) => l,
                 _ => {
-                    return Err(Errores::Error);
+                    return Err(Errores::nuevo(
+                        TipoError::Error,
+                        "Error leyendo el archivo".to_string(),
+                    ));
                 }
             };
             if linea == buscada {
@@ -0,0 +1,105 @@
+use crate::comandos::comandos_implementacion::Comandos;
+use crate::ejecutor::ejecutor_implementacion::{
+    columna_inexistente, crear_ruta, listar_tablas, obtener_primera_linea,
+};
+use crate::ejecutor::transaccion::hay_transaccion_activa;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{cargar_esquema, guardar_esquema};
+use crate::esquema::indices::{indices_de_tabla, reconstruir_indice, tabla_del_indice, Indice};
+use std::path::Path;
+
+/// Esta funcion se encarga de ejecutar la consulta. Construye el indice sobre la version actual de la tabla y lo declara en su esquema.
+/// Si el indice es UNIQUE y la columna ya tiene valores repetidos, el indice no se crea.
+pub fn ejecutar_create_index(comando: &Comandos, path: &String) -> Result<(), Errores> {
+    let (nombre, tabla, columna, unico) = match comando {
+        Comandos::CreateIndex {
+            nombre,
+            tabla,
+            columna,
+            unico,
+        } => (nombre, tabla, columna, *unico),
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
+        }
+    };
+    verificar_sin_transaccion(path)?;
+    let columnas = obtener_primera_linea(&crear_ruta(path, tabla))?;
+    if !columnas.contains(columna) {
+        return Err(columna_inexistente(columna, &columnas));
+    }
+    if tabla_del_indice(path, nombre)?.is_some() {
+        return Err(
+            Errores::nuevo(TipoError::Error, format!("El indice {} ya existe", nombre))
+                .en_token(nombre),
+        );
+    }
+    let mut esquema = cargar_esquema(path, tabla, &columnas)?;
+    let indice = Indice {
+        nombre: nombre.to_string(),
+        columna: columna.to_string(),
+        unico,
+    };
+    reconstruir_indice(path, tabla, &indice, &columnas)?;
+    esquema.metadatos.insert(indice.clave(), indice.valor());
+    guardar_esquema(path, tabla, &esquema)
+}
+
+/// Esta funcion se encarga de ejecutar la consulta. Reconstruye desde cero el indice indicado, los indices de la tabla indicada o, si no se indica ningun nombre, todos los indices del directorio.
+pub fn ejecutar_reindex(comando: &Comandos, path: &String) -> Result<(), Errores> {
+    let nombre = match comando {
+        Comandos::Reindex { nombre } => nombre,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
+        }
+    };
+    verificar_sin_transaccion(path)?;
+    for tabla in tablas_a_reindexar(path, nombre)? {
+        let columnas = obtener_primera_linea(&crear_ruta(path, &tabla))?;
+        let esquema = cargar_esquema(path, &tabla, &columnas)?;
+        for indice in indices_de_tabla(&esquema) {
+            if nombre
+                .as_ref()
+                .is_none_or(|n| n == &tabla || n == &indice.nombre)
+            {
+                reconstruir_indice(path, &tabla, &indice, &columnas)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Esta funcion devuelve las tablas cuyos indices reconstruye la instruccion REINDEX: la tabla indicada, la tabla del indice indicado o todas las tablas del directorio.
+pub fn tablas_a_reindexar(path: &String, nombre: &Option<String>) -> Result<Vec<String>, Errores> {
+    let nombre = match nombre {
+        Some(nombre) => nombre,
+        None => return listar_tablas(path),
+    };
+    if Path::new(&crear_ruta(path, nombre)).exists() {
+        return Ok(vec![nombre.to_string()]);
+    }
+    match tabla_del_indice(path, nombre)? {
+        Some(tabla) => Ok(vec![tabla]),
+        None => Err(Errores::nuevo(
+            TipoError::InvalidTable,
+            format!("No existe una tabla ni un indice llamado {}", nombre),
+        )
+        .en_token(nombre)),
+    }
+}
+
+/// Los indices describen el contenido confirmado de las tablas, por lo que no se crean ni se reconstruyen dentro de una transaccion.
+fn verificar_sin_transaccion(path: &String) -> Result<(), Errores> {
+    if hay_transaccion_activa(path) {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Los indices no pueden crearse ni reconstruirse dentro de una transaccion".to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -1,10 +1,28 @@
 use crate::comandos::comandos_implementacion::Comandos;
-use crate::ejecutor::ejecutor_implementacion::{crear_ruta, limpiar_lista, obtener_primera_linea};
-use crate::errores::errores_implementacion::{imprimir_error, Errores};
-use std::fs::{File, OpenOptions};
-use std::io::{BufReader, Read, Write};
-/// Esta funcion se encarga de ejecutar la consulta.
-pub fn ejecutar_insert(comando: &Comandos, path: &String) -> Result<(), Errores> {
+use crate::comandos::parametros::decodificar_parametro;
+use crate::ejecutor::bitacora::{nombre_temporal, Bitacora};
+use crate::ejecutor::cambios_pendientes::CambiosPendientes;
+use crate::ejecutor::ejecutor_implementacion::{
+    columna_inexistente, crear_ruta, limpiar_lista, obtener_indices_columnas, obtener_primera_linea,
+};
+use crate::ejecutor::integridad_referencial::{verificar_referencias, VerificadorReferencias};
+use crate::ejecutor::transaccion::{ruta_escritura, ruta_tabla};
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{cargar_esquema, guardar_esquema, Esquema};
+use crate::esquema::indices::{sincronizar_indices, Estampa};
+use crate::esquema::restricciones::{validar_fila, ValidadorUnicidad};
+use crate::esquema::secuencias::{asignar_identidades, siguiente_valor};
+use crate::esquema::valores_por_defecto::completar_por_defecto;
+use crate::planificador::ejecucion::construir_operador;
+use crate::planificador::operadores::Operador;
+use crate::planificador::optimizador::optimizar;
+use crate::planificador::planificador_implementacion::{construir_plan, ColumnaPlan};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+
+/// Esta funcion se encarga de ejecutar la consulta. Devuelve la cantidad de filas insertadas.
+pub fn ejecutar_insert(comando: &Comandos, path: &String) -> Result<usize, Errores> {
     let (tabla, into, valores) = match comando {
         Comandos::Insert {
             tabla,
@@ -12,13 +30,26 @@ pub fn ejecutar_insert(comando: &Comandos, path: &String) -> Result<(), Errores>
             valores,
         } => (tabla, into, valores),
         _ => {
-            imprimir_error(Errores::Error, "Error procesando la consulta".to_string());
-            return Err(Errores::Error);
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
         }
     };
     let mut valores_limpia: Vec<Vec<String>> = Vec::new();
     for valor in valores.iter() {
-        let resultado: Vec<String> = limpiar_lista(valor);
+        if es_null(valor) {
+            valores_limpia.push(vec!["".to_string()]);
+            continue;
+        }
+        if let Some(secuencia) = obtener_nextval(valor) {
+            valores_limpia.push(vec![siguiente_valor(path, &secuencia)?]);
+            continue;
+        }
+        let resultado: Vec<String> = limpiar_lista(valor)
+            .into_iter()
+            .map(|v| decodificar_parametro(&v).unwrap_or(v))
+            .collect();
         valores_limpia.push(resultado);
     }
     let valores_final: Vec<Vec<String>> = juntar_valores(valores_limpia, into.len());
@@ -31,8 +62,251 @@ pub fn ejecutar_insert(comando: &Comandos, path: &String) -> Result<(), Errores>
                 .to_string()
         })
         .collect();
-    let ruta: String = crear_ruta(path, tabla);
-    procesar_archivo_insert(&ruta, into_final, valores_final)
+    procesar_archivo_insert(path, tabla, into_final, valores_final)
+}
+
+/// Esta funcion se encarga de ejecutar la consulta INSERT ... SELECT. Devuelve la cantidad de filas insertadas.
+/// Antes de leer la primera fila se verifica que el SELECT devuelva tantas columnas como las indicadas y que sus tipos puedan insertarse en ellas. Ademas, cada valor se verifica contra el tipo de su columna.
+/// Las filas del SELECT se validan y se escriben en un archivo auxiliar a medida que se producen, sin guardarlas en memoria, y el auxiliar se agrega a la tabla recien cuando todas resultaron validas.
+pub fn ejecutar_insert_select(comando: &Comandos, path: &String) -> Result<usize, Errores> {
+    let (tabla, into, consulta, posicion_consulta) = match comando {
+        Comandos::InsertSelect {
+            tabla,
+            into,
+            consulta,
+            posicion_consulta,
+        } => (tabla, into, consulta, *posicion_consulta),
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
+        }
+    };
+    let columnas: Vec<String> = obtener_primera_linea(&ruta_tabla(path, tabla))?;
+    let indicadas: Vec<String> = if into.is_empty() {
+        columnas.clone()
+    } else {
+        into.clone()
+    };
+    if let Some(columna) = indicadas.iter().find(|c| !columnas.contains(c)) {
+        return Err(columna_inexistente(columna, &columnas));
+    }
+    let mut esquema: Esquema = cargar_esquema(path, tabla, &columnas)?;
+    // Los errores del SELECT se ubican a partir de su comienzo, no desde el principio de la consulta.
+    let en_consulta = |error: Errores| error.desplazar(posicion_consulta);
+    let plan = optimizar(construir_plan(consulta, path).map_err(en_consulta)?, path)
+        .map_err(en_consulta)?;
+    let columnas_select = plan.columnas().map_err(en_consulta)?;
+    verificar_columnas_del_select(&esquema, &indicadas, &columnas_select)?;
+    let mut operador = construir_operador(&plan, path, false).map_err(en_consulta)?;
+
+    let anterior = Estampa::de(&crear_ruta(path, tabla));
+    let destino: String = ruta_escritura(path, tabla)?;
+    let datos = nombre_temporal(path, "anexo");
+    let resultado = escribir_filas_del_select(
+        path,
+        tabla,
+        &mut esquema,
+        &columnas,
+        &indicadas,
+        operador.as_mut(),
+        &datos,
+    );
+    let (filas, identidades_asignadas) = match resultado {
+        Ok(resultado) => resultado,
+        Err(error) => {
+            let _ = fs::remove_file(&datos);
+            return Err(error);
+        }
+    };
+    if identidades_asignadas {
+        guardar_esquema(path, tabla, &esquema)?;
+    }
+    let mut bitacora = Bitacora::new(path);
+    bitacora.anexar_archivo(&destino, datos)?;
+    bitacora.confirmar()?;
+    sincronizar_indices(path, tabla, anterior);
+    Ok(filas)
+}
+
+/// Verifica que el SELECT devuelva una columna por cada columna indicada y que el tipo de cada una pueda insertarse en la columna correspondiente de la tabla.
+fn verificar_columnas_del_select(
+    esquema: &Esquema,
+    indicadas: &[String],
+    columnas_select: &[ColumnaPlan],
+) -> Result<(), Errores> {
+    if columnas_select.len() != indicadas.len() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidColumn,
+            format!(
+                "El SELECT devuelve {} columnas pero se indicaron {} columnas a insertar ({})",
+                columnas_select.len(),
+                indicadas.len(),
+                indicadas.join(", ")
+            ),
+        ));
+    }
+    for (indicada, columna_select) in indicadas.iter().zip(columnas_select) {
+        let tipo_destino = esquema.columna(indicada).and_then(|c| c.tipo.as_deref());
+        let tipo_origen = columna_select.tipo.as_deref();
+        if let (Some(tipo_origen), Some(tipo_destino)) = (tipo_origen, tipo_destino) {
+            if !ClaseDeTipo::de(tipo_destino).admite(&ClaseDeTipo::de(tipo_origen)) {
+                return Err(Errores::nuevo(
+                    TipoError::InvalidColumn,
+                    format!(
+                        "La columna {} del SELECT es de tipo {} y no puede insertarse en la columna {} de tipo {}",
+                        columna_select.nombre, tipo_origen, indicada, tipo_destino
+                    ),
+                )
+                .en_token(indicada));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+/// Representa los valores que admite un tipo de columna, a efectos de verificar si una columna puede insertarse en otra.
+enum ClaseDeTipo {
+    Entero,
+    Real,
+    Texto,
+}
+
+impl ClaseDeTipo {
+    fn de(tipo: &str) -> ClaseDeTipo {
+        match tipo
+            .split('(')
+            .next()
+            .unwrap_or(tipo)
+            .trim()
+            .to_uppercase()
+            .as_str()
+        {
+            "INT" | "INTEGER" | "BIGINT" | "SMALLINT" | "TINYINT" => ClaseDeTipo::Entero,
+            "REAL" | "FLOAT" | "DOUBLE" | "DECIMAL" | "NUMERIC" => ClaseDeTipo::Real,
+            _ => ClaseDeTipo::Texto,
+        }
+    }
+
+    /// Una columna de texto admite cualquier valor, una real admite enteros y reales y una entera solo enteros.
+    fn admite(&self, origen: &ClaseDeTipo) -> bool {
+        match self {
+            ClaseDeTipo::Texto => true,
+            ClaseDeTipo::Real => *origen != ClaseDeTipo::Texto,
+            ClaseDeTipo::Entero => *origen == ClaseDeTipo::Entero,
+        }
+    }
+
+    /// Indica si el valor puede guardarse en una columna de esta clase. Un valor vacio es NULL y se admite en cualquiera.
+    fn admite_valor(&self, valor: &str) -> bool {
+        let valor = valor.trim();
+        match self {
+            _ if valor.is_empty() => true,
+            ClaseDeTipo::Texto => true,
+            ClaseDeTipo::Real => valor.parse::<f64>().is_ok(),
+            ClaseDeTipo::Entero => valor.parse::<i64>().is_ok(),
+        }
+    }
+}
+
+/// Verifica que cada valor de la fila del SELECT pueda guardarse en la columna a la que se inserta, segun el tipo declarado en el esquema.
+/// Hace falta aunque los tipos de las columnas sean compatibles, porque una columna sin tipo declarado puede contener cualquier valor.
+fn verificar_valores_de_la_fila(
+    esquema: &Esquema,
+    indicadas: &[String],
+    fila: &[String],
+) -> Result<(), Errores> {
+    for (indicada, valor) in indicadas.iter().zip(fila) {
+        if let Some(tipo) = esquema.columna(indicada).and_then(|c| c.tipo.as_deref()) {
+            if !ClaseDeTipo::de(tipo).admite_valor(valor) {
+                return Err(Errores::nuevo(
+                    TipoError::InvalidColumn,
+                    format!(
+                        "El valor {} no puede insertarse en la columna {} de tipo {}",
+                        valor, indicada, tipo
+                    ),
+                )
+                .en_token(indicada));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lee las filas del SELECT y escribe en el archivo de datos las lineas a agregar a la tabla, verificando cada una a medida que se produce. Las claves foraneas se verifican al final, recorriendo una unica vez cada tabla padre.
+/// Devuelve la cantidad de filas escritas y si se asignaron valores a columnas de identidad, en cuyo caso el esquema debe guardarse.
+fn escribir_filas_del_select(
+    path: &String,
+    tabla: &String,
+    esquema: &mut Esquema,
+    columnas: &[String],
+    indicadas: &[String],
+    operador: &mut dyn Operador,
+    datos: &str,
+) -> Result<(usize, bool), Errores> {
+    let indice_columnas = obtener_indices_columnas(columnas);
+    let mut unicidad = validador_de_tabla(&ruta_tabla(path, tabla), esquema, &indice_columnas)?;
+    let mut referencias = VerificadorReferencias::new(tabla, esquema, columnas)?;
+    let error_escritura =
+        || Errores::nuevo(TipoError::Error, "Error escribiendo el archivo".to_string());
+    let mut escritor = BufWriter::new(File::create(datos).map_err(|_| error_escritura())?);
+    if !termina_en_salto(&ruta_escritura(path, tabla)?)? {
+        writeln!(escritor).map_err(|_| error_escritura())?;
+    }
+    let mut filas: usize = 0;
+    let mut identidades_asignadas = false;
+    while let Some(fila) = operador.siguiente()? {
+        verificar_valores_de_la_fila(esquema, indicadas, &fila)?;
+        let mut linea = armar_linea(path, columnas, indicadas, &fila, esquema)?;
+        identidades_asignadas |= asignar_identidades(
+            path,
+            tabla,
+            esquema,
+            columnas,
+            indicadas,
+            std::slice::from_mut(&mut linea),
+        )?;
+        validar_fila(esquema, &indice_columnas, &linea)?;
+        unicidad.registrar_nueva(&linea)?;
+        referencias.registrar(&linea);
+        writeln!(escritor, "{}", linea.join(",")).map_err(|_| error_escritura())?;
+        filas += 1;
+    }
+    escritor.flush().map_err(|_| error_escritura())?;
+    referencias.verificar(&CambiosPendientes::new(path))?;
+    Ok((filas, identidades_asignadas))
+}
+
+/// Indica si el valor ingresado es la palabra NULL sin comillas, en cuyo caso se escribe un campo vacio.
+fn es_null(valor: &str) -> bool {
+    valor
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(';')
+        .trim_end_matches(',')
+        .trim_end_matches(')')
+        .eq_ignore_ascii_case("NULL")
+}
+
+/// Si el valor ingresado es de la forma NEXTVAL('secuencia'), devuelve el nombre de la secuencia.
+fn obtener_nextval(valor: &str) -> Option<String> {
+    let limpio = valor
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(';')
+        .trim_end_matches(',')
+        .trim_end_matches(')');
+    if !limpio
+        .get(..8)
+        .is_some_and(|prefijo| prefijo.eq_ignore_ascii_case("NEXTVAL("))
+    {
+        return None;
+    }
+    limpio
+        .get(8..)
+        .map(|nombre| nombre.trim_matches('\'').to_string())
 }
 
 fn juntar_valores(valores_limpia: Vec<Vec<String>>, tam: usize) -> Vec<Vec<String>> {
@@ -53,97 +327,168 @@ fn juntar_valores(valores_limpia: Vec<Vec<String>>, tam: usize) -> Vec<Vec<Strin
 }
 
 fn procesar_archivo_insert(
-    path: &String,
+    ruta_directorio: &String,
+    tabla: &String,
     into: Vec<String>,
     valores: Vec<Vec<String>>,
-) -> Result<(), Errores> {
-    let columnas: Vec<String> = match obtener_primera_linea(path) {
-        Ok(columna) => columna,
-        _ => {
-            imprimir_error(Errores::Error, "Error leyendo el archivo".to_string());
-            return Err(Errores::Error);
-        }
-    };
+) -> Result<usize, Errores> {
+    let path: &String = &ruta_tabla(ruta_directorio, tabla);
+    let columnas: Vec<String> = obtener_primera_linea(path)?;
 
     if columnas.len() < into.len() || !misma_len(&valores) || into.len() != valores[0].len() {
-        imprimir_error(
-            Errores::InvalidSyntax,
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
             "Los campos a ingresar no son validos".to_string(),
-        );
-        return Err(Errores::Error);
+        ));
     }
 
-    let linea_nueva: Vec<Vec<String>> = match obtener_linea_a_escribir(columnas, into, valores) {
-        Ok(linea) => linea,
-        Err(_e) => return Err(Errores::Error),
-    };
-
-    let mut tiene_salto: bool = false;
-
-    let file = match File::open(path) {
-        Ok(f) => f,
-        _ => {
-            imprimir_error(Errores::Error, "Error leyendo el archivo".to_string());
-            return Err(Errores::Error);
-        }
-    };
-    let mut reader = BufReader::new(file);
-    let mut buffer = String::new();
-    if reader.read_to_string(&mut buffer).is_ok() && buffer.ends_with('\n') {
-        tiene_salto = true;
+    let mut esquema: Esquema = cargar_esquema(ruta_directorio, tabla, &columnas)?;
+    let indicadas: Vec<String> = into
+        .iter()
+        .map(|c| c.trim_matches(&['(', ')', ' '][..]).to_string())
+        .collect();
+    let mut linea_nueva: Vec<Vec<String>> =
+        obtener_linea_a_escribir(ruta_directorio, &columnas, &indicadas, valores, &esquema)?;
+    let identidades_asignadas = asignar_identidades(
+        ruta_directorio,
+        tabla,
+        &mut esquema,
+        &columnas,
+        &indicadas,
+        &mut linea_nueva,
+    )?;
+    verificar_restricciones(path, &esquema, &columnas, &linea_nueva)?;
+    let cambios = CambiosPendientes::new(ruta_directorio);
+    verificar_referencias(tabla, &esquema, &columnas, &linea_nueva, &cambios)?;
+    if identidades_asignadas {
+        guardar_esquema(ruta_directorio, tabla, &esquema)?;
     }
 
-    let mut archivo: std::fs::File = match OpenOptions::new().append(true).open(path) {
-        Ok(file) => file,
-        Err(_e) => {
-            imprimir_error(Errores::Error, "Error escribiendo el archivo".to_string());
-            return Err(Errores::Error);
-        }
-    };
+    let destino: String = ruta_escritura(ruta_directorio, tabla)?;
+    let tiene_salto: bool = termina_en_salto(&destino)?;
+
+    let mut contenido = String::new();
     if !tiene_salto {
-        match writeln!(archivo) {
-            Ok(_) => {}
-            Err(_) => {
-                imprimir_error(Errores::Error, "Error escribiendo el archivo".to_string());
-                return Err(Errores::Error);
-            }
-        }
+        contenido.push('\n');
     }
     for elemento in linea_nueva.iter() {
-        if let Err(_e) = writeln!(archivo, "{}", elemento.join(",")) {
-            imprimir_error(Errores::Error, "Error escribiendo el archivo".to_string());
-            return Err(Errores::Error);
-        }
+        contenido.push_str(&elemento.join(","));
+        contenido.push('\n');
     }
+    let anterior = Estampa::de(&crear_ruta(ruta_directorio, tabla));
+    let mut bitacora = Bitacora::new(ruta_directorio);
+    bitacora.anexar(&destino, &contenido)?;
+    bitacora.confirmar()?;
+    sincronizar_indices(ruta_directorio, tabla, anterior);
+    Ok(linea_nueva.len())
+}
 
+/// Esta funcion verifica que las lineas a insertar cumplan con las restricciones NOT NULL, PRIMARY KEY y UNIQUE de la tabla.
+/// Las lineas se verifican todas juntas antes de escribir, de manera que si alguna no es valida no se inserta ninguna.
+fn verificar_restricciones(
+    path: &String,
+    esquema: &Esquema,
+    columnas: &[String],
+    lineas_nuevas: &[Vec<String>],
+) -> Result<(), Errores> {
+    let indice_columnas = obtener_indices_columnas(columnas);
+    for linea in lineas_nuevas {
+        validar_fila(esquema, &indice_columnas, linea)?;
+    }
+    let mut validador = validador_de_tabla(path, esquema, &indice_columnas)?;
+    for linea in lineas_nuevas {
+        validador.registrar_nueva(linea)?;
+    }
     Ok(())
 }
 
-/// Esta funcion devuelve la linea/s a insertar en la tabla.
+/// Devuelve el validador de unicidad de la tabla, con sus filas actuales ya registradas.
+fn validador_de_tabla(
+    path: &String,
+    esquema: &Esquema,
+    indice_columnas: &HashMap<String, usize>,
+) -> Result<ValidadorUnicidad, Errores> {
+    let mut validador = ValidadorUnicidad::new(esquema, indice_columnas);
+    if validador.esta_vacio() {
+        return Ok(validador);
+    }
+    let tabla = match File::open(path) {
+        Ok(f) => f,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error leyendo el archivo".to_string(),
+            ));
+        }
+    };
+    for linea in BufReader::new(tabla).lines().skip(1) {
+        let linea = match linea {
+            Ok(l) => l,
+            _ => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error leyendo el archivo".to_string(),
+                ));
+            }
+        };
+        let fila: Vec<String> = linea.split(',').map(|s| s.to_string()).collect();
+        validador.registrar_existente(&fila);
+    }
+    Ok(validador)
+}
+
+/// Esta funcion devuelve la linea/s a insertar en la tabla. Las columnas que no se indican toman su valor por defecto, o quedan vacias si no lo tienen.
 fn obtener_linea_a_escribir(
-    columnas: Vec<String>,
-    into: Vec<String>,
+    ruta_directorio: &String,
+    columnas: &[String],
+    indicadas: &[String],
     valores: Vec<Vec<String>>,
+    esquema: &Esquema,
 ) -> Result<Vec<Vec<String>>, Errores> {
-    let mut lineas: Vec<Vec<String>> = Vec::new();
+    valores
+        .iter()
+        .map(|valor| armar_linea(ruta_directorio, columnas, indicadas, valor, esquema))
+        .collect()
+}
 
-    for valor in valores.iter() {
-        let mut linea_actual: Vec<String> = vec!["".to_string(); columnas.len()];
-
-        for (i, columna_into) in into.iter().enumerate() {
-            let columna_limpia = columna_into.trim_matches(&['(', ')', ' '][..]);
-            if let Some(indice) = columnas.iter().position(|x| x == columna_limpia) {
-                linea_actual[indice] = valor.get(i).unwrap_or(&"".to_string()).to_string();
-            } else {
-                imprimir_error(Errores::InvalidSyntax, "Error en la insercion".to_string());
-                return Err(Errores::InvalidSyntax);
-            }
-        }
+/// Esta funcion devuelve la linea a insertar con los valores de las columnas indicadas, completando las demas con su valor por defecto.
+fn armar_linea(
+    ruta_directorio: &String,
+    columnas: &[String],
+    indicadas: &[String],
+    valor: &[String],
+    esquema: &Esquema,
+) -> Result<Vec<String>, Errores> {
+    let mut linea_actual: Vec<String> = vec!["".to_string(); columnas.len()];
 
-        lineas.push(linea_actual);
+    for (i, columna_limpia) in indicadas.iter().enumerate() {
+        if let Some(indice) = columnas.iter().position(|x| x == columna_limpia) {
+            linea_actual[indice] = valor.get(i).unwrap_or(&"".to_string()).to_string();
+        } else {
+            return Err(columna_inexistente(columna_limpia, columnas));
+        }
     }
+    completar_por_defecto(
+        ruta_directorio,
+        esquema,
+        columnas,
+        indicadas,
+        &mut linea_actual,
+    )?;
+    Ok(linea_actual)
+}
 
-    Ok(lineas)
+/// Indica si el archivo termina en un salto de linea, en cuyo caso las lineas nuevas pueden agregarse directamente al final.
+fn termina_en_salto(ruta: &str) -> Result<bool, Errores> {
+    let error = || Errores::nuevo(TipoError::Error, "Error leyendo el archivo".to_string());
+    let mut archivo = File::open(ruta).map_err(|_| error())?;
+    if archivo.seek(SeekFrom::End(0)).map_err(|_| error())? == 0 {
+        return Ok(false);
+    }
+    archivo.seek(SeekFrom::End(-1)).map_err(|_| error())?;
+    let mut ultimo = [0u8; 1];
+    archivo.read_exact(&mut ultimo).map_err(|_| error())?;
+    Ok(ultimo[0] == b'\n')
 }
 
 fn misma_len(lista: &[Vec<String>]) -> bool {
@@ -1,159 +1,67 @@
-use crate::analizador::analizador_implementacion::{cumple_c_w, parsear_expresion};
-use crate::analizador::expresion::Expresion;
 use crate::comandos::comandos_implementacion::Comandos;
-use crate::ejecutor::ejecutor_implementacion::{
-    adaptar_where, crear_ruta, obtener_indices_columnas, obtener_primera_linea,
-};
-use crate::errores::errores_implementacion::{imprimir_error, Errores};
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use crate::ejecutor::resultado::{Resultado, Valor};
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::planificador::ejecucion::ejecutar_plan;
+use crate::planificador::optimizador::optimizar;
+use crate::planificador::planificador_implementacion::{construir_plan, mostrar_plan};
 
-pub fn ejecutar_select(comando: &Comandos, path: &String) -> Result<(), Errores> {
-    let (campos, tabla, clausula_where, clausula_order) = match comando {
-        Comandos::Select {
-            campos,
-            tabla,
-            clausula_where,
-            clausula_order,
-        } => (campos, tabla, clausula_where, clausula_order),
-        _ => {
-            imprimir_error(
-                Errores::InvalidSyntax,
-                "Error en la query Select".to_string(),
-            );
-            return Err(Errores::InvalidSyntax);
-        }
-    };
-    let ruta_archivo = crear_ruta(path, tabla);
-    let clausula_where_limpia: Vec<String> =
-        adaptar_where(clausula_where.trim_end_matches(";").trim_end_matches("; "));
-    let adaptada: Expresion = match parsear_expresion(clausula_where_limpia) {
-        Ok(a) => a,
-        _ => return Err(Errores::InvalidSyntax),
-    };
-    procesar_archivo_select(&ruta_archivo, adaptada, clausula_order, campos)
+/// Esta funcion se encarga de ejecutar la consulta. Devuelve las columnas pedidas y las filas que cumplen la condicion, con sus valores convertidos segun el tipo de cada columna.
+/// La consulta se ejecuta segun el plan elegido por el optimizador: si la condicion compara una columna indexada con un valor y se estima conveniente, solo se leen las filas que el indice indica en lugar de recorrer la tabla completa.
+pub fn ejecutar_select(comando: &Comandos, path: &String) -> Result<Resultado, Errores> {
+    let plan = optimizar(construir_plan(comando, path)?, path)?;
+    let (relacion, _) = ejecutar_plan(&plan, path)?;
+    let filas: Vec<Vec<Valor>> = relacion
+        .filas
+        .iter()
+        .map(|fila| {
+            fila.iter()
+                .zip(&relacion.columnas)
+                .map(|(valor, columna)| Valor::desde_texto(valor, columna.tipo.as_deref()))
+                .collect()
+        })
+        .collect();
+    Ok(Resultado {
+        columnas: relacion.columnas.into_iter().map(|c| c.nombre).collect(),
+        filas,
+        filas_afectadas: 0,
+    })
 }
 
-fn procesar_archivo_select(
-    ruta_archivo: &String,
-    clausula_where: Expresion,
-    clausula_order: &[String],
-    campos: &Vec<String>,
-) -> Result<(), Errores> {
-    let columnas: Vec<String> = match obtener_primera_linea(ruta_archivo) {
-        Ok(columna) => columna,
+/// Esta funcion se encarga de ejecutar la consulta EXPLAIN. Devuelve el plan con el que se ejecutaria el Select, una linea por nodo, en la columna "plan".
+/// Con EXPLAIN ANALYZE el Select ademas se ejecuta, y cada nodo indica cuantas filas produjo y cuanto tardo.
+pub fn ejecutar_explain(comando: &Comandos, path: &String) -> Result<Resultado, Errores> {
+    let (analizar, consulta) = match comando {
+        Comandos::Explain { analizar, consulta } => (*analizar, consulta),
         _ => {
-            return Err(Errores::Error);
-        }
-    };
-    let indice_columnas = obtener_indices_columnas(&columnas);
-    let mut resultado: Vec<String> = Vec::new();
-    let tabla = match File::open(ruta_archivo) {
-        Ok(f) => f,
-        _ => {
-            imprimir_error(Errores::Error, "No se pudo abrir el archivo".to_string());
-            return Err(Errores::Error);
-        }
-    };
-
-    let reader = BufReader::new(tabla);
-    let mut primera_fila: bool = true;
-    for linea in reader.lines() {
-        let linea = match linea {
-            Ok(l) => l,
-            _ => {
-                imprimir_error(Errores::Error, "Error leyendo la tabla".to_string());
-                return Err(Errores::Error);
-            }
-        };
-        if primera_fila {
-            primera_fila = false;
-            continue;
-        }
-        let cumple = match cumple_c_w(&linea, &clausula_where, &indice_columnas) {
-            Ok(boolean) => boolean,
-            _ => return Err(Errores::InvalidSyntax),
-        };
-        if cumple {
-            resultado.push(linea);
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "Error en la query Explain".to_string(),
+            ));
         }
-    }
-    let resultado_ordenado = match ordenar_resultado(resultado, &indice_columnas, clausula_order) {
-        Ok(r) => r,
-        Err(_e) => return Err(Errores::InvalidSyntax),
     };
-    mostrar_resultado(&resultado_ordenado, columnas, campos, &indice_columnas);
-    Ok(())
-}
-
-fn ordenar_resultado(
-    resultado: Vec<String>,
-    columnas: &HashMap<String, usize>,
-    clausula_order: &[String],
-) -> Result<Vec<Vec<String>>, Errores> {
-    let mut res: Vec<Vec<String>> = Vec::new();
-    for elemento in resultado {
-        let elem_spliteado: Vec<String> =
-            elemento.split(',').map(|s| s.trim().to_string()).collect();
-        res.push(elem_spliteado);
-    }
-    if clausula_order.is_empty() {
-        return Ok(res);
-    }
-    let columna = &clausula_order[0];
-    let modo_ordenamiento = if clausula_order.len() >= 2 {
-        clausula_order[1]
-            .to_uppercase()
-            .trim_end_matches(";")
-            .to_string()
+    let plan = optimizar(construir_plan(consulta, path)?, path)?;
+    let lineas = if analizar {
+        let (_, analisis) = ejecutar_plan(&plan, path)?;
+        mostrar_plan(&plan, Some(&analisis))
     } else {
-        "ASC".to_string()
+        mostrar_plan(&plan, None)
     };
-    if let Some(&indice_columna) = columnas.get(columna) {
-        match modo_ordenamiento.as_str() {
-            "ASC" => {
-                res.sort_by(|a, b| a[indice_columna].trim().cmp(b[indice_columna].trim()));
-            }
-            "DESC" => {
-                res.sort_by(|a, b| b[indice_columna].trim().cmp(a[indice_columna].trim()));
-            }
-            _ => return Err(Errores::Error),
-        }
-    } else {
-        imprimir_error(
-            Errores::InvalidSyntax,
-            "Error en los argumentos de ORDER BY".to_string(),
-        );
-        return Err(Errores::InvalidSyntax);
-    }
-
-    Ok(res)
+    Ok(Resultado {
+        columnas: vec!["plan".to_string()],
+        filas: lineas.into_iter().map(|l| vec![Valor::Texto(l)]).collect(),
+        filas_afectadas: 0,
+    })
 }
 
-fn mostrar_resultado(
-    res: &Vec<Vec<String>>,
-    columnas: Vec<String>,
-    campos: &Vec<String>,
-    indice_columnas: &HashMap<String, usize>,
-) {
-    if campos == &vec!["*".to_string()] {
-        println!("{}", columnas.join(","));
-        for linea in res {
-            println!("{}", linea.join(","));
-        }
-    } else {
-        println!("{}", campos.join(","));
-        for elemento in res {
-            let mut fila: Vec<&str> = Vec::new();
-            for campo in campos {
-                if let Some(&indice) = indice_columnas.get(campo.trim()) {
-                    if let Some(valor) = elemento.get(indice) {
-                        fila.push(valor);
-                    }
-                }
-            }
-            println!("{}", fila.join(","));
+/// Esta funcion devuelve las tablas que lee un Select, o el Select de un EXPLAIN: la tabla del FROM y las de sus JOIN.
+pub fn tablas_de_consulta(comando: &Comandos) -> Vec<String> {
+    match comando {
+        Comandos::Select { tabla, joins, .. } => {
+            let mut tablas = vec![tabla.to_string()];
+            tablas.extend(joins.iter().map(|join| join.tabla.to_string()));
+            tablas
         }
+        Comandos::Explain { consulta, .. } => tablas_de_consulta(consulta),
+        _ => Vec::new(),
     }
 }
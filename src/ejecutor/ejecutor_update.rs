@@ -1,16 +1,22 @@
-use crate::analizador::analizador_implementacion::{cumple_c_w, parsear_expresion};
-use crate::analizador::expresion::Expresion;
 use crate::comandos::comandos_implementacion::Comandos;
+use crate::comandos::parametros::decodificar_parametro;
+use crate::ejecutor::cambios_pendientes::CambiosPendientes;
 use crate::ejecutor::ejecutor_implementacion::{
-    adaptar_where, crear_ruta, obtener_indices_columnas, obtener_primera_linea,
+    columna_inexistente, obtener_indices_columnas, obtener_primera_linea,
 };
-use crate::errores::errores_implementacion::{imprimir_error, Errores};
+use crate::ejecutor::integridad_referencial::{
+    propagar_update, verificar_referencias, FilaModificada,
+};
+use crate::ejecutor::reescritura::{filas_a_modificar, reescribir_filas};
+use crate::ejecutor::transaccion::ruta_tabla;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{cargar_esquema, Esquema, Identidad};
+use crate::esquema::restricciones::{validar_fila, ValidadorUnicidad};
+use crate::planificador::operadores::Operador;
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Write};
 
-/// Esta funcion se encarga de ejecutar la consulta.
-pub fn ejecutar_update(comando: &Comandos, path: &String) -> Result<(), Errores> {
+/// Esta funcion se encarga de ejecutar la consulta. Devuelve la cantidad de filas modificadas.
+pub fn ejecutar_update(comando: &Comandos, path: &String) -> Result<usize, Errores> {
     let (tabla, clausula_set, clausula_where) = match comando {
         Comandos::Update {
             tabla,
@@ -18,133 +24,116 @@ pub fn ejecutar_update(comando: &Comandos, path: &String) -> Result<(), Errores>
             clausula_where,
         } => (tabla, clausula_set, clausula_where),
         _ => {
-            imprimir_error(Errores::Error, "Error procesando la consulta".to_string());
-            return Err(Errores::Error);
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
         }
     };
-    let clausula_set_limpia: Vec<Vec<String>> = match clausula_set_limpia(clausula_set) {
-        Ok(lista) => lista,
-        _ => {
-            imprimir_error(Errores::Error, "Error procesando la consulta".to_string());
-            return Err(Errores::Error);
-        }
-    };
-    let ruta_tabla: String = crear_ruta(path, tabla);
-    let clausula_where_limpia: Vec<String> =
-        adaptar_where(clausula_where.trim_end_matches(";").trim_end_matches("; "));
-    let adaptada: Expresion = match parsear_expresion(clausula_where_limpia) {
-        Ok(a) => a,
-        _ => return Err(Errores::InvalidSyntax),
-    };
-    procesar_archivo_update(&ruta_tabla, clausula_set_limpia, adaptada, path)
+    let clausula_set_limpia: Vec<Vec<String>> = clausula_set_limpia(clausula_set)?;
+    procesar_archivo_update(clausula_set_limpia, clausula_where, path, tabla)
 }
 
 fn procesar_archivo_update(
-    path: &String,
     clausula_set: Vec<Vec<String>>,
-    clausula_where: Expresion,
+    clausula_where: &str,
     ruta_directorio: &String,
-) -> Result<(), Errores> {
-    let columnas: Vec<String> = match obtener_primera_linea(path) {
-        Ok(columna) => columna,
-        _ => {
-            return Err(Errores::Error);
-        }
-    };
+    tabla: &String,
+) -> Result<usize, Errores> {
+    let columnas: Vec<String> = obtener_primera_linea(&ruta_tabla(ruta_directorio, tabla))?;
     let indices_columnas: HashMap<String, usize> = obtener_indices_columnas(&columnas);
     let sintaxis_set: bool = validar_clausula_set(&clausula_set, &columnas);
     if columnas.is_empty() {
-        imprimir_error(Errores::InvalidTable, "La tabla es invalida".to_string());
-        return Err(Errores::InvalidTable);
-    } else if !sintaxis_set {
-        imprimir_error(
-            Errores::InvalidSyntax,
+        return Err(Errores::nuevo(
+            TipoError::InvalidTable,
+            "La tabla es invalida".to_string(),
+        ));
+    }
+    if let Some(asignacion) = clausula_set
+        .iter()
+        .find(|asignacion| !columnas.contains(&asignacion[0].trim().to_string()))
+    {
+        return Err(columna_inexistente(asignacion[0].trim(), &columnas));
+    }
+    if !sintaxis_set {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
             "La clausula set es invalida".to_string(),
-        );
-        return Err(Errores::InvalidColumn);
+        ));
     }
-    actualizar_archivo(
-        path,
-        columnas,
-        indices_columnas,
+    let esquema: Esquema = cargar_esquema(ruta_directorio, tabla, &columnas)?;
+    for asignacion in &clausula_set {
+        let columna = asignacion[0].trim();
+        if let Some(Identidad::Siempre) = esquema.columna(columna).and_then(|c| c.identidad) {
+            return Err(Errores::nuevo(
+                TipoError::ConstraintViolation,
+                format!(
+                    "La columna {} es GENERATED ALWAYS AS IDENTITY y no puede modificarse",
+                    columna
+                ),
+            )
+            .en_token(columna));
+        }
+    }
+    let mut operador = filas_a_modificar(ruta_directorio, tabla, clausula_where)?;
+    let mut cambios = CambiosPendientes::new(ruta_directorio);
+    let modificadas = actualizar_archivo(
+        &columnas,
+        &indices_columnas,
         clausula_set,
-        clausula_where,
+        operador.as_mut(),
+        &esquema,
+        tabla,
+        &mut cambios,
+    )?;
+    let filas_nuevas: Vec<Vec<String>> = modificadas.iter().map(|(_, f)| f.clone()).collect();
+    verificar_referencias(tabla, &esquema, &columnas, &filas_nuevas, &cambios)?;
+    propagar_update(
         ruta_directorio,
-    )
+        tabla,
+        &columnas,
+        &modificadas,
+        &mut cambios,
+    )?;
+    cambios.publicar()?;
+    Ok(modificadas.len())
 }
 
+/// Esta funcion escribe la tabla actualizada en un archivo auxiliar, que queda registrado en los cambios pendientes para luego reemplazar al original. Las filas a modificar son las que produce el operador.
+/// Las filas modificadas se verifican contra las restricciones NOT NULL y de unicidad una vez recorrida toda la tabla. Devuelve los pares (fila anterior, fila nueva) de las filas modificadas.
 fn actualizar_archivo(
-    path: &String,
-    columnas: Vec<String>,
-    indice_columnas: HashMap<String, usize>,
+    columnas: &[String],
+    indice_columnas: &HashMap<String, usize>,
     clausula_set: Vec<Vec<String>>,
-    clausula_where: Expresion,
-    ruta_directorio: &String,
-) -> Result<(), Errores> {
-    let tabla = match File::open(path) {
-        Ok(f) => f,
-        _ => {
-            imprimir_error(Errores::Error, "No se pudo abrir el archivo".to_string());
-            return Err(Errores::Error);
-        }
-    };
-    let reader = BufReader::new(tabla);
-    let archivo_temporal = crear_ruta(ruta_directorio, &"archivo_temporal".to_string());
-    let mut archivo_actualizado = match File::create(&archivo_temporal) {
-        Ok(f) => f,
-        _ => {
-            imprimir_error(Errores::Error, "Error actualizando la tabla".to_string());
-            return Err(Errores::Error);
-        }
-    };
-    let mut primera_linea: bool = true;
-    for linea in reader.lines() {
-        let mut linea = match linea {
-            Ok(l) => l,
-            _ => {
-                imprimir_error(Errores::Error, "Error leyendo la tabla".to_string());
-                return Err(Errores::Error);
-            }
-        };
-        if primera_linea {
-            if let Err(_e) = writeln!(archivo_actualizado, "{}", columnas.join(",")) {
-                imprimir_error(Errores::Error, "Error escribiendo el archivo".to_string());
-                return Err(Errores::Error);
-            }
-            primera_linea = false;
-            continue;
-        }
-        //Tengo la exp, tengo que evaluar acá:
-        let cumple = match cumple_c_w(&linea, &clausula_where, &indice_columnas) {
-            Ok(boolean) => boolean,
-            _ => return Err(Errores::InvalidSyntax),
-        };
-        if cumple {
-            linea = match actualizar_linea(&linea, &clausula_set, &indice_columnas) {
-                Ok(l) => l,
-                _ => return Err(Errores::Error),
-            };
-        }
-        // if cumple_con_where(&linea, &clausula_where, &indice_columnas) {
-        //     linea = match actualizar_linea(&linea, &clausula_set, &indice_columnas) {
-        //         Ok(l) => l,
-        //         _ => return Err(Errores::Error),
-        //     };
-        // }
-
-        if let Err(_e) = writeln!(archivo_actualizado, "{}", linea) {
-            imprimir_error(Errores::Error, "Error escribiendo el archivo".to_string());
-            return Err(Errores::Error);
-        }
-    }
-    if let Err(_e) = fs::rename(&archivo_temporal, path) {
-        imprimir_error(Errores::Error, "Error guardando los cambios".to_string());
-        if let Err(_e) = fs::remove_file(&archivo_temporal) {
-            return Err(Errores::Error);
-        }
-        return Err(Errores::Error);
+    operador: &mut dyn Operador,
+    esquema: &Esquema,
+    tabla_nombre: &String,
+    cambios: &mut CambiosPendientes,
+) -> Result<Vec<FilaModificada>, Errores> {
+    let mut validador = ValidadorUnicidad::new(esquema, indice_columnas);
+    let mut filas_modificadas: Vec<FilaModificada> = Vec::new();
+    reescribir_filas(
+        operador,
+        tabla_nombre,
+        columnas,
+        cambios,
+        |linea| {
+            let anterior: Vec<String> = linea.split(',').map(|s| s.to_string()).collect();
+            let nueva = actualizar_linea(linea, &clausula_set, indice_columnas)?;
+            filas_modificadas.push((anterior, nueva.split(',').map(|s| s.to_string()).collect()));
+            Ok(Some(nueva))
+        },
+        |linea| {
+            let anterior: Vec<String> = linea.split(',').map(|s| s.to_string()).collect();
+            validador.registrar_existente(&anterior);
+            Ok(())
+        },
+    )?;
+    for (_, fila) in &filas_modificadas {
+        validar_fila(esquema, indice_columnas, fila)?;
+        validador.registrar_nueva(fila)?;
     }
-    Ok(())
+    Ok(filas_modificadas)
 }
 
 fn actualizar_linea(
@@ -155,22 +144,33 @@ fn actualizar_linea(
     let mut linea_separada: Vec<String> = linea.split(',').map(|s| s.to_string()).collect();
     for actual in clausula_set {
         let columna_a_modificar = actual[0].trim();
-        let nuevo_valor = actual[1]
-            .trim_start_matches(" \'")
-            .trim_start_matches("\'")
-            .trim_start_matches(" ")
-            .trim_end_matches(" \'")
-            .trim_end_matches("\'");
+        let nuevo_valor = if let Some(valor) = decodificar_parametro(actual[1].trim()) {
+            valor
+        } else if actual[1].trim().eq_ignore_ascii_case("NULL") {
+            "".to_string()
+        } else {
+            actual[1]
+                .trim_start_matches(" \'")
+                .trim_start_matches("\'")
+                .trim_start_matches(" ")
+                .trim_end_matches(" \'")
+                .trim_end_matches("\'")
+                .to_string()
+        };
         if let Some(&indice) = indice_columnas.get(columna_a_modificar) {
             if indice < linea_separada.len() {
-                linea_separada[indice] = nuevo_valor.to_string();
+                linea_separada[indice] = nuevo_valor;
             } else {
-                imprimir_error(Errores::Error, "Error actualizando valores".to_string());
-                return Err(Errores::Error);
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error actualizando valores".to_string(),
+                ));
             }
         } else {
-            imprimir_error(Errores::Error, "Error actualizando valores".to_string());
-            return Err(Errores::Error);
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error actualizando valores".to_string(),
+            ));
         }
     }
     Ok(linea_separada.join(","))
@@ -178,10 +178,7 @@ fn actualizar_linea(
 
 fn clausula_set_limpia(clausula_set: &str) -> Result<Vec<Vec<String>>, Errores> {
     let clausula_separada: Vec<String> = clausula_set.split(",").map(|s| s.to_string()).collect();
-    match obtener_clausula(clausula_separada) {
-        Ok(res) => Ok(res),
-        _ => Err(Errores::Error),
-    }
+    obtener_clausula(clausula_separada)
 }
 
 fn obtener_clausula(clausula_separada: Vec<String>) -> Result<Vec<Vec<String>>, Errores> {
@@ -189,11 +186,12 @@ fn obtener_clausula(clausula_separada: Vec<String>) -> Result<Vec<Vec<String>>,
     for item in clausula_separada.iter() {
         let actual: Vec<String> = item.split("=").map(|s| s.to_string()).collect();
         if actual.len() != 2 {
-            imprimir_error(
-                Errores::InvalidSyntax,
+            let palabra = item.split_whitespace().next().unwrap_or("SET");
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
                 "Error en la clausula set de la instruccion update".to_string(),
-            );
-            return Err(Errores::InvalidSyntax);
+            )
+            .en_token(palabra));
         }
         resultado.push(actual);
     }
@@ -0,0 +1,496 @@
+use crate::analizador::analizador_implementacion::{columnas_de_expresion, parsear_expresion};
+use crate::comandos::comandos_implementacion::{parsear, Comandos};
+use crate::ejecutor::cambios_pendientes::CambiosPendientes;
+use crate::ejecutor::ejecutor_create::verificar_nombre_libre;
+use crate::ejecutor::ejecutor_implementacion::{
+    adaptar_where, columna_inexistente, obtener_primera_linea,
+};
+use crate::ejecutor::ejecutor_select::tablas_de_consulta;
+use crate::ejecutor::transaccion::{hay_transaccion_activa, ruta_tabla};
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{cargar_esquema, guardar_esquema, Columna, Esquema};
+use crate::esquema::valores_por_defecto::fecha_hora_actual;
+use crate::esquema::vistas::{
+    definicion_de_vista, eliminar_vista, guardar_vista, leer_vista, leer_vista_materializada,
+    listar_vistas, VistaMaterializada,
+};
+use crate::planificador::catalogo::verificar_tabla_modificable;
+use crate::planificador::ejecucion::construir_operador;
+use crate::planificador::optimizador::optimizar;
+use crate::planificador::planificador_implementacion::{
+    construir_plan_de_vista, parsear_agregado, ColumnaPlan,
+};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const SIN_TRANSACCION_VISTAS: &str =
+    "Las vistas no pueden crearse ni eliminarse dentro de una transaccion";
+const SIN_TRANSACCION_MATERIALIZADAS: &str =
+    "Las vistas materializadas no pueden crearse ni actualizarse dentro de una transaccion";
+
+/// Esta funcion se encarga de ejecutar la consulta. Verifica que el SELECT de la vista sea valido y que sus columnas tengan nombres distintos, y guarda su definicion.
+/// La vista no guarda filas: cada consulta que la usa vuelve a ejecutar su SELECT.
+pub fn ejecutar_create_view(comando: &Comandos, path: &String) -> Result<(), Errores> {
+    let (nombre, definicion, consulta, reemplazar) = match comando {
+        Comandos::CreateView {
+            nombre,
+            definicion,
+            consulta,
+            reemplazar,
+        } => (nombre, definicion, consulta, *reemplazar),
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
+        }
+    };
+    verificar_sin_transaccion(path, SIN_TRANSACCION_VISTAS)?;
+    if Path::new(&ruta_tabla(path, nombre)).exists() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidTable,
+            format!("Ya existe una tabla llamada {}", nombre),
+        )
+        .en_token(nombre));
+    }
+    if !reemplazar && definicion_de_vista(path, nombre).is_some() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidTable,
+            format!("La vista {} ya existe", nombre),
+        )
+        .en_token(nombre));
+    }
+    validar_select(path, nombre, consulta)?;
+    guardar_vista(path, nombre, definicion)
+}
+
+/// Esta funcion se encarga de ejecutar la consulta. Crea la tabla de la vista materializada con las filas de su SELECT y guarda la definicion en su esquema, junto con las columnas y sus tipos.
+/// Devuelve la cantidad de filas de la vista.
+pub fn ejecutar_create_materialized_view(
+    comando: &Comandos,
+    path: &String,
+) -> Result<usize, Errores> {
+    let (nombre, definicion, consulta) = match comando {
+        Comandos::CreateMaterializedView {
+            nombre,
+            definicion,
+            consulta,
+        } => (nombre, definicion, consulta),
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
+        }
+    };
+    verificar_sin_transaccion(path, SIN_TRANSACCION_MATERIALIZADAS)?;
+    verificar_nombre_libre(path, nombre)?;
+    let (columnas, filas, cambios) = materializar(path, nombre, consulta)?;
+    let mut esquema = esquema_de_columnas(columnas);
+    let vista = VistaMaterializada {
+        definicion: definicion.to_string(),
+        actualizada: fecha_hora_actual(),
+    };
+    vista.guardar_en(&mut esquema);
+    cambios.publicar()?;
+    guardar_esquema(path, nombre, &esquema)?;
+    Ok(filas)
+}
+
+/// Verifica que la vista materializada pueda crearse: que no haya una transaccion activa, que su nombre este libre y que su SELECT sea valido.
+pub fn validar_create_materialized_view(
+    path: &String,
+    nombre: &String,
+    consulta: &Comandos,
+) -> Result<(), Errores> {
+    verificar_sin_transaccion(path, SIN_TRANSACCION_MATERIALIZADAS)?;
+    verificar_nombre_libre(path, nombre)?;
+    validar_select(path, nombre, consulta)
+}
+
+/// Esta funcion se encarga de ejecutar la consulta. Vuelve a calcular las filas de la vista materializada y reemplaza con ellas las de la tabla, conservando su esquema, sus indices y sus estadisticas, que dejan de estar vigentes hasta volver a ejecutar ANALYZE.
+/// Las filas se escriben en un archivo auxiliar que reemplaza a la tabla una vez completo, de manera que las consultas nunca ven la tabla a medio actualizar. Devuelve la cantidad de filas de la vista.
+pub fn ejecutar_refresh_materialized_view(
+    comando: &Comandos,
+    path: &String,
+) -> Result<usize, Errores> {
+    let nombre = match comando {
+        Comandos::RefreshMaterializedView { nombre } => nombre,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
+        }
+    };
+    verificar_sin_transaccion(path, SIN_TRANSACCION_MATERIALIZADAS)?;
+    if leer_vista_materializada(path, nombre)?.is_none() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidTable,
+            format!("La tabla {} no es una vista materializada", nombre),
+        )
+        .en_token(nombre));
+    }
+    let encabezado = obtener_primera_linea(&ruta_tabla(path, nombre))?;
+    let mut esquema = cargar_esquema(path, nombre, &encabezado)?;
+    let mut vista = match VistaMaterializada::de_esquema(&esquema) {
+        Some(vista) => vista,
+        None => {
+            return Err(Errores::nuevo(
+                TipoError::InvalidTable,
+                format!("La tabla {} no es una vista materializada", nombre),
+            ));
+        }
+    };
+    let consulta = match parsear(&vista.definicion) {
+        Ok(consulta @ Comandos::Select { .. }) => consulta,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                format!(
+                    "La definicion de la vista materializada {} es invalida",
+                    nombre
+                ),
+            ));
+        }
+    };
+    let (columnas, filas, cambios) = materializar(path, nombre, &consulta)?;
+    let nombres: Vec<String> = columnas.into_iter().map(|c| c.nombre).collect();
+    if nombres != encabezado {
+        return Err(Errores::nuevo(
+            TipoError::InvalidColumn,
+            format!(
+                "Las columnas del SELECT de la vista materializada {} ({}) ya no coinciden con las de la tabla ({})",
+                nombre,
+                nombres.join(", "),
+                encabezado.join(", ")
+            ),
+        ));
+    }
+    cambios.publicar()?;
+    vista.actualizada = fecha_hora_actual();
+    vista.guardar_en(&mut esquema);
+    guardar_esquema(path, nombre, &esquema)?;
+    Ok(filas)
+}
+
+/// Devuelve el esquema de una tabla con las columnas del SELECT y sus tipos, sin restricciones.
+/// Las columnas de los agregados no son identificadores validos en un esquema, por lo que se guardan sin declarar.
+pub fn esquema_de_columnas(columnas: Vec<ColumnaPlan>) -> Esquema {
+    Esquema {
+        columnas: columnas
+            .into_iter()
+            .filter(|columna| {
+                columna
+                    .nombre
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_')
+            })
+            .map(|columna| Columna {
+                nombre: columna.nombre,
+                tipo: columna.tipo,
+                not_null: false,
+                por_defecto: None,
+                identidad: None,
+            })
+            .collect(),
+        ..Esquema::default()
+    }
+}
+
+/// Ejecuta el SELECT y escribe sus filas, a medida que se producen, en un archivo auxiliar para la tabla indicada que queda registrado en los cambios pendientes. Al publicarlos, el auxiliar reemplaza a la tabla.
+/// Se usa para las vistas materializadas y para CREATE TABLE AS. Devuelve las columnas del SELECT, la cantidad de filas escritas y los cambios pendientes.
+pub fn materializar(
+    path: &String,
+    nombre: &String,
+    consulta: &Comandos,
+) -> Result<(Vec<ColumnaPlan>, usize, CambiosPendientes), Errores> {
+    let plan = optimizar(construir_plan_de_vista(nombre, consulta, path)?, path)?;
+    let columnas = plan.columnas()?;
+    verificar_columnas_distintas(nombre, &columnas)?;
+    let mut operador = construir_operador(&plan, path, false)?;
+    let mut cambios = CambiosPendientes::new(path);
+    let temporal = cambios.nuevo_temporal(nombre);
+    let archivo = match File::create(&temporal) {
+        Ok(archivo) => archivo,
+        Err(_) => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error creando la tabla".to_string(),
+            ));
+        }
+    };
+    cambios.registrar(nombre, temporal);
+    let mut destino = BufWriter::new(archivo);
+    let encabezado: Vec<&str> = columnas.iter().map(|c| c.nombre.as_str()).collect();
+    let mut escribir = |linea: &str| -> Result<(), Errores> {
+        match writeln!(destino, "{}", linea) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Errores::nuevo(
+                TipoError::Error,
+                "Error escribiendo el archivo".to_string(),
+            )),
+        }
+    };
+    escribir(&encabezado.join(","))?;
+    let mut filas: usize = 0;
+    while let Some(fila) = operador.siguiente()? {
+        escribir(&fila.join(","))?;
+        filas += 1;
+    }
+    if destino.flush().is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error escribiendo el archivo".to_string(),
+        ));
+    }
+    Ok((columnas, filas, cambios))
+}
+
+/// Verifica que el SELECT de la vista o de la tabla sea valido y que sus columnas tengan nombres distintos, sin ejecutarlo.
+pub fn validar_select(path: &String, nombre: &str, consulta: &Comandos) -> Result<(), Errores> {
+    let columnas = construir_plan_de_vista(nombre, consulta, path)?.columnas()?;
+    verificar_columnas_distintas(nombre, &columnas)
+}
+
+/// Devuelve error si dos columnas del SELECT de la vista o de la tabla tienen el mismo nombre.
+fn verificar_columnas_distintas(nombre: &str, columnas: &[ColumnaPlan]) -> Result<(), Errores> {
+    for (i, columna) in columnas.iter().enumerate() {
+        if columnas[..i].iter().any(|c| c.nombre == columna.nombre) {
+            return Err(Errores::nuevo(
+                TipoError::InvalidColumn,
+                format!(
+                    "El resultado de {} tendria dos columnas llamadas {}, deben elegirse columnas con nombres distintos",
+                    nombre, columna.nombre
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Esta funcion se encarga de ejecutar la consulta. Elimina la vista, siempre que ninguna otra vista la use.
+pub fn ejecutar_drop_view(comando: &Comandos, path: &String) -> Result<(), Errores> {
+    let nombre = match comando {
+        Comandos::DropView { nombre } => nombre,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error procesando la consulta".to_string(),
+            ));
+        }
+    };
+    verificar_sin_transaccion(path, SIN_TRANSACCION_VISTAS)?;
+    if definicion_de_vista(path, nombre).is_none() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidTable,
+            format!("La vista {} no existe", nombre),
+        )
+        .en_token(nombre));
+    }
+    for vista in listar_vistas(path)? {
+        if let Some(consulta) = leer_vista(path, &vista)? {
+            if tablas_de_consulta(&consulta).contains(nombre) {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    format!(
+                        "No puede eliminarse la vista {} porque la vista {} la usa",
+                        nombre, vista
+                    ),
+                )
+                .en_token(nombre));
+            }
+        }
+    }
+    eliminar_vista(path, nombre)
+}
+
+/// Esta funcion reescribe un INSERT (con VALUES o con SELECT), UPDATE o DELETE sobre una vista como la misma instruccion sobre la tabla de la vista. Las demas consultas, y las que modifican tablas, se devuelven sin cambios.
+/// Las vistas materializadas no pueden modificarse de esta manera.
+/// Solo pueden modificarse las vistas que leen una unica tabla sin JOIN, GROUP BY, agregados ni LIMIT, y solo en las columnas que la vista selecciona. Un UPDATE o un DELETE solo alcanza a las filas que cumplen la condicion de la vista.
+pub fn reescribir_sobre_vista(comando: Comandos, path: &String) -> Result<Comandos, Errores> {
+    let vista = match &comando {
+        Comandos::Insert { tabla, .. }
+        | Comandos::InsertSelect { tabla, .. }
+        | Comandos::Update { tabla, .. }
+        | Comandos::Delete { tabla, .. } => tabla.to_string(),
+        _ => return Ok(comando),
+    };
+    let consulta = match leer_vista(path, &vista)? {
+        Some(consulta) => consulta,
+        None => {
+            if leer_vista_materializada(path, &vista)?.is_some() {
+                return Err(Errores::nuevo(
+                    TipoError::InvalidTable,
+                    format!(
+                        "La tabla {} es una vista materializada, sus filas solo cambian con REFRESH MATERIALIZED VIEW",
+                        vista
+                    ),
+                )
+                .en_token(&vista));
+            }
+            return Ok(comando);
+        }
+    };
+    let (base, columnas, condicion) = tabla_de_vista(&vista, &consulta)?;
+    verificar_tabla_modificable(&base)?;
+    let comando = match comando {
+        Comandos::Insert { into, valores, .. } => {
+            verificar_columnas(&columnas, into.iter().map(|c| c.as_str()))?;
+            Comandos::Insert {
+                tabla: base,
+                into,
+                valores,
+            }
+        }
+        Comandos::InsertSelect {
+            into,
+            consulta,
+            posicion_consulta,
+            ..
+        } => {
+            verificar_columnas(&columnas, into.iter().map(|c| c.as_str()))?;
+            // Sin columnas indicadas, las filas se insertan en todas las columnas de la vista.
+            let into = match (into.is_empty(), &columnas) {
+                (true, Some(columnas)) => columnas.clone(),
+                _ => into,
+            };
+            Comandos::InsertSelect {
+                tabla: base,
+                into,
+                consulta,
+                posicion_consulta,
+            }
+        }
+        Comandos::Update {
+            clausula_set,
+            clausula_where,
+            ..
+        } => {
+            let asignadas = clausula_set
+                .split(',')
+                .filter_map(|asignacion| asignacion.split_once('='))
+                .map(|(columna, _)| columna.trim());
+            verificar_columnas(&columnas, asignadas)?;
+            verificar_columnas_de_where(&columnas, &clausula_where)?;
+            Comandos::Update {
+                tabla: base,
+                clausula_set,
+                clausula_where: combinar_where(&condicion, &clausula_where),
+            }
+        }
+        Comandos::Delete { clausula_where, .. } => {
+            verificar_columnas_de_where(&columnas, &clausula_where)?;
+            Comandos::Delete {
+                tabla: base,
+                clausula_where: combinar_where(&condicion, &clausula_where),
+            }
+        }
+        otro => otro,
+    };
+    // La tabla de la vista puede ser a su vez una vista.
+    reescribir_sobre_vista(comando, path)
+}
+
+/// Devuelve la tabla que lee la vista, las columnas que selecciona (None si las selecciona todas) y su condicion. Devuelve error si la vista no puede modificarse.
+fn tabla_de_vista(
+    vista: &str,
+    consulta: &Comandos,
+) -> Result<(String, Option<Vec<String>>, String), Errores> {
+    let error = || {
+        Errores::nuevo(
+            TipoError::InvalidTable,
+            format!(
+                "La vista {} no puede modificarse: solo pueden modificarse las vistas que leen una unica tabla sin JOIN, GROUP BY, agregados ni LIMIT",
+                vista
+            ),
+        )
+        .en_token(vista)
+    };
+    match consulta {
+        Comandos::Select {
+            campos,
+            tabla,
+            joins,
+            clausula_where,
+            agrupar_por,
+            limite: None,
+            ..
+        } if joins.is_empty() && agrupar_por.is_empty() => {
+            if campos.iter().any(|campo| parsear_agregado(campo).is_some()) {
+                return Err(error());
+            }
+            let columnas = if campos == &vec!["*".to_string()] {
+                None
+            } else {
+                Some(
+                    campos
+                        .iter()
+                        .map(|campo| match campo.trim().rsplit_once('.') {
+                            Some((_, columna)) => columna.to_string(),
+                            None => campo.trim().to_string(),
+                        })
+                        .collect(),
+                )
+            };
+            Ok((tabla.to_string(), columnas, clausula_where.to_string()))
+        }
+        _ => Err(error()),
+    }
+}
+
+/// Devuelve error si alguna de las columnas no esta entre las que selecciona la vista.
+fn verificar_columnas<'a, I>(columnas: &Option<Vec<String>>, usadas: I) -> Result<(), Errores>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let columnas = match columnas {
+        Some(columnas) => columnas,
+        None => return Ok(()),
+    };
+    for columna in usadas {
+        if !columnas.iter().any(|c| c == columna) {
+            return Err(columna_inexistente(columna, columnas));
+        }
+    }
+    Ok(())
+}
+
+/// Devuelve error si la clausula WHERE usa columnas que no estan entre las que selecciona la vista.
+fn verificar_columnas_de_where(
+    columnas: &Option<Vec<String>>,
+    clausula_where: &str,
+) -> Result<(), Errores> {
+    if columnas.is_none() {
+        return Ok(());
+    }
+    let condicion = parsear_expresion(adaptar_where(limpiar_where(clausula_where)))?;
+    let usadas = columnas_de_expresion(&condicion);
+    verificar_columnas(columnas, usadas.iter().map(|c| c.as_str()))
+}
+
+/// Une la condicion de la vista con la de la consulta, de modo que se cumplan ambas.
+fn combinar_where(condicion_vista: &str, clausula_where: &str) -> String {
+    let condicion_vista = limpiar_where(condicion_vista);
+    let clausula_where = limpiar_where(clausula_where);
+    if condicion_vista.is_empty() {
+        clausula_where.to_string()
+    } else if clausula_where.is_empty() {
+        condicion_vista.to_string()
+    } else {
+        format!("({}) AND ({})", condicion_vista, clausula_where)
+    }
+}
+
+fn limpiar_where(clausula_where: &str) -> &str {
+    clausula_where.trim().trim_end_matches(';').trim()
+}
+
+/// Devuelve el error indicado si hay una transaccion activa: las vistas no forman parte de las transacciones.
+fn verificar_sin_transaccion(path: &String, mensaje: &str) -> Result<(), Errores> {
+    if hay_transaccion_activa(path) {
+        return Err(Errores::nuevo(TipoError::Error, mensaje.to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,490 @@
+use crate::ejecutor::cambios_pendientes::CambiosPendientes;
+use crate::ejecutor::ejecutor_implementacion::{obtener_indices_columnas, obtener_primera_linea};
+use crate::ejecutor::transaccion::ruta_tabla;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{
+    cargar_esquema, AccionReferencial, Esquema, Restriccion,
+};
+use crate::esquema::restricciones::{es_nulo, validar_fila};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Representa una fila modificada por una consulta: (fila anterior, fila nueva).
+pub type FilaModificada = (Vec<String>, Vec<String>);
+
+/// Representa una tabla cuyo esquema tiene claves foraneas hacia otra tabla.
+struct TablaHija {
+    tabla: String,
+    columnas: Vec<String>,
+    esquema: Esquema,
+}
+
+/// Esta funcion verifica que las filas nuevas o modificadas de la tabla referencien filas existentes en las tablas padre de cada una de sus claves foraneas.
+/// Las claves con algun valor nulo no se verifican.
+pub fn verificar_referencias(
+    tabla: &String,
+    esquema: &Esquema,
+    columnas: &[String],
+    filas: &[Vec<String>],
+    cambios: &CambiosPendientes,
+) -> Result<(), Errores> {
+    let mut verificador = VerificadorReferencias::new(tabla, esquema, columnas)?;
+    for fila in filas {
+        verificador.registrar(fila);
+    }
+    verificador.verificar(cambios)
+}
+
+/// Representa una clave foranea de la tabla junto con los valores referenciados por las filas registradas que todavia no se encontraron.
+struct ReferenciaPendiente {
+    columnas: Vec<String>,
+    tabla_referenciada: String,
+    columnas_referenciadas: Vec<String>,
+    indices: Vec<usize>,
+    /// Posicion de las columnas referenciadas en la fila, si la tabla se referencia a si misma.
+    indices_propios: Option<Vec<usize>>,
+    /// Valores de las columnas referenciadas en las filas registradas, si la tabla se referencia a si misma.
+    propios: HashSet<Vec<String>>,
+    faltantes: HashSet<Vec<String>>,
+}
+
+/// Acumula los valores que las filas nuevas o modificadas de una tabla referencian a traves de sus claves foraneas, para verificarlos recorriendo una unica vez cada tabla padre.
+/// Las filas pueden registrarse a medida que se producen: solo se guardan sus claves.
+pub struct VerificadorReferencias {
+    tabla: String,
+    referencias: Vec<ReferenciaPendiente>,
+}
+
+impl VerificadorReferencias {
+    /// Crea un verificador con una referencia pendiente por cada clave foranea del esquema.
+    pub fn new(
+        tabla: &String,
+        esquema: &Esquema,
+        columnas: &[String],
+    ) -> Result<VerificadorReferencias, Errores> {
+        let indice_columnas = obtener_indices_columnas(columnas);
+        let mut referencias: Vec<ReferenciaPendiente> = Vec::new();
+        for restriccion in &esquema.restricciones {
+            let (columnas_fk, tabla_referenciada, columnas_referenciadas) = match restriccion {
+                Restriccion::ForeignKey {
+                    columnas,
+                    tabla_referenciada,
+                    columnas_referenciadas,
+                    ..
+                } => (columnas, tabla_referenciada, columnas_referenciadas),
+                _ => continue,
+            };
+            let indices_propios = if tabla_referenciada == tabla {
+                Some(obtener_indices(columnas_referenciadas, &indice_columnas)?)
+            } else {
+                None
+            };
+            referencias.push(ReferenciaPendiente {
+                columnas: columnas_fk.clone(),
+                tabla_referenciada: tabla_referenciada.to_string(),
+                columnas_referenciadas: columnas_referenciadas.clone(),
+                indices: obtener_indices(columnas_fk, &indice_columnas)?,
+                indices_propios,
+                propios: HashSet::new(),
+                faltantes: HashSet::new(),
+            });
+        }
+        Ok(VerificadorReferencias {
+            tabla: tabla.to_string(),
+            referencias,
+        })
+    }
+
+    /// Registra una fila nueva o modificada. Si la tabla se referencia a si misma, la fila puede ser referenciada por las demas filas registradas, anteriores o posteriores.
+    pub fn registrar(&mut self, fila: &[String]) {
+        for referencia in self.referencias.iter_mut() {
+            if let Some(indices_propios) = &referencia.indices_propios {
+                if let Some(clave) = obtener_clave(fila, indices_propios) {
+                    referencia.faltantes.remove(&clave);
+                    referencia.propios.insert(clave);
+                }
+            }
+            if let Some(clave) = obtener_clave(fila, &referencia.indices) {
+                if !referencia.propios.contains(&clave) {
+                    referencia.faltantes.insert(clave);
+                }
+            }
+        }
+    }
+
+    /// Busca en las tablas padre los valores referenciados que no se encontraron entre las filas registradas, devolviendo error si alguno no existe.
+    pub fn verificar(mut self, cambios: &CambiosPendientes) -> Result<(), Errores> {
+        for referencia in self.referencias.iter_mut() {
+            if referencia.faltantes.is_empty() {
+                continue;
+            }
+            let ruta_padre = cambios.ruta_actual(&referencia.tabla_referenciada);
+            if !Path::new(&ruta_padre).exists() {
+                return Err(Errores::nuevo(
+                    TipoError::InvalidTable,
+                    format!(
+                        "No existe la tabla referenciada {}",
+                        referencia.tabla_referenciada
+                    ),
+                )
+                .en_token(&referencia.tabla_referenciada));
+            }
+            let columnas_padre = obtener_primera_linea(&ruta_padre)?;
+            let indices_padre = obtener_indices(
+                &referencia.columnas_referenciadas,
+                &obtener_indices_columnas(&columnas_padre),
+            )?;
+            let faltantes = &mut referencia.faltantes;
+            recorrer_filas(&ruta_padre, |fila| {
+                if let Some(clave) = obtener_clave(&fila, &indices_padre) {
+                    faltantes.remove(&clave);
+                }
+            })?;
+            if let Some(clave) = referencia.faltantes.iter().next() {
+                return Err(Errores::nuevo(
+                    TipoError::ConstraintViolation,
+                    format!(
+                        "No existe en {} ({}) el valor ({}) referenciado por {} ({})",
+                        referencia.tabla_referenciada,
+                        referencia.columnas_referenciadas.join(", "),
+                        clave.join(", "),
+                        self.tabla,
+                        referencia.columnas.join(", ")
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Esta funcion aplica las acciones ON DELETE de las claves foraneas que referencian a la tabla, luego de que se eliminaron las filas recibidas.
+/// Las tablas hijas modificadas se escriben en los cambios pendientes, y las eliminaciones en cascada se propagan a su vez a las tablas que las referencian.
+pub fn propagar_delete(
+    ruta_directorio: &String,
+    tabla: &str,
+    columnas: &[String],
+    eliminadas: &[Vec<String>],
+    cambios: &mut CambiosPendientes,
+) -> Result<(), Errores> {
+    let indice_columnas = obtener_indices_columnas(columnas);
+    for hija in obtener_tablas_hijas(ruta_directorio, tabla)? {
+        let indice_hija = obtener_indices_columnas(&hija.columnas);
+        for restriccion in hija.esquema.claves_foraneas_hacia(tabla) {
+            let (columnas_fk, columnas_referenciadas, accion) = match restriccion {
+                Restriccion::ForeignKey {
+                    columnas,
+                    columnas_referenciadas,
+                    on_delete,
+                    ..
+                } => (columnas, columnas_referenciadas, *on_delete),
+                _ => continue,
+            };
+            let indices_padre = obtener_indices(columnas_referenciadas, &indice_columnas)?;
+            let mut perdidas: HashSet<Vec<String>> = eliminadas
+                .iter()
+                .filter_map(|f| obtener_clave(f, &indices_padre))
+                .collect();
+            descartar_claves_existentes(cambios, tabla, &indices_padre, &mut perdidas)?;
+            if perdidas.is_empty() {
+                continue;
+            }
+            let indices_fk = obtener_indices(columnas_fk, &indice_hija)?;
+            let mut eliminadas_hija: Vec<Vec<String>> = Vec::new();
+            cambios.reescribir_tabla(&hija.tabla, |mut fila| {
+                let referencia_perdida = match obtener_clave(&fila, &indices_fk) {
+                    Some(clave) => perdidas.contains(&clave),
+                    None => false,
+                };
+                if !referencia_perdida {
+                    return Ok(Some(fila));
+                }
+                match accion {
+                    AccionReferencial::Restrict => {
+                        Err(violacion_referencia(tabla, &hija.tabla, "eliminar"))
+                    }
+                    AccionReferencial::Cascade => {
+                        eliminadas_hija.push(fila);
+                        Ok(None)
+                    }
+                    AccionReferencial::SetNull => {
+                        anular_columnas(&mut fila, &indices_fk);
+                        validar_fila(&hija.esquema, &indice_hija, &fila)?;
+                        Ok(Some(fila))
+                    }
+                }
+            })?;
+            if !eliminadas_hija.is_empty() {
+                propagar_delete(
+                    ruta_directorio,
+                    &hija.tabla,
+                    &hija.columnas,
+                    &eliminadas_hija,
+                    cambios,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Esta funcion aplica las acciones ON UPDATE de las claves foraneas que referencian a la tabla, para las filas cuya clave referenciada cambio.
+/// Recibe los pares (fila anterior, fila nueva) de las filas modificadas.
+pub fn propagar_update(
+    ruta_directorio: &String,
+    tabla: &str,
+    columnas: &[String],
+    modificadas: &[FilaModificada],
+    cambios: &mut CambiosPendientes,
+) -> Result<(), Errores> {
+    let indice_columnas = obtener_indices_columnas(columnas);
+    for hija in obtener_tablas_hijas(ruta_directorio, tabla)? {
+        let indice_hija = obtener_indices_columnas(&hija.columnas);
+        for restriccion in hija.esquema.claves_foraneas_hacia(tabla) {
+            let (columnas_fk, columnas_referenciadas, accion) = match restriccion {
+                Restriccion::ForeignKey {
+                    columnas,
+                    columnas_referenciadas,
+                    on_update,
+                    ..
+                } => (columnas, columnas_referenciadas, *on_update),
+                _ => continue,
+            };
+            let indices_padre = obtener_indices(columnas_referenciadas, &indice_columnas)?;
+            let mut reemplazos: HashMap<Vec<String>, Vec<String>> = HashMap::new();
+            for (anterior, nueva) in modificadas {
+                let clave_anterior = obtener_clave(anterior, &indices_padre);
+                let clave_nueva = obtener_valores(nueva, &indices_padre);
+                if let Some(clave_anterior) = clave_anterior {
+                    if clave_anterior != clave_nueva {
+                        reemplazos.insert(clave_anterior, clave_nueva);
+                    }
+                }
+            }
+            let mut perdidas: HashSet<Vec<String>> = reemplazos.keys().cloned().collect();
+            descartar_claves_existentes(cambios, tabla, &indices_padre, &mut perdidas)?;
+            if perdidas.is_empty() {
+                continue;
+            }
+            let indices_fk = obtener_indices(columnas_fk, &indice_hija)?;
+            let mut modificadas_hija: Vec<FilaModificada> = Vec::new();
+            cambios.reescribir_tabla(&hija.tabla, |mut fila| {
+                let clave = match obtener_clave(&fila, &indices_fk) {
+                    Some(clave) if perdidas.contains(&clave) => clave,
+                    _ => return Ok(Some(fila)),
+                };
+                let anterior = fila.clone();
+                match accion {
+                    AccionReferencial::Restrict => {
+                        return Err(violacion_referencia(tabla, &hija.tabla, "modificar"))
+                    }
+                    AccionReferencial::Cascade => {
+                        for (indice, valor) in indices_fk.iter().zip(&reemplazos[&clave]) {
+                            fila[*indice] = valor.to_string();
+                        }
+                    }
+                    AccionReferencial::SetNull => anular_columnas(&mut fila, &indices_fk),
+                }
+                validar_fila(&hija.esquema, &indice_hija, &fila)?;
+                modificadas_hija.push((anterior, fila.clone()));
+                Ok(Some(fila))
+            })?;
+            if !modificadas_hija.is_empty() {
+                propagar_update(
+                    ruta_directorio,
+                    &hija.tabla,
+                    &hija.columnas,
+                    &modificadas_hija,
+                    cambios,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Indica si alguna tabla del directorio tiene claves foraneas hacia la tabla indicada.
+pub fn es_referenciada(ruta_directorio: &String, tabla: &str) -> Result<bool, Errores> {
+    Ok(!obtener_tablas_hijas(ruta_directorio, tabla)?.is_empty())
+}
+
+/// Devuelve las tablas a las que hace referencia la tabla indicada mediante claves foraneas. Si la tabla no existe devuelve una lista vacia.
+pub fn tablas_referenciadas(ruta_directorio: &String, tabla: &str) -> Result<Vec<String>, Errores> {
+    let ruta = ruta_tabla(ruta_directorio, tabla);
+    if !Path::new(&ruta).exists() {
+        return Ok(Vec::new());
+    }
+    let columnas = obtener_primera_linea(&ruta)?;
+    let esquema = cargar_esquema(ruta_directorio, &tabla.to_string(), &columnas)?;
+    let mut referenciadas: Vec<String> = Vec::new();
+    for restriccion in &esquema.restricciones {
+        if let Restriccion::ForeignKey {
+            tabla_referenciada, ..
+        } = restriccion
+        {
+            referenciadas.push(tabla_referenciada.to_string());
+        }
+    }
+    Ok(referenciadas)
+}
+
+/// Devuelve las tablas a las que puede propagarse un DELETE o UPDATE sobre la tabla indicada: las que la referencian, directa o indirectamente.
+pub fn tablas_dependientes(ruta_directorio: &String, tabla: &str) -> Result<Vec<String>, Errores> {
+    let mut dependientes: Vec<String> = Vec::new();
+    let mut pendientes: Vec<String> = vec![tabla.to_string()];
+    while let Some(actual) = pendientes.pop() {
+        for hija in obtener_tablas_hijas(ruta_directorio, &actual)? {
+            if hija.tabla != tabla && !dependientes.contains(&hija.tabla) {
+                dependientes.push(hija.tabla.to_string());
+                pendientes.push(hija.tabla);
+            }
+        }
+    }
+    Ok(dependientes)
+}
+
+/// Recorre los archivos de esquema del directorio y devuelve las tablas que tienen claves foraneas hacia la tabla indicada.
+fn obtener_tablas_hijas(ruta_directorio: &String, tabla: &str) -> Result<Vec<TablaHija>, Errores> {
+    let entradas = match fs::read_dir(ruta_directorio) {
+        Ok(e) => e,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error leyendo el directorio".to_string(),
+            ));
+        }
+    };
+    let mut nombres: Vec<String> = entradas
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let nombre = e.file_name().to_string_lossy().to_string();
+            nombre.strip_suffix(".esquema").map(|n| n.to_string())
+        })
+        .collect();
+    nombres.sort();
+    let mut hijas: Vec<TablaHija> = Vec::new();
+    for nombre in nombres {
+        let ruta = ruta_tabla(ruta_directorio, &nombre);
+        if !Path::new(&ruta).exists() {
+            continue;
+        }
+        let columnas = obtener_primera_linea(&ruta)?;
+        let esquema = cargar_esquema(ruta_directorio, &nombre, &columnas)?;
+        if !esquema.claves_foraneas_hacia(tabla).is_empty() {
+            hijas.push(TablaHija {
+                tabla: nombre,
+                columnas,
+                esquema,
+            });
+        }
+    }
+    Ok(hijas)
+}
+
+/// Quita del conjunto las claves que todavia existen en la version actual de la tabla padre, ya que las filas que las referencian siguen siendo validas.
+fn descartar_claves_existentes(
+    cambios: &CambiosPendientes,
+    tabla: &str,
+    indices: &[usize],
+    claves: &mut HashSet<Vec<String>>,
+) -> Result<(), Errores> {
+    if claves.is_empty() {
+        return Ok(());
+    }
+    recorrer_filas(&cambios.ruta_actual(tabla), |fila| {
+        if let Some(clave) = obtener_clave(&fila, indices) {
+            claves.remove(&clave);
+        }
+    })
+}
+
+/// Lee las filas de la tabla (sin el encabezado) aplicando la funcion a cada una.
+fn recorrer_filas<F>(ruta: &String, mut funcion: F) -> Result<(), Errores>
+where
+    F: FnMut(Vec<String>),
+{
+    let archivo = match File::open(ruta) {
+        Ok(f) => f,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "No se pudo abrir el archivo".to_string(),
+            ));
+        }
+    };
+    for linea in BufReader::new(archivo).lines().skip(1) {
+        match linea {
+            Ok(l) => funcion(l.split(',').map(|s| s.to_string()).collect()),
+            _ => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error leyendo la tabla".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Devuelve la posicion de cada columna en la fila.
+fn obtener_indices(
+    columnas: &[String],
+    indice_columnas: &HashMap<String, usize>,
+) -> Result<Vec<usize>, Errores> {
+    let mut indices: Vec<usize> = Vec::new();
+    for columna in columnas {
+        match indice_columnas.get(columna) {
+            Some(&indice) => indices.push(indice),
+            None => {
+                return Err(Errores::nuevo(
+                    TipoError::InvalidColumn,
+                    format!("La columna {} de la clave foranea no existe", columna),
+                ));
+            }
+        }
+    }
+    Ok(indices)
+}
+
+/// Devuelve los valores de la fila en las posiciones indicadas.
+fn obtener_valores(fila: &[String], indices: &[usize]) -> Vec<String> {
+    indices
+        .iter()
+        .map(|&i| {
+            fila.get(i)
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Devuelve los valores de la clave, o None si alguno de ellos es nulo.
+fn obtener_clave(fila: &[String], indices: &[usize]) -> Option<Vec<String>> {
+    let valores = obtener_valores(fila, indices);
+    if valores.iter().any(|v| es_nulo(v)) {
+        return None;
+    }
+    Some(valores)
+}
+
+/// Deja en nulo las columnas indicadas de la fila.
+fn anular_columnas(fila: &mut [String], indices: &[usize]) {
+    for &indice in indices {
+        if indice < fila.len() {
+            fila[indice] = "".to_string();
+        }
+    }
+}
+
+/// Devuelve el error para cuando se intenta eliminar o modificar una fila que es referenciada por otra tabla.
+fn violacion_referencia(tabla: &str, tabla_hija: &str, accion: &str) -> Errores {
+    Errores::nuevo(
+        TipoError::ConstraintViolation,
+        format!(
+            "No se puede {} la fila de {} porque es referenciada por {}",
+            accion, tabla, tabla_hija
+        ),
+    )
+}
@@ -1,5 +1,16 @@
+pub mod bitacora;
+pub mod bloqueos;
+pub mod cambios_pendientes;
+pub mod ejecutor_analyze;
+pub mod ejecutor_create;
 pub mod ejecutor_delete;
 pub mod ejecutor_implementacion;
+pub mod ejecutor_indice;
 pub mod ejecutor_insert;
 pub mod ejecutor_select;
 pub mod ejecutor_update;
+pub mod ejecutor_vista;
+pub mod integridad_referencial;
+pub mod reescritura;
+pub mod resultado;
+pub mod transaccion;
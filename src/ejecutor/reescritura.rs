@@ -0,0 +1,103 @@
+use crate::ejecutor::cambios_pendientes::CambiosPendientes;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::indices::LineasConPosicion;
+use crate::planificador::ejecucion::construir_operador;
+use crate::planificador::operadores::Operador;
+use crate::planificador::optimizador::optimizar;
+use crate::planificador::planificador_implementacion::construir_plan_de_modificacion;
+use std::fs::File;
+use std::io::{BufReader, Write};
+
+/// Esta funcion arma el operador que produce las filas de la tabla que cumplen la clausula WHERE de un UPDATE o un DELETE, con el plan que elige el optimizador.
+/// Las filas se producen en el orden del archivo y el operador indica la posicion de cada una, de manera que puedan ubicarse al reescribir la tabla.
+pub fn filas_a_modificar(
+    path: &String,
+    tabla: &String,
+    clausula_where: &str,
+) -> Result<Box<dyn Operador>, Errores> {
+    let plan = optimizar(
+        construir_plan_de_modificacion(path, tabla, clausula_where)?,
+        path,
+    )?;
+    construir_operador(&plan, path, false)
+}
+
+/// Esta funcion reescribe la version actual de la tabla en un archivo auxiliar, que queda registrado en los cambios pendientes, a medida que el operador le indica que filas cumplen la condicion.
+/// Cada fila que cumple la condicion se pasa a la funcion modificar, que devuelve la linea a escribir en su lugar o None para eliminarla; el resto de las filas se pasa a la funcion conservar y se copia sin cambios.
+/// Devuelve la cantidad de filas que cumplieron la condicion. Si ninguna la cumple la tabla no se reescribe.
+pub fn reescribir_filas<F, G>(
+    operador: &mut dyn Operador,
+    tabla: &String,
+    columnas: &[String],
+    cambios: &mut CambiosPendientes,
+    mut modificar: F,
+    mut conservar: G,
+) -> Result<usize, Errores>
+where
+    F: FnMut(&str) -> Result<Option<String>, Errores>,
+    G: FnMut(&str) -> Result<(), Errores>,
+{
+    let mut siguiente = siguiente_posicion(operador)?;
+    if siguiente.is_none() {
+        return Ok(0);
+    }
+    let origen = match File::open(cambios.ruta_actual(tabla)) {
+        Ok(f) => f,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "No se pudo abrir el archivo".to_string(),
+            ));
+        }
+    };
+    let archivo_temporal = cambios.nuevo_temporal(tabla);
+    let mut destino = match File::create(&archivo_temporal) {
+        Ok(f) => f,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error actualizando la tabla".to_string(),
+            ));
+        }
+    };
+    cambios.registrar(tabla, archivo_temporal);
+    let mut escribir = |linea: &str| -> Result<(), Errores> {
+        match writeln!(destino, "{}", linea) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(Errores::nuevo(
+                TipoError::Error,
+                "Error escribiendo el archivo".to_string(),
+            )),
+        }
+    };
+    escribir(&columnas.join(","))?;
+    let mut cantidad: usize = 0;
+    for linea in LineasConPosicion::new(BufReader::new(origen), 0).skip(1) {
+        let (posicion, linea) = linea?;
+        if siguiente == Some(posicion) {
+            cantidad += 1;
+            siguiente = siguiente_posicion(operador)?;
+            if let Some(nueva) = modificar(&linea)? {
+                escribir(&nueva)?;
+            }
+        } else {
+            conservar(&linea)?;
+            escribir(&linea)?;
+        }
+    }
+    Ok(cantidad)
+}
+
+/// Pide al operador la siguiente fila y devuelve la posicion en la que comienza.
+fn siguiente_posicion(operador: &mut dyn Operador) -> Result<Option<u64>, Errores> {
+    match operador.siguiente()? {
+        Some(_) => match operador.posicion() {
+            Some(posicion) => Ok(Some(posicion)),
+            None => Err(Errores::nuevo(
+                TipoError::Error,
+                "No se pudo ubicar la fila en la tabla".to_string(),
+            )),
+        },
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,165 @@
+use crate::analizador::analizador_implementacion::es_numero;
+use std::fmt;
+
+/// Representa un valor de una fila del resultado de una consulta.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Valor {
+    /// Un campo vacio en el archivo .csv.
+    Nulo,
+    Entero(i64),
+    Real(f64),
+    Texto(String),
+}
+
+impl Valor {
+    /// Esta funcion convierte el texto leido de la tabla en un valor, segun el tipo declarado de la columna.
+    /// Si la columna no declara un tipo, solo se convierten a Entero o Real los numeros que se escriben igual al mostrarse (por ejemplo "007" o "1.50" quedan como Texto), de manera que no se pierde informacion.
+    /// Un valor que no puede convertirse al tipo declarado se conserva como Texto.
+    pub fn desde_texto(texto: &str, tipo: Option<&str>) -> Valor {
+        if texto.is_empty() {
+            return Valor::Nulo;
+        }
+        let tipo = tipo.map(|t| t.split('(').next().unwrap_or(t).trim().to_uppercase());
+        let valor = match tipo.as_deref() {
+            None => Valor::numero(texto).filter(|valor| valor.to_string() == texto),
+            Some("INT" | "INTEGER" | "BIGINT" | "SMALLINT" | "TINYINT") => {
+                texto.parse::<i64>().ok().map(Valor::Entero)
+            }
+            Some("REAL" | "FLOAT" | "DOUBLE" | "DECIMAL" | "NUMERIC") => {
+                Valor::numero(texto).map(|valor| match valor {
+                    Valor::Entero(entero) => Valor::Real(entero as f64),
+                    otro => otro,
+                })
+            }
+            Some(_) => None,
+        };
+        valor.unwrap_or_else(|| Valor::Texto(texto.to_string()))
+    }
+
+    /// Convierte el texto en un Entero o un Real si es un numero.
+    fn numero(texto: &str) -> Option<Valor> {
+        if !es_numero(texto) {
+            return None;
+        }
+        match texto.parse::<i64>() {
+            Ok(entero) => Some(Valor::Entero(entero)),
+            Err(_) => texto.parse::<f64>().ok().map(Valor::Real),
+        }
+    }
+
+    pub fn es_nulo(&self) -> bool {
+        matches!(self, Valor::Nulo)
+    }
+}
+
+impl From<i64> for Valor {
+    fn from(entero: i64) -> Valor {
+        Valor::Entero(entero)
+    }
+}
+
+impl From<i32> for Valor {
+    fn from(entero: i32) -> Valor {
+        Valor::Entero(entero as i64)
+    }
+}
+
+impl From<f64> for Valor {
+    fn from(real: f64) -> Valor {
+        Valor::Real(real)
+    }
+}
+
+impl From<&str> for Valor {
+    fn from(texto: &str) -> Valor {
+        Valor::Texto(texto.to_string())
+    }
+}
+
+impl From<String> for Valor {
+    fn from(texto: String) -> Valor {
+        Valor::Texto(texto)
+    }
+}
+
+/// Los valores opcionales vacios se convierten en nulos.
+impl<T: Into<Valor>> From<Option<T>> for Valor {
+    fn from(valor: Option<T>) -> Valor {
+        valor.map(|v| v.into()).unwrap_or(Valor::Nulo)
+    }
+}
+
+/// Muestra el valor tal como se guarda en la tabla: los nulos como texto vacio.
+impl fmt::Display for Valor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Valor::Nulo => Ok(()),
+            Valor::Entero(entero) => write!(f, "{}", entero),
+            Valor::Real(real) => write!(f, "{}", real),
+            Valor::Texto(texto) => write!(f, "{}", texto),
+        }
+    }
+}
+
+/// Representa el resultado de ejecutar una consulta.
+/// Los SELECT devuelven los nombres de las columnas y las filas; los INSERT, UPDATE y DELETE la cantidad de filas afectadas.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Resultado {
+    pub columnas: Vec<String>,
+    pub filas: Vec<Vec<Valor>>,
+    /// Cantidad de filas insertadas, modificadas o eliminadas. No incluye las filas de otras tablas modificadas por claves foraneas.
+    pub filas_afectadas: usize,
+}
+
+impl Resultado {
+    /// Crea el resultado de una consulta que modifica filas.
+    pub fn con_filas_afectadas(filas_afectadas: usize) -> Resultado {
+        Resultado {
+            filas_afectadas,
+            ..Default::default()
+        }
+    }
+
+    /// Indica si el resultado corresponde a una consulta que devuelve filas.
+    pub fn es_consulta(&self) -> bool {
+        !self.columnas.is_empty()
+    }
+
+    /// Devuelve las filas con cada valor convertido a texto.
+    pub fn filas_como_texto(&self) -> Vec<Vec<String>> {
+        self.filas
+            .iter()
+            .map(|fila| fila.iter().map(|valor| valor.to_string()).collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Valor;
+
+    #[test]
+    fn test_valor_desde_texto() {
+        assert_eq!(Valor::desde_texto("", Some("INT")), Valor::Nulo);
+        assert_eq!(Valor::desde_texto("42", None), Valor::Entero(42));
+        assert_eq!(Valor::desde_texto("-1.5", None), Valor::Real(-1.5));
+        assert_eq!(Valor::desde_texto("3", Some("real")), Valor::Real(3.0));
+        assert_eq!(Valor::desde_texto("007", Some("INT")), Valor::Entero(7));
+        assert_eq!(
+            Valor::desde_texto("007", None),
+            Valor::Texto("007".to_string())
+        );
+        assert_eq!(
+            Valor::desde_texto("1.50", None),
+            Valor::Texto("1.50".to_string())
+        );
+        assert_eq!(
+            Valor::desde_texto("12", Some("VARCHAR(10)")),
+            Valor::Texto("12".to_string())
+        );
+        assert_eq!(
+            Valor::desde_texto("abc", Some("INT")),
+            Valor::Texto("abc".to_string())
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use crate::ejecutor::bitacora::{escribir_archivo, sincronizar_archivo, Bitacora};
+use crate::ejecutor::ejecutor_implementacion::crear_ruta;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::crear_ruta_esquema;
+use crate::esquema::indices::sincronizar_indices;
+use std::fs;
+use std::path::Path;
+
+/// Nombre del archivo que indica que hay una transaccion activa en el directorio. Contiene una linea por cada tabla modificada en la transaccion; las tablas creadas dentro de ella se marcan con un '+' al comienzo.
+const ARCHIVO_TRANSACCION: &str = "transaccion_activa";
+
+/// Esta funcion devuelve la ruta al archivo sombra de la tabla, que contiene su version con los cambios de la transaccion activa.
+fn crear_ruta_sombra(path: &String, tabla: &str) -> String {
+    format!("{}/{}.sombra", path, tabla)
+}
+
+fn crear_ruta_transaccion(path: &String) -> String {
+    format!("{}/{}", path, ARCHIVO_TRANSACCION)
+}
+
+/// Indica si hay una transaccion activa en el directorio.
+pub fn hay_transaccion_activa(path: &String) -> bool {
+    Path::new(&crear_ruta_transaccion(path)).exists()
+}
+
+/// Esta funcion devuelve la ruta desde la que debe leerse la tabla: su archivo sombra si fue modificada en la transaccion activa, o su .csv si no.
+/// De esta manera las consultas dentro de una transaccion ven los cambios que todavia no fueron confirmados.
+pub fn ruta_tabla(path: &String, tabla: &str) -> String {
+    let sombra = crear_ruta_sombra(path, tabla);
+    if Path::new(&sombra).exists() {
+        return sombra;
+    }
+    crear_ruta(path, &tabla.to_string())
+}
+
+/// Esta funcion devuelve la ruta en la que deben escribirse los cambios de la tabla.
+/// Sin transaccion activa es el .csv de la tabla. Con una transaccion activa es su archivo sombra, que se crea como copia del .csv la primera vez que la tabla se modifica.
+pub fn ruta_escritura(path: &String, tabla: &str) -> Result<String, Errores> {
+    if !hay_transaccion_activa(path) {
+        return Ok(crear_ruta(path, &tabla.to_string()));
+    }
+    let sombra = crear_ruta_sombra(path, tabla);
+    if Path::new(&sombra).exists() {
+        return Ok(sombra);
+    }
+    let original = crear_ruta(path, &tabla.to_string());
+    let registro = if Path::new(&original).exists() {
+        if fs::copy(&original, &sombra).is_err() || sincronizar_archivo(&sombra).is_err() {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error preparando la transaccion".to_string(),
+            ));
+        }
+        tabla.to_string()
+    } else {
+        format!("+{}", tabla)
+    };
+    let mut tablas = leer_tablas(path)?;
+    tablas.push(registro);
+    escribir_tablas(path, &tablas)?;
+    Ok(sombra)
+}
+
+/// Esta funcion inicia una transaccion en el directorio. Devuelve error si ya hay una activa.
+pub fn iniciar_transaccion(path: &String) -> Result<(), Errores> {
+    if hay_transaccion_activa(path) {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Ya hay una transaccion activa".to_string(),
+        ));
+    }
+    escribir_tablas(path, &[])
+}
+
+/// Esta funcion confirma la transaccion activa, reemplazando cada tabla modificada por su archivo sombra.
+/// Los reemplazos se registran en la bitacora junto con la eliminacion del archivo de la transaccion, de manera que se aplican todos o ninguno. Luego se actualizan los indices de las tablas modificadas.
+pub fn confirmar_transaccion(path: &String) -> Result<(), Errores> {
+    let mut bitacora = Bitacora::new(path);
+    let tablas = leer_tablas(path)?;
+    for registro in &tablas {
+        let tabla = registro.trim_start_matches('+');
+        bitacora.renombrar(
+            &crear_ruta_sombra(path, tabla),
+            &crear_ruta(path, &tabla.to_string()),
+        )?;
+    }
+    bitacora.eliminar(&crear_ruta_transaccion(path));
+    bitacora.confirmar()?;
+    for registro in &tablas {
+        sincronizar_indices(path, registro.trim_start_matches('+'), None);
+    }
+    Ok(())
+}
+
+/// Esta funcion deshace la transaccion activa, descartando los archivos sombra. Las tablas creadas dentro de la transaccion se eliminan junto con su esquema.
+pub fn deshacer_transaccion(path: &String) -> Result<(), Errores> {
+    let mut bitacora = Bitacora::new(path);
+    for registro in leer_tablas(path)? {
+        let tabla = registro.trim_start_matches('+');
+        bitacora.eliminar(&crear_ruta_sombra(path, tabla));
+        if registro.starts_with('+') {
+            bitacora.eliminar(&crear_ruta_esquema(path, &tabla.to_string()));
+        }
+    }
+    bitacora.eliminar(&crear_ruta_transaccion(path));
+    bitacora.confirmar()
+}
+
+/// Devuelve las tablas modificadas en la transaccion activa, o una lista vacia si no hay ninguna.
+pub fn tablas_de_transaccion(path: &String) -> Vec<String> {
+    match fs::read_to_string(crear_ruta_transaccion(path)) {
+        Ok(contenido) => contenido
+            .lines()
+            .map(|l| l.trim().trim_start_matches('+').to_string())
+            .filter(|l| !l.is_empty())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Elimina los archivos sombra que no pertenecen a la transaccion activa, por ejemplo porque el programa se interrumpio mientras se copiaba la tabla.
+pub fn descartar_sombras_huerfanas(path: &String) {
+    let tablas = tablas_de_transaccion(path);
+    let entradas = match fs::read_dir(path) {
+        Ok(entradas) => entradas,
+        Err(_) => return,
+    };
+    for entrada in entradas.filter_map(|e| e.ok()) {
+        let nombre = entrada.file_name().to_string_lossy().to_string();
+        if let Some(tabla) = nombre.strip_suffix(".sombra") {
+            if !tablas.iter().any(|t| t == tabla) {
+                let _ = fs::remove_file(entrada.path());
+            }
+        }
+    }
+}
+
+/// Devuelve las tablas modificadas en la transaccion activa. Devuelve error si no hay ninguna transaccion activa.
+fn leer_tablas(path: &String) -> Result<Vec<String>, Errores> {
+    match fs::read_to_string(crear_ruta_transaccion(path)) {
+        Ok(contenido) => Ok(contenido
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.trim().to_string())
+            .collect()),
+        Err(_) => Err(Errores::nuevo(
+            TipoError::Error,
+            "No hay una transaccion activa".to_string(),
+        )),
+    }
+}
+
+fn escribir_tablas(path: &String, tablas: &[String]) -> Result<(), Errores> {
+    let mut contenido = String::new();
+    for tabla in tablas {
+        contenido.push_str(tabla);
+        contenido.push('\n');
+    }
+    if escribir_archivo(&crear_ruta_transaccion(path), &contenido).is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error guardando el estado de la transaccion".to_string(),
+        ));
+    }
+    Ok(())
+}
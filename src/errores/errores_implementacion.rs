@@ -1,24 +1,286 @@
-#[derive(Debug, PartialEq)]
+use std::fmt;
 
 /// Representa los tipos de errores que pueden ocurrir durante la ejecucion del programa.
-pub enum Errores {
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TipoError {
     /// Tipo de error para cuando existen problemas relacionados al archivo .csv donde se encuentran los datos.
     InvalidTable,
     /// Tipo de error para cuando se intenta acceder o modificar una columna que no se encuentra en el archivo.
     InvalidColumn,
     /// Tipo de error para cuando se le pasa como parametro al programa una query invalida.
     InvalidSyntax,
-    /// Tipo de error generico para fallos inesperados.
+    /// Tipo de error para cuando una consulta viola alguna restriccion declarada en el esquema de la tabla (PRIMARY KEY, UNIQUE, NOT NULL).
+    ConstraintViolation,
+    /// Tipo de error para cuando no se pudo obtener el bloqueo de una tabla antes de que se agote el tiempo de espera.
+    LockTimeout,
+    /// Tipo de error generico para fallos inesperados, como los errores al leer o escribir archivos.
     Error,
 }
-/// Esta funcion se encarga de imprimir un error con una descripcion, ambos son pasados como parametro.
+
+impl TipoError {
+    /// Devuelve el nombre con el que se muestra el tipo de error.
+    pub fn nombre(&self) -> &'static str {
+        match self {
+            TipoError::InvalidTable => "INVALID_TABLE",
+            TipoError::InvalidColumn => "INVALID_COLUMN",
+            TipoError::InvalidSyntax => "INVALID_SYNTAX",
+            TipoError::ConstraintViolation => "CONSTRAINT_VIOLATION",
+            TipoError::LockTimeout => "LOCK_TIMEOUT",
+            TipoError::Error => "ERROR",
+        }
+    }
+
+    /// Devuelve el codigo de salida del programa cuando una consulta falla con este tipo de error.
+    pub fn codigo_salida(&self) -> i32 {
+        match self {
+            TipoError::Error => 1,
+            TipoError::InvalidSyntax => 2,
+            TipoError::InvalidTable => 3,
+            TipoError::InvalidColumn => 4,
+            TipoError::ConstraintViolation => 5,
+            TipoError::LockTimeout => 6,
+        }
+    }
+}
+
+/// Representa la ubicacion de un fragmento dentro de la consulta original. La linea y la columna se cuentan desde 1.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Posicion {
+    pub linea: usize,
+    pub columna: usize,
+    /// Cantidad de caracteres del fragmento.
+    pub largo: usize,
+    /// Texto completo de la linea de la consulta donde se encuentra el fragmento.
+    pub texto_linea: String,
+}
+
+/// Representa un error ocurrido al parsear o ejecutar una consulta.
+/// Los errores no se imprimen donde se producen: quien ejecuta la consulta decide si mostrarlos con `imprimir_error`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Errores {
+    pub tipo: TipoError,
+    pub mensaje: String,
+    /// Palabra de la consulta que causo el error, si se conoce. Se usa para ubicar el error con `ubicar`.
+    pub token: Option<String>,
+    /// Posicion en bytes desde la que se busca el token, medida desde la primera palabra de la consulta. El parser la completa con la posicion exacta del token, o con la del comienzo de la clausula donde ocurrio el error.
+    pub desplazamiento: Option<usize>,
+    /// Ubicacion del token en la consulta original, una vez ubicado. Se guarda en el heap para que los Result que devuelven errores sigan siendo chicos.
+    pub posicion: Option<Box<Posicion>>,
+    /// Nombre parecido al que causo el error (una palabra clave, tabla o columna), que probablemente quiso escribirse.
+    pub sugerencia: Option<String>,
+}
+
+impl Errores {
+    pub fn nuevo(tipo: TipoError, mensaje: String) -> Errores {
+        Errores {
+            tipo,
+            mensaje,
+            token: None,
+            desplazamiento: None,
+            posicion: None,
+            sugerencia: None,
+        }
+    }
+
+    /// Esta funcion agrega al error la sugerencia indicada, si la hay.
+    pub fn con_sugerencia(mut self, sugerencia: Option<String>) -> Errores {
+        self.sugerencia = sugerencia;
+        self
+    }
+
+    /// Esta funcion indica la palabra de la consulta que causo el error.
+    pub fn en_token(mut self, token: &str) -> Errores {
+        if !token.trim().is_empty() {
+            self.token = Some(token.trim().to_string());
+        }
+        self
+    }
+
+    /// Esta funcion indica la posicion en bytes del token dentro del texto que se estaba procesando.
+    pub fn en_desplazamiento(mut self, desplazamiento: usize) -> Errores {
+        self.desplazamiento = Some(desplazamiento);
+        self
+    }
+
+    /// Esta funcion corre la posicion del error la cantidad de bytes indicada. Se usa cuando el error ocurrio procesando un fragmento que comienza en esa posicion de la consulta.
+    pub fn desplazar(mut self, inicio: usize) -> Errores {
+        self.desplazamiento = Some(self.desplazamiento.unwrap_or(0) + inicio);
+        self
+    }
+
+    /// Esta funcion corre la linea del error ya ubicado la cantidad de lineas indicada. Se usa cuando la consulta es parte de un texto mas largo, como un script.
+    pub fn desplazar_lineas(mut self, lineas: usize) -> Errores {
+        if let Some(posicion) = self.posicion.as_mut() {
+            posicion.linea += lineas;
+        }
+        self
+    }
+
+    /// Esta funcion busca el token del error en la consulta original y guarda su ubicacion. Si el error no tiene token, o ya fue ubicado, no cambia.
+    /// Se busca la primera aparicion del token como palabra completa a partir de su desplazamiento, sin distinguir mayusculas y fuera de los textos entre comillas.
+    pub fn ubicar(mut self, consulta: &str) -> Errores {
+        if self.posicion.is_some() {
+            return self;
+        }
+        if let Some(token) = &self.token {
+            let espacios_iniciales = consulta.len() - consulta.trim_start().len();
+            let desde = espacios_iniciales + self.desplazamiento.unwrap_or(0);
+            self.posicion = buscar_token(consulta, token, desde).map(Box::new);
+        }
+        self
+    }
+}
+
+/// Muestra el error como "[TIPO]: MENSAJE", seguido de la sugerencia si la hay. Si el error fue ubicado, se agrega la linea de la consulta con el token marcado debajo.
+impl fmt::Display for Errores {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]: {}", self.tipo.nombre(), self.mensaje)?;
+        if let Some(sugerencia) = &self.sugerencia {
+            write!(f, ". ¿Quiso decir {}?", sugerencia)?;
+        }
+        if let Some(posicion) = &self.posicion {
+            write!(
+                f,
+                " (linea {}, columna {})\n{}\n{}{}",
+                posicion.linea,
+                posicion.columna,
+                posicion.texto_linea,
+                " ".repeat(posicion.columna - 1),
+                "^".repeat(posicion.largo.max(1))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Errores {}
+
+/// Esta funcion imprime el error en la salida de errores, para no mezclarlo con los resultados de las consultas.
 /// Imprime: [TIPO ERROR]: DESCRIPCION
-pub fn imprimir_error(error: Errores, descripcion: String) {
-    let tipo = match error {
-        Errores::InvalidTable => "INVALID_TABLE",
-        Errores::InvalidColumn => "INVALID_COLUMN",
-        Errores::InvalidSyntax => "INVALID_SYNTAX",
-        Errores::Error => "ERROR",
-    };
-    println!("[{}]: {}", tipo, descripcion);
+pub fn imprimir_error(error: &Errores) {
+    eprintln!("{}", error);
+}
+
+fn es_caracter_de_palabra(caracter: char) -> bool {
+    caracter.is_alphanumeric() || caracter == '_'
+}
+
+/// Devuelve la ubicacion de la primera aparicion del token en la consulta como palabra completa, a partir del byte indicado.
+fn buscar_token(consulta: &str, token: &str, desde: usize) -> Option<Posicion> {
+    let caracteres: Vec<char> = consulta.chars().collect();
+    let desde = consulta
+        .get(..desde)
+        .map_or(0, |previo| previo.chars().count());
+    let buscado: Vec<char> = token.to_lowercase().chars().collect();
+    let entre_comillas_permitido = token.starts_with('\'');
+    let mut entre_comillas = false;
+    let mut linea = 1;
+    let mut inicio_linea = 0;
+    for i in 0..caracteres.len() {
+        let caracter = caracteres[i];
+        if caracter == '\n' {
+            linea += 1;
+            inicio_linea = i + 1;
+            continue;
+        }
+        if i >= desde
+            && (!entre_comillas || entre_comillas_permitido)
+            && coincide(&caracteres, i, &buscado)
+        {
+            let texto_linea: String = caracteres[inicio_linea..]
+                .iter()
+                .take_while(|c| **c != '\n')
+                .collect();
+            return Some(Posicion {
+                linea,
+                columna: i - inicio_linea + 1,
+                largo: buscado.len(),
+                texto_linea: texto_linea.trim_end().to_string(),
+            });
+        }
+        if caracter == '\'' {
+            entre_comillas = !entre_comillas;
+        }
+    }
+    None
+}
+
+/// Indica si el token aparece en la posicion indicada sin formar parte de una palabra mas larga.
+fn coincide(caracteres: &[char], inicio: usize, buscado: &[char]) -> bool {
+    let fin = inicio + buscado.len();
+    if fin > caracteres.len() || buscado.is_empty() {
+        return false;
+    }
+    let iguales = caracteres[inicio..fin]
+        .iter()
+        .zip(buscado)
+        .all(|(c, b)| c.to_lowercase().eq(b.to_lowercase()));
+    let limite_inicio = inicio == 0
+        || !es_caracter_de_palabra(buscado[0])
+        || !es_caracter_de_palabra(caracteres[inicio - 1]);
+    let limite_fin = fin == caracteres.len()
+        || !es_caracter_de_palabra(buscado[buscado.len() - 1])
+        || !es_caracter_de_palabra(caracteres[fin]);
+    iguales && limite_inicio && limite_fin
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Errores, TipoError};
+
+    #[test]
+    fn test_ubicar_error() {
+        let consulta = "SELECT id, 'edad'\nFROM clientes WHERE edad > 3";
+        let error = Errores::nuevo(
+            TipoError::InvalidColumn,
+            "La columna edad no existe".to_string(),
+        )
+        .en_token("EDAD")
+        .ubicar(consulta);
+        let posicion = error.posicion.clone().unwrap();
+        assert_eq!(
+            (posicion.linea, posicion.columna, posicion.largo),
+            (2, 21, 4)
+        );
+        assert_eq!(
+            error.to_string(),
+            "[INVALID_COLUMN]: La columna edad no existe (linea 2, columna 21)\n\
+             FROM clientes WHERE edad > 3\n                    ^^^^"
+        );
+    }
+
+    #[test]
+    fn test_codigos_de_salida_distintos() {
+        let codigos: Vec<i32> = [
+            TipoError::Error,
+            TipoError::InvalidSyntax,
+            TipoError::InvalidTable,
+            TipoError::InvalidColumn,
+            TipoError::ConstraintViolation,
+            TipoError::LockTimeout,
+        ]
+        .iter()
+        .map(|tipo| tipo.codigo_salida())
+        .collect();
+        assert_eq!(codigos, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_ubicar_desde_el_desplazamiento() {
+        let consulta = "  SELECT id FROM t WHERE id = 1 AND id = = 2";
+        let error = Errores::nuevo(TipoError::InvalidSyntax, "Error".to_string())
+            .en_token("=")
+            .en_desplazamiento(31)
+            .ubicar(consulta);
+        assert_eq!(error.posicion.unwrap().columna, 40);
+    }
+
+    #[test]
+    fn test_error_sin_ubicacion() {
+        let error = Errores::nuevo(TipoError::InvalidTable, "No existe".to_string())
+            .en_token("ventas")
+            .ubicar("SELECT * FROM clientes");
+        assert_eq!(error.posicion, None);
+        assert_eq!(error.to_string(), "[INVALID_TABLE]: No existe");
+    }
 }
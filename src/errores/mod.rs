@@ -1 +1,2 @@
 pub mod errores_implementacion;
+pub mod sugerencias;
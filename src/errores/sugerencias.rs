@@ -0,0 +1,159 @@
+/// Palabras clave de SQL que reconoce el parser, con las que se comparan las palabras desconocidas de una consulta.
+pub const PALABRAS_CLAVE: [&str; 43] = [
+    "SELECT",
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "CREATE",
+    "DROP",
+    "BEGIN",
+    "START",
+    "TRANSACTION",
+    "COMMIT",
+    "ROLLBACK",
+    "FROM",
+    "WHERE",
+    "INTO",
+    "VALUES",
+    "SET",
+    "ORDER",
+    "GROUP",
+    "BY",
+    "JOIN",
+    "INNER",
+    "ASC",
+    "DESC",
+    "LIMIT",
+    "AND",
+    "OR",
+    "NOT",
+    "TABLE",
+    "SEQUENCE",
+    "INDEX",
+    "VIEW",
+    "REPLACE",
+    "MATERIALIZED",
+    "REFRESH",
+    "REINDEX",
+    "EXPLAIN",
+    "ANALYZE",
+    "PRIMARY",
+    "KEY",
+    "UNIQUE",
+    "DEFAULT",
+    "CHECK",
+    "REFERENCES",
+];
+
+/// Esta funcion devuelve la cantidad minima de inserciones, eliminaciones, reemplazos o intercambios de caracteres adyacentes necesarios para convertir un texto en el otro, sin distinguir mayusculas.
+pub fn distancia_edicion(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut distancias = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, fila) in distancias.iter_mut().enumerate() {
+        fila[0] = i;
+    }
+    for (j, distancia) in distancias[0].iter_mut().enumerate() {
+        *distancia = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let costo = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distancia = (distancias[i - 1][j] + 1)
+                .min(distancias[i][j - 1] + 1)
+                .min(distancias[i - 1][j - 1] + costo);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distancia = distancia.min(distancias[i - 2][j - 2] + 1);
+            }
+            distancias[i][j] = distancia;
+        }
+    }
+    distancias[a.len()][b.len()]
+}
+
+/// Esta funcion devuelve el candidato mas parecido a la palabra, si la diferencia es de a lo sumo un caracter cada tres (y al menos uno).
+/// Si varios candidatos estan a la misma distancia se elige el primero en orden alfabetico. No se sugiere la palabra misma.
+pub fn sugerir<'a, I>(palabra: &str, candidatos: I) -> Option<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let largo = palabra.chars().count();
+    let limite = (largo / 3).max(1);
+    candidatos
+        .into_iter()
+        .filter(|candidato| !candidato.eq_ignore_ascii_case(palabra))
+        .map(|candidato| (distancia_edicion(palabra, candidato), candidato))
+        .filter(|(distancia, _)| *distancia <= limite && *distancia < largo)
+        .min()
+        .map(|(_, candidato)| candidato.to_string())
+}
+
+/// Esta funcion quita del nombre de una columna los signos que lo separan del resto de la consulta, como la coma de una lista de columnas o el punto y coma final.
+pub fn limpiar_identificador(nombre: &str) -> &str {
+    nombre.trim_matches(|c: char| c == ',' || c == ';' || c.is_whitespace())
+}
+
+/// Esta funcion busca en la consulta la primera palabra, fuera de los textos entre comillas, que no es una palabra clave pero se parece a una.
+/// Devuelve la palabra y la palabra clave sugerida.
+pub fn sugerir_palabra_clave(consulta: &str) -> Option<(String, String)> {
+    let mut entre_comillas = false;
+    let sin_textos: String = consulta
+        .chars()
+        .map(|caracter| {
+            if caracter == '\'' {
+                entre_comillas = !entre_comillas;
+                ' '
+            } else if entre_comillas {
+                ' '
+            } else {
+                caracter
+            }
+        })
+        .collect();
+    sin_textos
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|palabra| palabra.chars().all(|c| c.is_alphabetic()))
+        .filter(|palabra| {
+            !PALABRAS_CLAVE
+                .iter()
+                .any(|clave| clave.eq_ignore_ascii_case(palabra))
+        })
+        .find_map(|palabra| {
+            sugerir(palabra, PALABRAS_CLAVE).map(|clave| (palabra.to_string(), clave))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{distancia_edicion, limpiar_identificador, sugerir, sugerir_palabra_clave};
+
+    #[test]
+    fn test_distancia_edicion() {
+        assert_eq!(distancia_edicion("SELCT", "select"), 1);
+        assert_eq!(distancia_edicion("FORM", "FROM"), 1);
+        assert_eq!(distancia_edicion("cliente", "clientes"), 1);
+        assert_eq!(distancia_edicion("", "abc"), 3);
+        assert_eq!(distancia_edicion("ventas", "clientes"), 4);
+    }
+
+    #[test]
+    fn test_sugerir() {
+        let tablas = ["clientes", "ordenes", "productos"];
+        assert_eq!(sugerir("cliente", tablas), Some("clientes".to_string()));
+        assert_eq!(sugerir("ORDENES", tablas), None);
+        assert_eq!(sugerir("ventas", tablas), None);
+        assert_eq!(sugerir("id", ["ip", "idx"]), Some("idx".to_string()));
+        assert_eq!(
+            sugerir_palabra_clave("SELECT * FORM clientes WHERE nombre = 'FORM'"),
+            Some(("FORM".to_string(), "FROM".to_string()))
+        );
+        assert_eq!(sugerir_palabra_clave("SELECT * FROM clientes"), None);
+    }
+
+    #[test]
+    fn test_limpiar_identificador() {
+        assert_eq!(limpiar_identificador("apellido,"), "apellido");
+        assert_eq!(limpiar_identificador(" id;"), "id");
+        assert_eq!(limpiar_identificador("COUNT(*)"), "COUNT(*)");
+    }
+}
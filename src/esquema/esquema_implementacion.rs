@@ -0,0 +1,814 @@
+use crate::analizador::analizador_implementacion::{
+    columnas_de_expresion, es_numero, parsear_expresion,
+};
+use crate::analizador::expresion::Expresion;
+use crate::ejecutor::bitacora::escribir_archivo;
+use crate::ejecutor::ejecutor_implementacion::adaptar_where;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::indices::indices_de_tabla;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa una columna declarada en el esquema de una tabla.
+pub struct Columna {
+    /// Nombre de la columna, debe coincidir con el encabezado del archivo .csv.
+    pub nombre: String,
+    /// Tipo declarado de la columna (por ejemplo INT o TEXT).
+    pub tipo: Option<String>,
+    /// Indica si la columna no admite valores nulos.
+    pub not_null: bool,
+    /// Valor que toma la columna cuando no se indica en un INSERT.
+    pub por_defecto: Option<ValorPorDefecto>,
+    /// Indica si la columna es autoincremental, y en ese caso si admite que se le indiquen valores.
+    pub identidad: Option<Identidad>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Representa los tipos de columnas autoincrementales. El ultimo valor asignado se guarda en los metadatos de la tabla, de manera que no se reutilizan valores aunque se eliminen filas.
+pub enum Identidad {
+    /// GENERATED ALWAYS AS IDENTITY: la columna no admite que se le indiquen valores.
+    Siempre,
+    /// GENERATED BY DEFAULT AS IDENTITY o AUTOINCREMENT: solo se genera un valor si no se indica uno.
+    PorDefecto,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa los valores por defecto que puede tener una columna.
+pub enum ValorPorDefecto {
+    /// Un numero o un texto entre comillas, guardado tal y como fue escrito.
+    Literal(String),
+    /// La fecha actual (CURRENT_DATE), con formato AAAA-MM-DD.
+    FechaActual,
+    /// La hora actual (CURRENT_TIME), con formato HH:MM:SS.
+    HoraActual,
+    /// La fecha y hora actual (CURRENT_TIMESTAMP o NOW()).
+    FechaHoraActual,
+    /// El siguiente valor de una secuencia (NEXTVAL('secuencia')).
+    Secuencia(String),
+}
+
+impl ValorPorDefecto {
+    /// Devuelve el valor por defecto tal y como se escribe en un CREATE TABLE.
+    pub fn descripcion(&self) -> String {
+        match self {
+            ValorPorDefecto::Literal(literal) => literal.to_string(),
+            ValorPorDefecto::FechaActual => "CURRENT_DATE".to_string(),
+            ValorPorDefecto::HoraActual => "CURRENT_TIME".to_string(),
+            ValorPorDefecto::FechaHoraActual => "CURRENT_TIMESTAMP".to_string(),
+            ValorPorDefecto::Secuencia(secuencia) => format!("NEXTVAL('{}')", secuencia),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa las restricciones que pueden involucrar a una o mas columnas de la tabla.
+pub enum Restriccion {
+    /// Clave primaria, sus columnas no admiten nulos ni valores repetidos.
+    PrimaryKey { columnas: Vec<String> },
+    /// Restriccion de unicidad, los valores no nulos no pueden repetirse.
+    Unique { columnas: Vec<String> },
+    /// Clave foranea, los valores no nulos deben existir en las columnas referenciadas de otra tabla.
+    ForeignKey {
+        columnas: Vec<String>,
+        tabla_referenciada: String,
+        columnas_referenciadas: Vec<String>,
+        on_delete: AccionReferencial,
+        on_update: AccionReferencial,
+    },
+    /// Condicion que deben cumplir todas las filas de la tabla, escrita con la misma sintaxis que una clausula WHERE.
+    Check { texto: String, expresion: Expresion },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Representa lo que se hace con las filas que referencian a una fila de la tabla padre cuando esta se elimina o se modifica su clave.
+pub enum AccionReferencial {
+    /// Se rechaza la consulta sobre la tabla padre.
+    Restrict,
+    /// Se eliminan (o actualizan) tambien las filas que la referencian.
+    Cascade,
+    /// Las columnas de las filas que la referencian pasan a ser nulas.
+    SetNull,
+}
+
+impl AccionReferencial {
+    /// Devuelve la accion tal y como se escribe en un CREATE TABLE.
+    fn descripcion(&self) -> &str {
+        match self {
+            AccionReferencial::Restrict => "RESTRICT",
+            AccionReferencial::Cascade => "CASCADE",
+            AccionReferencial::SetNull => "SET NULL",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// Representa el esquema de una tabla. Se guarda en un archivo <tabla>.esquema junto al .csv, con una definicion por linea tal y como se escribiria dentro de un CREATE TABLE.
+/// Ademas de las definiciones, el archivo guarda los metadatos de la tabla en lineas de la forma "@clave valor".
+/// Si una tabla no tiene archivo de esquema se considera que no tiene restricciones.
+pub struct Esquema {
+    pub columnas: Vec<Columna>,
+    pub restricciones: Vec<Restriccion>,
+    pub metadatos: BTreeMap<String, String>,
+}
+
+/// Representa cada uno de los elementos que pueden aparecer en la definicion de una tabla.
+enum Definicion {
+    Columna(Columna, Vec<Restriccion>),
+    Restriccion(Restriccion),
+}
+
+impl Restriccion {
+    /// Devuelve las columnas involucradas en la restriccion.
+    pub fn columnas(&self) -> Vec<String> {
+        match self {
+            Restriccion::PrimaryKey { columnas } => columnas.to_vec(),
+            Restriccion::Unique { columnas } => columnas.to_vec(),
+            Restriccion::ForeignKey { columnas, .. } => columnas.to_vec(),
+            Restriccion::Check { expresion, .. } => columnas_de_expresion(expresion),
+        }
+    }
+
+    /// Devuelve la restriccion tal y como se escribe en un CREATE TABLE.
+    pub fn descripcion(&self) -> String {
+        match self {
+            Restriccion::PrimaryKey { columnas } => {
+                format!("PRIMARY KEY ({})", columnas.join(", "))
+            }
+            Restriccion::Unique { columnas } => format!("UNIQUE ({})", columnas.join(", ")),
+            Restriccion::ForeignKey {
+                columnas,
+                tabla_referenciada,
+                columnas_referenciadas,
+                on_delete,
+                on_update,
+            } => format!(
+                "FOREIGN KEY ({}) REFERENCES {} ({}) ON DELETE {} ON UPDATE {}",
+                columnas.join(", "),
+                tabla_referenciada,
+                columnas_referenciadas.join(", "),
+                on_delete.descripcion(),
+                on_update.descripcion()
+            ),
+            Restriccion::Check { texto, .. } => format!("CHECK ({})", texto),
+        }
+    }
+}
+
+impl Columna {
+    /// Devuelve la columna tal y como se escribe en un CREATE TABLE.
+    fn descripcion(&self) -> String {
+        let mut descripcion = self.nombre.to_string();
+        if let Some(tipo) = &self.tipo {
+            descripcion.push_str(&format!(" {}", tipo));
+        }
+        if self.not_null {
+            descripcion.push_str(" NOT NULL");
+        }
+        if let Some(por_defecto) = &self.por_defecto {
+            descripcion.push_str(&format!(" DEFAULT {}", por_defecto.descripcion()));
+        }
+        match self.identidad {
+            Some(Identidad::Siempre) => descripcion.push_str(" GENERATED ALWAYS AS IDENTITY"),
+            Some(Identidad::PorDefecto) => {
+                descripcion.push_str(" GENERATED BY DEFAULT AS IDENTITY")
+            }
+            None => {}
+        }
+        descripcion
+    }
+}
+
+impl Esquema {
+    /// Construye un esquema a partir de las definiciones de un CREATE TABLE (o de las lineas de un archivo de esquema).
+    pub fn desde_definiciones(definiciones: &[String]) -> Result<Esquema, Errores> {
+        let mut esquema = Esquema::default();
+        for definicion in definiciones {
+            match parsear_definicion(definicion)? {
+                Definicion::Columna(columna, restricciones) => {
+                    if esquema.columna(&columna.nombre).is_some() {
+                        return Err(Errores::nuevo(
+                            TipoError::InvalidSyntax,
+                            format!("La columna {} fue declarada mas de una vez", columna.nombre),
+                        ));
+                    }
+                    esquema.columnas.push(columna);
+                    esquema.restricciones.extend(restricciones);
+                }
+                Definicion::Restriccion(restriccion) => esquema.restricciones.push(restriccion),
+            }
+        }
+        let primary_keys = esquema
+            .restricciones
+            .iter()
+            .filter(|r| matches!(r, Restriccion::PrimaryKey { columnas: _ }))
+            .count();
+        if primary_keys > 1 {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "La tabla no puede tener mas de una PRIMARY KEY".to_string(),
+            ));
+        }
+        Ok(esquema)
+    }
+
+    /// Devuelve la columna con el nombre indicado, si fue declarada.
+    pub fn columna(&self, nombre: &str) -> Option<&Columna> {
+        self.columnas.iter().find(|c| c.nombre == nombre)
+    }
+
+    /// Devuelve el nombre de las columnas que no admiten valores nulos, incluyendo a las que forman parte de la PRIMARY KEY.
+    pub fn columnas_not_null(&self) -> Vec<String> {
+        let mut resultado: Vec<String> = Vec::new();
+        for columna in self.columnas.iter().filter(|c| c.not_null) {
+            resultado.push(columna.nombre.to_string());
+        }
+        for restriccion in &self.restricciones {
+            if let Restriccion::PrimaryKey { columnas } = restriccion {
+                for columna in columnas {
+                    if !resultado.contains(columna) {
+                        resultado.push(columna.to_string());
+                    }
+                }
+            }
+        }
+        resultado
+    }
+
+    /// Devuelve las claves foraneas de la tabla que referencian a la tabla indicada.
+    pub fn claves_foraneas_hacia(&self, tabla: &str) -> Vec<&Restriccion> {
+        self.restricciones
+            .iter()
+            .filter(|r| match r {
+                Restriccion::ForeignKey {
+                    tabla_referenciada, ..
+                } => tabla_referenciada == tabla,
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Verifica que todas las columnas mencionadas en el esquema existan en el encabezado de la tabla.
+    pub fn validar_columnas(&self, encabezado: &[String]) -> Result<(), Errores> {
+        let mut mencionadas: Vec<String> =
+            self.columnas.iter().map(|c| c.nombre.to_string()).collect();
+        for restriccion in &self.restricciones {
+            mencionadas.extend(restriccion.columnas());
+        }
+        for columna in mencionadas {
+            if !encabezado.contains(&columna) {
+                return Err(Errores::nuevo(
+                    TipoError::InvalidColumn,
+                    format!("La columna {} del esquema no existe en la tabla", columna),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Devuelve la instruccion CREATE TABLE que crea la tabla con este esquema, seguida de las instrucciones CREATE INDEX de sus indices. Las columnas del encabezado que no estan declaradas en el esquema se escriben sin tipo.
+    pub fn sentencia_create(&self, tabla: &str, encabezado: &[String]) -> String {
+        let mut lineas: Vec<String> = encabezado
+            .iter()
+            .map(|nombre| match self.columna(nombre) {
+                Some(columna) => columna.descripcion(),
+                None => nombre.to_string(),
+            })
+            .collect();
+        for restriccion in &self.restricciones {
+            lineas.push(restriccion.descripcion());
+        }
+        let mut sentencia = format!(
+            "CREATE TABLE {} (\n    {}\n);",
+            tabla,
+            lineas.join(",\n    ")
+        );
+        for indice in indices_de_tabla(self) {
+            sentencia.push('\n');
+            sentencia.push_str(&indice.sentencia_create(tabla));
+        }
+        sentencia
+    }
+
+    /// Devuelve el contenido del archivo de esquema, una definicion por linea.
+    fn serializar(&self) -> String {
+        let mut lineas: Vec<String> = self.columnas.iter().map(|c| c.descripcion()).collect();
+        for restriccion in &self.restricciones {
+            lineas.push(restriccion.descripcion());
+        }
+        for (clave, valor) in &self.metadatos {
+            lineas.push(format!("@{} {}", clave, valor));
+        }
+        let mut contenido = lineas.join("\n");
+        contenido.push('\n');
+        contenido
+    }
+}
+
+/// Esta funcion recibe la ruta al directorio y el nombre de la tabla, y devuelve la ruta al archivo donde se guarda su esquema.
+pub fn crear_ruta_esquema(path: &String, tabla: &String) -> String {
+    format!("{}/{}.esquema", path, tabla)
+}
+
+/// Esta funcion lee el esquema de la tabla y verifica que sea consistente con el encabezado del .csv.
+/// Si la tabla no tiene archivo de esquema devuelve un esquema vacio, es decir, sin restricciones.
+pub fn cargar_esquema(
+    path: &String,
+    tabla: &String,
+    encabezado: &[String],
+) -> Result<Esquema, Errores> {
+    let ruta = crear_ruta_esquema(path, tabla);
+    if !Path::new(&ruta).exists() {
+        return Ok(Esquema::default());
+    }
+    let contenido = match fs::read_to_string(&ruta) {
+        Ok(c) => c,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::InvalidTable,
+                format!("Error leyendo el esquema de la tabla {}", tabla),
+            ));
+        }
+    };
+    let lineas: Vec<String> = contenido
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty() && !l.starts_with("--"))
+        .collect();
+    let definiciones: Vec<String> = lineas
+        .iter()
+        .filter(|l| !l.starts_with('@'))
+        .cloned()
+        .collect();
+    let mut esquema = Esquema::desde_definiciones(&definiciones)?;
+    for linea in lineas.iter().filter_map(|l| l.strip_prefix('@')) {
+        let (clave, valor) = linea.split_once(' ').unwrap_or((linea, ""));
+        esquema
+            .metadatos
+            .insert(clave.to_string(), valor.trim().to_string());
+    }
+    esquema.validar_columnas(encabezado)?;
+    Ok(esquema)
+}
+
+/// Esta funcion escribe el esquema de la tabla en su archivo correspondiente.
+/// Se escribe primero un archivo auxiliar que luego reemplaza al original, para no dejar el esquema a medio escribir.
+pub fn guardar_esquema(path: &String, tabla: &String, esquema: &Esquema) -> Result<(), Errores> {
+    let ruta = crear_ruta_esquema(path, tabla);
+    if escribir_archivo(&ruta, &esquema.serializar()).is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            format!("Error guardando el esquema de la tabla {}", tabla),
+        ));
+    }
+    Ok(())
+}
+
+/// Esta funcion recibe la lista de definiciones de un CREATE TABLE, es decir, lo que se encuentra entre los parentesis, y la separa por las comas que no estan dentro de parentesis ni de comillas.
+/// Ejemplo:
+/// "(id INT PRIMARY KEY, UNIQUE (a, b))" -> ["id INT PRIMARY KEY", "UNIQUE (a, b)"]
+pub fn separar_definiciones(definicion: &str) -> Result<Vec<String>, Errores> {
+    let definicion = definicion.trim().trim_end_matches(';').trim();
+    if !definicion.starts_with('(') || !definicion.ends_with(')') || definicion.len() < 2 {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "La definicion de la tabla debe estar entre parentesis".to_string(),
+        ));
+    }
+    let interior = &definicion[1..definicion.len() - 1];
+    let mut resultado: Vec<String> = Vec::new();
+    let mut actual = String::new();
+    let mut profundidad: i32 = 0;
+    let mut en_comillas = false;
+    for c in interior.chars() {
+        match c {
+            '\'' => {
+                en_comillas = !en_comillas;
+                actual.push(c);
+            }
+            '(' if !en_comillas => {
+                profundidad += 1;
+                actual.push(c);
+            }
+            ')' if !en_comillas => {
+                profundidad -= 1;
+                actual.push(c);
+            }
+            ',' if !en_comillas && profundidad == 0 => {
+                resultado.push(actual.trim().to_string());
+                actual.clear();
+            }
+            _ => actual.push(c),
+        }
+        if profundidad < 0 {
+            break;
+        }
+    }
+    resultado.push(actual.trim().to_string());
+    if profundidad != 0 || en_comillas || resultado.iter().any(|d| d.is_empty()) {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "La definicion de la tabla fue escrita de manera incorrecta".to_string(),
+        ));
+    }
+    Ok(resultado)
+}
+
+/// Separa una definicion en palabras, dejando los parentesis y las comas como elementos propios y respetando los valores entre comillas.
+fn tokenizar_definicion(definicion: &str) -> Vec<String> {
+    let mut resultado: Vec<String> = Vec::new();
+    let mut token = String::new();
+    let mut en_comillas = false;
+    for c in definicion.chars() {
+        if c == '\'' {
+            en_comillas = !en_comillas;
+            token.push(c);
+        } else if en_comillas {
+            token.push(c);
+        } else if c.is_whitespace() || c == '(' || c == ')' || c == ',' {
+            if !token.is_empty() {
+                resultado.push(token.clone());
+                token.clear();
+            }
+            if !c.is_whitespace() {
+                resultado.push(c.to_string());
+            }
+        } else {
+            token.push(c);
+        }
+    }
+    if !token.is_empty() {
+        resultado.push(token);
+    }
+    resultado
+}
+
+/// Parsea una definicion, que puede ser una columna con sus restricciones o una restriccion de tabla.
+fn parsear_definicion(definicion: &str) -> Result<Definicion, Errores> {
+    let tokens = tokenizar_definicion(definicion);
+    if tokens.is_empty() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "Definicion de tabla vacia".to_string(),
+        ));
+    }
+    match tokens[0].to_uppercase().as_str() {
+        "PRIMARY" | "UNIQUE" | "FOREIGN" | "CHECK" => {
+            Ok(Definicion::Restriccion(parsear_restriccion_tabla(&tokens)?))
+        }
+        _ => parsear_columna(&tokens),
+    }
+}
+
+/// Parsea una restriccion de tabla, por ejemplo: PRIMARY KEY (a, b), UNIQUE (a) o FOREIGN KEY (a) REFERENCES t (b).
+fn parsear_restriccion_tabla(tokens: &[String]) -> Result<Restriccion, Errores> {
+    if palabra_es(tokens, 0, "CHECK") {
+        let (restriccion, fin) = parsear_check(tokens, 0)?;
+        if fin != tokens.len() {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                format!("Restriccion invalida: {}", tokens.join(" ")),
+            ));
+        }
+        return Ok(restriccion);
+    }
+    if palabra_es(tokens, 0, "FOREIGN") && palabra_es(tokens, 1, "KEY") {
+        let (columnas, fin) = parsear_lista_columnas(tokens, 2)?;
+        let (restriccion, fin) = parsear_referencia(tokens, fin, columnas)?;
+        if fin != tokens.len() {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                format!("Restriccion invalida: {}", tokens.join(" ")),
+            ));
+        }
+        return Ok(restriccion);
+    }
+    let (es_primary, inicio) = if palabra_es(tokens, 0, "PRIMARY") && palabra_es(tokens, 1, "KEY") {
+        (true, 2)
+    } else {
+        (false, 1)
+    };
+    let (columnas, fin) = parsear_lista_columnas(tokens, inicio)?;
+    if fin != tokens.len() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            format!("Restriccion invalida: {}", tokens.join(" ")),
+        ));
+    }
+    if es_primary {
+        Ok(Restriccion::PrimaryKey { columnas })
+    } else {
+        Ok(Restriccion::Unique { columnas })
+    }
+}
+
+/// Parsea una restriccion CHECK (condicion) que comienza en la posicion indicada, devolviendo tambien la posicion siguiente al parentesis de cierre.
+fn parsear_check(tokens: &[String], inicio: usize) -> Result<(Restriccion, usize), Errores> {
+    if !palabra_es(tokens, inicio + 1, "(") {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            format!(
+                "La condicion CHECK debe estar entre parentesis: {}",
+                tokens.join(" ")
+            ),
+        ));
+    }
+    let mut profundidad = 0;
+    let mut indice = inicio + 1;
+    let mut condicion: Vec<String> = Vec::new();
+    while indice < tokens.len() {
+        match tokens[indice].as_str() {
+            "(" => profundidad += 1,
+            ")" => profundidad -= 1,
+            _ => {}
+        }
+        if profundidad == 0 {
+            break;
+        }
+        if indice > inicio + 1 {
+            condicion.push(tokens[indice].to_string());
+        }
+        indice += 1;
+    }
+    if indice >= tokens.len() || condicion.is_empty() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            format!("Condicion CHECK invalida: {}", tokens.join(" ")),
+        ));
+    }
+    let texto = condicion.join(" ");
+    let expresion = parsear_expresion(adaptar_where(&texto))?;
+    Ok((Restriccion::Check { texto, expresion }, indice + 1))
+}
+
+/// Parsea el valor por defecto de una columna: un numero, un texto entre comillas, NULL, CURRENT_DATE, CURRENT_TIME, CURRENT_TIMESTAMP (tambien NOW()) o NEXTVAL('secuencia').
+/// Devuelve el valor (None si es NULL) y la posicion siguiente.
+fn parsear_por_defecto(
+    tokens: &[String],
+    indice: usize,
+) -> Result<(Option<ValorPorDefecto>, usize), Errores> {
+    let valor = match tokens.get(indice) {
+        Some(valor) => valor,
+        None => {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                format!("Falta el valor de DEFAULT en: {}", tokens.join(" ")),
+            ));
+        }
+    };
+    let por_defecto = match valor.to_uppercase().as_str() {
+        "NULL" => None,
+        "CURRENT_DATE" => Some(ValorPorDefecto::FechaActual),
+        "CURRENT_TIME" => Some(ValorPorDefecto::HoraActual),
+        "CURRENT_TIMESTAMP" => Some(ValorPorDefecto::FechaHoraActual),
+        "NOW" if palabra_es(tokens, indice + 1, "(") && palabra_es(tokens, indice + 2, ")") => {
+            return Ok((Some(ValorPorDefecto::FechaHoraActual), indice + 3));
+        }
+        "NEXTVAL" if palabra_es(tokens, indice + 1, "(") && palabra_es(tokens, indice + 3, ")") => {
+            let secuencia = tokens[indice + 2].trim_matches('\'').to_string();
+            return Ok((Some(ValorPorDefecto::Secuencia(secuencia)), indice + 4));
+        }
+        _ if es_numero(valor)
+            || (valor.len() >= 2 && valor.starts_with('\'') && valor.ends_with('\'')) =>
+        {
+            Some(ValorPorDefecto::Literal(valor.to_string()))
+        }
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                format!("Valor por defecto invalido: {}", valor),
+            ));
+        }
+    };
+    Ok((por_defecto, indice + 1))
+}
+
+/// Parsea una columna autoincremental que comienza en la posicion indicada: GENERATED ALWAYS AS IDENTITY o GENERATED BY DEFAULT AS IDENTITY.
+fn parsear_identidad(tokens: &[String], indice: usize) -> Result<(Identidad, usize), Errores> {
+    let (identidad, siguiente) = if palabra_es(tokens, indice + 1, "ALWAYS") {
+        (Identidad::Siempre, indice + 2)
+    } else if palabra_es(tokens, indice + 1, "BY") && palabra_es(tokens, indice + 2, "DEFAULT") {
+        (Identidad::PorDefecto, indice + 3)
+    } else {
+        (Identidad::PorDefecto, indice)
+    };
+    if siguiente == indice
+        || !palabra_es(tokens, siguiente, "AS")
+        || !palabra_es(tokens, siguiente + 1, "IDENTITY")
+    {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            format!("Columna autoincremental invalida: {}", tokens.join(" ")),
+        ));
+    }
+    Ok((identidad, siguiente + 2))
+}
+
+/// Parsea una referencia a otra tabla que comienza en la posicion indicada: REFERENCES tabla (columnas) [ON DELETE accion] [ON UPDATE accion]
+/// Devuelve la clave foranea y la posicion siguiente al ultimo elemento de la referencia.
+fn parsear_referencia(
+    tokens: &[String],
+    inicio: usize,
+    columnas: Vec<String>,
+) -> Result<(Restriccion, usize), Errores> {
+    if !palabra_es(tokens, inicio, "REFERENCES") || inicio + 1 >= tokens.len() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            format!("Se esperaba REFERENCES en: {}", tokens.join(" ")),
+        ));
+    }
+    let tabla_referenciada = tokens[inicio + 1].to_string();
+    let (columnas_referenciadas, mut indice) = parsear_lista_columnas(tokens, inicio + 2)?;
+    if columnas_referenciadas.len() != columnas.len() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            format!(
+                "La clave foranea ({}) no tiene la misma cantidad de columnas que la referencia",
+                columnas.join(", ")
+            ),
+        ));
+    }
+    let mut on_delete = AccionReferencial::Restrict;
+    let mut on_update = AccionReferencial::Restrict;
+    while palabra_es(tokens, indice, "ON") {
+        let (accion, siguiente) = parsear_accion(tokens, indice + 2)?;
+        if palabra_es(tokens, indice + 1, "DELETE") {
+            on_delete = accion;
+        } else if palabra_es(tokens, indice + 1, "UPDATE") {
+            on_update = accion;
+        } else {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                format!("Se esperaba ON DELETE u ON UPDATE en: {}", tokens.join(" ")),
+            ));
+        }
+        indice = siguiente;
+    }
+    let restriccion = Restriccion::ForeignKey {
+        columnas,
+        tabla_referenciada,
+        columnas_referenciadas,
+        on_delete,
+        on_update,
+    };
+    Ok((restriccion, indice))
+}
+
+/// Parsea la accion de una clave foranea (RESTRICT, NO ACTION, CASCADE o SET NULL) devolviendo tambien la posicion siguiente.
+fn parsear_accion(tokens: &[String], indice: usize) -> Result<(AccionReferencial, usize), Errores> {
+    if palabra_es(tokens, indice, "RESTRICT") {
+        Ok((AccionReferencial::Restrict, indice + 1))
+    } else if palabra_es(tokens, indice, "NO") && palabra_es(tokens, indice + 1, "ACTION") {
+        Ok((AccionReferencial::Restrict, indice + 2))
+    } else if palabra_es(tokens, indice, "CASCADE") {
+        Ok((AccionReferencial::Cascade, indice + 1))
+    } else if palabra_es(tokens, indice, "SET") && palabra_es(tokens, indice + 1, "NULL") {
+        Ok((AccionReferencial::SetNull, indice + 2))
+    } else {
+        Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            format!(
+                "Accion invalida para la clave foranea: {}",
+                tokens.join(" ")
+            ),
+        ))
+    }
+}
+
+/// Parsea una lista de columnas entre parentesis que comienza en la posicion indicada, devolviendo las columnas y la posicion siguiente al parentesis de cierre.
+fn parsear_lista_columnas(
+    tokens: &[String],
+    inicio: usize,
+) -> Result<(Vec<String>, usize), Errores> {
+    if tokens.get(inicio).map(|t| t.as_str()) != Some("(") {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            format!("Se esperaba una lista de columnas en: {}", tokens.join(" ")),
+        ));
+    }
+    let mut columnas: Vec<String> = Vec::new();
+    let mut indice = inicio + 1;
+    while indice < tokens.len() && tokens[indice] != ")" {
+        if tokens[indice] != "," {
+            columnas.push(tokens[indice].to_string());
+        }
+        indice += 1;
+    }
+    if indice >= tokens.len() || columnas.is_empty() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            format!("Lista de columnas invalida en: {}", tokens.join(" ")),
+        ));
+    }
+    Ok((columnas, indice + 1))
+}
+
+/// Parsea la definicion de una columna: nombre [tipo] [PRIMARY KEY] [UNIQUE] [NOT NULL | NULL] [DEFAULT valor] [CHECK (condicion)] [REFERENCES tabla (columna) ...]
+fn parsear_columna(tokens: &[String]) -> Result<Definicion, Errores> {
+    let nombre = tokens[0].to_string();
+    let mut columna = Columna {
+        nombre: nombre.to_string(),
+        tipo: None,
+        not_null: false,
+        por_defecto: None,
+        identidad: None,
+    };
+    let mut restricciones: Vec<Restriccion> = Vec::new();
+    let mut indice = 1;
+    if indice < tokens.len() && !es_palabra_de_restriccion(&tokens[indice]) {
+        let mut tipo = tokens[indice].to_uppercase();
+        indice += 1;
+        if palabra_es(tokens, indice, "(") {
+            while indice < tokens.len() && tokens[indice] != ")" {
+                tipo.push_str(&tokens[indice]);
+                indice += 1;
+            }
+            if indice >= tokens.len() {
+                return Err(Errores::nuevo(
+                    TipoError::InvalidSyntax,
+                    format!("Tipo invalido para la columna {}", nombre),
+                )
+                .en_token(&nombre));
+            }
+            tipo.push(')');
+            indice += 1;
+        }
+        columna.tipo = Some(tipo);
+    }
+    while indice < tokens.len() {
+        if palabra_es(tokens, indice, "PRIMARY") && palabra_es(tokens, indice + 1, "KEY") {
+            restricciones.push(Restriccion::PrimaryKey {
+                columnas: vec![nombre.to_string()],
+            });
+            indice += 2;
+        } else if palabra_es(tokens, indice, "UNIQUE") {
+            restricciones.push(Restriccion::Unique {
+                columnas: vec![nombre.to_string()],
+            });
+            indice += 1;
+        } else if palabra_es(tokens, indice, "NOT") && palabra_es(tokens, indice + 1, "NULL") {
+            columna.not_null = true;
+            indice += 2;
+        } else if palabra_es(tokens, indice, "NULL") {
+            indice += 1;
+        } else if palabra_es(tokens, indice, "GENERATED") {
+            let (identidad, siguiente) = parsear_identidad(tokens, indice)?;
+            columna.identidad = Some(identidad);
+            indice = siguiente;
+        } else if palabra_es(tokens, indice, "AUTOINCREMENT")
+            || palabra_es(tokens, indice, "AUTO_INCREMENT")
+        {
+            columna.identidad = Some(Identidad::PorDefecto);
+            indice += 1;
+        } else if palabra_es(tokens, indice, "DEFAULT") {
+            let (por_defecto, siguiente) = parsear_por_defecto(tokens, indice + 1)?;
+            columna.por_defecto = por_defecto;
+            indice = siguiente;
+        } else if palabra_es(tokens, indice, "CHECK") {
+            let (restriccion, siguiente) = parsear_check(tokens, indice)?;
+            restricciones.push(restriccion);
+            indice = siguiente;
+        } else if palabra_es(tokens, indice, "REFERENCES") {
+            let (restriccion, siguiente) =
+                parsear_referencia(tokens, indice, vec![nombre.to_string()])?;
+            restricciones.push(restriccion);
+            indice = siguiente;
+        } else {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                format!(
+                    "Restriccion desconocida en la columna {}: {}",
+                    nombre, tokens[indice]
+                ),
+            )
+            .en_token(&tokens[indice]));
+        }
+    }
+    Ok(Definicion::Columna(columna, restricciones))
+}
+
+/// Indica si la palabra es el comienzo de alguna restriccion de columna.
+fn es_palabra_de_restriccion(palabra: &str) -> bool {
+    matches!(
+        palabra.to_uppercase().as_str(),
+        "PRIMARY"
+            | "UNIQUE"
+            | "NOT"
+            | "NULL"
+            | "REFERENCES"
+            | "DEFAULT"
+            | "CHECK"
+            | "GENERATED"
+            | "AUTOINCREMENT"
+            | "AUTO_INCREMENT"
+    )
+}
+
+/// Indica si el token en la posicion indicada es la palabra buscada, sin distinguir mayusculas.
+fn palabra_es(tokens: &[String], indice: usize, palabra: &str) -> bool {
+    match tokens.get(indice) {
+        Some(token) => token.to_uppercase() == palabra,
+        None => false,
+    }
+}
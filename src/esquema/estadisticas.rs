@@ -0,0 +1,459 @@
+use crate::analizador::analizador_implementacion::{comparar_orden_total, es_numero};
+use crate::ejecutor::ejecutor_implementacion::crear_ruta;
+use crate::ejecutor::transaccion::ruta_tabla;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::Esquema;
+use crate::esquema::indices::Estampa;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader};
+
+/// Clave de los metadatos de la tabla en la que se guardan la estampa de la version analizada y la cantidad de filas.
+const CLAVE: &str = "estadisticas";
+/// Prefijo de las claves de los metadatos de la tabla en las que se guardan las estadisticas de cada columna.
+const PREFIJO_CLAVE: &str = "estadisticas.";
+/// Cantidad de tramos del histograma de cada columna.
+const TRAMOS_HISTOGRAMA: usize = 10;
+/// Cantidad maxima de valores de cada columna que se conservan como muestra para armar su histograma.
+const TAMANO_MUESTRA: usize = 10_000;
+/// Cantidad de valores de hash mas chicos de cada columna que se conservan para estimar cuantos valores distintos tiene.
+const HASHES_CONSERVADOS: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa las estadisticas de una tabla calculadas con ANALYZE: la cantidad de filas y, de cada columna, cuantos valores distintos y cuantos nulos tiene y como se distribuyen sus valores.
+/// Se guardan en los metadatos del esquema de la tabla, en una linea "@estadisticas <estampa> <filas>" y una linea "@estadisticas.<columna> <distintos> <nulos> <histograma>" por columna, donde el histograma son los limites de sus tramos separados por comas.
+/// En la clave, los espacios y los '%' del nombre de la columna se escriben como "%20" y "%25", de manera que la clave nunca contiene espacios.
+pub struct Estadisticas {
+    /// La estampa de la version de la tabla que se analizo. Las estadisticas solo describen la tabla mientras esta conserve la misma estampa.
+    pub estampa: Estampa,
+    pub filas: usize,
+    pub columnas: Vec<EstadisticasColumna>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa las estadisticas de una columna de una tabla.
+pub struct EstadisticasColumna {
+    pub nombre: String,
+    /// La cantidad estimada de valores distintos, sin contar el nulo. Es exacta si la columna tiene pocos valores distintos.
+    pub distintos: usize,
+    /// La cantidad de filas en las que la columna es nula.
+    pub nulos: usize,
+    /// Los limites de los tramos de un histograma de igual profundidad: entre cada par de limites consecutivos se encuentra aproximadamente la misma cantidad de valores.
+    /// El primer limite es el minimo de la columna y el ultimo su maximo. Esta vacio si todos los valores son nulos.
+    pub histograma: Vec<String>,
+}
+
+impl Estadisticas {
+    /// Lee de los metadatos del esquema las estadisticas de la tabla, con sus columnas en el orden del encabezado. Devuelve None si la tabla no fue analizada.
+    /// Las columnas cuyas estadisticas faltan o no pueden leerse se omiten, sin descartar las de las demas columnas.
+    pub fn de_esquema(esquema: &Esquema, encabezado: &[String]) -> Option<Estadisticas> {
+        let (estampa, filas) = esquema.metadatos.get(CLAVE)?.rsplit_once(' ')?;
+        let columnas: Vec<EstadisticasColumna> = encabezado
+            .iter()
+            .filter_map(|nombre| {
+                let valor = esquema.metadatos.get(&clave_columna(nombre))?;
+                EstadisticasColumna::desde_texto(nombre, valor)
+            })
+            .collect();
+        Some(Estadisticas {
+            estampa: Estampa::desde_texto(estampa)?,
+            filas: filas.parse().ok()?,
+            columnas,
+        })
+    }
+
+    /// Guarda las estadisticas en los metadatos del esquema, reemplazando las que hubiera.
+    pub fn guardar_en(&self, esquema: &mut Esquema) {
+        esquema
+            .metadatos
+            .retain(|clave, _| clave != CLAVE && !clave.starts_with(PREFIJO_CLAVE));
+        esquema.metadatos.insert(
+            CLAVE.to_string(),
+            format!("{} {}", self.estampa, self.filas),
+        );
+        for columna in &self.columnas {
+            esquema.metadatos.insert(
+                clave_columna(&columna.nombre),
+                format!(
+                    "{} {} {}",
+                    columna.distintos,
+                    columna.nulos,
+                    columna.histograma.join(",")
+                ),
+            );
+        }
+    }
+
+    /// Devuelve las estadisticas de la columna indicada.
+    pub fn columna(&self, nombre: &str) -> Option<&EstadisticasColumna> {
+        self.columnas.iter().find(|c| c.nombre == nombre)
+    }
+}
+
+/// Devuelve la clave de los metadatos en la que se guardan las estadisticas de la columna.
+fn clave_columna(nombre: &str) -> String {
+    let mut clave = PREFIJO_CLAVE.to_string();
+    for caracter in nombre.chars() {
+        match caracter {
+            '%' => clave.push_str("%25"),
+            c if c.is_whitespace() => clave.push_str(&format!("%{:02X}", c as u32)),
+            c => clave.push(c),
+        }
+    }
+    clave
+}
+
+impl EstadisticasColumna {
+    /// Lee las estadisticas de la columna del valor guardado en los metadatos: "<distintos> <nulos> <histograma>". Devuelve None si el valor es invalido.
+    fn desde_texto(nombre: &str, valor: &str) -> Option<EstadisticasColumna> {
+        let mut partes = valor.splitn(3, ' ');
+        let distintos = partes.next()?.parse().ok()?;
+        let nulos = partes.next()?.parse().ok()?;
+        let histograma = match partes.next() {
+            Some(limites) if !limites.is_empty() => {
+                limites.split(',').map(|l| l.to_string()).collect()
+            }
+            _ => Vec::new(),
+        };
+        Some(EstadisticasColumna {
+            nombre: nombre.to_string(),
+            distintos,
+            nulos,
+            histograma,
+        })
+    }
+
+    /// Devuelve el menor valor de la columna, sin contar el nulo.
+    pub fn minimo(&self) -> Option<&str> {
+        self.histograma.first().map(|l| l.as_str())
+    }
+
+    /// Devuelve el mayor valor de la columna, sin contar el nulo.
+    pub fn maximo(&self) -> Option<&str> {
+        self.histograma.last().map(|l| l.as_str())
+    }
+
+    /// Devuelve la fraccion de las filas en las que la columna es nula.
+    pub fn fraccion_nulos(&self, filas: usize) -> f64 {
+        if filas == 0 {
+            return 0.0;
+        }
+        self.nulos as f64 / filas as f64
+    }
+
+    /// Estima la fraccion de las filas en las que la columna es igual al valor: si el valor esta entre el minimo y el maximo, se supone que todos los valores distintos se repiten la misma cantidad de veces.
+    pub fn fraccion_igual(&self, valor: &str, filas: usize) -> f64 {
+        let (minimo, maximo) = match (self.minimo(), self.maximo()) {
+            (Some(minimo), Some(maximo)) => (minimo, maximo),
+            _ => return 0.0,
+        };
+        if comparar_orden_total(valor, minimo).is_lt()
+            || comparar_orden_total(valor, maximo).is_gt()
+        {
+            return 0.0;
+        }
+        (1.0 - self.fraccion_nulos(filas)) / self.distintos.max(1) as f64
+    }
+
+    /// Estima la fraccion de las filas en las que la columna es menor al valor, ubicandolo en el histograma. Dentro de un tramo se supone que los numeros se distribuyen de manera uniforme y que el valor esta en la mitad si no lo son.
+    pub fn fraccion_menor(&self, valor: &str, filas: usize) -> f64 {
+        let histograma = &self.histograma;
+        if histograma.is_empty() || comparar_orden_total(valor, &histograma[0]).is_le() {
+            return 0.0;
+        }
+        let no_nulos = 1.0 - self.fraccion_nulos(filas);
+        let tramos = histograma.len() - 1;
+        let tramo = match histograma[1..]
+            .iter()
+            .position(|limite| comparar_orden_total(valor, limite).is_le())
+        {
+            Some(tramo) => tramo,
+            None => return no_nulos,
+        };
+        let (desde, hasta) = (&histograma[tramo], &histograma[tramo + 1]);
+        let numeros = [desde.as_str(), hasta.as_str(), valor]
+            .iter()
+            .all(|texto| es_numero(texto));
+        let dentro = match (
+            desde.parse::<f64>(),
+            hasta.parse::<f64>(),
+            valor.parse::<f64>(),
+        ) {
+            (Ok(desde), Ok(hasta), Ok(numero)) if numeros && hasta > desde => {
+                ((numero - desde) / (hasta - desde)).clamp(0.0, 1.0)
+            }
+            _ => 0.5,
+        };
+        no_nulos * (tramo as f64 + dentro) / tramos as f64
+    }
+}
+
+/// Representa lo recolectado de una columna al recorrer la tabla.
+struct Recoleccion {
+    nulos: usize,
+    /// Cantidad de valores no nulos vistos.
+    valores: usize,
+    minimo: Option<String>,
+    maximo: Option<String>,
+    /// Los valores de hash mas chicos entre los de todos los valores de la columna.
+    hashes: BTreeSet<u64>,
+    /// Una muestra uniforme de los valores de la columna, elegida a medida que se recorre la tabla.
+    muestra: Vec<String>,
+}
+
+impl Recoleccion {
+    fn new() -> Recoleccion {
+        Recoleccion {
+            nulos: 0,
+            valores: 0,
+            minimo: None,
+            maximo: None,
+            hashes: BTreeSet::new(),
+            muestra: Vec::new(),
+        }
+    }
+
+    fn agregar(&mut self, valor: &str, aleatorio: &mut u64) {
+        if valor.is_empty() {
+            self.nulos += 1;
+            return;
+        }
+        self.valores += 1;
+        if self
+            .minimo
+            .as_deref()
+            .is_none_or(|minimo| comparar_orden_total(valor, minimo).is_lt())
+        {
+            self.minimo = Some(valor.to_string());
+        }
+        if self
+            .maximo
+            .as_deref()
+            .is_none_or(|maximo| comparar_orden_total(valor, maximo).is_gt())
+        {
+            self.maximo = Some(valor.to_string());
+        }
+        let mut hasher = DefaultHasher::new();
+        valor.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self.hashes.len() < HASHES_CONSERVADOS {
+            self.hashes.insert(hash);
+        } else if self.hashes.last().is_some_and(|&mayor| hash < mayor) && self.hashes.insert(hash)
+        {
+            self.hashes.pop_last();
+        }
+        if self.muestra.len() < TAMANO_MUESTRA {
+            self.muestra.push(valor.to_string());
+        } else {
+            let elegido = (siguiente_aleatorio(aleatorio) % self.valores as u64) as usize;
+            if elegido < TAMANO_MUESTRA {
+                self.muestra[elegido] = valor.to_string();
+            }
+        }
+    }
+
+    /// Estima la cantidad de valores distintos a partir de los hashes mas chicos: si los valores de hash se distribuyen de manera uniforme, el mayor de los k conservados es aproximadamente k / distintos del total de valores de hash posibles.
+    fn distintos(&self) -> usize {
+        if self.hashes.len() < HASHES_CONSERVADOS {
+            return self.hashes.len();
+        }
+        let mayor = self.hashes.last().copied().unwrap_or(u64::MAX) as f64 + 1.0;
+        let estimados = (HASHES_CONSERVADOS - 1) as f64 * (u64::MAX as f64 / mayor);
+        (estimados.round() as usize).clamp(HASHES_CONSERVADOS, self.valores)
+    }
+
+    fn terminar(mut self, nombre: &str) -> EstadisticasColumna {
+        let distintos = self.distintos();
+        self.muestra.sort_by(|a, b| comparar_orden_total(a, b));
+        let mut histograma: Vec<String> = Vec::new();
+        if let (Some(minimo), Some(maximo)) = (self.minimo, self.maximo) {
+            let ultimo = self.muestra.len() - 1;
+            let tramos = TRAMOS_HISTOGRAMA.min(ultimo).max(1);
+            histograma = (0..=tramos)
+                .map(|i| self.muestra[i * ultimo / tramos].to_string())
+                .collect();
+            histograma[0] = minimo;
+            histograma[tramos] = maximo;
+        }
+        EstadisticasColumna {
+            nombre: nombre.to_string(),
+            distintos,
+            nulos: self.nulos,
+            histograma,
+        }
+    }
+}
+
+/// Genera el siguiente numero pseudoaleatorio con xorshift, de manera que la muestra sea siempre la misma para el mismo archivo.
+fn siguiente_aleatorio(estado: &mut u64) -> u64 {
+    *estado ^= *estado << 13;
+    *estado ^= *estado >> 7;
+    *estado ^= *estado << 17;
+    *estado
+}
+
+/// Esta funcion recorre la tabla una vez y calcula sus estadisticas. Las filas a las que les faltan valores se cuentan como nulas en esas columnas.
+/// La cantidad de valores distintos se estima sin guardar todos los valores, y el histograma se arma con una muestra de los valores de cada columna, por lo que la memoria necesaria no depende del tamaño de la tabla.
+pub fn calcular_estadisticas(ruta: &str) -> Result<Estadisticas, Errores> {
+    let error = || Errores::nuevo(TipoError::Error, "Error leyendo la tabla".to_string());
+    let estampa = Estampa::de(ruta).ok_or_else(error)?;
+    let archivo = File::open(ruta).map_err(|_| error())?;
+    let mut lineas = BufReader::new(archivo).lines();
+    let encabezado: Vec<String> = match lineas.next() {
+        Some(Ok(linea)) => linea.split(',').map(|c| c.trim().to_string()).collect(),
+        _ => return Err(error()),
+    };
+    let mut recolecciones: Vec<Recoleccion> =
+        encabezado.iter().map(|_| Recoleccion::new()).collect();
+    let mut aleatorio: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut filas: usize = 0;
+    for linea in lineas {
+        let linea = linea.map_err(|_| error())?;
+        let mut valores = linea.split(',');
+        for recoleccion in recolecciones.iter_mut() {
+            recoleccion.agregar(valores.next().unwrap_or("").trim(), &mut aleatorio);
+        }
+        filas += 1;
+    }
+    Ok(Estadisticas {
+        estampa,
+        filas,
+        columnas: recolecciones
+            .into_iter()
+            .zip(&encabezado)
+            .map(|(recoleccion, nombre)| recoleccion.terminar(nombre))
+            .collect(),
+    })
+}
+
+/// Esta funcion devuelve las estadisticas guardadas en el esquema de la tabla si todavia describen la version que leen las consultas, es decir, si la tabla no se modifico desde que se analizo.
+/// Dentro de una transaccion que modifico la tabla las consultas leen su archivo sombra, que las estadisticas no describen.
+pub fn estadisticas_vigentes(
+    path: &String,
+    tabla: &str,
+    esquema: &Esquema,
+    encabezado: &[String],
+) -> Option<Estadisticas> {
+    let ruta = crear_ruta(path, &tabla.to_string());
+    if ruta_tabla(path, tabla) != ruta {
+        return None;
+    }
+    Estadisticas::de_esquema(esquema, encabezado)
+        .filter(|estadisticas| Some(estadisticas.estampa) == Estampa::de(&ruta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ejecutor::ejecutor_implementacion::test::crear_directorio_prueba;
+    use crate::esquema::esquema_implementacion::{cargar_esquema, guardar_esquema};
+
+    #[test]
+    fn test_calcular_estadisticas() {
+        let mut contenido = "id,nombre,saldo\n".to_string();
+        for id in 1..=100 {
+            let nombre = ["Ana", "Juan", "Luis", "Zoe"][id % 4];
+            let saldo = if id % 5 == 0 {
+                String::new()
+            } else {
+                (id * 10).to_string()
+            };
+            contenido.push_str(&format!("{},{},{}\n", id, nombre, saldo));
+        }
+        contenido.push_str("101,Eva\n");
+        let directorio = crear_directorio_prueba("estadisticas", &[("datos.csv", &contenido)]);
+        let ruta = crear_ruta(&directorio, &"datos".to_string());
+        let estadisticas = calcular_estadisticas(&ruta).unwrap();
+        assert_eq!(estadisticas.filas, 101);
+        let id = estadisticas.columna("id").unwrap();
+        assert_eq!((id.distintos, id.nulos), (101, 0));
+        assert_eq!((id.minimo(), id.maximo()), (Some("1"), Some("101")));
+        assert_eq!(id.histograma.len(), TRAMOS_HISTOGRAMA + 1);
+        let nombre = estadisticas.columna("nombre").unwrap();
+        assert_eq!((nombre.distintos, nombre.nulos), (5, 0));
+        assert_eq!(
+            (nombre.minimo(), nombre.maximo()),
+            (Some("Ana"), Some("Zoe"))
+        );
+        let saldo = estadisticas.columna("saldo").unwrap();
+        assert_eq!((saldo.distintos, saldo.nulos), (80, 21));
+
+        let cerca = |a: f64, b: f64| (a - b).abs() < 0.05;
+        assert!(cerca(id.fraccion_menor("51", 101), 0.5));
+        assert!(cerca(id.fraccion_menor("1000", 101), 1.0));
+        assert_eq!(id.fraccion_menor("0", 101), 0.0);
+        assert!(cerca(id.fraccion_igual("7", 101), 0.01));
+        assert_eq!(id.fraccion_igual("500", 101), 0.0);
+        assert!(cerca(nombre.fraccion_igual("Juan", 101), 0.2));
+        assert!(cerca(saldo.fraccion_menor("505", 101), 0.4));
+
+        let mut esquema = Esquema::default();
+        esquema
+            .metadatos
+            .insert("indice.datos_id".to_string(), "id".to_string());
+        estadisticas.guardar_en(&mut esquema);
+        let encabezado: Vec<String> = ["id", "nombre", "saldo"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            Estadisticas::de_esquema(&esquema, &encabezado),
+            Some(estadisticas)
+        );
+        assert!(esquema.metadatos.contains_key("indice.datos_id"));
+        assert!(estadisticas_vigentes(&directorio, "datos", &esquema, &encabezado).is_some());
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        std::fs::write(&ruta, format!("{}102,Ana,5\n", contenido)).unwrap();
+        assert!(estadisticas_vigentes(&directorio, "datos", &esquema, &encabezado).is_none());
+    }
+
+    #[test]
+    fn test_estadisticas_de_columna_con_espacios() {
+        let directorio = crear_directorio_prueba(
+            "estadisticas_espacios",
+            &[(
+                "personas.csv",
+                "id,Correo electronico,nombre\n1,a@x.com,Ana\n2,,Juan\n",
+            )],
+        );
+        let tabla = "personas".to_string();
+        let estadisticas = calcular_estadisticas(&crear_ruta(&directorio, &tabla)).unwrap();
+        let encabezado: Vec<String> = ["id", "Correo electronico", "nombre"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let mut esquema = Esquema::default();
+        estadisticas.guardar_en(&mut esquema);
+        guardar_esquema(&directorio, &tabla, &esquema).unwrap();
+        let mut esquema = cargar_esquema(&directorio, &tabla, &encabezado).unwrap();
+        assert!(esquema
+            .metadatos
+            .contains_key("estadisticas.Correo%20electronico"));
+        assert_eq!(
+            Estadisticas::de_esquema(&esquema, &encabezado),
+            Some(estadisticas)
+        );
+        // Una entrada invalida solo descarta las estadisticas de su columna.
+        esquema
+            .metadatos
+            .insert("estadisticas.id".to_string(), "x".to_string());
+        let leidas = Estadisticas::de_esquema(&esquema, &encabezado).unwrap();
+        let nombres: Vec<&str> = leidas.columnas.iter().map(|c| c.nombre.as_str()).collect();
+        assert_eq!(nombres, ["Correo electronico", "nombre"]);
+        assert_eq!(leidas.columna("Correo electronico").unwrap().nulos, 1);
+    }
+
+    #[test]
+    fn test_distintos_estimados() {
+        let mut recoleccion = Recoleccion::new();
+        let mut aleatorio: u64 = 1;
+        for i in 0..50_000 {
+            recoleccion.agregar(&(i % 20_000).to_string(), &mut aleatorio);
+        }
+        let distintos = recoleccion.distintos() as f64;
+        assert!((distintos - 20_000.0).abs() < 2_000.0, "{}", distintos);
+        assert_eq!(recoleccion.muestra.len(), TAMANO_MUESTRA);
+    }
+}
@@ -0,0 +1,861 @@
+use crate::analizador::analizador_implementacion::{
+    comparar_texto_o_numero, es_numero, valor_literal,
+};
+use crate::analizador::condicional::Condicional;
+use crate::analizador::expresion::Expresion;
+use crate::ejecutor::bitacora::escribir_archivo;
+use crate::ejecutor::ejecutor_implementacion::{
+    columna_inexistente, crear_ruta, listar_tablas, obtener_primera_linea,
+};
+use crate::ejecutor::transaccion::ruta_tabla;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{cargar_esquema, Esquema};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// Prefijo de las claves de los metadatos de la tabla en las que se declaran sus indices.
+const PREFIJO_CLAVE: &str = "indice.";
+/// Cantidad de digitos con los que se escribe la posicion de la fila al comienzo de cada entrada de un tramo.
+const ANCHO_POSICION: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa un indice creado con CREATE INDEX sobre una columna de una tabla.
+/// Se declara en los metadatos del esquema de la tabla con una linea "@indice.<nombre> <columna> [UNIQUE]", y sus entradas se guardan en el archivo <nombre>.indice y en los tramos que este enumera.
+pub struct Indice {
+    pub nombre: String,
+    pub columna: String,
+    /// Indica si el indice no admite valores repetidos en la columna.
+    pub unico: bool,
+}
+
+impl Indice {
+    /// Devuelve la clave de los metadatos de la tabla en la que se declara el indice.
+    pub fn clave(&self) -> String {
+        format!("{}{}", PREFIJO_CLAVE, self.nombre)
+    }
+
+    /// Devuelve el valor con el que se declara el indice en los metadatos de la tabla.
+    pub fn valor(&self) -> String {
+        if self.unico {
+            format!("{} UNIQUE", self.columna)
+        } else {
+            self.columna.to_string()
+        }
+    }
+
+    /// Devuelve la instruccion CREATE INDEX que crea el indice sobre la tabla.
+    pub fn sentencia_create(&self, tabla: &str) -> String {
+        format!(
+            "CREATE {}INDEX {} ON {} ({});",
+            if self.unico { "UNIQUE " } else { "" },
+            self.nombre,
+            tabla,
+            self.columna
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa el resultado de buscar en un indice las filas que pueden cumplir una clausula WHERE.
+pub struct BusquedaIndice {
+    /// El indice usado.
+    pub indice: Indice,
+    /// La comparacion de la clausula que se resolvio con el indice, por ejemplo "id = 4".
+    pub condicion: String,
+    /// Las posiciones en el .csv de las filas encontradas, ordenadas.
+    pub posiciones: Vec<u64>,
+    /// La cantidad total de entradas del indice, es decir, de filas de la tabla.
+    pub entradas: usize,
+    /// La cantidad de tramos en los que se busco.
+    pub tramos: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Identifica una version del archivo de una tabla por su longitud y su fecha de modificacion.
+/// Cada indice guarda la estampa de la version sobre la que fue construido, y solo se usa mientras la tabla conserve esa misma estampa.
+pub struct Estampa {
+    longitud: u64,
+    modificacion: u128,
+}
+
+impl Estampa {
+    /// Devuelve la estampa de la version actual del archivo, o None si no puede leerse.
+    pub fn de(ruta: &str) -> Option<Estampa> {
+        let metadatos = fs::metadata(ruta).ok()?;
+        let modificacion = metadatos
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+        Some(Estampa {
+            longitud: metadatos.len(),
+            modificacion,
+        })
+    }
+
+    /// Interpreta una estampa escrita como "<longitud> <modificacion>".
+    pub fn desde_texto(texto: &str) -> Option<Estampa> {
+        let mut partes = texto.split_whitespace();
+        Some(Estampa {
+            longitud: partes.next()?.parse().ok()?,
+            modificacion: partes.next()?.parse().ok()?,
+        })
+    }
+}
+
+impl fmt::Display for Estampa {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.longitud, self.modificacion)
+    }
+}
+
+/// Representa el contenido del archivo <nombre>.indice: la estampa de la tabla sobre la que se construyo el indice y los tramos que lo forman.
+/// La primera linea contiene la estampa y cada una de las siguientes el nombre de un tramo.
+struct Manifiesto {
+    estampa: Estampa,
+    tramos: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa una entrada de un indice: el valor de la columna en una fila y la posicion en bytes en la que comienza esa fila dentro del .csv.
+struct Entrada {
+    clave: String,
+    posicion: u64,
+}
+
+/// Representa un tramo del indice abierto para buscar en el. Un tramo es un archivo con sus entradas ordenadas por clave; la primera linea indica el ancho de cada entrada y la cantidad de entradas.
+/// Todas las entradas ocupan la misma cantidad de bytes, de manera que la entrada i se lee directamente en la posicion inicio + i * ancho y la busqueda binaria no necesita leer el tramo completo.
+struct Tramo {
+    lector: BufReader<File>,
+    inicio: u64,
+    ancho: u64,
+    cantidad: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Representa la comparacion entre el valor de la columna indexada y el valor literal de la clausula WHERE.
+enum Comparacion {
+    Igual,
+    Mayor,
+    MayorIgual,
+    Menor,
+    MenorIgual,
+}
+
+/// Recorre las lineas de un archivo junto con la posicion en bytes en la que comienza cada una. Al igual que BufRead::lines, las lineas se devuelven sin el salto de linea.
+pub struct LineasConPosicion<R> {
+    lector: R,
+    posicion: u64,
+}
+
+impl<R: BufRead> LineasConPosicion<R> {
+    /// Crea el recorrido a partir de un lector ubicado en la posicion indicada del archivo.
+    pub fn new(lector: R, posicion: u64) -> LineasConPosicion<R> {
+        LineasConPosicion { lector, posicion }
+    }
+}
+
+impl<R: BufRead> Iterator for LineasConPosicion<R> {
+    type Item = Result<(u64, String), Errores>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut linea = String::new();
+        match self.lector.read_line(&mut linea) {
+            Ok(0) => None,
+            Ok(leidos) => {
+                let inicio = self.posicion;
+                self.posicion += leidos as u64;
+                if linea.ends_with('\n') {
+                    linea.pop();
+                    if linea.ends_with('\r') {
+                        linea.pop();
+                    }
+                }
+                Some(Ok((inicio, linea)))
+            }
+            Err(_) => Some(Err(Errores::nuevo(
+                TipoError::Error,
+                "Error leyendo la tabla".to_string(),
+            ))),
+        }
+    }
+}
+
+/// Recorre las lineas de la tabla que comienzan en las posiciones indicadas, en el mismo orden que las posiciones, junto con cada posicion.
+pub struct LineasEnPosiciones {
+    lector: BufReader<File>,
+    posiciones: std::vec::IntoIter<u64>,
+}
+
+impl LineasEnPosiciones {
+    /// Abre la tabla para leer las lineas que comienzan en las posiciones indicadas.
+    pub fn new(ruta: &str, posiciones: Vec<u64>) -> Result<LineasEnPosiciones, Errores> {
+        match File::open(ruta) {
+            Ok(archivo) => Ok(LineasEnPosiciones {
+                lector: BufReader::new(archivo),
+                posiciones: posiciones.into_iter(),
+            }),
+            Err(_) => Err(Errores::nuevo(
+                TipoError::Error,
+                "No se pudo abrir el archivo".to_string(),
+            )),
+        }
+    }
+}
+
+impl Iterator for LineasEnPosiciones {
+    type Item = Result<(u64, String), Errores>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let posicion = self.posiciones.next()?;
+        if self.lector.seek(SeekFrom::Start(posicion)).is_err() {
+            return Some(Err(Errores::nuevo(
+                TipoError::Error,
+                "Error leyendo la tabla".to_string(),
+            )));
+        }
+        match LineasConPosicion::new(&mut self.lector, posicion).next() {
+            Some(linea) => Some(linea),
+            None => Some(Err(Errores::nuevo(
+                TipoError::Error,
+                "Error leyendo la tabla".to_string(),
+            ))),
+        }
+    }
+}
+
+/// Esta funcion devuelve los indices declarados en el esquema de la tabla.
+pub fn indices_de_tabla(esquema: &Esquema) -> Vec<Indice> {
+    esquema
+        .metadatos
+        .iter()
+        .filter_map(|(clave, valor)| {
+            let nombre = clave.strip_prefix(PREFIJO_CLAVE)?;
+            let mut palabras = valor.split_whitespace();
+            let columna = palabras.next()?;
+            Some(Indice {
+                nombre: nombre.to_string(),
+                columna: columna.to_string(),
+                unico: palabras
+                    .next()
+                    .is_some_and(|p| p.eq_ignore_ascii_case("UNIQUE")),
+            })
+        })
+        .collect()
+}
+
+/// Esta funcion indica si el indice corresponde a la version actual de la tabla, es decir, si las consultas pueden usarlo.
+pub fn indice_vigente(path: &String, tabla: &str, indice: &Indice) -> bool {
+    let ruta = crear_ruta(path, &tabla.to_string());
+    leer_manifiesto(path, &indice.nombre)
+        .is_some_and(|manifiesto| Some(manifiesto.estampa) == Estampa::de(&ruta))
+}
+
+/// Esta funcion devuelve la tabla sobre la que esta creado el indice, o None si ninguna tabla del directorio tiene un indice con ese nombre.
+pub fn tabla_del_indice(path: &String, nombre: &str) -> Result<Option<String>, Errores> {
+    for tabla in listar_tablas(path)? {
+        let columnas = obtener_primera_linea(&ruta_tabla(path, &tabla))?;
+        let esquema = cargar_esquema(path, &tabla, &columnas)?;
+        if indices_de_tabla(&esquema)
+            .iter()
+            .any(|i| i.nombre == nombre)
+        {
+            return Ok(Some(tabla));
+        }
+    }
+    Ok(None)
+}
+
+/// Esta funcion recibe la ruta al directorio y el nombre del indice, y devuelve la ruta al archivo que enumera sus tramos.
+pub fn crear_ruta_indice(path: &String, nombre: &str) -> String {
+    format!("{}/{}.indice", path, nombre)
+}
+
+/// Esta funcion construye el indice desde cero, con un unico tramo que contiene una entrada por cada fila de la version actual de la tabla.
+/// Si el indice es UNIQUE y la columna tiene valores repetidos devuelve error sin modificar el indice.
+pub fn reconstruir_indice(
+    path: &String,
+    tabla: &str,
+    indice: &Indice,
+    columnas: &[String],
+) -> Result<(), Errores> {
+    let ruta = crear_ruta(path, &tabla.to_string());
+    let columna = match columnas.iter().position(|c| c == &indice.columna) {
+        Some(columna) => columna,
+        None => return Err(columna_inexistente(&indice.columna, columnas)),
+    };
+    let estampa = estampa_de_tabla(&ruta)?;
+    let mut entradas = leer_entradas(&ruta, columna, 0)?;
+    ordenar_entradas(&mut entradas);
+    if indice.unico {
+        verificar_unicidad(indice, &entradas)?;
+    }
+    let anterior = leer_manifiesto(path, &indice.nombre);
+    let tramo = nombre_tramo(indice, anterior.as_ref());
+    escribir_tramo(path, &tramo, &entradas)?;
+    escribir_manifiesto(
+        path,
+        &indice.nombre,
+        &Manifiesto {
+            estampa,
+            tramos: vec![tramo.to_string()],
+        },
+    )?;
+    descartar_tramos(path, indice, &[tramo]);
+    Ok(())
+}
+
+/// Esta funcion actualiza los indices de la tabla luego de modificarla. Si la tabla solo recibio filas al final y anterior es la estampa previa a agregarlas, las filas nuevas se agregan al indice como un tramo mas; en cualquier otro caso el indice se reconstruye.
+/// Los indices que ya corresponden a la version actual de la tabla, por ejemplo porque los cambios se escribieron en el archivo sombra de una transaccion, no se modifican.
+/// Los cambios de la tabla ya fueron publicados cuando se llama a esta funcion, por lo que un error no los deshace: el indice queda desactualizado, las consultas dejan de usarlo y se reconstruye en la proxima modificacion de la tabla o con REINDEX.
+pub fn sincronizar_indices(path: &String, tabla: &str, anterior: Option<Estampa>) {
+    let _ = actualizar_indices(path, tabla, anterior);
+}
+
+fn actualizar_indices(
+    path: &String,
+    tabla: &str,
+    anterior: Option<Estampa>,
+) -> Result<(), Errores> {
+    let ruta = crear_ruta(path, &tabla.to_string());
+    if !Path::new(&ruta).exists() {
+        return Ok(());
+    }
+    let columnas = obtener_primera_linea(&ruta)?;
+    let esquema = cargar_esquema(path, &tabla.to_string(), &columnas)?;
+    let actual = estampa_de_tabla(&ruta)?;
+    for indice in indices_de_tabla(&esquema) {
+        match leer_manifiesto(path, &indice.nombre) {
+            Some(manifiesto) if manifiesto.estampa == actual => {}
+            Some(manifiesto)
+                if Some(manifiesto.estampa) == anterior
+                    && manifiesto.estampa.longitud <= actual.longitud =>
+            {
+                if agregar_tramo(path, &ruta, &indice, &columnas, manifiesto, actual).is_err() {
+                    reconstruir_indice(path, tabla, &indice, &columnas)?;
+                }
+            }
+            _ => reconstruir_indice(path, tabla, &indice, &columnas)?,
+        }
+    }
+    Ok(())
+}
+
+/// Agrega al indice un tramo con las filas que se encuentran a partir del final de la version anterior de la tabla.
+/// Para que la cantidad de tramos se mantenga pequeña, el tramo nuevo se fusiona con los ultimos tramos mientras estos no tengan mas entradas que el.
+fn agregar_tramo(
+    path: &String,
+    ruta: &str,
+    indice: &Indice,
+    columnas: &[String],
+    mut manifiesto: Manifiesto,
+    actual: Estampa,
+) -> Result<(), Errores> {
+    let columna = match columnas.iter().position(|c| c == &indice.columna) {
+        Some(columna) => columna,
+        None => return Err(columna_inexistente(&indice.columna, columnas)),
+    };
+    let mut entradas = leer_entradas(ruta, columna, manifiesto.estampa.longitud)?;
+    let tramo = nombre_tramo(indice, Some(&manifiesto));
+    let mut fusionados: Vec<String> = Vec::new();
+    while let Some(ultimo) = manifiesto.tramos.last() {
+        let mut tramo = abrir_tramo(path, ultimo).ok_or_else(error_de_indice)?;
+        if tramo.cantidad as usize > entradas.len() {
+            break;
+        }
+        entradas.extend(
+            tramo
+                .entradas(0, tramo.cantidad)
+                .ok_or_else(error_de_indice)?,
+        );
+        fusionados.extend(manifiesto.tramos.pop());
+    }
+    ordenar_entradas(&mut entradas);
+    escribir_tramo(path, &tramo, &entradas)?;
+    manifiesto.tramos.push(tramo);
+    manifiesto.estampa = actual;
+    escribir_manifiesto(path, &indice.nombre, &manifiesto)?;
+    for tramo in fusionados {
+        let _ = fs::remove_file(format!("{}/{}", path, tramo));
+    }
+    Ok(())
+}
+
+/// Esta funcion busca, usando los indices de la tabla, las posiciones en el .csv de las filas que pueden cumplir la clausula WHERE. Las posiciones se devuelven ordenadas, de manera que las filas se leen en el mismo orden que al recorrer la tabla, y cada fila debe verificarse igualmente contra la clausula completa.
+/// Devuelve None si hay que recorrer la tabla completa: cuando la clausula no exige comparar una columna indexada con un valor (por ejemplo, porque la comparacion esta dentro de un OR), cuando la consulta lee el archivo sombra de una transaccion o cuando el indice no corresponde a la version actual de la tabla o no puede leerse.
+pub fn buscar_posiciones(
+    path: &String,
+    tabla: &str,
+    esquema: &Esquema,
+    indice_columnas: &HashMap<String, usize>,
+    clausula_where: &Expresion,
+) -> Option<Vec<u64>> {
+    buscar_con_indice(path, tabla, esquema, indice_columnas, clausula_where)
+        .map(|busqueda| busqueda.posiciones)
+}
+
+/// Esta funcion realiza la misma busqueda que buscar_posiciones, pero ademas indica que indice y que condicion se usaron y cuantas entradas tiene el indice, para que el planificador pueda comparar el costo de usarlo con el de recorrer la tabla.
+pub fn buscar_con_indice(
+    path: &String,
+    tabla: &str,
+    esquema: &Esquema,
+    indice_columnas: &HashMap<String, usize>,
+    clausula_where: &Expresion,
+) -> Option<BusquedaIndice> {
+    let ruta = crear_ruta(path, &tabla.to_string());
+    if ruta_tabla(path, tabla) != ruta {
+        return None;
+    }
+    let indices: Vec<Indice> = indices_de_tabla(esquema)
+        .into_iter()
+        .filter(|i| indice_columnas.contains_key(&i.columna))
+        .collect();
+    let mut condiciones = Vec::new();
+    condiciones_indexables(clausula_where, &mut condiciones);
+    let (indice, comparacion, literal) = condiciones
+        .into_iter()
+        .filter_map(|(columna, comparacion, literal)| {
+            let indice = indices.iter().find(|i| i.columna == columna)?;
+            Some((indice, comparacion, literal))
+        })
+        .min_by_key(|(_, comparacion, _)| *comparacion != Comparacion::Igual)?;
+    let manifiesto = leer_manifiesto(path, &indice.nombre)?;
+    if Some(manifiesto.estampa) != Estampa::de(&ruta) {
+        return None;
+    }
+    let mut posiciones: Vec<u64> = Vec::new();
+    let mut entradas: u64 = 0;
+    for nombre in &manifiesto.tramos {
+        let mut tramo = abrir_tramo(path, nombre)?;
+        entradas += tramo.cantidad;
+        posiciones.extend(tramo.buscar(comparacion, &literal)?);
+    }
+    posiciones.sort_unstable();
+    posiciones.dedup();
+    let literal = if es_numero(&literal) {
+        literal
+    } else {
+        format!("'{}'", literal)
+    };
+    Some(BusquedaIndice {
+        indice: indice.clone(),
+        condicion: format!("{} {} {}", indice.columna, comparacion.simbolo(), literal),
+        posiciones,
+        entradas: entradas as usize,
+        tramos: manifiesto.tramos.len(),
+    })
+}
+
+/// Agrega las comparaciones entre una columna y un valor literal que toda fila que cumpla la expresion debe cumplir, es decir, las que no estan dentro de un OR ni de un NOT.
+fn condiciones_indexables(
+    expresion: &Expresion,
+    condiciones: &mut Vec<(String, Comparacion, String)>,
+) {
+    match expresion {
+        Expresion::And { izquierda, derecha } => {
+            condiciones_indexables(izquierda, condiciones);
+            condiciones_indexables(derecha, condiciones);
+        }
+        Expresion::Operacion { condicional } => {
+            let comparacion = match condicional {
+                Condicional::Igual { .. } => Comparacion::Igual,
+                Condicional::Mayor { .. } => Comparacion::Mayor,
+                Condicional::MayorIgual { .. } => Comparacion::MayorIgual,
+                Condicional::Menor { .. } => Comparacion::Menor,
+                Condicional::MenorIgual { .. } => Comparacion::MenorIgual,
+            };
+            let (miembro1, miembro2) = condicional.miembros();
+            if let Some(literal) = valor_literal(miembro2) {
+                condiciones.push((miembro1.to_string(), comparacion, literal));
+            } else if let Some(literal) = valor_literal(miembro1) {
+                condiciones.push((miembro2.to_string(), comparacion.invertida(), literal));
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Comparacion {
+    /// Devuelve el operador con el que se escribe la comparacion en una consulta.
+    fn simbolo(self) -> &'static str {
+        match self {
+            Comparacion::Igual => "=",
+            Comparacion::Mayor => ">",
+            Comparacion::MayorIgual => ">=",
+            Comparacion::Menor => "<",
+            Comparacion::MenorIgual => "<=",
+        }
+    }
+
+    /// Devuelve la comparacion equivalente al intercambiar los miembros: "5 < id" equivale a "id > 5".
+    fn invertida(self) -> Comparacion {
+        match self {
+            Comparacion::Igual => Comparacion::Igual,
+            Comparacion::Mayor => Comparacion::Menor,
+            Comparacion::MayorIgual => Comparacion::MenorIgual,
+            Comparacion::Menor => Comparacion::Mayor,
+            Comparacion::MenorIgual => Comparacion::MayorIgual,
+        }
+    }
+
+    /// Indica si el resultado de comparar el valor de la columna con el literal cumple la comparacion.
+    fn acepta(self, orden: Ordering) -> bool {
+        match self {
+            Comparacion::Igual => orden.is_eq(),
+            Comparacion::Mayor => orden.is_gt(),
+            Comparacion::MayorIgual => orden.is_ge(),
+            Comparacion::Menor => orden.is_lt(),
+            Comparacion::MenorIgual => orden.is_le(),
+        }
+    }
+}
+
+impl Tramo {
+    /// Devuelve las posiciones de las entradas cuya clave cumple la comparacion con el literal, con el mismo criterio que la clausula WHERE.
+    /// En los tramos los numeros estan antes que los textos. Si el literal es un numero, ambos grupos estan ordenados segun el criterio de la clausula y se busca en cada uno por busqueda binaria; si es un texto, los numeros se comparan con el como texto y deben recorrerse.
+    fn buscar(&mut self, comparacion: Comparacion, literal: &str) -> Option<Vec<u64>> {
+        let fin_numeros = self.primera(0, self.cantidad, |clave| !es_numero(clave))?;
+        let mut posiciones: Vec<u64> = Vec::new();
+        if es_numero(literal) {
+            posiciones.extend(self.rango(0, fin_numeros, comparacion, literal)?);
+        } else {
+            for entrada in self.entradas(0, fin_numeros)? {
+                if comparacion.acepta(comparar_texto_o_numero(&entrada.clave, literal)) {
+                    posiciones.push(entrada.posicion);
+                }
+            }
+        }
+        posiciones.extend(self.rango(fin_numeros, self.cantidad, comparacion, literal)?);
+        Some(posiciones)
+    }
+
+    /// Devuelve las posiciones de las entradas entre desde y hasta que cumplen la comparacion, sabiendo que en ese rango las claves estan ordenadas segun el criterio de la clausula WHERE.
+    fn rango(
+        &mut self,
+        desde: u64,
+        hasta: u64,
+        comparacion: Comparacion,
+        literal: &str,
+    ) -> Option<Vec<u64>> {
+        let menores = self.primera(desde, hasta, |clave| {
+            comparar_texto_o_numero(clave, literal).is_ge()
+        })?;
+        let hasta_iguales = self.primera(menores, hasta, |clave| {
+            comparar_texto_o_numero(clave, literal).is_gt()
+        })?;
+        let (inicio, fin) = match comparacion {
+            Comparacion::Igual => (menores, hasta_iguales),
+            Comparacion::Mayor => (hasta_iguales, hasta),
+            Comparacion::MayorIgual => (menores, hasta),
+            Comparacion::Menor => (desde, menores),
+            Comparacion::MenorIgual => (desde, hasta_iguales),
+        };
+        let entradas = self.entradas(inicio, fin)?;
+        Some(entradas.into_iter().map(|e| e.posicion).collect())
+    }
+
+    /// Devuelve, por busqueda binaria, la primera entrada entre desde y hasta cuya clave cumple la condicion, o hasta si ninguna la cumple. La condicion debe ser falsa para las primeras entradas y verdadera para las restantes.
+    fn primera<F>(&mut self, mut desde: u64, mut hasta: u64, condicion: F) -> Option<u64>
+    where
+        F: Fn(&str) -> bool,
+    {
+        while desde < hasta {
+            let medio = desde + (hasta - desde) / 2;
+            let entrada = self.entradas(medio, medio + 1)?.pop()?;
+            if condicion(&entrada.clave) {
+                hasta = medio;
+            } else {
+                desde = medio + 1;
+            }
+        }
+        Some(desde)
+    }
+
+    /// Lee las entradas entre desde y hasta.
+    fn entradas(&mut self, desde: u64, hasta: u64) -> Option<Vec<Entrada>> {
+        let mut entradas: Vec<Entrada> = Vec::new();
+        if desde >= hasta {
+            return Some(entradas);
+        }
+        self.lector
+            .seek(SeekFrom::Start(self.inicio + desde * self.ancho))
+            .ok()?;
+        let mut datos = vec![0; self.ancho as usize];
+        for _ in desde..hasta {
+            self.lector.read_exact(&mut datos).ok()?;
+            let texto = std::str::from_utf8(&datos).ok()?;
+            entradas.push(Entrada {
+                posicion: texto.get(..ANCHO_POSICION)?.parse().ok()?,
+                clave: texto.get(ANCHO_POSICION + 1..)?.trim_end().to_string(),
+            });
+        }
+        Some(entradas)
+    }
+}
+
+/// Compara dos claves segun el orden de los tramos: primero los numeros, en orden numerico, y despues los textos, en orden alfabetico.
+fn comparar_claves(a: &str, b: &str) -> Ordering {
+    match (es_numero(a), es_numero(b)) {
+        (true, true) => comparar_texto_o_numero(a, b),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.cmp(b),
+    }
+}
+
+/// Ordena las entradas por clave. Las claves iguales quedan juntas aunque esten escritas de distinta manera (por ejemplo 3 y 3.0), ordenadas por su texto y su posicion.
+fn ordenar_entradas(entradas: &mut [Entrada]) {
+    entradas.sort_by(|a, b| {
+        comparar_claves(&a.clave, &b.clave)
+            .then_with(|| a.clave.cmp(&b.clave))
+            .then(a.posicion.cmp(&b.posicion))
+    });
+}
+
+/// Verifica que las entradas ordenadas no repitan ninguna clave. Las claves vacias corresponden a valores nulos y pueden repetirse.
+fn verificar_unicidad(indice: &Indice, entradas: &[Entrada]) -> Result<(), Errores> {
+    for par in entradas.windows(2) {
+        if par[0].clave == par[1].clave && !par[0].clave.is_empty() {
+            return Err(Errores::nuevo(
+                TipoError::ConstraintViolation,
+                format!(
+                    "Valor duplicado ({}) para el indice UNIQUE {}",
+                    par[0].clave, indice.nombre
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Lee las filas de la tabla que comienzan a partir de la posicion indicada y devuelve una entrada por cada una, con el valor de la columna. Si la lectura comienza al principio del archivo se saltea el encabezado.
+fn leer_entradas(ruta: &str, columna: usize, desde: u64) -> Result<Vec<Entrada>, Errores> {
+    let mut archivo = match File::open(ruta) {
+        Ok(f) => f,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "No se pudo abrir el archivo".to_string(),
+            ));
+        }
+    };
+    if archivo.seek(SeekFrom::Start(desde)).is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error leyendo la tabla".to_string(),
+        ));
+    }
+    let mut entradas: Vec<Entrada> = Vec::new();
+    let lineas = LineasConPosicion::new(BufReader::new(archivo), desde);
+    for linea in lineas.skip(if desde == 0 { 1 } else { 0 }) {
+        let (posicion, linea) = linea?;
+        if linea.is_empty() {
+            continue;
+        }
+        if let Some(valor) = linea.split(',').nth(columna) {
+            entradas.push(Entrada {
+                clave: valor.trim().to_string(),
+                posicion,
+            });
+        }
+    }
+    Ok(entradas)
+}
+
+fn estampa_de_tabla(ruta: &str) -> Result<Estampa, Errores> {
+    match Estampa::de(ruta) {
+        Some(estampa) => Ok(estampa),
+        None => Err(Errores::nuevo(
+            TipoError::Error,
+            "Error leyendo la tabla".to_string(),
+        )),
+    }
+}
+
+fn error_de_indice() -> Errores {
+    Errores::nuevo(TipoError::Error, "Error leyendo el indice".to_string())
+}
+
+/// Devuelve el nombre de un tramo nuevo para el indice, distinto de los tramos que enumera su manifiesto.
+fn nombre_tramo(indice: &Indice, manifiesto: Option<&Manifiesto>) -> String {
+    let siguiente = manifiesto
+        .iter()
+        .flat_map(|m| m.tramos.iter())
+        .filter_map(|t| t.rsplit('.').next()?.parse::<u64>().ok())
+        .max()
+        .map_or(0, |ultimo| ultimo + 1);
+    format!("{}.indice.{}", indice.nombre, siguiente)
+}
+
+fn abrir_tramo(path: &String, nombre: &str) -> Option<Tramo> {
+    let mut lector = BufReader::new(File::open(format!("{}/{}", path, nombre)).ok()?);
+    let mut encabezado = String::new();
+    lector.read_line(&mut encabezado).ok()?;
+    let mut partes = encabezado.split_whitespace();
+    let ancho: u64 = partes.next()?.parse().ok()?;
+    let cantidad: u64 = partes.next()?.parse().ok()?;
+    if ancho <= ANCHO_POSICION as u64 {
+        return None;
+    }
+    Some(Tramo {
+        lector,
+        inicio: encabezado.len() as u64,
+        ancho,
+        cantidad,
+    })
+}
+
+/// Escribe un tramo con las entradas ya ordenadas. Cada entrada ocupa una linea con la posicion completada con ceros y la clave completada con espacios hasta el largo de la clave mas larga.
+fn escribir_tramo(path: &String, nombre: &str, entradas: &[Entrada]) -> Result<(), Errores> {
+    let largo = entradas.iter().map(|e| e.clave.len()).max().unwrap_or(0);
+    let ancho = ANCHO_POSICION + 1 + largo + 1;
+    let mut contenido = format!("{} {}\n", ancho, entradas.len());
+    for entrada in entradas {
+        contenido.push_str(&format!(
+            "{:0ancho$} {}{}\n",
+            entrada.posicion,
+            entrada.clave,
+            " ".repeat(largo - entrada.clave.len()),
+            ancho = ANCHO_POSICION
+        ));
+    }
+    if escribir_archivo(&format!("{}/{}", path, nombre), &contenido).is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error guardando el indice".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn leer_manifiesto(path: &String, nombre: &str) -> Option<Manifiesto> {
+    let contenido = fs::read_to_string(crear_ruta_indice(path, nombre)).ok()?;
+    let mut lineas = contenido.lines();
+    let estampa = Estampa::desde_texto(lineas.next()?)?;
+    let tramos: Vec<String> = lineas
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect();
+    Some(Manifiesto { estampa, tramos })
+}
+
+fn escribir_manifiesto(
+    path: &String,
+    nombre: &str,
+    manifiesto: &Manifiesto,
+) -> Result<(), Errores> {
+    let mut contenido = format!("{}\n", manifiesto.estampa);
+    for tramo in &manifiesto.tramos {
+        contenido.push_str(tramo);
+        contenido.push('\n');
+    }
+    if escribir_archivo(&crear_ruta_indice(path, nombre), &contenido).is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error guardando el indice".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Elimina los tramos del indice que no estan entre los indicados, incluidos los que quedaron de una actualizacion interrumpida.
+fn descartar_tramos(path: &String, indice: &Indice, conservar: &[String]) {
+    let prefijo = format!("{}.indice.", indice.nombre);
+    let entradas = match fs::read_dir(path) {
+        Ok(entradas) => entradas,
+        Err(_) => return,
+    };
+    for entrada in entradas.filter_map(|e| e.ok()) {
+        let nombre = entrada.file_name().to_string_lossy().to_string();
+        if nombre.starts_with(&prefijo) && !conservar.contains(&nombre) {
+            let _ = fs::remove_file(entrada.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        abrir_tramo, escribir_tramo, ordenar_entradas, Comparacion, Entrada, Indice,
+        LineasConPosicion,
+    };
+    use crate::analizador::analizador_implementacion::comparar_texto_o_numero;
+    use crate::ejecutor::ejecutor_implementacion::test::crear_directorio_prueba;
+
+    #[test]
+    fn test_lineas_con_posicion() {
+        let texto = "id,nombre\n1,Juan\r\n2,Ana";
+        let lineas: Vec<(u64, String)> = LineasConPosicion::new(texto.as_bytes(), 0)
+            .map(|l| l.unwrap())
+            .collect();
+        assert_eq!(
+            lineas,
+            vec![
+                (0, "id,nombre".to_string()),
+                (10, "1,Juan".to_string()),
+                (18, "2,Ana".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_busqueda_en_tramo_coincide_con_el_where() {
+        let path = crear_directorio_prueba("tramo_indice", &[]);
+        let claves = [
+            "10", "9", "-2", "3.0", "3", "", "abc", "Zeta", "1a", "100", "b", "3", "2.5",
+        ];
+        let mut entradas: Vec<Entrada> = claves
+            .iter()
+            .enumerate()
+            .map(|(posicion, clave)| Entrada {
+                clave: clave.to_string(),
+                posicion: posicion as u64,
+            })
+            .collect();
+        ordenar_entradas(&mut entradas);
+        escribir_tramo(&path, "prueba.indice.0", &entradas).unwrap();
+        let mut tramo = abrir_tramo(&path, "prueba.indice.0").unwrap();
+        assert_eq!(tramo.cantidad, claves.len() as u64);
+        for comparacion in [
+            Comparacion::Igual,
+            Comparacion::Mayor,
+            Comparacion::MayorIgual,
+            Comparacion::Menor,
+            Comparacion::MenorIgual,
+        ] {
+            for literal in ["3", "9", "-5", "1000", "", "b", "1", "Zeta", "2.5"] {
+                let mut encontradas = tramo.buscar(comparacion, literal).unwrap();
+                encontradas.sort_unstable();
+                let esperadas: Vec<u64> = claves
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, clave)| {
+                        comparacion.acepta(comparar_texto_o_numero(clave, literal))
+                    })
+                    .map(|(posicion, _)| posicion as u64)
+                    .collect();
+                assert_eq!(encontradas, esperadas, "{:?} {}", comparacion, literal);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sentencia_create_index() {
+        let indice = Indice {
+            nombre: "clientes_email".to_string(),
+            columna: "email".to_string(),
+            unico: true,
+        };
+        assert_eq!(
+            indice.sentencia_create("clientes"),
+            "CREATE UNIQUE INDEX clientes_email ON clientes (email);"
+        );
+        assert_eq!(indice.valor(), "email UNIQUE");
+    }
+}
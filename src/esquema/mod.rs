@@ -0,0 +1,7 @@
+pub mod esquema_implementacion;
+pub mod estadisticas;
+pub mod indices;
+pub mod restricciones;
+pub mod secuencias;
+pub mod valores_por_defecto;
+pub mod vistas;
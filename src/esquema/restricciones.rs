@@ -0,0 +1,178 @@
+use crate::analizador::analizador_implementacion::{columnas_de_expresion, cumple_c_w};
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{Esquema, Restriccion};
+use crate::esquema::indices::indices_de_tabla;
+use std::collections::{HashMap, HashSet};
+
+/// Indica si un valor de la tabla debe ser considerado nulo. En los archivos .csv un valor nulo es un campo vacio.
+pub fn es_nulo(valor: &str) -> bool {
+    valor.trim().is_empty()
+}
+
+/// Verifica que la fila no tenga valores nulos en las columnas declaradas como NOT NULL o PRIMARY KEY.
+pub fn validar_not_null(
+    esquema: &Esquema,
+    indice_columnas: &HashMap<String, usize>,
+    fila: &[String],
+) -> Result<(), Errores> {
+    for columna in esquema.columnas_not_null() {
+        let valor = match indice_columnas.get(&columna) {
+            Some(&indice) => fila.get(indice).map(|v| v.as_str()).unwrap_or(""),
+            None => "",
+        };
+        if es_nulo(valor) {
+            return Err(Errores::nuevo(
+                TipoError::ConstraintViolation,
+                format!("La columna {} no admite valores nulos", columna),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Verifica que la fila cumpla las condiciones CHECK del esquema.
+/// Al igual que en SQL, una condicion que involucra columnas nulas no se considera violada.
+pub fn validar_checks(
+    esquema: &Esquema,
+    indice_columnas: &HashMap<String, usize>,
+    fila: &[String],
+) -> Result<(), Errores> {
+    for restriccion in &esquema.restricciones {
+        if let Restriccion::Check { texto, expresion } = restriccion {
+            if cumple_c_w(&fila.join(","), expresion, indice_columnas)? {
+                continue;
+            }
+            let involucra_nulos =
+                columnas_de_expresion(expresion)
+                    .iter()
+                    .any(|c| match indice_columnas.get(c) {
+                        Some(&indice) => {
+                            es_nulo(fila.get(indice).map(|v| v.as_str()).unwrap_or(""))
+                        }
+                        None => false,
+                    });
+            if !involucra_nulos {
+                return Err(Errores::nuevo(
+                    TipoError::ConstraintViolation,
+                    format!(
+                        "La fila ({}) no cumple la restriccion CHECK ({})",
+                        fila.join(","),
+                        texto
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Verifica que la fila cumpla todas las restricciones que pueden comprobarse sin mirar el resto de la tabla: NOT NULL y CHECK.
+pub fn validar_fila(
+    esquema: &Esquema,
+    indice_columnas: &HashMap<String, usize>,
+    fila: &[String],
+) -> Result<(), Errores> {
+    validar_not_null(esquema, indice_columnas, fila)?;
+    validar_checks(esquema, indice_columnas, fila)
+}
+
+/// Representa una restriccion PRIMARY KEY o UNIQUE junto con los valores que ya fueron vistos para ella.
+struct ClaveUnica {
+    descripcion: String,
+    indices: Vec<usize>,
+    admite_nulos: bool,
+    vistas: HashSet<Vec<String>>,
+}
+
+/// Lleva registro de los valores presentes en la tabla para cada restriccion PRIMARY KEY o UNIQUE, de manera de poder detectar duplicados.
+pub struct ValidadorUnicidad {
+    claves: Vec<ClaveUnica>,
+}
+
+impl ValidadorUnicidad {
+    /// Crea un validador con una clave por cada restriccion de unicidad del esquema y por cada indice UNIQUE de la tabla.
+    pub fn new(esquema: &Esquema, indice_columnas: &HashMap<String, usize>) -> ValidadorUnicidad {
+        let mut claves: Vec<ClaveUnica> = Vec::new();
+        for restriccion in &esquema.restricciones {
+            let (columnas, admite_nulos) = match restriccion {
+                Restriccion::PrimaryKey { columnas } => (columnas, false),
+                Restriccion::Unique { columnas } => (columnas, true),
+                _ => continue,
+            };
+            let indices: Vec<usize> = columnas
+                .iter()
+                .filter_map(|c| indice_columnas.get(c).copied())
+                .collect();
+            claves.push(ClaveUnica {
+                descripcion: restriccion.descripcion(),
+                indices,
+                admite_nulos,
+                vistas: HashSet::new(),
+            });
+        }
+        for indice in indices_de_tabla(esquema).into_iter().filter(|i| i.unico) {
+            let columna = match indice_columnas.get(&indice.columna) {
+                Some(&columna) => columna,
+                None => continue,
+            };
+            claves.push(ClaveUnica {
+                descripcion: format!("UNIQUE INDEX {}", indice.nombre),
+                indices: vec![columna],
+                admite_nulos: true,
+                vistas: HashSet::new(),
+            });
+        }
+        ValidadorUnicidad { claves }
+    }
+
+    /// Indica si no hay restricciones de unicidad que verificar, en cuyo caso no hace falta recorrer la tabla.
+    pub fn esta_vacio(&self) -> bool {
+        self.claves.is_empty()
+    }
+
+    /// Registra una fila que ya se encuentra en la tabla sin verificarla, para que las filas nuevas no puedan repetir sus valores.
+    pub fn registrar_existente(&mut self, fila: &[String]) {
+        for clave in self.claves.iter_mut() {
+            if let Some(valores) = obtener_clave(clave, fila) {
+                clave.vistas.insert(valores);
+            }
+        }
+    }
+
+    /// Registra una fila nueva, devolviendo error si alguna de sus claves ya fue registrada.
+    pub fn registrar_nueva(&mut self, fila: &[String]) -> Result<(), Errores> {
+        for clave in self.claves.iter_mut() {
+            if let Some(valores) = obtener_clave(clave, fila) {
+                if clave.vistas.contains(&valores) {
+                    return Err(Errores::nuevo(
+                        TipoError::ConstraintViolation,
+                        format!(
+                            "Valor duplicado ({}) para la restriccion {}",
+                            valores.join(", "),
+                            clave.descripcion
+                        ),
+                    ));
+                }
+                clave.vistas.insert(valores);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Devuelve los valores de la fila que forman la clave, o None si la clave admite nulos y alguno de ellos es nulo.
+fn obtener_clave(clave: &ClaveUnica, fila: &[String]) -> Option<Vec<String>> {
+    let valores: Vec<String> = clave
+        .indices
+        .iter()
+        .map(|&i| {
+            fila.get(i)
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        })
+        .collect();
+    if clave.admite_nulos && valores.iter().any(|v| es_nulo(v)) {
+        return None;
+    }
+    Some(valores)
+}
@@ -0,0 +1,241 @@
+use crate::ejecutor::bitacora::escribir_archivo;
+use crate::ejecutor::bloqueos::{bloquear, tiempo_de_espera, ModoBloqueo};
+use crate::ejecutor::transaccion::ruta_tabla;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{Esquema, Identidad};
+use crate::esquema::restricciones::es_nulo;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Representa una secuencia creada con CREATE SEQUENCE. Se guarda en un archivo <nombre>.secuencia con una linea por atributo ("inicio N", "incremento N" y "ultimo N").
+/// Mientras no se pida ningun valor la secuencia no tiene ultimo valor.
+#[derive(Debug, PartialEq)]
+pub struct Secuencia {
+    pub inicio: i64,
+    pub incremento: i64,
+    pub ultimo: Option<i64>,
+}
+
+impl Secuencia {
+    fn serializar(&self) -> String {
+        let mut contenido = format!("inicio {}\nincremento {}\n", self.inicio, self.incremento);
+        if let Some(ultimo) = self.ultimo {
+            contenido.push_str(&format!("ultimo {}\n", ultimo));
+        }
+        contenido
+    }
+
+    fn deserializar(contenido: &str) -> Option<Secuencia> {
+        let mut secuencia = Secuencia {
+            inicio: 1,
+            incremento: 1,
+            ultimo: None,
+        };
+        for linea in contenido.lines().filter(|l| !l.trim().is_empty()) {
+            let (clave, valor) = linea.trim().split_once(' ')?;
+            let valor: i64 = valor.trim().parse().ok()?;
+            match clave {
+                "inicio" => secuencia.inicio = valor,
+                "incremento" => secuencia.incremento = valor,
+                "ultimo" => secuencia.ultimo = Some(valor),
+                _ => return None,
+            }
+        }
+        Some(secuencia)
+    }
+}
+
+/// Esta funcion devuelve la ruta al archivo de la secuencia indicada.
+pub fn crear_ruta_secuencia(path: &String, nombre: &str) -> String {
+    format!("{}/{}.secuencia", path, nombre)
+}
+
+/// Esta funcion crea el archivo de una secuencia nueva. Devuelve error si ya existe una secuencia con ese nombre.
+pub fn crear_secuencia(
+    path: &String,
+    nombre: &str,
+    inicio: i64,
+    incremento: i64,
+) -> Result<(), Errores> {
+    if incremento == 0 {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "El incremento de una secuencia no puede ser 0".to_string(),
+        ));
+    }
+    let ruta = crear_ruta_secuencia(path, nombre);
+    if Path::new(&ruta).exists() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            format!("La secuencia {} ya existe", nombre),
+        ));
+    }
+    let secuencia = Secuencia {
+        inicio,
+        incremento,
+        ultimo: None,
+    };
+    escribir_secuencia(&ruta, &secuencia)
+}
+
+/// Esta funcion avanza la secuencia indicada y devuelve el nuevo valor, que queda guardado antes de ser devuelto para que no se repita.
+/// Mientras se avanza, la secuencia queda bloqueada para que dos procesos no obtengan el mismo valor.
+pub fn siguiente_valor(path: &String, nombre: &str) -> Result<String, Errores> {
+    let _bloqueo = bloquear(
+        path,
+        &format!("{}.secuencia", nombre),
+        ModoBloqueo::Exclusivo,
+        tiempo_de_espera(),
+    )?;
+    let ruta = crear_ruta_secuencia(path, nombre);
+    let mut secuencia = match fs::read_to_string(&ruta) {
+        Ok(contenido) => match Secuencia::deserializar(&contenido) {
+            Some(secuencia) => secuencia,
+            None => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    format!("El archivo de la secuencia {} es invalido", nombre),
+                ));
+            }
+        },
+        Err(_) => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                format!("La secuencia {} no existe", nombre),
+            )
+            .en_token(nombre));
+        }
+    };
+    let valor = match secuencia.ultimo {
+        Some(ultimo) => ultimo + secuencia.incremento,
+        None => secuencia.inicio,
+    };
+    secuencia.ultimo = Some(valor);
+    escribir_secuencia(&ruta, &secuencia)?;
+    Ok(valor.to_string())
+}
+
+/// Escribe la secuencia en un archivo auxiliar que luego reemplaza al original.
+fn escribir_secuencia(ruta: &str, secuencia: &Secuencia) -> Result<(), Errores> {
+    if escribir_archivo(ruta, &secuencia.serializar()).is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error guardando la secuencia".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Devuelve la clave de los metadatos de la tabla en la que se guarda el ultimo valor asignado a una columna autoincremental.
+pub fn clave_identidad(columna: &str) -> String {
+    format!("identidad.{}", columna)
+}
+
+/// Esta funcion asigna el siguiente valor a las columnas autoincrementales que no fueron indicadas en el INSERT.
+/// El ultimo valor asignado se actualiza en los metadatos del esquema, que deben guardarse antes de escribir las filas. Si la tabla todavia no tiene ese metadato, se continua a partir del mayor valor presente en la tabla.
+/// Devuelve true si los metadatos fueron modificados.
+pub fn asignar_identidades(
+    path: &String,
+    tabla: &str,
+    esquema: &mut Esquema,
+    columnas: &[String],
+    indicadas: &[String],
+    lineas: &mut [Vec<String>],
+) -> Result<bool, Errores> {
+    let mut modificado = false;
+    for (indice, nombre) in columnas.iter().enumerate() {
+        let identidad = match esquema.columna(nombre).and_then(|c| c.identidad) {
+            Some(identidad) => identidad,
+            None => continue,
+        };
+        let clave = clave_identidad(nombre);
+        let mut ultimo: i64 = match esquema.metadatos.get(&clave) {
+            Some(valor) => match valor.parse() {
+                Ok(valor) => valor,
+                Err(_) => {
+                    return Err(Errores::nuevo(
+                        TipoError::Error,
+                        format!("El contador de la columna {} es invalido", nombre),
+                    ));
+                }
+            },
+            None => maximo_de_columna(&ruta_tabla(path, tabla), indice)?,
+        };
+        let indicada = indicadas.contains(nombre);
+        for linea in lineas.iter_mut() {
+            if !indicada || es_nulo(&linea[indice]) {
+                ultimo += 1;
+                linea[indice] = ultimo.to_string();
+                continue;
+            }
+            if identidad == Identidad::Siempre {
+                return Err(Errores::nuevo(
+                    TipoError::ConstraintViolation,
+                    format!(
+                        "La columna {} es GENERATED ALWAYS AS IDENTITY y no admite valores",
+                        nombre
+                    ),
+                ));
+            }
+            if let Ok(valor) = linea[indice].trim().parse::<i64>() {
+                ultimo = ultimo.max(valor);
+            }
+        }
+        esquema.metadatos.insert(clave, ultimo.to_string());
+        modificado = true;
+    }
+    Ok(modificado)
+}
+
+/// Devuelve el mayor valor numerico de la columna en la tabla, o 0 si no hay ninguno.
+fn maximo_de_columna(ruta_tabla: &str, indice: usize) -> Result<i64, Errores> {
+    let archivo = match File::open(ruta_tabla) {
+        Ok(f) => f,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "Error leyendo el archivo".to_string(),
+            ));
+        }
+    };
+    let mut maximo: i64 = 0;
+    for linea in BufReader::new(archivo).lines().skip(1) {
+        let linea = match linea {
+            Ok(l) => l,
+            _ => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "Error leyendo el archivo".to_string(),
+                ));
+            }
+        };
+        if let Some(Ok(valor)) = linea
+            .split(',')
+            .nth(indice)
+            .map(|v| v.trim().parse::<i64>())
+        {
+            maximo = maximo.max(valor);
+        }
+    }
+    Ok(maximo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Secuencia;
+
+    #[test]
+    fn test_secuencia_serializar_y_deserializar() {
+        let secuencia = Secuencia {
+            inicio: 10,
+            incremento: 5,
+            ultimo: Some(20),
+        };
+        assert_eq!(
+            Secuencia::deserializar(&secuencia.serializar()),
+            Some(secuencia)
+        );
+        assert_eq!(Secuencia::deserializar("inicio x"), None);
+    }
+}
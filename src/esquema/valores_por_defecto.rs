@@ -0,0 +1,105 @@
+use crate::errores::errores_implementacion::Errores;
+use crate::esquema::esquema_implementacion::{Esquema, ValorPorDefecto};
+use crate::esquema::secuencias::siguiente_valor;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SEGUNDOS_POR_DIA: u64 = 86400;
+
+/// Devuelve el valor que toma una columna con el valor por defecto indicado. Las fechas y horas se calculan en UTC.
+/// Si el valor por defecto es NEXTVAL, la secuencia se avanza en el directorio indicado.
+pub fn evaluar_por_defecto(path: &String, valor: &ValorPorDefecto) -> Result<String, Errores> {
+    let resultado = match valor {
+        ValorPorDefecto::Literal(literal) => literal
+            .trim_start_matches('\'')
+            .trim_end_matches('\'')
+            .to_string(),
+        ValorPorDefecto::FechaActual => formatear_fecha(segundos_actuales()),
+        ValorPorDefecto::HoraActual => formatear_hora(segundos_actuales()),
+        ValorPorDefecto::FechaHoraActual => fecha_hora_actual(),
+        ValorPorDefecto::Secuencia(secuencia) => siguiente_valor(path, secuencia)?,
+    };
+    Ok(resultado)
+}
+
+/// Completa las columnas de la fila que no fueron indicadas en el INSERT con su valor por defecto, si lo tienen.
+pub fn completar_por_defecto(
+    path: &String,
+    esquema: &Esquema,
+    columnas: &[String],
+    indicadas: &[String],
+    fila: &mut [String],
+) -> Result<(), Errores> {
+    for (indice, nombre) in columnas.iter().enumerate() {
+        if indicadas.contains(nombre) {
+            continue;
+        }
+        if let Some(columna) = esquema.columna(nombre) {
+            if let Some(por_defecto) = &columna.por_defecto {
+                fila[indice] = evaluar_por_defecto(path, por_defecto)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Devuelve la fecha y hora actual en UTC, con formato AAAA-MM-DD HH:MM:SS.
+pub fn fecha_hora_actual() -> String {
+    let segundos = segundos_actuales();
+    format!("{} {}", formatear_fecha(segundos), formatear_hora(segundos))
+}
+
+fn segundos_actuales() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duracion) => duracion.as_secs(),
+        Err(_) => 0,
+    }
+}
+
+/// Convierte los segundos desde 1970-01-01 en una fecha con formato AAAA-MM-DD.
+fn formatear_fecha(segundos: u64) -> String {
+    let (anio, mes, dia) = fecha_desde_dias((segundos / SEGUNDOS_POR_DIA) as i64);
+    format!("{:04}-{:02}-{:02}", anio, mes, dia)
+}
+
+/// Convierte los segundos desde 1970-01-01 en una hora con formato HH:MM:SS.
+fn formatear_hora(segundos: u64) -> String {
+    let del_dia = segundos % SEGUNDOS_POR_DIA;
+    format!(
+        "{:02}:{:02}:{:02}",
+        del_dia / 3600,
+        (del_dia % 3600) / 60,
+        del_dia % 60
+    )
+}
+
+/// Calcula el año, mes y dia correspondientes a una cantidad de dias desde 1970-01-01 en el calendario gregoriano.
+fn fecha_desde_dias(dias: i64) -> (i64, i64, i64) {
+    let z = dias + 719468;
+    let era = z.div_euclid(146097);
+    let dia_de_era = z.rem_euclid(146097);
+    let anio_de_era =
+        (dia_de_era - dia_de_era / 1460 + dia_de_era / 36524 - dia_de_era / 146096) / 365;
+    let dia_del_anio = dia_de_era - (365 * anio_de_era + anio_de_era / 4 - anio_de_era / 100);
+    let mes_desde_marzo = (5 * dia_del_anio + 2) / 153;
+    let dia = dia_del_anio - (153 * mes_desde_marzo + 2) / 5 + 1;
+    let mes = if mes_desde_marzo < 10 {
+        mes_desde_marzo + 3
+    } else {
+        mes_desde_marzo - 9
+    };
+    let anio = anio_de_era + era * 400 + if mes <= 2 { 1 } else { 0 };
+    (anio, mes, dia)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{formatear_fecha, formatear_hora};
+
+    #[test]
+    fn test_formatear_fecha_y_hora() {
+        assert_eq!(formatear_fecha(0), "1970-01-01");
+        assert_eq!(formatear_fecha(951782400), "2000-02-29");
+        assert_eq!(formatear_fecha(1791590400), "2026-10-10");
+        assert_eq!(formatear_hora(1791590400 + 3661), "01:01:01");
+    }
+}
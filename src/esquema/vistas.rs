@@ -0,0 +1,158 @@
+use crate::comandos::comandos_implementacion::{parsear, Comandos};
+use crate::ejecutor::bitacora::escribir_archivo;
+use crate::ejecutor::ejecutor_implementacion::obtener_primera_linea;
+use crate::ejecutor::ejecutor_select::tablas_de_consulta;
+use crate::ejecutor::transaccion::ruta_tabla;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::{cargar_esquema, Esquema};
+use std::fs;
+use std::path::Path;
+
+/// Esta funcion devuelve la ruta al archivo de la vista indicada. Cada vista creada con CREATE VIEW se guarda en un archivo <nombre>.vista con el texto del SELECT que la define.
+pub fn crear_ruta_vista(path: &String, nombre: &str) -> String {
+    format!("{}/{}.vista", path, nombre)
+}
+
+/// Esta funcion devuelve el texto del SELECT que define la vista, o None si no existe una vista con ese nombre.
+pub fn definicion_de_vista(path: &String, nombre: &str) -> Option<String> {
+    fs::read_to_string(crear_ruta_vista(path, nombre))
+        .ok()
+        .map(|definicion| definicion.trim().to_string())
+}
+
+/// Esta funcion devuelve el SELECT que define la vista, ya parseado, o None si no existe una vista con ese nombre.
+pub fn leer_vista(path: &String, nombre: &str) -> Result<Option<Comandos>, Errores> {
+    let definicion = match definicion_de_vista(path, nombre) {
+        Some(definicion) => definicion,
+        None => return Ok(None),
+    };
+    match parsear(&definicion) {
+        Ok(consulta @ Comandos::Select { .. }) => Ok(Some(consulta)),
+        _ => Err(Errores::nuevo(
+            TipoError::Error,
+            format!("El archivo de la vista {} es invalido", nombre),
+        )
+        .en_token(nombre)),
+    }
+}
+
+/// Esta funcion guarda la definicion de la vista, reemplazando la anterior si existia.
+pub fn guardar_vista(path: &String, nombre: &str, definicion: &str) -> Result<(), Errores> {
+    if escribir_archivo(
+        &crear_ruta_vista(path, nombre),
+        &format!("{}\n", definicion),
+    )
+    .is_err()
+    {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error guardando la vista".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Esta funcion elimina el archivo de la vista.
+pub fn eliminar_vista(path: &String, nombre: &str) -> Result<(), Errores> {
+    if fs::remove_file(crear_ruta_vista(path, nombre)).is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error eliminando la vista".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Esta funcion devuelve, ordenados, los nombres de las vistas del directorio.
+pub fn listar_vistas(path: &String) -> Result<Vec<String>, Errores> {
+    let entradas = match fs::read_dir(path) {
+        Ok(entradas) => entradas,
+        Err(_) => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "No se pudo leer el directorio de las tablas".to_string(),
+            ));
+        }
+    };
+    let mut vistas: Vec<String> = entradas
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let nombre = e.file_name().to_string_lossy().to_string();
+            nombre.strip_suffix(".vista").map(|v| v.to_string())
+        })
+        .collect();
+    vistas.sort();
+    Ok(vistas)
+}
+
+/// Esta funcion reemplaza cada vista de la lista por las tablas que lee su SELECT, y estas a su vez si tambien son vistas, hasta llegar a las tablas del directorio. Las tablas que no son vistas se conservan.
+/// Cada vista se expande una unica vez, por lo que una definicion que se referencia a si misma no hace que la expansion sea infinita.
+pub fn expandir_vistas(path: &String, tablas: Vec<String>) -> Result<Vec<String>, Errores> {
+    let mut pendientes = tablas;
+    let mut expandidas: Vec<String> = Vec::new();
+    let mut resultado: Vec<String> = Vec::new();
+    while let Some(tabla) = pendientes.pop() {
+        if expandidas.contains(&tabla) || resultado.contains(&tabla) {
+            continue;
+        }
+        match leer_vista(path, &tabla)? {
+            Some(consulta) => {
+                pendientes.extend(tablas_de_consulta(&consulta));
+                expandidas.push(tabla);
+            }
+            None => resultado.push(tabla),
+        }
+    }
+    resultado.sort();
+    Ok(resultado)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa una vista materializada: una tabla cuyas filas son el resultado de un SELECT, calculado al crearla y cada vez que se actualiza con REFRESH MATERIALIZED VIEW.
+/// Se guarda en los metadatos del esquema de la tabla, en las claves "vista_materializada", con el texto del SELECT, y "actualizada", con la fecha y hora en UTC en la que se calcularon sus filas por ultima vez.
+pub struct VistaMaterializada {
+    pub definicion: String,
+    pub actualizada: String,
+}
+
+impl VistaMaterializada {
+    /// Lee la vista materializada de los metadatos del esquema. Devuelve None si la tabla no es una vista materializada.
+    pub fn de_esquema(esquema: &Esquema) -> Option<VistaMaterializada> {
+        Some(VistaMaterializada {
+            definicion: esquema.metadatos.get(CLAVE_DEFINICION)?.to_string(),
+            actualizada: esquema
+                .metadatos
+                .get(CLAVE_ACTUALIZACION)
+                .cloned()
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Guarda la vista materializada en los metadatos del esquema, reemplazando la anterior.
+    pub fn guardar_en(&self, esquema: &mut Esquema) {
+        esquema
+            .metadatos
+            .insert(CLAVE_DEFINICION.to_string(), self.definicion.to_string());
+        esquema.metadatos.insert(
+            CLAVE_ACTUALIZACION.to_string(),
+            self.actualizada.to_string(),
+        );
+    }
+}
+
+const CLAVE_DEFINICION: &str = "vista_materializada";
+const CLAVE_ACTUALIZACION: &str = "actualizada";
+
+/// Esta funcion devuelve la vista materializada con el nombre indicado, o None si no existe una tabla con ese nombre o la tabla no es una vista materializada.
+pub fn leer_vista_materializada(
+    path: &String,
+    tabla: &str,
+) -> Result<Option<VistaMaterializada>, Errores> {
+    let ruta = ruta_tabla(path, tabla);
+    if !Path::new(&ruta).exists() {
+        return Ok(None);
+    }
+    let encabezado = obtener_primera_linea(&ruta)?;
+    let esquema = cargar_esquema(path, &tabla.to_string(), &encabezado)?;
+    Ok(VistaMaterializada::de_esquema(&esquema))
+}
@@ -0,0 +1,496 @@
+use crate::analizador::analizador_implementacion::es_numero;
+use crate::ejecutor::resultado::Resultado;
+use std::sync::Mutex;
+
+/// Representa los formatos en los que pueden mostrarse los resultados de una consulta.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Formato {
+    /// Tabla con las columnas alineadas y bordes.
+    Tabla,
+    /// Valores separados por comas. Los valores que contienen comas, comillas o saltos de linea se encierran entre comillas dobles.
+    Csv,
+    /// Valores separados por tabulaciones.
+    Tsv,
+    /// Valores separados por '|', sin alinear.
+    Lista,
+    /// Un arreglo JSON con un objeto por fila.
+    Json,
+    /// Un objeto JSON por linea.
+    JsonLines,
+    /// Tabla de Markdown.
+    Markdown,
+    /// Tabla HTML.
+    Html,
+}
+
+impl Formato {
+    /// Esta funcion devuelve el formato correspondiente al nombre indicado, sin distinguir mayusculas de minusculas.
+    pub fn desde_nombre(nombre: &str) -> Option<Formato> {
+        match nombre.to_lowercase().as_str() {
+            "table" => Some(Formato::Tabla),
+            "csv" => Some(Formato::Csv),
+            "tsv" => Some(Formato::Tsv),
+            "list" => Some(Formato::Lista),
+            "json" => Some(Formato::Json),
+            "jsonl" => Some(Formato::JsonLines),
+            "markdown" | "md" => Some(Formato::Markdown),
+            "html" => Some(Formato::Html),
+            _ => None,
+        }
+    }
+}
+
+/// Representa las opciones con las que se muestran los resultados.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OpcionesFormato {
+    pub formato: Formato,
+    /// Indica si se muestran los nombres de las columnas.
+    pub encabezado: bool,
+    /// Indica si se informa, en la salida de errores, la cantidad de filas afectadas por las sentencias que no son consultas.
+    pub filas_afectadas: bool,
+}
+
+impl Default for OpcionesFormato {
+    fn default() -> OpcionesFormato {
+        OPCIONES_POR_DEFECTO
+    }
+}
+
+const OPCIONES_POR_DEFECTO: OpcionesFormato = OpcionesFormato {
+    formato: Formato::Csv,
+    encabezado: true,
+    filas_afectadas: false,
+};
+
+/// Opciones con las que se muestran los resultados de los SELECT. Se configuran con --format, --no-header y --changes, o desde la consola con .mode, .headers y .changes.
+static OPCIONES: Mutex<OpcionesFormato> = Mutex::new(OPCIONES_POR_DEFECTO);
+
+/// Esta funcion cambia las opciones con las que se muestran los resultados de las consultas.
+pub fn configurar_formato(opciones: OpcionesFormato) {
+    if let Ok(mut actuales) = OPCIONES.lock() {
+        *actuales = opciones;
+    }
+}
+
+/// Esta funcion devuelve las opciones con las que se muestran los resultados de las consultas.
+pub fn opciones_formato() -> OpcionesFormato {
+    match OPCIONES.lock() {
+        Ok(opciones) => *opciones,
+        Err(_) => OPCIONES_POR_DEFECTO,
+    }
+}
+
+/// Esta funcion devuelve el texto con el que se muestran las filas indicadas, terminado en un salto de linea.
+pub fn formatear(columnas: &[String], filas: &[Vec<String>], opciones: &OpcionesFormato) -> String {
+    let encabezado = opciones.encabezado;
+    match opciones.formato {
+        Formato::Tabla => formatear_tabla(columnas, filas, encabezado),
+        Formato::Csv => formatear_separado(columnas, filas, encabezado, ",", escapar_csv),
+        Formato::Tsv => formatear_separado(columnas, filas, encabezado, "\t", escapar_tsv),
+        Formato::Lista => formatear_separado(columnas, filas, encabezado, "|", |v| v.to_string()),
+        Formato::Json => formatear_json(columnas, filas, encabezado),
+        Formato::JsonLines => formatear_json_lines(columnas, filas, encabezado),
+        Formato::Markdown => formatear_markdown(columnas, filas, encabezado),
+        Formato::Html => formatear_html(columnas, filas, encabezado),
+    }
+}
+
+/// Esta funcion muestra por pantalla, con las opciones configuradas, las filas del resultado si corresponde a una consulta.
+/// Si no es una consulta y se configuro, informa la cantidad de filas afectadas en la salida de errores, para no mezclarla con los resultados.
+pub fn imprimir_resultado(resultado: &Resultado) {
+    let opciones = opciones_formato();
+    if resultado.es_consulta() {
+        print!(
+            "{}",
+            formatear(
+                &resultado.columnas,
+                &resultado.filas_como_texto(),
+                &opciones
+            )
+        );
+    } else if opciones.filas_afectadas {
+        eprintln!("{}", descripcion_filas_afectadas(resultado.filas_afectadas));
+    }
+}
+
+/// Devuelve el texto con el que se informa la cantidad de filas afectadas.
+pub fn descripcion_filas_afectadas(cantidad: usize) -> String {
+    match cantidad {
+        1 => "1 fila afectada".to_string(),
+        cantidad => format!("{} filas afectadas", cantidad),
+    }
+}
+
+/// Esta funcion devuelve la cantidad de columnas que ocupa el texto en una terminal.
+/// Los caracteres combinantes y de ancho cero no ocupan lugar, y los caracteres de escritura asiatica y los emoji ocupan dos columnas.
+pub fn ancho_visible(texto: &str) -> usize {
+    texto.chars().map(ancho_caracter).sum()
+}
+
+fn ancho_caracter(caracter: char) -> usize {
+    let codigo = caracter as u32;
+    if caracter.is_control() || es_de_ancho_cero(codigo) {
+        0
+    } else if es_ancho(codigo) {
+        2
+    } else {
+        1
+    }
+}
+
+fn es_de_ancho_cero(codigo: u32) -> bool {
+    matches!(
+        codigo,
+        0x0300..=0x036F
+            | 0x0483..=0x0489
+            | 0x0591..=0x05BD
+            | 0x1AB0..=0x1AFF
+            | 0x1DC0..=0x1DFF
+            | 0x200B..=0x200F
+            | 0x20D0..=0x20FF
+            | 0xFE00..=0xFE0F
+            | 0xFE20..=0xFE2F
+            | 0xFEFF
+    )
+}
+
+fn es_ancho(codigo: u32) -> bool {
+    matches!(
+        codigo,
+        0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F300..=0x1F64F
+            | 0x1F900..=0x1F9FF
+            | 0x20000..=0x3FFFD
+    )
+}
+
+/// Completa el texto con espacios hasta ocupar el ancho indicado. Los numeros se alinean a la derecha.
+fn alinear(texto: &str, ancho: usize) -> String {
+    let relleno = " ".repeat(ancho.saturating_sub(ancho_visible(texto)));
+    if es_numero(texto) {
+        format!("{}{}", relleno, texto)
+    } else {
+        format!("{}{}", texto, relleno)
+    }
+}
+
+/// Calcula el ancho de cada columna, considerando el encabezado si se muestra.
+fn anchos_de_columnas(columnas: &[String], filas: &[Vec<String>], encabezado: bool) -> Vec<usize> {
+    let mut anchos: Vec<usize> = columnas
+        .iter()
+        .map(|c| if encabezado { ancho_visible(c) } else { 0 })
+        .collect();
+    for fila in filas {
+        for (ancho, valor) in anchos.iter_mut().zip(fila) {
+            *ancho = (*ancho).max(ancho_visible(valor));
+        }
+    }
+    anchos
+}
+
+fn formatear_tabla(columnas: &[String], filas: &[Vec<String>], encabezado: bool) -> String {
+    let anchos = anchos_de_columnas(columnas, filas, encabezado);
+    let borde: String = anchos
+        .iter()
+        .map(|ancho| format!("+{}", "-".repeat(ancho + 2)))
+        .collect::<String>()
+        + "+\n";
+    let linea = |valores: &[String]| -> String {
+        anchos
+            .iter()
+            .zip(valores)
+            .map(|(ancho, valor)| format!("| {} ", alinear(valor, *ancho)))
+            .collect::<String>()
+            + "|\n"
+    };
+    let mut salida = borde.clone();
+    if encabezado {
+        salida.push_str(&linea(columnas));
+        salida.push_str(&borde);
+    }
+    for fila in filas {
+        salida.push_str(&linea(fila));
+    }
+    if !filas.is_empty() || !encabezado {
+        salida.push_str(&borde);
+    }
+    salida
+}
+
+fn formatear_separado(
+    columnas: &[String],
+    filas: &[Vec<String>],
+    encabezado: bool,
+    separador: &str,
+    escapar: fn(&str) -> String,
+) -> String {
+    let mut salida = String::new();
+    let lineas = filas.iter().map(|f| f.as_slice());
+    let lineas: Vec<&[String]> = if encabezado {
+        std::iter::once(columnas).chain(lineas).collect()
+    } else {
+        lineas.collect()
+    };
+    for linea in lineas {
+        let valores: Vec<String> = linea.iter().map(|v| escapar(v)).collect();
+        salida.push_str(&valores.join(separador));
+        salida.push('\n');
+    }
+    salida
+}
+
+fn escapar_csv(valor: &str) -> String {
+    if valor.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", valor.replace('"', "\"\""))
+    } else {
+        valor.to_string()
+    }
+}
+
+fn escapar_tsv(valor: &str) -> String {
+    valor
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Devuelve el valor como un valor JSON: los numeros sin comillas, los valores vacios como null y el resto como textos.
+/// Los numeros con ceros a la izquierda (por ejemplo codigos postales) se mantienen como textos para no perder los ceros.
+fn valor_json(valor: &str) -> String {
+    if valor.is_empty() {
+        return "null".to_string();
+    }
+    let entera = valor
+        .trim_start_matches('-')
+        .split('.')
+        .next()
+        .unwrap_or("");
+    if es_numero(valor) && (entera == "0" || !entera.starts_with('0')) {
+        return valor.to_string();
+    }
+    texto_json(valor)
+}
+
+fn texto_json(texto: &str) -> String {
+    let mut salida = String::from("\"");
+    for caracter in texto.chars() {
+        match caracter {
+            '"' => salida.push_str("\\\""),
+            '\\' => salida.push_str("\\\\"),
+            '\n' => salida.push_str("\\n"),
+            '\r' => salida.push_str("\\r"),
+            '\t' => salida.push_str("\\t"),
+            c if c.is_control() => salida.push_str(&format!("\\u{:04x}", c as u32)),
+            c => salida.push(c),
+        }
+    }
+    salida.push('"');
+    salida
+}
+
+/// Devuelve la fila como un objeto JSON, o como un arreglo si no se muestran los nombres de las columnas.
+fn fila_json(columnas: &[String], fila: &[String], encabezado: bool) -> String {
+    let valores: Vec<String> = if encabezado {
+        columnas
+            .iter()
+            .zip(fila)
+            .map(|(columna, valor)| format!("{}: {}", texto_json(columna), valor_json(valor)))
+            .collect()
+    } else {
+        fila.iter().map(|valor| valor_json(valor)).collect()
+    };
+    if encabezado {
+        format!("{{{}}}", valores.join(", "))
+    } else {
+        format!("[{}]", valores.join(", "))
+    }
+}
+
+fn formatear_json(columnas: &[String], filas: &[Vec<String>], encabezado: bool) -> String {
+    if filas.is_empty() {
+        return "[]\n".to_string();
+    }
+    let filas: Vec<String> = filas
+        .iter()
+        .map(|fila| format!("  {}", fila_json(columnas, fila, encabezado)))
+        .collect();
+    format!("[\n{}\n]\n", filas.join(",\n"))
+}
+
+fn formatear_json_lines(columnas: &[String], filas: &[Vec<String>], encabezado: bool) -> String {
+    filas
+        .iter()
+        .map(|fila| fila_json(columnas, fila, encabezado) + "\n")
+        .collect()
+}
+
+fn formatear_markdown(columnas: &[String], filas: &[Vec<String>], encabezado: bool) -> String {
+    let escapar = |valor: &String| valor.replace('|', "\\|").replace('\n', " ");
+    let columnas: Vec<String> = columnas.iter().map(escapar).collect();
+    let filas: Vec<Vec<String>> = filas
+        .iter()
+        .map(|fila| fila.iter().map(escapar).collect())
+        .collect();
+    // Markdown requiere la fila de encabezado, por lo que sin encabezado se deja vacia.
+    let nombres: Vec<String> = if encabezado {
+        columnas.clone()
+    } else {
+        vec![String::new(); columnas.len()]
+    };
+    let anchos: Vec<usize> = anchos_de_columnas(&nombres, &filas, true)
+        .into_iter()
+        .map(|ancho| ancho.max(3))
+        .collect();
+    let linea = |valores: &[String]| -> String {
+        anchos
+            .iter()
+            .zip(valores)
+            .map(|(ancho, valor)| format!("| {} ", alinear(valor, *ancho)))
+            .collect::<String>()
+            + "|\n"
+    };
+    let mut salida = linea(&nombres);
+    salida.push_str(
+        &(anchos
+            .iter()
+            .map(|ancho| format!("| {} ", "-".repeat(*ancho)))
+            .collect::<String>()
+            + "|\n"),
+    );
+    for fila in &filas {
+        salida.push_str(&linea(fila));
+    }
+    salida
+}
+
+fn escapar_html(valor: &str) -> String {
+    valor
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn formatear_html(columnas: &[String], filas: &[Vec<String>], encabezado: bool) -> String {
+    let linea = |valores: &[String], etiqueta: &str| -> String {
+        let celdas: String = valores
+            .iter()
+            .map(|valor| format!("<{}>{}</{}>", etiqueta, escapar_html(valor), etiqueta))
+            .collect();
+        format!("  <tr>{}</tr>\n", celdas)
+    };
+    let mut salida = String::from("<table>\n");
+    if encabezado {
+        salida.push_str(&linea(columnas, "th"));
+    }
+    for fila in filas {
+        salida.push_str(&linea(fila, "td"));
+    }
+    salida.push_str("</table>\n");
+    salida
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ancho_visible, formatear, valor_json, Formato, OpcionesFormato};
+
+    fn datos() -> (Vec<String>, Vec<Vec<String>>) {
+        let columnas = vec!["id".to_string(), "apellido".to_string()];
+        let filas = vec![
+            vec!["1".to_string(), "López".to_string()],
+            vec!["10".to_string(), "Pérez, \"Juan\"".to_string()],
+        ];
+        (columnas, filas)
+    }
+
+    fn con_formato(formato: Formato, encabezado: bool) -> String {
+        let (columnas, filas) = datos();
+        formatear(
+            &columnas,
+            &filas,
+            &OpcionesFormato {
+                formato,
+                encabezado,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_ancho_visible() {
+        assert_eq!(ancho_visible("López"), 5);
+        assert_eq!(ancho_visible("Lo\u{301}pez"), 5);
+        assert_eq!(ancho_visible("東京"), 4);
+    }
+
+    #[test]
+    fn test_valores_json() {
+        assert_eq!(valor_json(""), "null");
+        assert_eq!(valor_json("-0.5"), "-0.5");
+        assert_eq!(valor_json("007"), "\"007\"");
+        assert_eq!(valor_json("a\tb"), "\"a\\tb\"");
+    }
+
+    #[test]
+    fn test_formato_tabla() {
+        assert_eq!(
+            con_formato(Formato::Tabla, true),
+            "+----+---------------+\n\
+             | id | apellido      |\n\
+             +----+---------------+\n\
+             |  1 | López         |\n\
+             | 10 | Pérez, \"Juan\" |\n\
+             +----+---------------+\n"
+        );
+    }
+
+    #[test]
+    fn test_formatos_separados() {
+        assert_eq!(
+            con_formato(Formato::Csv, true),
+            "id,apellido\n1,López\n10,\"Pérez, \"\"Juan\"\"\"\n"
+        );
+        assert_eq!(
+            con_formato(Formato::Tsv, false),
+            "1\tLópez\n10\tPérez, \"Juan\"\n"
+        );
+    }
+
+    #[test]
+    fn test_formatos_json() {
+        assert_eq!(
+            con_formato(Formato::Json, true),
+            "[\n  {\"id\": 1, \"apellido\": \"López\"},\n  {\"id\": 10, \"apellido\": \"Pérez, \\\"Juan\\\"\"}\n]\n"
+        );
+        assert_eq!(
+            con_formato(Formato::JsonLines, false),
+            "[1, \"López\"]\n[10, \"Pérez, \\\"Juan\\\"\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_formatos_markdown_y_html() {
+        assert_eq!(
+            con_formato(Formato::Markdown, true),
+            "| id  | apellido      |\n\
+             | --- | ------------- |\n\
+             |   1 | López         |\n\
+             |  10 | Pérez, \"Juan\" |\n"
+        );
+        assert_eq!(
+            con_formato(Formato::Html, true),
+            "<table>\n  <tr><th>id</th><th>apellido</th></tr>\n  <tr><td>1</td><td>López</td></tr>\n  <tr><td>10</td><td>Pérez, &quot;Juan&quot;</td></tr>\n</table>\n"
+        );
+    }
+}
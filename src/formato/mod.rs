@@ -0,0 +1 @@
+pub mod formato_implementacion;
@@ -0,0 +1,17 @@
+//! SQL-RUSTico: un motor de consultas SQL sobre tablas guardadas en archivos .csv.
+//!
+//! La forma mas simple de usarlo es abrir un directorio con `Database::open` y ejecutar consultas con `Database::execute`,
+//! que devuelve un `QueryResult` con los nombres de las columnas, las filas con sus valores tipados y la cantidad de filas afectadas.
+pub mod analizador;
+pub mod base_de_datos;
+pub mod comandos;
+pub mod consola;
+pub mod ejecutor;
+pub mod errores;
+pub mod esquema;
+pub mod formato;
+pub mod planificador;
+
+pub use base_de_datos::base_de_datos_implementacion::{Database, PreparedStatement};
+pub use ejecutor::resultado::{Resultado as QueryResult, Valor as Value};
+pub use errores::errores_implementacion::{Errores as Error, TipoError as ErrorKind};
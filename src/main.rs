@@ -1,27 +1,71 @@
-mod analizador;
-mod comandos;
-mod ejecutor;
-mod errores;
-use comandos::comandos_implementacion::{parsear, Comandos};
-use ejecutor::ejecutor_implementacion::ejecutar_comando;
-use errores::errores_implementacion::{imprimir_error, Errores};
 use std::env;
+use std::io::{self, IsTerminal};
 use std::process::exit;
+use tp1::comandos::parametros::SentenciaPreparada;
+use tp1::consola::argumentos::{asignar_parametros, parsear_argumentos, Argumentos};
+use tp1::consola::consola_implementacion::iniciar_consola;
+use tp1::consola::script::{ejecutar_script, leer_script};
+use tp1::ejecutor::bloqueos::iniciar_sesion;
+use tp1::ejecutor::ejecutor_implementacion::{ejecutar_comando, ejecutar_consulta};
+use tp1::ejecutor::resultado::Resultado;
+use tp1::errores::errores_implementacion::{imprimir_error, Errores};
+use tp1::formato::formato_implementacion::{configurar_formato, imprimir_resultado};
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 3 {
-        imprimir_error(
-            Errores::Error,
-            "Los argumentos del programa fueron ingresados de manera incorrecta".to_string(),
-        );
-        return;
-    }
-    let path: &String = &args[1];
-    let comando: &String = &args[2];
-    let parseado: Comandos = match parsear(&comando.to_string()) {
-        Ok(comando_parseado) => comando_parseado,
-        _ => exit(1),
+    let args: Vec<String> = env::args().skip(1).collect();
+    let argumentos: Argumentos = match parsear_argumentos(&args) {
+        Ok(argumentos) => argumentos,
+        Err(error) => salir_con_error(&error),
+    };
+    configurar_formato(argumentos.formato);
+    let path: &String = &argumentos.directorio;
+    let _sesion = match iniciar_sesion(path) {
+        Ok(sesion) => sesion,
+        Err(error) => salir_con_error(&error),
+    };
+    let archivo = match (&argumentos.archivo, &argumentos.consulta) {
+        (Some(archivo), _) => archivo.as_str(),
+        (None, None) if !io::stdin().is_terminal() => "-",
+        (None, None) => {
+            iniciar_consola(path);
+            return;
+        }
+        (None, Some(comando)) => {
+            match ejecutar(comando, &argumentos.parametros, path) {
+                Ok(resultado) => imprimir_resultado(&resultado),
+                Err(error) => salir_con_error(&error),
+            }
+            return;
+        }
     };
-    if let Ok(()) = ejecutar_comando(parseado, path) {}
+    let script = match leer_script(archivo) {
+        Ok(script) => script,
+        Err(error) => salir_con_error(&error),
+    };
+    let resumen = ejecutar_script(path, &script, argumentos.continuar_ante_error);
+    if argumentos.continuar_ante_error {
+        eprintln!("{}", resumen.descripcion());
+    }
+    if let Some(tipo) = resumen.primer_error {
+        exit(tipo.codigo_salida());
+    }
+}
+
+/// Ejecuta la consulta indicada en la linea de comandos. Si se indicaron parametros con --param, se prepara y se le asignan sus valores.
+fn ejecutar(consulta: &str, parametros: &[String], path: &String) -> Result<Resultado, Errores> {
+    if parametros.is_empty() {
+        return ejecutar_consulta(consulta, path);
+    }
+    SentenciaPreparada::preparar(consulta)
+        .and_then(|mut sentencia| {
+            asignar_parametros(&mut sentencia, parametros)?;
+            ejecutar_comando(sentencia.comando()?, path)
+        })
+        .map_err(|error| error.ubicar(consulta))
+}
+
+/// Imprime el error y termina el programa con el codigo de salida que corresponde a su tipo.
+fn salir_con_error(error: &Errores) -> ! {
+    imprimir_error(error);
+    exit(error.tipo.codigo_salida())
 }
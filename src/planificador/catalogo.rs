@@ -0,0 +1,326 @@
+use crate::ejecutor::ejecutor_implementacion::{crear_ruta, listar_tablas, obtener_primera_linea};
+use crate::ejecutor::transaccion::ruta_tabla;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::cargar_esquema;
+use crate::esquema::estadisticas::Estadisticas;
+use crate::esquema::indices::{indice_vigente, indices_de_tabla, Estampa};
+use crate::esquema::vistas::{
+    definicion_de_vista, leer_vista, leer_vista_materializada, listar_vistas,
+};
+use crate::planificador::operadores::Operador;
+use crate::planificador::planificador_implementacion::{construir_plan_de_vista, ColumnaPlan};
+use std::path::Path;
+use std::vec;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Representa las tablas del catalogo: tablas de solo lectura que describen el contenido del directorio. No tienen un archivo, sus filas se calculan cada vez que se leen, y pueden consultarse con SELECT como cualquier otra tabla.
+pub enum TablaCatalogo {
+    /// information_schema.tables: una fila por tabla, incluidas las vistas y las del catalogo.
+    Tablas,
+    /// information_schema.columns: una fila por columna de cada tabla, con su posicion, su tipo declarado, si admite nulos y su valor por defecto.
+    Columnas,
+    /// information_schema.views: una fila por vista creada con CREATE VIEW, con el SELECT que la define.
+    Vistas,
+    /// sqlrustico_matviews: una fila por vista materializada, con el SELECT que la define y la fecha y hora en la que se calcularon sus filas por ultima vez.
+    VistasMaterializadas,
+    /// sqlrustico_indexes: una fila por indice creado con CREATE INDEX.
+    Indices,
+    /// sqlrustico_stats: una fila por cada columna de cada tabla analizada con ANALYZE, con sus estadisticas.
+    Estadisticas,
+}
+
+/// Las tablas del catalogo, en el orden en que se listan en information_schema.tables.
+const TABLAS_DEL_CATALOGO: [TablaCatalogo; 6] = [
+    TablaCatalogo::Tablas,
+    TablaCatalogo::Columnas,
+    TablaCatalogo::Vistas,
+    TablaCatalogo::VistasMaterializadas,
+    TablaCatalogo::Indices,
+    TablaCatalogo::Estadisticas,
+];
+
+impl TablaCatalogo {
+    /// Devuelve la tabla del catalogo con el nombre indicado, si existe.
+    pub fn desde_nombre(nombre: &str) -> Option<TablaCatalogo> {
+        TABLAS_DEL_CATALOGO
+            .into_iter()
+            .find(|tabla| tabla.nombre() == nombre)
+    }
+
+    pub fn nombre(&self) -> &'static str {
+        match self {
+            TablaCatalogo::Tablas => "information_schema.tables",
+            TablaCatalogo::Columnas => "information_schema.columns",
+            TablaCatalogo::Vistas => "information_schema.views",
+            TablaCatalogo::VistasMaterializadas => "sqlrustico_matviews",
+            TablaCatalogo::Indices => "sqlrustico_indexes",
+            TablaCatalogo::Estadisticas => "sqlrustico_stats",
+        }
+    }
+
+    /// Devuelve las columnas de la tabla, con sus tipos.
+    pub fn columnas(&self) -> Vec<ColumnaPlan> {
+        let columnas: &[(&str, Option<&str>)] = match self {
+            TablaCatalogo::Tablas => &[("table_name", Some("TEXT")), ("table_type", Some("TEXT"))],
+            TablaCatalogo::Columnas => &[
+                ("table_name", Some("TEXT")),
+                ("column_name", Some("TEXT")),
+                ("ordinal_position", Some("INT")),
+                ("data_type", Some("TEXT")),
+                ("is_nullable", Some("TEXT")),
+                ("column_default", Some("TEXT")),
+            ],
+            TablaCatalogo::Vistas => &[
+                ("table_name", Some("TEXT")),
+                ("view_definition", Some("TEXT")),
+            ],
+            TablaCatalogo::VistasMaterializadas => &[
+                ("vista", Some("TEXT")),
+                ("definicion", Some("TEXT")),
+                ("actualizada", Some("TIMESTAMP")),
+            ],
+            TablaCatalogo::Indices => &[
+                ("indice", Some("TEXT")),
+                ("tabla", Some("TEXT")),
+                ("columna", Some("TEXT")),
+                ("unico", Some("TEXT")),
+                ("vigente", Some("TEXT")),
+            ],
+            TablaCatalogo::Estadisticas => &[
+                ("tabla", Some("TEXT")),
+                ("columna", Some("TEXT")),
+                ("filas", Some("INT")),
+                ("distintos", Some("INT")),
+                ("fraccion_nulos", Some("REAL")),
+                ("minimo", None),
+                ("maximo", None),
+                ("histograma", Some("TEXT")),
+                ("vigentes", Some("TEXT")),
+            ],
+        };
+        columnas
+            .iter()
+            .map(|(nombre, tipo)| ColumnaPlan {
+                tabla: Some(self.nombre().to_string()),
+                nombre: nombre.to_string(),
+                tipo: tipo.map(|t| t.to_string()),
+            })
+            .collect()
+    }
+
+    /// Calcula las filas de la tabla a partir del contenido actual del directorio.
+    pub fn filas(&self, path: &String) -> Result<Vec<Vec<String>>, Errores> {
+        match self {
+            TablaCatalogo::Tablas => filas_de_tablas(path),
+            TablaCatalogo::Columnas => filas_de_columnas(path),
+            TablaCatalogo::Vistas => filas_de_vistas(path),
+            TablaCatalogo::VistasMaterializadas => filas_de_vistas_materializadas(path),
+            TablaCatalogo::Indices => filas_de_indices(path),
+            TablaCatalogo::Estadisticas => filas_de_estadisticas(path),
+        }
+    }
+}
+
+/// Esta funcion devuelve un error si la tabla pertenece al catalogo, cuyas tablas no pueden crearse ni modificarse.
+pub fn verificar_tabla_modificable(tabla: &str) -> Result<(), Errores> {
+    if TablaCatalogo::desde_nombre(tabla).is_some() {
+        return Err(Errores::nuevo(
+            TipoError::InvalidTable,
+            format!(
+                "La tabla {} pertenece al catalogo y es de solo lectura",
+                tabla
+            ),
+        )
+        .en_token(tabla));
+    }
+    Ok(())
+}
+
+/// Devuelve el nombre y el tipo de cada tabla del directorio, incluidas las creadas dentro de la transaccion activa y las vistas materializadas, luego los de las vistas y por ultimo los de las tablas del catalogo.
+fn filas_de_tablas(path: &String) -> Result<Vec<Vec<String>>, Errores> {
+    let mut filas: Vec<Vec<String>> = Vec::new();
+    for tabla in listar_tablas(path)? {
+        let tipo = match leer_vista_materializada(path, &tabla)? {
+            Some(_) => "MATERIALIZED VIEW",
+            None => "BASE TABLE",
+        };
+        filas.push(vec![tabla, tipo.to_string()]);
+    }
+    for vista in listar_vistas(path)? {
+        filas.push(vec![vista, "VIEW".to_string()]);
+    }
+    for tabla in TABLAS_DEL_CATALOGO {
+        filas.push(vec![tabla.nombre().to_string(), "SYSTEM VIEW".to_string()]);
+    }
+    Ok(filas)
+}
+
+/// Devuelve las columnas de cada tabla, en el orden de su encabezado, con lo que declara su esquema. Los valores que el esquema no declara quedan nulos.
+fn filas_de_columnas(path: &String) -> Result<Vec<Vec<String>>, Errores> {
+    let mut filas: Vec<Vec<String>> = Vec::new();
+    for tabla in listar_tablas(path)? {
+        let encabezado = obtener_primera_linea(&ruta_tabla(path, &tabla))?;
+        let esquema = cargar_esquema(path, &tabla, &encabezado)?;
+        let not_null = esquema.columnas_not_null();
+        for (posicion, nombre) in encabezado.iter().enumerate() {
+            let columna = esquema.columna(nombre);
+            filas.push(vec![
+                tabla.to_string(),
+                nombre.to_string(),
+                (posicion + 1).to_string(),
+                columna.and_then(|c| c.tipo.clone()).unwrap_or_default(),
+                si_o_no(!not_null.contains(nombre)),
+                columna
+                    .and_then(|c| c.por_defecto.as_ref())
+                    .map(|valor| valor.descripcion())
+                    .unwrap_or_default(),
+            ]);
+        }
+    }
+    // Las columnas de una vista son las de su plan. Si la vista ya no puede planificarse, por ejemplo porque cambio una de sus tablas, no se listan.
+    for vista in listar_vistas(path)? {
+        let plan = leer_vista(path, &vista)?
+            .map(|consulta| construir_plan_de_vista(&vista, &consulta, path));
+        let columnas = match plan.map(|plan| plan.and_then(|plan| plan.columnas())) {
+            Some(Ok(columnas)) => columnas,
+            _ => continue,
+        };
+        for (posicion, columna) in columnas.into_iter().enumerate() {
+            filas.push(vec![
+                vista.to_string(),
+                columna.nombre,
+                (posicion + 1).to_string(),
+                columna.tipo.unwrap_or_default(),
+                si_o_no(true),
+                String::new(),
+            ]);
+        }
+    }
+    for tabla in TABLAS_DEL_CATALOGO {
+        for (posicion, columna) in tabla.columnas().into_iter().enumerate() {
+            filas.push(vec![
+                tabla.nombre().to_string(),
+                columna.nombre,
+                (posicion + 1).to_string(),
+                columna.tipo.unwrap_or_default(),
+                si_o_no(true),
+                String::new(),
+            ]);
+        }
+    }
+    Ok(filas)
+}
+
+/// Devuelve el nombre de cada vista y el texto del SELECT que la define.
+fn filas_de_vistas(path: &String) -> Result<Vec<Vec<String>>, Errores> {
+    Ok(listar_vistas(path)?
+        .into_iter()
+        .map(|vista| {
+            let definicion = definicion_de_vista(path, &vista).unwrap_or_default();
+            vec![vista, definicion]
+        })
+        .collect())
+}
+
+/// Devuelve el nombre de cada vista materializada, el texto del SELECT que la define y la fecha y hora, en UTC, en la que se calcularon sus filas por ultima vez.
+fn filas_de_vistas_materializadas(path: &String) -> Result<Vec<Vec<String>>, Errores> {
+    let mut filas: Vec<Vec<String>> = Vec::new();
+    for tabla in listar_tablas(path)? {
+        if let Some(vista) = leer_vista_materializada(path, &tabla)? {
+            filas.push(vec![tabla, vista.definicion, vista.actualizada]);
+        }
+    }
+    Ok(filas)
+}
+
+/// Devuelve los indices de cada tabla. La columna vigente indica si el indice corresponde a la version actual de la tabla; si no, las consultas no lo usan hasta que se actualice, por ejemplo con REINDEX.
+fn filas_de_indices(path: &String) -> Result<Vec<Vec<String>>, Errores> {
+    let mut filas: Vec<Vec<String>> = Vec::new();
+    for tabla in listar_tablas(path)? {
+        let encabezado = obtener_primera_linea(&ruta_tabla(path, &tabla))?;
+        let esquema = cargar_esquema(path, &tabla, &encabezado)?;
+        for indice in indices_de_tabla(&esquema) {
+            filas.push(vec![
+                indice.nombre.to_string(),
+                tabla.to_string(),
+                indice.columna.to_string(),
+                indice.unico.to_string(),
+                indice_vigente(path, &tabla, &indice).to_string(),
+            ]);
+        }
+    }
+    Ok(filas)
+}
+
+fn si_o_no(valor: bool) -> String {
+    if valor { "YES" } else { "NO" }.to_string()
+}
+
+/// Devuelve las estadisticas de cada columna de las tablas analizadas. La columna vigentes indica si la tabla no se modifico desde que se analizo; si se modifico, las estadisticas ya no se usan para planificar las consultas hasta volver a ejecutar ANALYZE.
+/// Los limites del histograma se separan con " | ".
+fn filas_de_estadisticas(path: &String) -> Result<Vec<Vec<String>>, Errores> {
+    let mut filas: Vec<Vec<String>> = Vec::new();
+    for tabla in listar_tablas(path)? {
+        let ruta = crear_ruta(path, &tabla);
+        if !Path::new(&ruta).exists() {
+            continue;
+        }
+        let encabezado = obtener_primera_linea(&ruta)?;
+        let esquema = cargar_esquema(path, &tabla, &encabezado)?;
+        let estadisticas = match Estadisticas::de_esquema(&esquema, &encabezado) {
+            Some(estadisticas) => estadisticas,
+            None => continue,
+        };
+        let vigentes = Some(estadisticas.estampa) == Estampa::de(&ruta);
+        for columna in &estadisticas.columnas {
+            filas.push(vec![
+                tabla.to_string(),
+                columna.nombre.to_string(),
+                estadisticas.filas.to_string(),
+                columna.distintos.to_string(),
+                format!("{:.4}", columna.fraccion_nulos(estadisticas.filas)),
+                columna.minimo().unwrap_or_default().to_string(),
+                columna.maximo().unwrap_or_default().to_string(),
+                columna.histograma.join(" | "),
+                vigentes.to_string(),
+            ]);
+        }
+    }
+    Ok(filas)
+}
+
+/// Produce las filas de una tabla del catalogo. Las filas se calculan cuando se pide la primera.
+pub struct CatalogScan {
+    tabla: TablaCatalogo,
+    path: String,
+    columnas: Vec<ColumnaPlan>,
+    filas: Option<vec::IntoIter<Vec<String>>>,
+}
+
+impl CatalogScan {
+    pub fn new(tabla: TablaCatalogo, path: &String, columnas: Vec<ColumnaPlan>) -> CatalogScan {
+        CatalogScan {
+            tabla,
+            path: path.to_string(),
+            columnas,
+            filas: None,
+        }
+    }
+}
+
+impl Operador for CatalogScan {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        &self.columnas
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        if self.filas.is_none() {
+            self.filas = Some(self.tabla.filas(&self.path)?.into_iter());
+        }
+        Ok(self.filas.as_mut().and_then(|filas| filas.next()))
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        Vec::new()
+    }
+}
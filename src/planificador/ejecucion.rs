@@ -0,0 +1,282 @@
+use crate::analizador::expresion::Expresion;
+use crate::ejecutor::ejecutor_implementacion::obtener_indices_columnas;
+use crate::ejecutor::transaccion::ruta_tabla;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::esquema_implementacion::cargar_esquema;
+use crate::esquema::indices::buscar_posiciones;
+use crate::planificador::catalogo::{CatalogScan, TablaCatalogo};
+use crate::planificador::operadores::{
+    Aggregate, Agrupacion, Filter, HashJoin, Limit, Medido, Operador, Project, Sort, TableScan,
+};
+use crate::planificador::paralelo::{rangos_de_tabla, AggregateParalelo, ScanParalelo};
+use crate::planificador::planificador_implementacion::{
+    resolver_columna, Acceso, Agregado, ColumnaPlan, Plan,
+};
+use std::time::Duration;
+
+/// Representa las filas que produce un nodo del plan, con los valores de cada fila ya separados.
+pub struct Relacion {
+    pub columnas: Vec<ColumnaPlan>,
+    pub filas: Vec<Vec<String>>,
+}
+
+/// Representa lo medido al ejecutar un nodo del plan: la cantidad de filas que produjo y el tiempo que tardo, incluido el de sus entradas, que se miden en el mismo orden que Plan::hijos.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analisis {
+    pub filas: usize,
+    pub tiempo: Duration,
+    pub hijos: Vec<Analisis>,
+}
+
+/// Esta funcion ejecuta el plan y devuelve las filas que produce, junto con lo medido en cada nodo.
+pub fn ejecutar_plan(plan: &Plan, path: &String) -> Result<(Relacion, Analisis), Errores> {
+    let mut operador = construir_operador(plan, path, true)?;
+    let mut filas: Vec<Vec<String>> = Vec::new();
+    while let Some(fila) = operador.siguiente()? {
+        filas.push(fila);
+    }
+    let relacion = Relacion {
+        columnas: operador.columnas().to_vec(),
+        filas,
+    };
+    Ok((relacion, analisis_de(operador.as_ref())))
+}
+
+/// Esta funcion arma el arbol de operadores que ejecuta el plan, con un operador por nodo. Si se pide medir, cada operador cuenta las filas que produce y el tiempo que tarda.
+/// Los operadores no leen nada hasta que se les pide la primera fila.
+pub fn construir_operador(
+    plan: &Plan,
+    path: &String,
+    medir: bool,
+) -> Result<Box<dyn Operador>, Errores> {
+    if !medir {
+        if let Some(operador) = crear_paralelo(plan, path)? {
+            return Ok(operador);
+        }
+    }
+    let entrada = |entrada: &Plan| construir_operador(entrada, path, medir);
+    let operador: Box<dyn Operador> = match plan {
+        Plan::Scan {
+            tabla,
+            columnas,
+            acceso: Acceso::Catalogo,
+            ..
+        } => Box::new(crear_catalog_scan(path, tabla, columnas)?),
+        Plan::Scan {
+            tabla,
+            columnas,
+            acceso,
+            ..
+        } => Box::new(crear_scan(path, tabla, columnas, acceso)?),
+        Plan::Filter {
+            condicion,
+            entrada: e,
+        } => match e.as_ref() {
+            // Sin medir cada nodo, la condicion se evalua en el mismo Scan, antes de separar los valores de cada linea.
+            Plan::Scan {
+                tabla,
+                columnas,
+                acceso,
+                ..
+            } if !medir && *acceso != Acceso::Catalogo => {
+                Box::new(crear_scan(path, tabla, columnas, acceso)?.con_filtro(condicion))
+            }
+            _ => Box::new(Filter::new(entrada(e)?, condicion)),
+        },
+        Plan::Join {
+            izquierda,
+            derecha,
+            columna_izquierda,
+            columna_derecha,
+        } => {
+            let izquierda = entrada(izquierda)?;
+            let derecha = entrada(derecha)?;
+            let indice_izquierda = resolver_columna(izquierda.columnas(), columna_izquierda)?;
+            let indice_derecha = resolver_columna(derecha.columnas(), columna_derecha)?;
+            Box::new(HashJoin::new(
+                izquierda,
+                derecha,
+                indice_izquierda,
+                indice_derecha,
+            ))
+        }
+        Plan::Aggregate {
+            agrupar_por,
+            agregados,
+            entrada: e,
+        } => {
+            let e = entrada(e)?;
+            let agrupacion = crear_agrupacion(e.columnas(), agrupar_por, agregados)?;
+            Box::new(Aggregate::new(e, agrupacion, plan.columnas()?))
+        }
+        Plan::Sort {
+            columna,
+            descendente,
+            entrada: e,
+        } => {
+            let e = entrada(e)?;
+            let indice = resolver_columna(e.columnas(), columna)?;
+            Box::new(Sort::new(e, indice, *descendente))
+        }
+        Plan::Limit {
+            cantidad,
+            entrada: e,
+        } => Box::new(Limit::new(entrada(e)?, *cantidad)),
+        Plan::Project {
+            campos, entrada: e, ..
+        } => {
+            let e = entrada(e)?;
+            let mut indices: Vec<usize> = Vec::new();
+            for campo in campos {
+                indices.push(resolver_columna(e.columnas(), campo)?);
+            }
+            Box::new(Project::new(e, indices, plan.columnas()?))
+        }
+        // La vista conserva las filas de su SELECT y solo cambia el nombre de sus columnas.
+        Plan::Vista { entrada: e, .. } => {
+            let e = entrada(e)?;
+            let indices: Vec<usize> = (0..e.columnas().len()).collect();
+            Box::new(Project::new(e, indices, plan.columnas()?))
+        }
+    };
+    if medir {
+        return Ok(Box::new(Medido::new(operador)));
+    }
+    Ok(operador)
+}
+
+/// Crea el operador que lee la tabla, completa o solo las filas que indica el indice. Si el indice ya no puede usarse se recorre la tabla completa.
+fn crear_scan(
+    path: &String,
+    tabla: &str,
+    columnas: &[ColumnaPlan],
+    acceso: &Acceso,
+) -> Result<TableScan, Errores> {
+    let ruta = ruta_tabla(path, tabla);
+    if let Acceso::Indice { filtro, .. } = acceso {
+        let nombres: Vec<String> = columnas.iter().map(|c| c.nombre.to_string()).collect();
+        let esquema = cargar_esquema(path, &tabla.to_string(), &nombres)?;
+        let indice_columnas = obtener_indices_columnas(&nombres);
+        if let Some(posiciones) = buscar_posiciones(path, tabla, &esquema, &indice_columnas, filtro)
+        {
+            return TableScan::por_posiciones(&ruta, posiciones, columnas.to_vec());
+        }
+    }
+    TableScan::completo(&ruta, columnas.to_vec())
+}
+
+/// Crea el operador que produce las filas de una tabla del catalogo.
+fn crear_catalog_scan(
+    path: &String,
+    tabla: &str,
+    columnas: &[ColumnaPlan],
+) -> Result<CatalogScan, Errores> {
+    match TablaCatalogo::desde_nombre(tabla) {
+        Some(catalogo) => Ok(CatalogScan::new(catalogo, path, columnas.to_vec())),
+        None => Err(Errores::nuevo(
+            TipoError::InvalidTable,
+            format!("La tabla {} no pertenece al catalogo", tabla),
+        )),
+    }
+}
+
+/// Crea, si conviene, el operador que ejecuta el plan leyendo la tabla con varios hilos: cuando el plan recorre una tabla completa, quizas con una condicion, o agrupa las filas que produce ese recorrido, y la tabla es lo suficientemente grande.
+/// Devuelve None si el plan debe ejecutarse con un unico hilo.
+fn crear_paralelo(plan: &Plan, path: &String) -> Result<Option<Box<dyn Operador>>, Errores> {
+    if let Plan::Aggregate {
+        agrupar_por,
+        agregados,
+        entrada,
+    } = plan
+    {
+        let (tabla, columnas, condicion) = match recorrido_completo(entrada) {
+            Some(recorrido) => recorrido,
+            None => return Ok(None),
+        };
+        let ruta = ruta_tabla(path, tabla);
+        let rangos = rangos_de_tabla(&ruta)?;
+        if rangos.len() < 2 {
+            return Ok(None);
+        }
+        let agrupacion = crear_agrupacion(columnas, agrupar_por, agregados)?;
+        return Ok(Some(Box::new(AggregateParalelo::new(
+            &ruta,
+            rangos,
+            columnas.to_vec(),
+            condicion,
+            agrupacion,
+            plan.columnas()?,
+        ))));
+    }
+    let (tabla, columnas, condicion) = match recorrido_completo(plan) {
+        Some(recorrido) => recorrido,
+        None => return Ok(None),
+    };
+    let ruta = ruta_tabla(path, tabla);
+    let rangos = rangos_de_tabla(&ruta)?;
+    if rangos.len() < 2 {
+        return Ok(None);
+    }
+    Ok(Some(Box::new(ScanParalelo::new(
+        &ruta,
+        rangos,
+        columnas.to_vec(),
+        condicion,
+    ))))
+}
+
+/// Si el plan recorre una tabla completa, sin indices, devuelve la tabla, sus columnas y la condicion con la que se filtran sus filas, si la hay.
+fn recorrido_completo(plan: &Plan) -> Option<(&String, &Vec<ColumnaPlan>, Option<&Expresion>)> {
+    match plan {
+        Plan::Scan {
+            tabla,
+            columnas,
+            acceso: Acceso::Completo,
+            ..
+        } => Some((tabla, columnas, None)),
+        Plan::Filter { condicion, entrada } => match entrada.as_ref() {
+            Plan::Scan {
+                tabla,
+                columnas,
+                acceso: Acceso::Completo,
+                ..
+            } => Some((tabla, columnas, Some(condicion))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Resuelve las columnas de agrupacion y las de los agregados entre las columnas de entrada.
+fn crear_agrupacion(
+    columnas: &[ColumnaPlan],
+    agrupar_por: &[String],
+    agregados: &[Agregado],
+) -> Result<Agrupacion, Errores> {
+    let mut indices_grupo: Vec<usize> = Vec::new();
+    for columna in agrupar_por {
+        indices_grupo.push(resolver_columna(columnas, columna)?);
+    }
+    let mut con_indices = Vec::new();
+    for agregado in agregados {
+        let indice = match &agregado.columna {
+            Some(columna) => Some(resolver_columna(columnas, columna)?),
+            None => None,
+        };
+        con_indices.push((agregado.clone(), indice));
+    }
+    Ok(Agrupacion {
+        indices_grupo,
+        agregados: con_indices,
+    })
+}
+
+/// Arma lo medido en cada nodo a partir de los operadores medidos.
+fn analisis_de(operador: &dyn Operador) -> Analisis {
+    let (filas, tiempo) = operador.medicion().unwrap_or((0, Duration::ZERO));
+    Analisis {
+        filas,
+        tiempo,
+        hijos: operador.entradas().into_iter().map(analisis_de).collect(),
+    }
+}
@@ -0,0 +1,6 @@
+pub mod catalogo;
+pub mod ejecucion;
+pub mod operadores;
+pub mod optimizador;
+pub mod paralelo;
+pub mod planificador_implementacion;
@@ -0,0 +1,846 @@
+use crate::analizador::analizador_implementacion::{comparar_orden_total, es_numero};
+use crate::analizador::expresion::Expresion;
+use crate::analizador::predicado::Predicado;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::indices::{LineasConPosicion, LineasEnPosiciones};
+use crate::planificador::planificador_implementacion::{
+    mapa_columnas, Agregado, ColumnaPlan, FuncionAgregada,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::time::{Duration, Instant};
+
+/// Representa un operador de ejecucion de un plan. Cada operador produce sus filas de a una, a medida que se le piden, pidiendo a su vez a sus entradas solo las filas que necesita.
+/// Las filas son los valores de cada columna, ya separados.
+pub trait Operador {
+    /// Devuelve las columnas de las filas que produce el operador.
+    fn columnas(&self) -> &[ColumnaPlan];
+
+    /// Devuelve la siguiente fila, o None si no quedan mas.
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores>;
+
+    /// Devuelve la posicion en el archivo de la tabla en la que comienza la ultima fila devuelta, si la fila proviene directamente de una tabla.
+    fn posicion(&self) -> Option<u64> {
+        None
+    }
+
+    /// Devuelve las entradas del operador, en el mismo orden que Plan::hijos.
+    fn entradas(&self) -> Vec<&dyn Operador>;
+
+    /// Devuelve la cantidad de filas producidas y el tiempo que tardo el operador, si se lo esta midiendo.
+    fn medicion(&self) -> Option<(usize, Duration)> {
+        None
+    }
+}
+
+/// Lee las filas de una tabla en el orden del archivo: todas, salteando el encabezado, o solo las que comienzan en las posiciones indicadas.
+/// Puede recibir ademas una condicion, que se evalua sobre cada linea antes de separar sus valores, para descartar sin copiarlas las filas que no la cumplen.
+pub struct TableScan {
+    columnas: Vec<ColumnaPlan>,
+    lineas: Box<dyn Iterator<Item = Result<(u64, String), Errores>>>,
+    filtro: Option<Predicado>,
+    posicion: Option<u64>,
+}
+
+impl TableScan {
+    /// Crea el operador que recorre la tabla completa.
+    pub fn completo(ruta: &str, columnas: Vec<ColumnaPlan>) -> Result<TableScan, Errores> {
+        let archivo = match File::open(ruta) {
+            Ok(f) => f,
+            _ => {
+                return Err(Errores::nuevo(
+                    TipoError::Error,
+                    "No se pudo abrir el archivo".to_string(),
+                ));
+            }
+        };
+        let lineas = LineasConPosicion::new(BufReader::new(archivo), 0).skip(1);
+        Ok(TableScan {
+            columnas,
+            lineas: Box::new(lineas),
+            filtro: None,
+            posicion: None,
+        })
+    }
+
+    /// Crea el operador que lee solo las filas que comienzan en las posiciones indicadas, que deben estar ordenadas.
+    pub fn por_posiciones(
+        ruta: &str,
+        posiciones: Vec<u64>,
+        columnas: Vec<ColumnaPlan>,
+    ) -> Result<TableScan, Errores> {
+        Ok(TableScan {
+            columnas,
+            lineas: Box::new(LineasEnPosiciones::new(ruta, posiciones)?),
+            filtro: None,
+            posicion: None,
+        })
+    }
+
+    /// Devuelve el mismo operador, pero produciendo solo las filas que cumplen la condicion.
+    pub fn con_filtro(self, condicion: &Expresion) -> TableScan {
+        let filtro = Predicado::compilar(condicion, &mapa_columnas(&self.columnas));
+        TableScan {
+            filtro: Some(filtro),
+            ..self
+        }
+    }
+}
+
+impl Operador for TableScan {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        &self.columnas
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        for linea in self.lineas.by_ref() {
+            let (posicion, linea) = linea?;
+            if self
+                .filtro
+                .as_ref()
+                .is_some_and(|filtro| !filtro.cumple_linea(&linea))
+            {
+                continue;
+            }
+            self.posicion = Some(posicion);
+            return Ok(Some(
+                linea.split(',').map(|s| s.trim().to_string()).collect(),
+            ));
+        }
+        Ok(None)
+    }
+
+    fn posicion(&self) -> Option<u64> {
+        self.posicion
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        Vec::new()
+    }
+}
+
+/// Devuelve las filas de su entrada que cumplen la condicion, que se compila una unica vez al crear el operador.
+pub struct Filter {
+    entrada: Box<dyn Operador>,
+    predicado: Predicado,
+}
+
+impl Filter {
+    pub fn new(entrada: Box<dyn Operador>, condicion: &Expresion) -> Filter {
+        let predicado = Predicado::compilar(condicion, &mapa_columnas(entrada.columnas()));
+        Filter { entrada, predicado }
+    }
+}
+
+impl Operador for Filter {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        self.entrada.columnas()
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        while let Some(fila) = self.entrada.siguiente()? {
+            if self.predicado.cumple_fila(&fila) {
+                return Ok(Some(fila));
+            }
+        }
+        Ok(None)
+    }
+
+    fn posicion(&self) -> Option<u64> {
+        self.entrada.posicion()
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        vec![self.entrada.as_ref()]
+    }
+}
+
+/// Devuelve de cada fila de su entrada solo las columnas indicadas, en ese orden.
+pub struct Project {
+    entrada: Box<dyn Operador>,
+    indices: Vec<usize>,
+    columnas: Vec<ColumnaPlan>,
+}
+
+impl Project {
+    pub fn new(
+        entrada: Box<dyn Operador>,
+        indices: Vec<usize>,
+        columnas: Vec<ColumnaPlan>,
+    ) -> Project {
+        Project {
+            entrada,
+            indices,
+            columnas,
+        }
+    }
+}
+
+impl Operador for Project {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        &self.columnas
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        Ok(self.entrada.siguiente()?.map(|fila| {
+            self.indices
+                .iter()
+                .map(|&i| fila.get(i).cloned().unwrap_or_default())
+                .collect()
+        }))
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        vec![self.entrada.as_ref()]
+    }
+}
+
+/// Devuelve las filas de su entrada ordenadas segun una columna, comparando sus valores como texto. Para hacerlo necesita leer todas las filas de la entrada antes de devolver la primera.
+pub struct Sort {
+    entrada: Box<dyn Operador>,
+    indice: usize,
+    descendente: bool,
+    ordenadas: Option<std::vec::IntoIter<Vec<String>>>,
+}
+
+impl Sort {
+    pub fn new(entrada: Box<dyn Operador>, indice: usize, descendente: bool) -> Sort {
+        Sort {
+            entrada,
+            indice,
+            descendente,
+            ordenadas: None,
+        }
+    }
+}
+
+impl Operador for Sort {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        self.entrada.columnas()
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        if self.ordenadas.is_none() {
+            let mut filas: Vec<Vec<String>> = Vec::new();
+            while let Some(fila) = self.entrada.siguiente()? {
+                filas.push(fila);
+            }
+            let indice = self.indice;
+            filas.sort_by(|a, b| {
+                let orden = a.get(indice).cmp(&b.get(indice));
+                if self.descendente {
+                    orden.reverse()
+                } else {
+                    orden
+                }
+            });
+            self.ordenadas = Some(filas.into_iter());
+        }
+        Ok(self.ordenadas.as_mut().and_then(|filas| filas.next()))
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        vec![self.entrada.as_ref()]
+    }
+}
+
+/// Devuelve como maximo la cantidad indicada de filas de su entrada. Una vez alcanzada no le pide mas filas.
+pub struct Limit {
+    entrada: Box<dyn Operador>,
+    restantes: usize,
+}
+
+impl Limit {
+    pub fn new(entrada: Box<dyn Operador>, cantidad: usize) -> Limit {
+        Limit {
+            entrada,
+            restantes: cantidad,
+        }
+    }
+}
+
+impl Operador for Limit {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        self.entrada.columnas()
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        if self.restantes == 0 {
+            return Ok(None);
+        }
+        let fila = self.entrada.siguiente()?;
+        if fila.is_some() {
+            self.restantes -= 1;
+        }
+        Ok(fila)
+    }
+
+    fn posicion(&self) -> Option<u64> {
+        self.entrada.posicion()
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        vec![self.entrada.as_ref()]
+    }
+}
+
+/// Une cada fila de la izquierda con las filas de la derecha en las que las columnas indicadas son iguales. Antes de devolver la primera fila lee todas las filas de la derecha y arma con ellas una tabla de hash; las de la izquierda se leen de a una.
+/// Los valores nulos no se unen con ninguna fila.
+pub struct HashJoin {
+    izquierda: Box<dyn Operador>,
+    derecha: Box<dyn Operador>,
+    indice_izquierda: usize,
+    indice_derecha: usize,
+    columnas: Vec<ColumnaPlan>,
+    filas_derecha: Vec<Vec<String>>,
+    tabla_hash: Option<HashMap<String, Vec<usize>>>,
+    actual: Vec<String>,
+    pendientes: Vec<usize>,
+}
+
+impl HashJoin {
+    pub fn new(
+        izquierda: Box<dyn Operador>,
+        derecha: Box<dyn Operador>,
+        indice_izquierda: usize,
+        indice_derecha: usize,
+    ) -> HashJoin {
+        let mut columnas = izquierda.columnas().to_vec();
+        columnas.extend_from_slice(derecha.columnas());
+        HashJoin {
+            izquierda,
+            derecha,
+            indice_izquierda,
+            indice_derecha,
+            columnas,
+            filas_derecha: Vec::new(),
+            tabla_hash: None,
+            actual: Vec::new(),
+            pendientes: Vec::new(),
+        }
+    }
+
+    fn armar_tabla_hash(&mut self) -> Result<HashMap<String, Vec<usize>>, Errores> {
+        let mut tabla_hash: HashMap<String, Vec<usize>> = HashMap::new();
+        while let Some(fila) = self.derecha.siguiente()? {
+            if let Some(valor) = fila.get(self.indice_derecha).filter(|v| !v.is_empty()) {
+                tabla_hash
+                    .entry(clave_de_union(valor))
+                    .or_default()
+                    .push(self.filas_derecha.len());
+            }
+            self.filas_derecha.push(fila);
+        }
+        Ok(tabla_hash)
+    }
+}
+
+impl Operador for HashJoin {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        &self.columnas
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        if self.tabla_hash.is_none() {
+            self.tabla_hash = Some(self.armar_tabla_hash()?);
+        }
+        loop {
+            if let Some(i) = self.pendientes.pop() {
+                let mut unida = self.actual.clone();
+                unida.extend(self.filas_derecha[i].iter().cloned());
+                return Ok(Some(unida));
+            }
+            let fila = match self.izquierda.siguiente()? {
+                Some(fila) => fila,
+                None => return Ok(None),
+            };
+            let coincidencias = fila
+                .get(self.indice_izquierda)
+                .filter(|valor| !valor.is_empty())
+                .and_then(|valor| self.tabla_hash.as_ref()?.get(&clave_de_union(valor)));
+            // Se guardan en orden inverso para devolverlas en el orden de la derecha.
+            self.pendientes = coincidencias
+                .map(|c| c.iter().rev().copied().collect())
+                .unwrap_or_default();
+            self.actual = fila;
+        }
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        vec![self.izquierda.as_ref(), self.derecha.as_ref()]
+    }
+}
+
+/// Devuelve la clave con la que se comparan los valores en un JOIN: los numeros se normalizan para que, como en la clausula WHERE, 3 sea igual a 3.0.
+fn clave_de_union(valor: &str) -> String {
+    if es_numero(valor) {
+        if let Ok(numero) = valor.parse::<f64>() {
+            return numero.to_string();
+        }
+    }
+    valor.to_string()
+}
+
+/// Agrupa las filas de su entrada segun los valores de las columnas indicadas y devuelve una fila por grupo, en el orden en que aparece cada grupo, con esos valores seguidos de los agregados.
+/// Sin columnas de agrupacion devuelve una unica fila, aunque la entrada no tenga filas. Necesita leer todas las filas de la entrada antes de devolver la primera.
+pub struct Aggregate {
+    entrada: Box<dyn Operador>,
+    agrupacion: Agrupacion,
+    columnas: Vec<ColumnaPlan>,
+    grupos: Option<std::vec::IntoIter<Vec<String>>>,
+}
+
+impl Aggregate {
+    pub fn new(
+        entrada: Box<dyn Operador>,
+        agrupacion: Agrupacion,
+        columnas: Vec<ColumnaPlan>,
+    ) -> Aggregate {
+        Aggregate {
+            entrada,
+            agrupacion,
+            columnas,
+            grupos: None,
+        }
+    }
+}
+
+impl Operador for Aggregate {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        &self.columnas
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        if self.grupos.is_none() {
+            let mut grupos = self.agrupacion.grupos();
+            while let Some(fila) = self.entrada.siguiente()? {
+                self.agrupacion.agregar(&mut grupos, &fila);
+            }
+            self.grupos = Some(self.agrupacion.filas(grupos)?.into_iter());
+        }
+        Ok(self.grupos.as_mut().and_then(|grupos| grupos.next()))
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        vec![self.entrada.as_ref()]
+    }
+}
+
+/// Indica como agrupar las filas: los indices de las columnas de agrupacion y los agregados, cada uno con el indice de la columna a la que se aplica, o None en COUNT(*).
+#[derive(Debug, Clone)]
+pub struct Agrupacion {
+    pub indices_grupo: Vec<usize>,
+    pub agregados: Vec<(Agregado, Option<usize>)>,
+}
+
+/// Representa los grupos armados con parte de las filas: los valores de las columnas de agrupacion de cada grupo, en el orden en que aparecio, junto con sus agregados parciales.
+pub struct Grupos {
+    grupos: Vec<(Vec<String>, Vec<Acumulador>)>,
+    posicion_grupo: HashMap<Vec<String>, usize>,
+}
+
+impl Agrupacion {
+    /// Devuelve los grupos sin ninguna fila. Sin columnas de agrupacion ya existe el unico grupo, para que el resultado tenga una fila aunque no haya filas de entrada.
+    pub fn grupos(&self) -> Grupos {
+        let mut grupos = Grupos {
+            grupos: Vec::new(),
+            posicion_grupo: HashMap::new(),
+        };
+        if self.indices_grupo.is_empty() {
+            self.grupo(&mut grupos, Vec::new());
+        }
+        grupos
+    }
+
+    /// Devuelve la posicion del grupo con esos valores, creandolo si todavia no existe.
+    fn grupo(&self, grupos: &mut Grupos, clave: Vec<String>) -> usize {
+        if let Some(&posicion) = grupos.posicion_grupo.get(&clave) {
+            return posicion;
+        }
+        let acumuladores = self
+            .agregados
+            .iter()
+            .map(|(agregado, _)| Acumulador::new(agregado.funcion))
+            .collect();
+        grupos
+            .posicion_grupo
+            .insert(clave.clone(), grupos.grupos.len());
+        grupos.grupos.push((clave, acumuladores));
+        grupos.grupos.len() - 1
+    }
+
+    /// Agrega una fila a su grupo. Los valores nulos no se tienen en cuenta, salvo en COUNT(*).
+    pub fn agregar(&self, grupos: &mut Grupos, fila: &[String]) {
+        let clave: Vec<String> = self
+            .indices_grupo
+            .iter()
+            .map(|&i| fila.get(i).cloned().unwrap_or_default())
+            .collect();
+        let posicion = self.grupo(grupos, clave);
+        for ((_, indice), acumulador) in self.agregados.iter().zip(&mut grupos.grupos[posicion].1) {
+            match indice {
+                Some(indice) => {
+                    let valor = fila.get(*indice).map_or("", |v| v.as_str());
+                    if !valor.is_empty() {
+                        acumulador.agregar(valor);
+                    }
+                }
+                None => acumulador.agregar(""),
+            }
+        }
+    }
+
+    /// Une a los grupos los armados con las filas que les siguen, como si esas filas se hubieran agregado a continuacion.
+    pub fn unir(&self, grupos: &mut Grupos, siguientes: Grupos) {
+        for (clave, acumuladores) in siguientes.grupos {
+            let posicion = self.grupo(grupos, clave);
+            for (acumulador, siguiente) in grupos.grupos[posicion].1.iter_mut().zip(acumuladores) {
+                acumulador.unir(siguiente);
+            }
+        }
+    }
+
+    /// Devuelve una fila por grupo, con los valores de las columnas de agrupacion seguidos de los agregados.
+    pub fn filas(&self, grupos: Grupos) -> Result<Vec<Vec<String>>, Errores> {
+        let mut filas: Vec<Vec<String>> = Vec::new();
+        for (clave, acumuladores) in grupos.grupos {
+            let mut fila = clave;
+            for ((agregado, _), acumulador) in self.agregados.iter().zip(&acumuladores) {
+                fila.push(acumulador.valor(agregado)?);
+            }
+            filas.push(fila);
+        }
+        Ok(filas)
+    }
+}
+
+/// Representa el valor parcial de un agregado, calculado con parte de los valores no nulos de un grupo. Dos valores parciales pueden unirse sin perder precision, por lo que el resultado no depende de como se repartieron los valores.
+/// SUM y AVG solo admiten numeros y los suman en forma exacta; MIN y MAX ordenan los numeros antes que los textos, y entre valores iguales se quedan con el primero y el ultimo respectivamente.
+#[derive(Debug, Clone)]
+pub enum Acumulador {
+    Cantidad(usize),
+    Minimo(Option<String>),
+    Maximo(Option<String>),
+    Suma {
+        suma: Option<Decimal>,
+        cantidad: usize,
+        enteros: bool,
+        /// El primer valor que no es un numero, si hubo alguno.
+        invalido: Option<String>,
+    },
+}
+
+impl Acumulador {
+    pub fn new(funcion: FuncionAgregada) -> Acumulador {
+        match funcion {
+            FuncionAgregada::Count => Acumulador::Cantidad(0),
+            FuncionAgregada::Min => Acumulador::Minimo(None),
+            FuncionAgregada::Max => Acumulador::Maximo(None),
+            FuncionAgregada::Sum | FuncionAgregada::Avg => Acumulador::Suma {
+                suma: Some(Decimal::default()),
+                cantidad: 0,
+                enteros: true,
+                invalido: None,
+            },
+        }
+    }
+
+    pub fn agregar(&mut self, valor: &str) {
+        match self {
+            Acumulador::Cantidad(cantidad) => *cantidad += 1,
+            Acumulador::Minimo(minimo) => {
+                if minimo
+                    .as_ref()
+                    .is_none_or(|minimo| comparar_orden_total(valor, minimo).is_lt())
+                {
+                    *minimo = Some(valor.to_string());
+                }
+            }
+            Acumulador::Maximo(maximo) => {
+                if maximo
+                    .as_ref()
+                    .is_none_or(|maximo| comparar_orden_total(valor, maximo).is_ge())
+                {
+                    *maximo = Some(valor.to_string());
+                }
+            }
+            Acumulador::Suma {
+                suma,
+                cantidad,
+                enteros,
+                invalido,
+            } => {
+                if invalido.is_none() && !es_numero(valor) {
+                    *invalido = Some(valor.to_string());
+                }
+                *suma = suma.and_then(|suma| suma.sumar(Decimal::desde_texto(valor)?));
+                *cantidad += 1;
+                *enteros = *enteros && valor.parse::<i64>().is_ok();
+            }
+        }
+    }
+
+    /// Une al valor parcial el calculado con los valores que le siguen.
+    pub fn unir(&mut self, siguiente: Acumulador) {
+        match (self, siguiente) {
+            (Acumulador::Cantidad(a), Acumulador::Cantidad(b)) => *a += b,
+            (acumulador @ Acumulador::Minimo(_), Acumulador::Minimo(Some(b)))
+            | (acumulador @ Acumulador::Maximo(_), Acumulador::Maximo(Some(b))) => {
+                acumulador.agregar(&b)
+            }
+            (
+                Acumulador::Suma {
+                    suma,
+                    cantidad,
+                    enteros,
+                    invalido,
+                },
+                Acumulador::Suma {
+                    suma: suma_siguiente,
+                    cantidad: cantidad_siguiente,
+                    enteros: enteros_siguiente,
+                    invalido: invalido_siguiente,
+                },
+            ) => {
+                *suma = match (*suma, suma_siguiente) {
+                    (Some(a), Some(b)) => a.sumar(b),
+                    _ => None,
+                };
+                *cantidad += cantidad_siguiente;
+                *enteros = *enteros && enteros_siguiente;
+                if invalido.is_none() {
+                    *invalido = invalido_siguiente;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Devuelve el valor del agregado. Sin valores, los agregados salvo COUNT son nulos.
+    pub fn valor(&self, agregado: &Agregado) -> Result<String, Errores> {
+        match self {
+            Acumulador::Cantidad(cantidad) => Ok(cantidad.to_string()),
+            Acumulador::Minimo(valor) | Acumulador::Maximo(valor) => {
+                Ok(valor.clone().unwrap_or_default())
+            }
+            Acumulador::Suma {
+                suma,
+                cantidad,
+                enteros,
+                invalido,
+            } => {
+                if let Some(valor) = invalido {
+                    return Err(Errores::nuevo(
+                        TipoError::Error,
+                        format!(
+                            "{} solo puede aplicarse a numeros, pero se encontro el valor {}",
+                            agregado.etiqueta, valor
+                        ),
+                    ));
+                }
+                if *cantidad == 0 {
+                    return Ok(String::new());
+                }
+                let suma = match suma {
+                    Some(suma) => suma,
+                    None => {
+                        return Err(Errores::nuevo(
+                            TipoError::Error,
+                            format!("{} excede el rango de valores admitido", agregado.etiqueta),
+                        ));
+                    }
+                };
+                if agregado.funcion == FuncionAgregada::Avg {
+                    return Ok((suma.a_f64() / *cantidad as f64).to_string());
+                }
+                if *enteros {
+                    return Ok(suma.to_string());
+                }
+                Ok(suma.a_f64().to_string())
+            }
+        }
+    }
+}
+
+/// Representa un numero decimal exacto: mantisa / 10^escala. Permite sumar valores en cualquier orden y obtener siempre el mismo resultado.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Decimal {
+    mantisa: i128,
+    escala: u32,
+}
+
+impl Decimal {
+    /// Interpreta un numero como los que acepta es_numero. Devuelve None si no es un numero o si no puede representarse.
+    pub fn desde_texto(texto: &str) -> Option<Decimal> {
+        if !es_numero(texto) {
+            return None;
+        }
+        let (entera, decimal) = texto.split_once('.').unwrap_or((texto, ""));
+        let mantisa: i128 = format!("{}{}", entera, decimal).parse().ok()?;
+        Some(Decimal {
+            mantisa,
+            escala: decimal.len() as u32,
+        })
+    }
+
+    /// Devuelve la suma, o None si no puede representarse.
+    pub fn sumar(self, otro: Decimal) -> Option<Decimal> {
+        let escala = self.escala.max(otro.escala);
+        let a = self
+            .mantisa
+            .checked_mul(10_i128.checked_pow(escala - self.escala)?)?;
+        let b = otro
+            .mantisa
+            .checked_mul(10_i128.checked_pow(escala - otro.escala)?)?;
+        Some(Decimal {
+            mantisa: a.checked_add(b)?,
+            escala,
+        })
+    }
+
+    /// Devuelve el numero de punto flotante mas cercano.
+    pub fn a_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.escala == 0 {
+            return write!(f, "{}", self.mantisa);
+        }
+        let digitos = format!(
+            "{:0>ancho$}",
+            self.mantisa.unsigned_abs(),
+            ancho = self.escala as usize + 1
+        );
+        let (entera, decimal) = digitos.split_at(digitos.len() - self.escala as usize);
+        let signo = if self.mantisa < 0 { "-" } else { "" };
+        write!(f, "{}{}.{}", signo, entera, decimal)
+    }
+}
+
+/// Mide un operador: cuenta las filas que produce y acumula el tiempo de cada pedido de fila, que incluye el de sus entradas.
+pub struct Medido {
+    operador: Box<dyn Operador>,
+    filas: usize,
+    tiempo: Duration,
+}
+
+impl Medido {
+    pub fn new(operador: Box<dyn Operador>) -> Medido {
+        Medido {
+            operador,
+            filas: 0,
+            tiempo: Duration::ZERO,
+        }
+    }
+}
+
+impl Operador for Medido {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        self.operador.columnas()
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        let inicio = Instant::now();
+        let fila = self.operador.siguiente();
+        self.tiempo += inicio.elapsed();
+        if let Ok(Some(_)) = fila {
+            self.filas += 1;
+        }
+        fila
+    }
+
+    fn posicion(&self) -> Option<u64> {
+        self.operador.posicion()
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        self.operador.entradas()
+    }
+
+    fn medicion(&self) -> Option<(usize, Duration)> {
+        Some((self.filas, self.tiempo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ejecutor::ejecutor_implementacion::test::crear_directorio_prueba;
+
+    fn columnas(tabla: &str, nombres: &[&str]) -> Vec<ColumnaPlan> {
+        nombres
+            .iter()
+            .map(|nombre| ColumnaPlan {
+                tabla: Some(tabla.to_string()),
+                nombre: nombre.to_string(),
+                tipo: None,
+            })
+            .collect()
+    }
+
+    fn drenar(operador: &mut dyn Operador) -> Vec<Vec<String>> {
+        let mut filas = Vec::new();
+        while let Some(fila) = operador.siguiente().unwrap() {
+            filas.push(fila);
+        }
+        filas
+    }
+
+    #[test]
+    fn test_operadores() {
+        let ruta = crear_directorio_prueba(
+            "operadores",
+            &[
+                ("a.csv", "id,b\n1,x\n2,\n3,y\n4,x\n"),
+                ("b.csv", "id,valor\nx,10\ny,20\nx,30\n"),
+            ],
+        );
+        let scan_a = || {
+            TableScan::completo(&format!("{}/a.csv", ruta), columnas("a", &["id", "b"])).unwrap()
+        };
+        let scan_b = || {
+            TableScan::completo(&format!("{}/b.csv", ruta), columnas("b", &["id", "valor"]))
+                .unwrap()
+        };
+
+        let mut scan = scan_a();
+        assert_eq!(scan.siguiente().unwrap().unwrap(), ["1", "x"]);
+        assert_eq!(scan.posicion(), Some(5));
+        assert_eq!(scan.siguiente().unwrap().unwrap(), ["2", ""]);
+        assert_eq!(scan.posicion(), Some(9));
+
+        let mut por_posiciones = TableScan::por_posiciones(
+            &format!("{}/a.csv", ruta),
+            vec![9, 16],
+            columnas("a", &["id", "b"]),
+        )
+        .unwrap();
+        assert_eq!(drenar(&mut por_posiciones), [["2", ""], ["4", "x"]]);
+
+        // Los nulos no se unen y cada fila de la izquierda se une con las de la derecha en su orden.
+        let join = HashJoin::new(Box::new(scan_a()), Box::new(scan_b()), 1, 0);
+        let mut proyeccion =
+            Project::new(Box::new(join), vec![0, 3], columnas("a", &["id", "valor"]));
+        assert_eq!(
+            drenar(&mut proyeccion),
+            [
+                ["1", "10"],
+                ["1", "30"],
+                ["3", "20"],
+                ["4", "10"],
+                ["4", "30"]
+            ]
+        );
+
+        let mut limite = Limit::new(Box::new(Sort::new(Box::new(scan_a()), 0, true)), 2);
+        assert_eq!(drenar(&mut limite), [["4", "x"], ["3", "y"]]);
+        let mut limite = Limit::new(Box::new(Medido::new(Box::new(scan_a()))), 1);
+        assert_eq!(drenar(&mut limite).len(), 1);
+        assert_eq!(limite.entradas()[0].medicion().unwrap().0, 1);
+    }
+}
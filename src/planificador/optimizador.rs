@@ -0,0 +1,662 @@
+use crate::analizador::analizador_implementacion::{columnas_de_expresion, valor_literal};
+use crate::analizador::condicional::Condicional;
+use crate::analizador::expresion::Expresion;
+use crate::errores::errores_implementacion::Errores;
+use crate::esquema::esquema_implementacion::cargar_esquema;
+use crate::esquema::estadisticas::{Estadisticas, EstadisticasColumna};
+use crate::esquema::indices::buscar_con_indice;
+use crate::planificador::planificador_implementacion::{resolver_columna, Acceso, Plan};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+/// Cantidad de filas que se leen del comienzo de una tabla para estimar el largo promedio de sus filas.
+const FILAS_DE_MUESTRA: usize = 100;
+/// Costo de leer una fila saltando a su posicion, en relacion al de leerla al recorrer la tabla en orden.
+const COSTO_LECTURA_POR_POSICION: usize = 4;
+
+/// Esta funcion optimiza el plan logico de un Select aplicando, en orden, las siguientes reglas:
+/// - Cada parte de la clausula WHERE unida con AND que usa columnas de una sola tabla se evalua directamente sobre el Scan de esa tabla, antes de los JOIN.
+/// - Un Scan con una condicion que puede resolverse con un indice lee solo las filas que este indica, si se estima que cuesta menos que recorrer la tabla completa.
+/// - Los JOIN se reordenan comenzando por la tabla que se estima mas chica y agregando cada vez la mas chica de las que se unen con las anteriores, y en cada uno la tabla de hash se arma con la entrada mas chica.
+pub fn optimizar(plan: Plan, path: &String) -> Result<Plan, Errores> {
+    let plan = empujar_filtros(plan)?;
+    let plan = elegir_accesos(plan, path)?;
+    ordenar_joins(plan)
+}
+
+/// Esta funcion estima la cantidad de filas de una tabla dividiendo el tamaño del archivo por el largo promedio de sus primeras filas. Si la tabla tiene menos filas que la muestra, la cantidad es exacta.
+pub fn estimar_filas_tabla(ruta: &str) -> usize {
+    let archivo = match File::open(ruta) {
+        Ok(archivo) => archivo,
+        Err(_) => return 0,
+    };
+    let largo_total = archivo.metadata().map(|m| m.len()).unwrap_or(0);
+    let mut lector = BufReader::new(archivo);
+    let mut linea = String::new();
+    let encabezado = lector.read_line(&mut linea).unwrap_or(0) as u64;
+    let mut leidos: u64 = 0;
+    let mut filas: usize = 0;
+    while filas < FILAS_DE_MUESTRA {
+        linea.clear();
+        match lector.read_line(&mut linea) {
+            Ok(0) | Err(_) => return filas,
+            Ok(largo) => {
+                leidos += largo as u64;
+                filas += 1;
+            }
+        }
+    }
+    let largo_promedio = (leidos / filas as u64).max(1);
+    (largo_total.saturating_sub(encabezado) / largo_promedio) as usize
+}
+
+/// Esta funcion estima la cantidad de filas que produce un nodo del plan.
+/// Si las tablas fueron analizadas con ANALYZE, se usan sus estadisticas: una comparacion de una columna con un valor conserva la fraccion de filas que indica el histograma de la columna, un JOIN produce las filas de ambas entradas divididas por la mayor cantidad de valores distintos de las columnas que une, y una agrupacion produce tantas filas como combinaciones de valores distintos de sus columnas.
+/// Sin estadisticas se supone que una igualdad con un valor conserva una de cada diez filas, una comparacion con un valor una de cada tres y cualquier otra condicion la mitad, que un JOIN produce tantas filas como su entrada mas grande y que cada grupo reune diez filas.
+pub fn filas_estimadas(plan: &Plan) -> usize {
+    match plan {
+        Plan::Scan {
+            filas_estimadas, ..
+        } => *filas_estimadas,
+        Plan::Filter { condicion, entrada } => {
+            let filas = filas_estimadas(entrada);
+            if let Plan::Scan {
+                acceso: Acceso::Indice { .. },
+                ..
+            } = entrada.as_ref()
+            {
+                return filas;
+            }
+            let selectividad: f64 = conjunciones(condicion)
+                .iter()
+                .map(|condicion| selectividad(condicion, entrada))
+                .product();
+            if filas == 0 {
+                0
+            } else {
+                ((filas as f64 * selectividad).ceil() as usize).max(1)
+            }
+        }
+        Plan::Join {
+            izquierda,
+            derecha,
+            columna_izquierda,
+            columna_derecha,
+        } => {
+            let filas_izquierda = filas_estimadas(izquierda);
+            let filas_derecha = filas_estimadas(derecha);
+            match (
+                estadisticas_de_columna(izquierda, columna_izquierda),
+                estadisticas_de_columna(derecha, columna_derecha),
+            ) {
+                (Some((_, a)), Some((_, b))) => {
+                    let distintos = a
+                        .distintos
+                        .min(filas_izquierda)
+                        .max(b.distintos.min(filas_derecha))
+                        .max(1);
+                    (filas_izquierda as f64 * filas_derecha as f64 / distintos as f64).ceil()
+                        as usize
+                }
+                _ => filas_izquierda.max(filas_derecha),
+            }
+        }
+        Plan::Aggregate {
+            agrupar_por,
+            entrada,
+            ..
+        } => {
+            if agrupar_por.is_empty() {
+                return 1;
+            }
+            let filas = filas_estimadas(entrada);
+            let grupos: Option<usize> = agrupar_por.iter().try_fold(1usize, |grupos, columna| {
+                let (_, estadisticas) = estadisticas_de_columna(entrada, columna)?;
+                let distintos = estadisticas.distintos + usize::from(estadisticas.nulos > 0);
+                Some(grupos.saturating_mul(distintos))
+            });
+            match grupos {
+                Some(grupos) => grupos.min(filas).max(1),
+                None => (filas / 10).max(1),
+            }
+        }
+        Plan::Limit { cantidad, entrada } => filas_estimadas(entrada).min(*cantidad),
+        Plan::Sort { entrada, .. }
+        | Plan::Project { entrada, .. }
+        | Plan::Vista { entrada, .. } => filas_estimadas(entrada),
+    }
+}
+
+/// Estima la fraccion de las filas de la entrada que cumplen la condicion.
+fn selectividad(condicion: &Expresion, entrada: &Plan) -> f64 {
+    let condicional = match condicion {
+        Expresion::Operacion { condicional } => condicional,
+        _ => return 0.5,
+    };
+    if let Some(selectividad) = selectividad_con_estadisticas(condicional, entrada) {
+        return selectividad;
+    }
+    match condicional {
+        Condicional::Igual { .. } if compara_con_valor(condicional) => 0.1,
+        _ if compara_con_valor(condicional) => 1.0 / 3.0,
+        _ => 0.5,
+    }
+}
+
+/// Estima con las estadisticas de la columna la fraccion de las filas en las que una comparacion entre la columna y un valor se cumple. Devuelve None si la comparacion no es de ese tipo o la columna no tiene estadisticas.
+fn selectividad_con_estadisticas(condicional: &Condicional, entrada: &Plan) -> Option<f64> {
+    let (miembro1, miembro2) = condicional.miembros();
+    let (columna, valor, invertida) = match (valor_literal(miembro1), valor_literal(miembro2)) {
+        (None, Some(valor)) => (miembro1, valor, false),
+        (Some(valor), None) => (miembro2, valor, true),
+        _ => return None,
+    };
+    let (tabla, columna) = estadisticas_de_columna(entrada, columna)?;
+    let no_nulos = 1.0 - columna.fraccion_nulos(tabla.filas);
+    let igual = columna.fraccion_igual(&valor, tabla.filas);
+    let menor = columna.fraccion_menor(&valor, tabla.filas);
+    let selectividad = match (condicional, invertida) {
+        (Condicional::Igual { .. }, _) => igual,
+        (Condicional::Menor { .. }, false) | (Condicional::Mayor { .. }, true) => menor,
+        (Condicional::MenorIgual { .. }, false) | (Condicional::MayorIgual { .. }, true) => {
+            menor + igual
+        }
+        (Condicional::Mayor { .. }, false) | (Condicional::Menor { .. }, true) => {
+            no_nulos - menor - igual
+        }
+        (Condicional::MayorIgual { .. }, false) | (Condicional::MenorIgual { .. }, true) => {
+            no_nulos - menor
+        }
+    };
+    Some(selectividad.clamp(0.0, 1.0))
+}
+
+/// Busca las estadisticas de la columna en el Scan del que proviene, si la columna no fue transformada por una agrupacion.
+fn estadisticas_de_columna<'a>(
+    plan: &'a Plan,
+    columna: &str,
+) -> Option<(&'a Estadisticas, &'a EstadisticasColumna)> {
+    match plan {
+        Plan::Scan {
+            columnas,
+            estadisticas: Some(estadisticas),
+            ..
+        } => {
+            let indice = resolver_columna(columnas, columna).ok()?;
+            Some((
+                estadisticas,
+                estadisticas.columna(&columnas[indice].nombre)?,
+            ))
+        }
+        Plan::Join {
+            izquierda, derecha, ..
+        } => estadisticas_de_columna(izquierda, columna)
+            .or_else(|| estadisticas_de_columna(derecha, columna)),
+        Plan::Filter { entrada, .. } | Plan::Sort { entrada, .. } | Plan::Limit { entrada, .. } => {
+            estadisticas_de_columna(entrada, columna)
+        }
+        _ => None,
+    }
+}
+
+fn compara_con_valor(condicional: &Condicional) -> bool {
+    let (miembro1, miembro2) = condicional.miembros();
+    valor_literal(miembro1).is_some() != valor_literal(miembro2).is_some()
+}
+
+/// Separa la expresion en las partes unidas por AND.
+fn conjunciones(expresion: &Expresion) -> Vec<Expresion> {
+    match expresion {
+        Expresion::And { izquierda, derecha } => {
+            let mut partes = conjunciones(izquierda);
+            partes.extend(conjunciones(derecha));
+            partes
+        }
+        otra => vec![otra.clone()],
+    }
+}
+
+/// Une las partes con AND, o devuelve None si no hay ninguna.
+fn unir_conjunciones(partes: Vec<Expresion>) -> Option<Expresion> {
+    partes
+        .into_iter()
+        .reduce(|izquierda, derecha| Expresion::And {
+            izquierda: Box::new(izquierda),
+            derecha: Box::new(derecha),
+        })
+}
+
+/// Agrega un Filter sobre el plan si hay alguna condicion.
+fn filtrar(plan: Plan, partes: Vec<Expresion>) -> Plan {
+    match unir_conjunciones(partes) {
+        Some(condicion) => Plan::Filter {
+            condicion,
+            entrada: Box::new(plan),
+        },
+        None => plan,
+    }
+}
+
+/// Aplica a cada nodo del plan la funcion, que recibe el nodo con sus entradas ya transformadas.
+fn transformar<F>(plan: Plan, funcion: &F) -> Result<Plan, Errores>
+where
+    F: Fn(Plan) -> Result<Plan, Errores>,
+{
+    let plan = match plan {
+        Plan::Scan { .. } => plan,
+        Plan::Filter { condicion, entrada } => Plan::Filter {
+            condicion,
+            entrada: Box::new(transformar(*entrada, funcion)?),
+        },
+        Plan::Join {
+            izquierda,
+            derecha,
+            columna_izquierda,
+            columna_derecha,
+        } => Plan::Join {
+            izquierda: Box::new(transformar(*izquierda, funcion)?),
+            derecha: Box::new(transformar(*derecha, funcion)?),
+            columna_izquierda,
+            columna_derecha,
+        },
+        Plan::Aggregate {
+            agrupar_por,
+            agregados,
+            entrada,
+        } => Plan::Aggregate {
+            agrupar_por,
+            agregados,
+            entrada: Box::new(transformar(*entrada, funcion)?),
+        },
+        Plan::Sort {
+            columna,
+            descendente,
+            entrada,
+        } => Plan::Sort {
+            columna,
+            descendente,
+            entrada: Box::new(transformar(*entrada, funcion)?),
+        },
+        Plan::Limit { cantidad, entrada } => Plan::Limit {
+            cantidad,
+            entrada: Box::new(transformar(*entrada, funcion)?),
+        },
+        Plan::Project {
+            campos,
+            etiquetas,
+            entrada,
+        } => Plan::Project {
+            campos,
+            etiquetas,
+            entrada: Box::new(transformar(*entrada, funcion)?),
+        },
+        Plan::Vista { nombre, entrada } => Plan::Vista {
+            nombre,
+            entrada: Box::new(transformar(*entrada, funcion)?),
+        },
+    };
+    funcion(plan)
+}
+
+/// Mueve debajo de los JOIN las partes de cada Filter que solo usan columnas de uno de sus lados.
+fn empujar_filtros(plan: Plan) -> Result<Plan, Errores> {
+    match plan {
+        Plan::Filter { condicion, entrada } => match *entrada {
+            Plan::Join {
+                izquierda,
+                derecha,
+                columna_izquierda,
+                columna_derecha,
+            } => {
+                let columnas_izquierda = izquierda.columnas()?;
+                let columnas_derecha = derecha.columnas()?;
+                let mut a_izquierda: Vec<Expresion> = Vec::new();
+                let mut a_derecha: Vec<Expresion> = Vec::new();
+                let mut restantes: Vec<Expresion> = Vec::new();
+                for parte in conjunciones(&condicion) {
+                    let columnas = columnas_de_expresion(&parte);
+                    let todas_en = |lado| {
+                        !columnas.is_empty()
+                            && columnas.iter().all(|c| resolver_columna(lado, c).is_ok())
+                    };
+                    if todas_en(&columnas_izquierda) {
+                        a_izquierda.push(parte);
+                    } else if todas_en(&columnas_derecha) {
+                        a_derecha.push(parte);
+                    } else {
+                        restantes.push(parte);
+                    }
+                }
+                let join = Plan::Join {
+                    izquierda: Box::new(empujar_filtros(filtrar(*izquierda, a_izquierda))?),
+                    derecha: Box::new(empujar_filtros(filtrar(*derecha, a_derecha))?),
+                    columna_izquierda,
+                    columna_derecha,
+                };
+                Ok(filtrar(join, restantes))
+            }
+            entrada => Ok(Plan::Filter {
+                condicion,
+                entrada: Box::new(empujar_filtros(entrada)?),
+            }),
+        },
+        Plan::Scan { .. } => Ok(plan),
+        otro => transformar_hijos(otro, &empujar_filtros),
+    }
+}
+
+/// Aplica la funcion a las entradas del nodo, sin transformar el nodo.
+fn transformar_hijos<F>(plan: Plan, funcion: &F) -> Result<Plan, Errores>
+where
+    F: Fn(Plan) -> Result<Plan, Errores>,
+{
+    Ok(match plan {
+        Plan::Scan { .. } => plan,
+        Plan::Filter { condicion, entrada } => Plan::Filter {
+            condicion,
+            entrada: Box::new(funcion(*entrada)?),
+        },
+        Plan::Join {
+            izquierda,
+            derecha,
+            columna_izquierda,
+            columna_derecha,
+        } => Plan::Join {
+            izquierda: Box::new(funcion(*izquierda)?),
+            derecha: Box::new(funcion(*derecha)?),
+            columna_izquierda,
+            columna_derecha,
+        },
+        Plan::Aggregate {
+            agrupar_por,
+            agregados,
+            entrada,
+        } => Plan::Aggregate {
+            agrupar_por,
+            agregados,
+            entrada: Box::new(funcion(*entrada)?),
+        },
+        Plan::Sort {
+            columna,
+            descendente,
+            entrada,
+        } => Plan::Sort {
+            columna,
+            descendente,
+            entrada: Box::new(funcion(*entrada)?),
+        },
+        Plan::Limit { cantidad, entrada } => Plan::Limit {
+            cantidad,
+            entrada: Box::new(funcion(*entrada)?),
+        },
+        Plan::Project {
+            campos,
+            etiquetas,
+            entrada,
+        } => Plan::Project {
+            campos,
+            etiquetas,
+            entrada: Box::new(funcion(*entrada)?),
+        },
+        Plan::Vista { nombre, entrada } => Plan::Vista {
+            nombre,
+            entrada: Box::new(funcion(*entrada)?),
+        },
+    })
+}
+
+/// Elige como leer cada tabla sobre la que hay un Filter: con el indice que puede resolver la condicion, si se estima que es mas barato, o completa.
+/// Se estima que recorrer la tabla cuesta una unidad por fila, y que usar el indice cuesta una busqueda binaria en cada tramo mas la lectura de cada fila encontrada saltando a su posicion.
+fn elegir_accesos(plan: Plan, path: &String) -> Result<Plan, Errores> {
+    transformar(plan, &|plan| match plan {
+        Plan::Filter { condicion, entrada } => match *entrada {
+            Plan::Scan {
+                tabla,
+                columnas,
+                acceso: Acceso::Completo,
+                filas_estimadas,
+                estadisticas,
+            } => {
+                let nombres: Vec<String> = columnas.iter().map(|c| c.nombre.to_string()).collect();
+                let esquema = cargar_esquema(path, &tabla, &nombres)?;
+                let indice_columnas: HashMap<String, usize> = nombres
+                    .iter()
+                    .enumerate()
+                    .map(|(i, nombre)| (nombre.to_string(), i))
+                    .collect();
+                let filtro = sin_calificar(&condicion, &tabla);
+                let (acceso, filas_estimadas) =
+                    match buscar_con_indice(path, &tabla, &esquema, &indice_columnas, &filtro) {
+                        Some(busqueda) => {
+                            let busqueda_binaria =
+                                (usize::BITS - busqueda.entradas.leading_zeros()) as usize;
+                            let costo_indice = busqueda.tramos * busqueda_binaria
+                                + busqueda.posiciones.len() * COSTO_LECTURA_POR_POSICION;
+                            if costo_indice < busqueda.entradas.max(filas_estimadas) {
+                                let acceso = Acceso::Indice {
+                                    nombre: busqueda.indice.nombre,
+                                    condicion: busqueda.condicion,
+                                    filtro,
+                                };
+                                (acceso, busqueda.posiciones.len())
+                            } else {
+                                (Acceso::Completo, busqueda.entradas)
+                            }
+                        }
+                        None => (Acceso::Completo, filas_estimadas),
+                    };
+                Ok(Plan::Filter {
+                    condicion,
+                    entrada: Box::new(Plan::Scan {
+                        tabla,
+                        columnas,
+                        acceso,
+                        filas_estimadas,
+                        estadisticas,
+                    }),
+                })
+            }
+            entrada => Ok(Plan::Filter {
+                condicion,
+                entrada: Box::new(entrada),
+            }),
+        },
+        otro => Ok(otro),
+    })
+}
+
+/// Devuelve la expresion con los miembros calificados con el nombre de la tabla ("tabla.columna") reemplazados por el nombre de la columna, tal como se buscan en los indices.
+fn sin_calificar(expresion: &Expresion, tabla: &str) -> Expresion {
+    let prefijo = format!("{}.", tabla);
+    let quitar = |miembro: &String| {
+        miembro
+            .strip_prefix(&prefijo)
+            .unwrap_or(miembro)
+            .to_string()
+    };
+    match expresion {
+        Expresion::Not { derecha } => Expresion::Not {
+            derecha: Box::new(sin_calificar(derecha, tabla)),
+        },
+        Expresion::And { izquierda, derecha } => Expresion::And {
+            izquierda: Box::new(sin_calificar(izquierda, tabla)),
+            derecha: Box::new(sin_calificar(derecha, tabla)),
+        },
+        Expresion::Or { izquierda, derecha } => Expresion::Or {
+            izquierda: Box::new(sin_calificar(izquierda, tabla)),
+            derecha: Box::new(sin_calificar(derecha, tabla)),
+        },
+        Expresion::Operacion { condicional } => {
+            use Condicional::*;
+            let (miembro1, miembro2) = condicional.miembros();
+            let (miembro1, miembro2) = (quitar(miembro1), quitar(miembro2));
+            let condicional = match condicional {
+                Mayor { .. } => Mayor { miembro1, miembro2 },
+                MayorIgual { .. } => MayorIgual { miembro1, miembro2 },
+                Menor { .. } => Menor { miembro1, miembro2 },
+                MenorIgual { .. } => MenorIgual { miembro1, miembro2 },
+                Igual { .. } => Igual { miembro1, miembro2 },
+            };
+            Expresion::Operacion { condicional }
+        }
+        otra => otra.clone(),
+    }
+}
+
+/// Reordena cada cadena de JOIN: comienza por la entrada que se estima mas chica y agrega cada vez la mas chica de las que tienen una condicion con las ya unidas.
+/// En cada JOIN la entrada mas chica queda a la derecha, que es con la que se arma la tabla de hash.
+fn ordenar_joins(plan: Plan) -> Result<Plan, Errores> {
+    match plan {
+        Plan::Join { .. } => {
+            let mut entradas: Vec<Plan> = Vec::new();
+            let mut condiciones: Vec<(String, String)> = Vec::new();
+            separar_joins(plan.clone(), &mut entradas, &mut condiciones);
+            let mut entradas = entradas
+                .into_iter()
+                .map(ordenar_joins)
+                .collect::<Result<Vec<Plan>, Errores>>()?;
+            let primera = (0..entradas.len())
+                .min_by_key(|&i| filas_estimadas(&entradas[i]))
+                .unwrap_or(0);
+            let mut unido = entradas.remove(primera);
+            while !entradas.is_empty() {
+                let columnas_unidas = unido.columnas()?;
+                let mut elegida: Option<(usize, usize, String, String)> = None;
+                for (i, entrada) in entradas.iter().enumerate() {
+                    let columnas_entrada = entrada.columnas()?;
+                    for (c, (a, b)) in condiciones.iter().enumerate() {
+                        let orientada = if resolver_columna(&columnas_unidas, a).is_ok()
+                            && resolver_columna(&columnas_entrada, b).is_ok()
+                        {
+                            Some((a, b))
+                        } else if resolver_columna(&columnas_unidas, b).is_ok()
+                            && resolver_columna(&columnas_entrada, a).is_ok()
+                        {
+                            Some((b, a))
+                        } else {
+                            None
+                        };
+                        let mejor = elegida.as_ref().is_none_or(|(j, ..)| {
+                            filas_estimadas(entrada) < filas_estimadas(&entradas[*j])
+                        });
+                        if let (Some((a, b)), true) = (orientada, mejor) {
+                            elegida = Some((i, c, a.to_string(), b.to_string()));
+                        }
+                    }
+                }
+                // Si ninguna entrada se une con las anteriores se conserva el orden original.
+                let (i, c, columna_unida, columna_entrada) = match elegida {
+                    Some(elegida) => elegida,
+                    None => return transformar_hijos(plan, &ordenar_joins),
+                };
+                condiciones.remove(c);
+                let entrada = entradas.remove(i);
+                unido = if filas_estimadas(&entrada) <= filas_estimadas(&unido) {
+                    Plan::Join {
+                        izquierda: Box::new(unido),
+                        derecha: Box::new(entrada),
+                        columna_izquierda: columna_unida,
+                        columna_derecha: columna_entrada,
+                    }
+                } else {
+                    Plan::Join {
+                        izquierda: Box::new(entrada),
+                        derecha: Box::new(unido),
+                        columna_izquierda: columna_entrada,
+                        columna_derecha: columna_unida,
+                    }
+                };
+            }
+            Ok(unido)
+        }
+        Plan::Scan { .. } => Ok(plan),
+        otro => transformar_hijos(otro, &ordenar_joins),
+    }
+}
+
+/// Separa una cadena de JOIN en las entradas que une y las condiciones de cada union.
+fn separar_joins(plan: Plan, entradas: &mut Vec<Plan>, condiciones: &mut Vec<(String, String)>) {
+    match plan {
+        Plan::Join {
+            izquierda,
+            derecha,
+            columna_izquierda,
+            columna_derecha,
+        } => {
+            separar_joins(*izquierda, entradas, condiciones);
+            separar_joins(*derecha, entradas, condiciones);
+            condiciones.push((columna_izquierda, columna_derecha));
+        }
+        otro => entradas.push(otro),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::optimizar;
+    use crate::comandos::comandos_implementacion::parsear;
+    use crate::ejecutor::ejecutor_implementacion::ejecutar_consulta;
+    use crate::ejecutor::ejecutor_implementacion::test::crear_directorio_prueba;
+    use crate::planificador::planificador_implementacion::{construir_plan, Acceso, Plan};
+
+    /// Devuelve las tablas de los Scan del plan en el orden en que aparecen, indicando con un * las que se leen con un indice.
+    fn scans(plan: &Plan) -> Vec<String> {
+        match plan {
+            Plan::Scan { tabla, acceso, .. } => match acceso {
+                Acceso::Completo | Acceso::Catalogo => vec![tabla.to_string()],
+                Acceso::Indice { .. } => vec![format!("{}*", tabla)],
+            },
+            otro => otro.hijos().into_iter().flat_map(scans).collect(),
+        }
+    }
+
+    #[test]
+    fn test_optimizador() {
+        let mut ordenes = "id,id_cliente,id_producto\n".to_string();
+        for id in 1..=200 {
+            ordenes.push_str(&format!("{},{},{}\n", id, id % 20, id % 5));
+        }
+        let mut clientes = "id,nombre\n".to_string();
+        for id in 0..20 {
+            clientes.push_str(&format!("{},cliente{}\n", id, id));
+        }
+        let ruta = crear_directorio_prueba(
+            "optimizador",
+            &[
+                ("ordenes.csv", ordenes.as_str()),
+                ("clientes.csv", clientes.as_str()),
+                ("productos.csv", "id,descripcion\n0,a\n1,b\n2,c\n3,d\n4,e\n"),
+            ],
+        );
+        ejecutar_consulta("CREATE INDEX ordenes_id ON ordenes (id)", &ruta).unwrap();
+        let plan = |consulta: &str| {
+            let comando = parsear(consulta).unwrap();
+            optimizar(construir_plan(&comando, &ruta).unwrap(), &ruta).unwrap()
+        };
+        assert_eq!(
+            scans(&plan("SELECT * FROM ordenes WHERE id = 7")),
+            ["ordenes*"]
+        );
+        assert_eq!(
+            scans(&plan("SELECT * FROM ordenes WHERE id > 7")),
+            ["ordenes"]
+        );
+        assert_eq!(
+            scans(&plan("SELECT * FROM ordenes WHERE id > 190 OR id < 3")),
+            ["ordenes"]
+        );
+        assert_eq!(
+            scans(&plan(
+                "SELECT nombre FROM ordenes JOIN clientes ON id_cliente = clientes.id JOIN productos ON id_producto = productos.id"
+            )),
+            ["ordenes", "productos", "clientes"]
+        );
+        assert_eq!(
+            scans(&plan(
+                "SELECT nombre FROM clientes JOIN ordenes ON clientes.id = id_cliente WHERE ordenes.id = 40"
+            )),
+            ["clientes", "ordenes*"]
+        );
+    }
+}
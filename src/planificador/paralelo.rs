@@ -0,0 +1,470 @@
+use crate::analizador::expresion::Expresion;
+use crate::analizador::predicado::Predicado;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::esquema::indices::LineasConPosicion;
+use crate::planificador::operadores::{Agrupacion, Operador};
+use crate::planificador::planificador_implementacion::{mapa_columnas, ColumnaPlan};
+use std::env;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+const VARIABLE_HILOS: &str = "SQL_RUSTICO_HILOS";
+
+/// Cantidad minima de bytes de la tabla que lee cada hilo. Las tablas mas chicas se leen con menos hilos, o con uno solo.
+const TAMANO_MINIMO_RANGO: u64 = 1 << 20;
+
+/// Cantidad de filas que un hilo envia juntas al operador que las consume.
+const FILAS_POR_LOTE: usize = 1024;
+
+/// Cantidad de lotes que un hilo puede tener leidos sin que se hayan consumido. Acota la memoria que usa un Scan cuyas filas se consumen de a poco.
+const LOTES_EN_ESPERA: usize = 4;
+
+/// Representa un rango de bytes del archivo de una tabla que comienza al principio de una linea y termina al final de otra.
+pub type Rango = (u64, u64);
+
+/// Esta funcion devuelve la cantidad de hilos con los que se leen las tablas, configurable mediante la variable de entorno SQL_RUSTICO_HILOS. Por defecto es la cantidad de procesadores disponibles.
+pub fn cantidad_de_hilos() -> usize {
+    match env::var(VARIABLE_HILOS)
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+    {
+        Some(hilos) => hilos.max(1),
+        None => thread::available_parallelism().map_or(1, |hilos| hilos.get()),
+    }
+}
+
+/// Esta funcion divide las filas de la tabla en los rangos que se leen en paralelo, uno por hilo, de manera que cada hilo lea al menos TAMANO_MINIMO_RANGO bytes.
+pub fn rangos_de_tabla(ruta: &str) -> Result<Vec<Rango>, Errores> {
+    let tamano = match std::fs::metadata(ruta) {
+        Ok(metadatos) => metadatos.len(),
+        Err(_) => {
+            return Err(Errores::nuevo(
+                TipoError::Error,
+                "No se pudo abrir el archivo".to_string(),
+            ));
+        }
+    };
+    let partes = (tamano / TAMANO_MINIMO_RANGO).clamp(1, cantidad_de_hilos() as u64);
+    dividir_en_rangos(ruta, partes as usize)
+}
+
+/// Esta funcion divide las filas de la tabla, sin el encabezado, en como maximo la cantidad de partes indicada, de tamaños parecidos. Cada rango comienza al principio de una linea, y los rangos se devuelven en el orden del archivo.
+pub fn dividir_en_rangos(ruta: &str, partes: usize) -> Result<Vec<Rango>, Errores> {
+    let error = || Errores::nuevo(TipoError::Error, "Error leyendo la tabla".to_string());
+    let archivo = File::open(ruta)
+        .map_err(|_| Errores::nuevo(TipoError::Error, "No se pudo abrir el archivo".to_string()))?;
+    let tamano = archivo.metadata().map_err(|_| error())?.len();
+    let mut lector = BufReader::new(archivo);
+    let mut linea: Vec<u8> = Vec::new();
+    let inicio = lector.read_until(b'\n', &mut linea).map_err(|_| error())? as u64;
+    let mut limites: Vec<u64> = vec![inicio];
+    let partes = partes.max(1) as u64;
+    for i in 1..partes {
+        let objetivo = inicio + (tamano - inicio) * i / partes;
+        if objetivo <= *limites.last().unwrap_or(&inicio) {
+            continue;
+        }
+        // El limite es el comienzo de la primera linea que empieza en el objetivo o despues.
+        lector
+            .seek(SeekFrom::Start(objetivo - 1))
+            .map_err(|_| error())?;
+        linea.clear();
+        let leidos = lector.read_until(b'\n', &mut linea).map_err(|_| error())? as u64;
+        let limite = objetivo - 1 + leidos;
+        if limite > *limites.last().unwrap_or(&inicio) && limite < tamano {
+            limites.push(limite);
+        }
+    }
+    limites.push(tamano);
+    Ok(limites
+        .windows(2)
+        .map(|par| (par[0], par[1]))
+        .filter(|(desde, hasta)| desde < hasta)
+        .collect())
+}
+
+/// Recorre las lineas del rango, con su posicion, llamando a procesar con las que cumplen la condicion. Se detiene si procesar devuelve false.
+fn leer_rango<F>(
+    ruta: &str,
+    (desde, hasta): Rango,
+    filtro: Option<&Predicado>,
+    mut procesar: F,
+) -> Result<(), Errores>
+where
+    F: FnMut(u64, &str) -> bool,
+{
+    let mut archivo = File::open(ruta)
+        .map_err(|_| Errores::nuevo(TipoError::Error, "No se pudo abrir el archivo".to_string()))?;
+    if archivo.seek(SeekFrom::Start(desde)).is_err() {
+        return Err(Errores::nuevo(
+            TipoError::Error,
+            "Error leyendo la tabla".to_string(),
+        ));
+    }
+    for linea in LineasConPosicion::new(BufReader::new(archivo), desde) {
+        let (posicion, linea) = linea?;
+        if posicion >= hasta {
+            break;
+        }
+        if filtro.is_some_and(|filtro| !filtro.cumple_linea(&linea)) {
+            continue;
+        }
+        if !procesar(posicion, &linea) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn separar(linea: &str) -> Vec<String> {
+    linea.split(',').map(|s| s.trim().to_string()).collect()
+}
+
+type Lote = Result<Vec<(u64, Vec<String>)>, Errores>;
+
+/// Lee las filas de una tabla con un hilo por rango, evaluando en cada hilo la condicion, si la hay, antes de separar los valores de cada linea.
+/// Las filas se producen en el mismo orden que en el archivo: primero todas las del primer rango, luego las del segundo, y asi siguiendo, mientras los hilos de los rangos siguientes ya las van leyendo.
+/// Si el operador se descarta antes de leer todas las filas, por ejemplo debajo de un Limit, los hilos dejan de leer.
+pub struct ScanParalelo {
+    columnas: Vec<ColumnaPlan>,
+    receptores: std::vec::IntoIter<Receiver<Lote>>,
+    actual: Option<Receiver<Lote>>,
+    lote: std::vec::IntoIter<(u64, Vec<String>)>,
+    posicion: Option<u64>,
+}
+
+impl ScanParalelo {
+    pub fn new(
+        ruta: &str,
+        rangos: Vec<Rango>,
+        columnas: Vec<ColumnaPlan>,
+        condicion: Option<&Expresion>,
+    ) -> ScanParalelo {
+        let filtro = Arc::new(
+            condicion.map(|condicion| Predicado::compilar(condicion, &mapa_columnas(&columnas))),
+        );
+        let mut receptores = Vec::new();
+        for rango in rangos {
+            let (emisor, receptor) = sync_channel::<Lote>(LOTES_EN_ESPERA);
+            let ruta = ruta.to_string();
+            let filtro = Arc::clone(&filtro);
+            thread::spawn(move || {
+                let mut lote: Vec<(u64, Vec<String>)> = Vec::new();
+                let mut conectado = true;
+                let resultado =
+                    leer_rango(&ruta, rango, filtro.as_ref().as_ref(), |posicion, linea| {
+                        lote.push((posicion, separar(linea)));
+                        if lote.len() == FILAS_POR_LOTE {
+                            conectado = emisor.send(Ok(std::mem::take(&mut lote))).is_ok();
+                        }
+                        conectado
+                    });
+                let _ = match resultado {
+                    Ok(()) if conectado && !lote.is_empty() => emisor.send(Ok(lote)),
+                    Ok(()) => Ok(()),
+                    Err(error) => emisor.send(Err(error)),
+                };
+            });
+            receptores.push(receptor);
+        }
+        ScanParalelo {
+            columnas,
+            receptores: receptores.into_iter(),
+            actual: None,
+            lote: Vec::new().into_iter(),
+            posicion: None,
+        }
+    }
+}
+
+impl Operador for ScanParalelo {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        &self.columnas
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        loop {
+            if let Some((posicion, fila)) = self.lote.next() {
+                self.posicion = Some(posicion);
+                return Ok(Some(fila));
+            }
+            if self.actual.is_none() {
+                self.actual = self.receptores.next();
+            }
+            let lote = match &self.actual {
+                Some(receptor) => receptor.recv(),
+                None => return Ok(None),
+            };
+            match lote {
+                Ok(lote) => self.lote = lote?.into_iter(),
+                // El hilo del rango termino y ya se consumieron todas sus filas.
+                Err(_) => self.actual = None,
+            }
+        }
+    }
+
+    fn posicion(&self) -> Option<u64> {
+        self.posicion
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        Vec::new()
+    }
+}
+
+/// Agrupa las filas de una tabla con un hilo por rango: cada hilo evalua la condicion, si la hay, y arma los grupos con los agregados parciales de sus filas.
+/// Los grupos de cada rango se unen a los de los rangos anteriores en el orden del archivo, de manera que el resultado es el mismo que al agrupar las filas con un unico hilo.
+pub struct AggregateParalelo {
+    ruta: String,
+    rangos: Vec<Rango>,
+    columnas_entrada: Vec<ColumnaPlan>,
+    condicion: Option<Expresion>,
+    agrupacion: Agrupacion,
+    columnas: Vec<ColumnaPlan>,
+    grupos: Option<std::vec::IntoIter<Vec<String>>>,
+}
+
+impl AggregateParalelo {
+    /// Crea el operador. Las columnas de entrada son las de la tabla, y las columnas las de las filas que produce.
+    pub fn new(
+        ruta: &str,
+        rangos: Vec<Rango>,
+        columnas_entrada: Vec<ColumnaPlan>,
+        condicion: Option<&Expresion>,
+        agrupacion: Agrupacion,
+        columnas: Vec<ColumnaPlan>,
+    ) -> AggregateParalelo {
+        AggregateParalelo {
+            ruta: ruta.to_string(),
+            rangos,
+            columnas_entrada,
+            condicion: condicion.cloned(),
+            agrupacion,
+            columnas,
+            grupos: None,
+        }
+    }
+
+    fn agrupar(&self) -> Result<Vec<Vec<String>>, Errores> {
+        let filtro = self.condicion.as_ref().map(|condicion| {
+            Predicado::compilar(condicion, &mapa_columnas(&self.columnas_entrada))
+        });
+        let parciales = thread::scope(|alcance| {
+            let hilos: Vec<_> = self
+                .rangos
+                .iter()
+                .map(|&rango| {
+                    let filtro = filtro.as_ref();
+                    alcance.spawn(move || {
+                        let mut grupos = self.agrupacion.grupos();
+                        leer_rango(&self.ruta, rango, filtro, |_, linea| {
+                            self.agrupacion.agregar(&mut grupos, &separar(linea));
+                            true
+                        })?;
+                        Ok(grupos)
+                    })
+                })
+                .collect();
+            hilos
+                .into_iter()
+                .map(|hilo| {
+                    hilo.join().unwrap_or_else(|_| {
+                        Err(Errores::nuevo(
+                            TipoError::Error,
+                            "Error leyendo la tabla".to_string(),
+                        ))
+                    })
+                })
+                .collect::<Vec<Result<_, Errores>>>()
+        });
+        let mut grupos = self.agrupacion.grupos();
+        for parcial in parciales {
+            self.agrupacion.unir(&mut grupos, parcial?);
+        }
+        self.agrupacion.filas(grupos)
+    }
+}
+
+impl Operador for AggregateParalelo {
+    fn columnas(&self) -> &[ColumnaPlan] {
+        &self.columnas
+    }
+
+    fn siguiente(&mut self) -> Result<Option<Vec<String>>, Errores> {
+        if self.grupos.is_none() {
+            self.grupos = Some(self.agrupar()?.into_iter());
+        }
+        Ok(self.grupos.as_mut().and_then(|grupos| grupos.next()))
+    }
+
+    fn entradas(&self) -> Vec<&dyn Operador> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analizador::analizador_implementacion::parsear_expresion;
+    use crate::ejecutor::ejecutor_implementacion::adaptar_where;
+    use crate::ejecutor::ejecutor_implementacion::test::crear_directorio_prueba;
+    use crate::planificador::operadores::{Aggregate, TableScan};
+    use crate::planificador::planificador_implementacion::parsear_agregado;
+
+    /// Generador de numeros pseudoaleatorios (xorshift), para que las pruebas sean reproducibles.
+    struct Aleatorio(u64);
+
+    impl Aleatorio {
+        fn siguiente(&mut self, maximo: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % maximo as u64) as usize
+        }
+
+        fn elegir<'a>(&mut self, opciones: &[&'a str]) -> &'a str {
+            opciones[self.siguiente(opciones.len())]
+        }
+    }
+
+    /// Las filas que produce un operador, con la posicion de cada una.
+    type Filas = Vec<(Option<u64>, Vec<String>)>;
+
+    fn drenar(operador: &mut dyn Operador) -> Result<Filas, Errores> {
+        let mut filas = Vec::new();
+        while let Some(fila) = operador.siguiente()? {
+            filas.push((operador.posicion(), fila));
+        }
+        Ok(filas)
+    }
+
+    #[test]
+    fn test_paralelo_equivale_a_un_hilo() {
+        let mut aleatorio = Aleatorio(0x5eed_1234_abcd_0042);
+        let nombres = ["id", "grupo", "valor", "texto"];
+        let columnas: Vec<ColumnaPlan> = nombres
+            .iter()
+            .map(|nombre| ColumnaPlan {
+                tabla: Some("t".to_string()),
+                nombre: nombre.to_string(),
+                tipo: None,
+            })
+            .collect();
+        let condiciones = [
+            "",
+            "valor > 3",
+            "grupo = 'b' OR valor <= 2.5",
+            "NOT texto = 'z' AND id >= 10",
+            "valor = 7",
+        ];
+        let campos = [
+            "COUNT(*)",
+            "COUNT(valor)",
+            "SUM(valor)",
+            "AVG(valor)",
+            "MIN(valor)",
+            "MAX(valor)",
+            "MIN(texto)",
+            "MAX(texto)",
+            "SUM(texto)",
+        ];
+        let ruta_directorio = crear_directorio_prueba("paralelo", &[]);
+        for caso in 0..40 {
+            let filas = aleatorio.siguiente(300);
+            let mut contenido = nombres.join(",") + "\n";
+            for id in 0..filas {
+                let valor =
+                    aleatorio.elegir(&["", "7", "7.0", "-3", "2.5", "0.1", "0.2", "12", "1e3"]);
+                let grupo = aleatorio.elegir(&["a", "b", "c", ""]);
+                let texto = aleatorio.elegir(&["x", "y", "z", "", "7"]);
+                contenido.push_str(&format!("{},{}, {} ,{}\n", id, grupo, valor, texto));
+            }
+            let ruta = format!("{}/t{}.csv", ruta_directorio, caso);
+            std::fs::write(&ruta, &contenido).unwrap();
+
+            let clausula = aleatorio.elegir(&condiciones);
+            let condicion = parsear_expresion(adaptar_where(clausula)).unwrap();
+            let condicion = (condicion != Expresion::Unknown).then_some(&condicion);
+            let partes = 1 + aleatorio.siguiente(8);
+            let rangos = dividir_en_rangos(&ruta, partes).unwrap();
+            assert!(rangos.len() <= partes);
+
+            let mut serial = TableScan::completo(&ruta, columnas.clone()).unwrap();
+            if let Some(condicion) = condicion {
+                serial = serial.con_filtro(condicion);
+            }
+            let mut paralelo =
+                ScanParalelo::new(&ruta, rangos.clone(), columnas.clone(), condicion);
+            assert_eq!(
+                drenar(&mut paralelo),
+                drenar(&mut serial),
+                "{} con {} partes",
+                clausula,
+                partes
+            );
+
+            let agregados: Vec<_> = (0..1 + aleatorio.siguiente(3))
+                .map(|_| parsear_agregado(aleatorio.elegir(&campos)).unwrap())
+                .collect();
+            let agrupar_por = if aleatorio.siguiente(2) == 0 {
+                vec![1]
+            } else {
+                vec![]
+            };
+            let agrupacion = Agrupacion {
+                indices_grupo: agrupar_por,
+                agregados: agregados
+                    .iter()
+                    .map(|agregado| {
+                        let indice = agregado
+                            .columna
+                            .as_ref()
+                            .map(|c| nombres.iter().position(|n| n == c).unwrap());
+                        (agregado.clone(), indice)
+                    })
+                    .collect(),
+            };
+            let mut entrada = TableScan::completo(&ruta, columnas.clone()).unwrap();
+            if let Some(condicion) = condicion {
+                entrada = entrada.con_filtro(condicion);
+            }
+            let mut serial = Aggregate::new(Box::new(entrada), agrupacion.clone(), Vec::new());
+            let mut paralelo = AggregateParalelo::new(
+                &ruta,
+                rangos,
+                columnas.clone(),
+                condicion,
+                agrupacion,
+                Vec::new(),
+            );
+            assert_eq!(
+                drenar(&mut paralelo),
+                drenar(&mut serial),
+                "{:?}",
+                agregados
+            );
+        }
+    }
+
+    #[test]
+    fn test_rangos_alineados_a_lineas() {
+        let ruta = crear_directorio_prueba("rangos", &[("t.csv", "id\n1\n22\n333\n4444\n")]);
+        let ruta = format!("{}/t.csv", ruta);
+        assert_eq!(dividir_en_rangos(&ruta, 1).unwrap(), [(3, 17)]);
+        assert_eq!(
+            dividir_en_rangos(&ruta, 3).unwrap(),
+            [(3, 8), (8, 12), (12, 17)]
+        );
+        assert_eq!(
+            dividir_en_rangos(&ruta, 50).unwrap(),
+            [(3, 5), (5, 8), (8, 12), (12, 17)]
+        );
+        let sin_filas = crear_directorio_prueba("rangos_vacia", &[("t.csv", "id\n")]);
+        assert!(dividir_en_rangos(&format!("{}/t.csv", sin_filas), 4)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,672 @@
+use crate::analizador::analizador_implementacion::parsear_expresion;
+use crate::analizador::expresion::Expresion;
+use crate::comandos::comandos_implementacion::{Comandos, Join};
+use crate::ejecutor::ejecutor_implementacion::{
+    adaptar_where, columna_inexistente, listar_tablas, obtener_primera_linea,
+};
+use crate::ejecutor::transaccion::ruta_tabla;
+use crate::errores::errores_implementacion::{Errores, TipoError};
+use crate::errores::sugerencias::limpiar_identificador;
+use crate::esquema::esquema_implementacion::cargar_esquema;
+use crate::esquema::estadisticas::{estadisticas_vigentes, Estadisticas};
+use crate::esquema::vistas::leer_vista;
+use crate::planificador::catalogo::TablaCatalogo;
+use crate::planificador::ejecucion::Analisis;
+use crate::planificador::optimizador::{estimar_filas_tabla, filas_estimadas};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa una columna de las filas que produce un nodo del plan: la tabla de la que proviene, si proviene de una, su nombre y su tipo declarado.
+pub struct ColumnaPlan {
+    pub tabla: Option<String>,
+    pub nombre: String,
+    pub tipo: Option<String>,
+}
+
+impl ColumnaPlan {
+    /// Devuelve el nombre de la columna calificado con el de su tabla ("tabla.columna"), o solo su nombre si no proviene de una tabla.
+    pub fn nombre_completo(&self) -> String {
+        match &self.tabla {
+            Some(tabla) => format!("{}.{}", tabla, self.nombre),
+            None => self.nombre.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa la manera en la que un Scan lee las filas de su tabla.
+pub enum Acceso {
+    /// Recorre la tabla completa.
+    Completo,
+    /// Lee solo las filas que el indice encuentra para la condicion. El filtro es la clausula con la que se busca en el indice, con las columnas sin calificar.
+    Indice {
+        nombre: String,
+        condicion: String,
+        filtro: Expresion,
+    },
+    /// Produce las filas de una tabla del catalogo, que se calculan al leerlas.
+    Catalogo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Representa las funciones de agregacion que pueden usarse en los campos de un Select.
+pub enum FuncionAgregada {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa un campo agregado de un Select, por ejemplo COUNT(*) o SUM(total). La columna es None en COUNT(*).
+pub struct Agregado {
+    pub funcion: FuncionAgregada,
+    pub columna: Option<String>,
+    /// El nombre con el que se muestra el campo, con la funcion en mayusculas.
+    pub etiqueta: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Representa un nodo del plan logico de un Select. Cada nodo produce filas a partir de las de sus entradas, y las hojas son los Scan de las tablas.
+pub enum Plan {
+    /// Lee las filas de una tabla. Si la tabla fue analizada con ANALYZE y no se modifico desde entonces, el Scan lleva sus estadisticas para estimar cuantas filas producen los nodos que se aplican sobre el.
+    Scan {
+        tabla: String,
+        columnas: Vec<ColumnaPlan>,
+        acceso: Acceso,
+        filas_estimadas: usize,
+        estadisticas: Option<Estadisticas>,
+    },
+    /// Conserva las filas que cumplen la condicion.
+    Filter {
+        condicion: Expresion,
+        entrada: Box<Plan>,
+    },
+    /// Une cada fila de la izquierda con las filas de la derecha en las que las columnas indicadas son iguales. Con las filas de la derecha se arma una tabla de hash, por lo que conviene que sea la entrada mas chica.
+    Join {
+        izquierda: Box<Plan>,
+        derecha: Box<Plan>,
+        columna_izquierda: String,
+        columna_derecha: String,
+    },
+    /// Agrupa las filas por las columnas indicadas y calcula los agregados de cada grupo. Sin columnas de agrupacion produce una unica fila.
+    Aggregate {
+        agrupar_por: Vec<String>,
+        agregados: Vec<Agregado>,
+        entrada: Box<Plan>,
+    },
+    /// Ordena las filas segun una columna.
+    Sort {
+        columna: String,
+        descendente: bool,
+        entrada: Box<Plan>,
+    },
+    /// Devuelve como maximo la cantidad indicada de filas, sin pedir mas filas a su entrada una vez alcanzada.
+    Limit { cantidad: usize, entrada: Box<Plan> },
+    /// Elige las columnas del resultado. Las etiquetas son los nombres con los que se muestran.
+    Project {
+        campos: Vec<String>,
+        etiquetas: Vec<String>,
+        entrada: Box<Plan>,
+    },
+    /// Produce las filas del plan del SELECT que define una vista, con las columnas calificadas con el nombre de la vista.
+    Vista { nombre: String, entrada: Box<Plan> },
+}
+
+impl Plan {
+    /// Devuelve las columnas de las filas que produce el nodo.
+    pub fn columnas(&self) -> Result<Vec<ColumnaPlan>, Errores> {
+        match self {
+            Plan::Scan { columnas, .. } => Ok(columnas.clone()),
+            Plan::Filter { entrada, .. }
+            | Plan::Sort { entrada, .. }
+            | Plan::Limit { entrada, .. } => entrada.columnas(),
+            Plan::Join {
+                izquierda, derecha, ..
+            } => {
+                let mut columnas = izquierda.columnas()?;
+                columnas.extend(derecha.columnas()?);
+                Ok(columnas)
+            }
+            Plan::Aggregate {
+                agrupar_por,
+                agregados,
+                entrada,
+            } => {
+                let columnas_entrada = entrada.columnas()?;
+                let mut columnas: Vec<ColumnaPlan> = Vec::new();
+                for columna in agrupar_por {
+                    columnas.push(
+                        columnas_entrada[resolver_columna(&columnas_entrada, columna)?].clone(),
+                    );
+                }
+                for agregado in agregados {
+                    let tipo = match agregado.funcion {
+                        FuncionAgregada::Count => Some("INT".to_string()),
+                        FuncionAgregada::Avg => Some("REAL".to_string()),
+                        FuncionAgregada::Sum => None,
+                        FuncionAgregada::Min | FuncionAgregada::Max => match &agregado.columna {
+                            Some(columna) => columnas_entrada
+                                [resolver_columna(&columnas_entrada, columna)?]
+                            .tipo
+                            .clone(),
+                            None => None,
+                        },
+                    };
+                    columnas.push(ColumnaPlan {
+                        tabla: None,
+                        nombre: agregado.etiqueta.to_string(),
+                        tipo,
+                    });
+                }
+                Ok(columnas)
+            }
+            Plan::Project {
+                campos,
+                etiquetas,
+                entrada,
+            } => {
+                let columnas_entrada = entrada.columnas()?;
+                let mut columnas: Vec<ColumnaPlan> = Vec::new();
+                for (campo, etiqueta) in campos.iter().zip(etiquetas) {
+                    let columna = &columnas_entrada[resolver_columna(&columnas_entrada, campo)?];
+                    columnas.push(ColumnaPlan {
+                        tabla: columna.tabla.clone(),
+                        nombre: etiqueta.to_string(),
+                        tipo: columna.tipo.clone(),
+                    });
+                }
+                Ok(columnas)
+            }
+            Plan::Vista { nombre, entrada } => Ok(entrada
+                .columnas()?
+                .into_iter()
+                .map(|columna| ColumnaPlan {
+                    tabla: Some(nombre.to_string()),
+                    nombre: nombre_en_vista(&columna),
+                    tipo: columna.tipo,
+                })
+                .collect()),
+        }
+    }
+
+    /// Devuelve las entradas del nodo, de izquierda a derecha.
+    pub fn hijos(&self) -> Vec<&Plan> {
+        match self {
+            Plan::Scan { .. } => Vec::new(),
+            Plan::Join {
+                izquierda, derecha, ..
+            } => vec![izquierda, derecha],
+            Plan::Filter { entrada, .. }
+            | Plan::Aggregate { entrada, .. }
+            | Plan::Sort { entrada, .. }
+            | Plan::Limit { entrada, .. }
+            | Plan::Project { entrada, .. }
+            | Plan::Vista { entrada, .. } => vec![entrada],
+        }
+    }
+
+    /// Devuelve la descripcion del nodo que se muestra en el plan, sin sus entradas.
+    pub fn descripcion(&self) -> String {
+        match self {
+            Plan::Scan {
+                tabla,
+                acceso: Acceso::Completo,
+                ..
+            } => format!("Scan {}", tabla),
+            Plan::Scan {
+                tabla,
+                acceso: Acceso::Indice {
+                    nombre, condicion, ..
+                },
+                ..
+            } => format!("Index Scan {} usando {} ({})", tabla, nombre, condicion),
+            Plan::Scan {
+                tabla,
+                acceso: Acceso::Catalogo,
+                ..
+            } => format!("Catalog Scan {}", tabla),
+            Plan::Filter { condicion, .. } => format!("Filter ({})", condicion),
+            Plan::Join {
+                columna_izquierda,
+                columna_derecha,
+                ..
+            } => format!("Hash Join ({} = {})", columna_izquierda, columna_derecha),
+            Plan::Aggregate {
+                agrupar_por,
+                agregados,
+                ..
+            } => {
+                let etiquetas: Vec<&str> = agregados.iter().map(|a| a.etiqueta.as_str()).collect();
+                let mut descripcion = format!("Aggregate ({})", etiquetas.join(", "));
+                if !agrupar_por.is_empty() {
+                    descripcion.push_str(&format!(" GROUP BY {}", agrupar_por.join(", ")));
+                }
+                descripcion
+            }
+            Plan::Sort {
+                columna,
+                descendente,
+                ..
+            } => format!(
+                "Sort ({} {})",
+                columna,
+                if *descendente { "DESC" } else { "ASC" }
+            ),
+            Plan::Limit { cantidad, .. } => format!("Limit ({})", cantidad),
+            Plan::Project { etiquetas, .. } => format!("Project ({})", etiquetas.join(", ")),
+            Plan::Vista { nombre, .. } => format!("View {}", nombre),
+        }
+    }
+}
+
+/// Esta funcion construye el plan logico de un Select tal como esta escrito: los Scan de las tablas unidos en el orden de los JOIN, la clausula WHERE sobre ellos, y luego la agrupacion, el orden, el limite y la eleccion de las columnas.
+/// Verifica que existan las tablas y las columnas que se usan fuera del WHERE, pero no elige como leer cada tabla ni en que orden unirlas: de eso se encarga el optimizador.
+/// Las vistas se expanden en el plan de su SELECT, que queda como entrada de un nodo Vista.
+pub fn construir_plan(comando: &Comandos, path: &String) -> Result<Plan, Errores> {
+    plan_de_select(comando, path, &[])
+}
+
+/// Esta funcion construye el plan de una vista a partir del SELECT que la define, antes de guardarla. Devuelve error si el SELECT no es valido o si usa la misma vista, directamente o a traves de otras vistas.
+pub fn construir_plan_de_vista(
+    nombre: &str,
+    consulta: &Comandos,
+    path: &String,
+) -> Result<Plan, Errores> {
+    Ok(Plan::Vista {
+        nombre: nombre.to_string(),
+        entrada: Box::new(plan_de_select(consulta, path, &[nombre.to_string()])?),
+    })
+}
+
+/// Construye el plan de un Select que forma parte de la definicion de las vistas indicadas, que se estan expandiendo.
+fn plan_de_select(comando: &Comandos, path: &String, vistas: &[String]) -> Result<Plan, Errores> {
+    let (campos, tabla, joins, clausula_where, agrupar_por, clausula_order, limite) = match comando
+    {
+        Comandos::Select {
+            campos,
+            tabla,
+            joins,
+            clausula_where,
+            agrupar_por,
+            clausula_order,
+            limite,
+        } => (
+            campos,
+            tabla,
+            joins,
+            clausula_where,
+            agrupar_por,
+            clausula_order,
+            limite,
+        ),
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                "Error en la query Select".to_string(),
+            ));
+        }
+    };
+    let mut plan = plan_scan(path, tabla, vistas)?;
+    for join in joins {
+        plan = plan_join(plan, plan_scan(path, &join.tabla, vistas)?, join)?;
+    }
+    plan = plan_filter(plan, clausula_where)?;
+    let agregados: Vec<Agregado> = campos.iter().filter_map(|c| parsear_agregado(c)).collect();
+    if !agregados.is_empty() || !agrupar_por.is_empty() {
+        plan = plan_aggregate(plan, campos, agrupar_por, agregados)?;
+    }
+    if !clausula_order.is_empty() {
+        plan = plan_sort(plan, clausula_order)?;
+    }
+    if let Some(cantidad) = limite {
+        plan = Plan::Limit {
+            cantidad: *cantidad,
+            entrada: Box::new(plan),
+        };
+    }
+    let columnas = plan.columnas()?;
+    let (campos, etiquetas): (Vec<String>, Vec<String>) = if campos == &vec!["*".to_string()] {
+        columnas
+            .iter()
+            .map(|c| (c.nombre_completo(), c.nombre.to_string()))
+            .unzip()
+    } else {
+        let mut referencias: Vec<(String, String)> = Vec::new();
+        for campo in campos {
+            let campo = nombre_de_campo(campo.trim());
+            resolver_columna(&columnas, &campo)?;
+            referencias.push((campo.to_string(), campo));
+        }
+        referencias.into_iter().unzip()
+    };
+    Ok(Plan::Project {
+        campos,
+        etiquetas,
+        entrada: Box::new(plan),
+    })
+}
+
+/// Esta funcion construye el plan con el que un UPDATE o un DELETE encuentran las filas a modificar: el Scan de la tabla y, si hay clausula WHERE, un Filter sobre el.
+/// Las filas se producen en el mismo orden en el que estan en el archivo.
+pub fn construir_plan_de_modificacion(
+    path: &String,
+    tabla: &String,
+    clausula_where: &str,
+) -> Result<Plan, Errores> {
+    plan_filter(plan_scan(path, tabla, &[])?, clausula_where)
+}
+
+/// Esta funcion devuelve las lineas con las que se muestra el plan: un nodo por linea, con sus entradas debajo y mas indentadas.
+/// Cada nodo indica la cantidad de filas que el optimizador estima que produce y, si el plan fue ejecutado, la cantidad de filas que produjo y el tiempo que tardo, incluido el de sus entradas.
+pub fn mostrar_plan(plan: &Plan, analisis: Option<&Analisis>) -> Vec<String> {
+    let mut lineas: Vec<String> = Vec::new();
+    agregar_lineas(plan, analisis, 0, &mut lineas);
+    lineas
+}
+
+fn agregar_lineas(
+    plan: &Plan,
+    analisis: Option<&Analisis>,
+    nivel: usize,
+    lineas: &mut Vec<String>,
+) {
+    let prefijo = if nivel == 0 {
+        String::new()
+    } else {
+        format!("{}-> ", "   ".repeat(nivel - 1))
+    };
+    let mut linea = format!(
+        "{}{} (filas estimadas: {}",
+        prefijo,
+        plan.descripcion(),
+        filas_estimadas(plan)
+    );
+    if let Some(analisis) = analisis {
+        linea.push_str(&format!(
+            ", filas: {}, tiempo: {:.3} ms",
+            analisis.filas,
+            analisis.tiempo.as_secs_f64() * 1000.0
+        ));
+    }
+    linea.push(')');
+    lineas.push(linea);
+    for (i, hijo) in plan.hijos().into_iter().enumerate() {
+        let analisis_hijo = analisis.and_then(|a| a.hijos.get(i));
+        agregar_lineas(hijo, analisis_hijo, nivel + 1, lineas);
+    }
+}
+
+/// Esta funcion devuelve el indice de la columna dentro de las columnas de un nodo. El nombre puede calificarse con el de la tabla ("tabla.columna"), y debe hacerse si hay varias columnas con el mismo nombre.
+/// El nombre de la tabla puede a su vez contener puntos, como en "information_schema.columns.table_name".
+pub fn resolver_columna(columnas: &[ColumnaPlan], nombre: &str) -> Result<usize, Errores> {
+    let nombre = limpiar_identificador(nombre);
+    let coincide = |columna: &ColumnaPlan| match nombre.rsplit_once('.') {
+        Some((tabla, nombre_columna)) if columna.tabla.as_deref() == Some(tabla) => {
+            columna.nombre == nombre_columna
+        }
+        _ => columna.nombre == nombre,
+    };
+    let encontradas: Vec<usize> = (0..columnas.len())
+        .filter(|&i| coincide(&columnas[i]))
+        .collect();
+    match encontradas.as_slice() {
+        [indice] => Ok(*indice),
+        [] => {
+            let nombres: Vec<String> = columnas.iter().map(|c| c.nombre.to_string()).collect();
+            Err(columna_inexistente(nombre, &nombres))
+        }
+        _ => Err(Errores::nuevo(
+            TipoError::InvalidColumn,
+            format!(
+                "La columna {} es ambigua, debe indicarse su tabla como tabla.columna",
+                nombre
+            ),
+        )
+        .en_token(nombre)),
+    }
+}
+
+/// Esta funcion devuelve el indice de cada nombre con el que puede referirse una columna en una clausula WHERE: el nombre calificado con su tabla y, si no se repite, el nombre solo.
+pub fn mapa_columnas(columnas: &[ColumnaPlan]) -> HashMap<String, usize> {
+    let mut mapa: HashMap<String, usize> = HashMap::new();
+    for (i, columna) in columnas.iter().enumerate() {
+        mapa.insert(columna.nombre_completo(), i);
+        if columnas
+            .iter()
+            .filter(|c| c.nombre == columna.nombre)
+            .count()
+            == 1
+        {
+            mapa.insert(columna.nombre.to_string(), i);
+        }
+    }
+    mapa
+}
+
+/// Esta funcion interpreta un campo del Select como una funcion de agregacion (COUNT, SUM, MIN, MAX o AVG) aplicada a una columna, o a * en el caso de COUNT.
+pub fn parsear_agregado(campo: &str) -> Option<Agregado> {
+    let (funcion, resto) = campo.trim().split_once('(')?;
+    let argumento = resto.strip_suffix(')')?.trim();
+    let funcion = match funcion.trim().to_uppercase().as_str() {
+        "COUNT" => FuncionAgregada::Count,
+        "SUM" => FuncionAgregada::Sum,
+        "MIN" => FuncionAgregada::Min,
+        "MAX" => FuncionAgregada::Max,
+        "AVG" => FuncionAgregada::Avg,
+        _ => return None,
+    };
+    let columna = match argumento {
+        "*" if funcion == FuncionAgregada::Count => None,
+        "" | "*" => return None,
+        columna => Some(columna.to_string()),
+    };
+    let etiqueta = format!(
+        "{}({})",
+        funcion_en_texto(funcion),
+        columna.as_deref().unwrap_or("*")
+    );
+    Some(Agregado {
+        funcion,
+        columna,
+        etiqueta,
+    })
+}
+
+fn funcion_en_texto(funcion: FuncionAgregada) -> &'static str {
+    match funcion {
+        FuncionAgregada::Count => "COUNT",
+        FuncionAgregada::Sum => "SUM",
+        FuncionAgregada::Min => "MIN",
+        FuncionAgregada::Max => "MAX",
+        FuncionAgregada::Avg => "AVG",
+    }
+}
+
+/// Devuelve el nombre de una columna dentro de una vista: el de la columna, sin la tabla con la que se la califico en el SELECT de la vista. Los agregados conservan su etiqueta.
+fn nombre_en_vista(columna: &ColumnaPlan) -> String {
+    match (&columna.tabla, columna.nombre.rsplit_once('.')) {
+        (Some(_), Some((_, nombre))) => nombre.to_string(),
+        _ => columna.nombre.to_string(),
+    }
+}
+
+/// Devuelve el nombre con el que se refiere a un campo: la etiqueta si es un agregado, o el campo tal como esta escrito.
+fn nombre_de_campo(campo: &str) -> String {
+    parsear_agregado(campo)
+        .map(|agregado| agregado.etiqueta)
+        .unwrap_or_else(|| campo.to_string())
+}
+
+/// Agrega sobre el plan un Filter con la clausula WHERE, si la consulta tiene una.
+fn plan_filter(plan: Plan, clausula_where: &str) -> Result<Plan, Errores> {
+    let clausula_where_limpia: Vec<String> =
+        adaptar_where(clausula_where.trim_end_matches(";").trim_end_matches("; "));
+    let condicion: Expresion = parsear_expresion(clausula_where_limpia)?;
+    if condicion == Expresion::Unknown {
+        return Ok(plan);
+    }
+    Ok(Plan::Filter {
+        condicion,
+        entrada: Box::new(plan),
+    })
+}
+
+/// Crea el Scan de la tabla, estimando cuantas filas tiene con sus estadisticas si estan vigentes. Las tablas del catalogo no tienen un archivo: se estima que tienen una fila por tabla del directorio.
+/// Si la tabla es una vista, crea el plan de su SELECT. Una vista no puede aparecer en su propia definicion, ni directamente ni a traves de otras vistas.
+fn plan_scan(path: &String, tabla: &String, vistas: &[String]) -> Result<Plan, Errores> {
+    if let Some(catalogo) = TablaCatalogo::desde_nombre(tabla) {
+        return Ok(Plan::Scan {
+            tabla: tabla.to_string(),
+            columnas: catalogo.columnas(),
+            acceso: Acceso::Catalogo,
+            filas_estimadas: listar_tablas(path)?.len(),
+            estadisticas: None,
+        });
+    }
+    if let Some(consulta) = leer_vista(path, tabla)? {
+        if vistas.contains(tabla) {
+            return Err(Errores::nuevo(
+                TipoError::InvalidTable,
+                format!("La vista {} se define en terminos de si misma", tabla),
+            )
+            .en_token(tabla));
+        }
+        let mut expandiendo = vistas.to_vec();
+        expandiendo.push(tabla.to_string());
+        return Ok(Plan::Vista {
+            nombre: tabla.to_string(),
+            entrada: Box::new(plan_de_select(&consulta, path, &expandiendo)?),
+        });
+    }
+    let ruta = ruta_tabla(path, tabla);
+    let nombres: Vec<String> = obtener_primera_linea(&ruta)?;
+    let esquema = cargar_esquema(path, tabla, &nombres)?;
+    let estadisticas = estadisticas_vigentes(path, tabla, &esquema, &nombres);
+    let columnas: Vec<ColumnaPlan> = nombres
+        .iter()
+        .map(|nombre| ColumnaPlan {
+            tabla: Some(tabla.to_string()),
+            nombre: nombre.to_string(),
+            tipo: esquema.columna(nombre).and_then(|c| c.tipo.clone()),
+        })
+        .collect();
+    Ok(Plan::Scan {
+        tabla: tabla.to_string(),
+        columnas,
+        acceso: Acceso::Completo,
+        filas_estimadas: match &estadisticas {
+            Some(estadisticas) => estadisticas.filas,
+            None => estimar_filas_tabla(&ruta),
+        },
+        estadisticas,
+    })
+}
+
+/// Une el plan con la tabla del JOIN, ubicando cada columna de la condicion del lado al que pertenece.
+fn plan_join(izquierda: Plan, derecha: Plan, join: &Join) -> Result<Plan, Errores> {
+    let columnas_izquierda = izquierda.columnas()?;
+    let columnas_derecha = derecha.columnas()?;
+    let pertenecen = |a: &str, b: &str| {
+        resolver_columna(&columnas_izquierda, a).is_ok()
+            && resolver_columna(&columnas_derecha, b).is_ok()
+    };
+    let (columna_izquierda, columna_derecha) =
+        if pertenecen(&join.columna_izquierda, &join.columna_derecha) {
+            (&join.columna_izquierda, &join.columna_derecha)
+        } else if pertenecen(&join.columna_derecha, &join.columna_izquierda) {
+            (&join.columna_derecha, &join.columna_izquierda)
+        } else {
+            let mut todas = columnas_izquierda;
+            todas.extend(columnas_derecha);
+            resolver_columna(&todas, &join.columna_izquierda)?;
+            resolver_columna(&todas, &join.columna_derecha)?;
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                format!(
+                    "La condicion del JOIN con {} debe comparar una columna de cada lado",
+                    join.tabla
+                ),
+            )
+            .en_token(&join.tabla));
+        };
+    Ok(Plan::Join {
+        izquierda: Box::new(izquierda),
+        derecha: Box::new(derecha),
+        columna_izquierda: columna_izquierda.to_string(),
+        columna_derecha: columna_derecha.to_string(),
+    })
+}
+
+/// Agrupa el plan. Los campos que no son agregados deben ser columnas de la agrupacion.
+fn plan_aggregate(
+    plan: Plan,
+    campos: &[String],
+    agrupar_por: &[String],
+    agregados: Vec<Agregado>,
+) -> Result<Plan, Errores> {
+    let columnas = plan.columnas()?;
+    let mut agrupadas: Vec<usize> = Vec::new();
+    for columna in agrupar_por {
+        agrupadas.push(resolver_columna(&columnas, columna)?);
+    }
+    for agregado in &agregados {
+        if let Some(columna) = &agregado.columna {
+            resolver_columna(&columnas, columna)?;
+        }
+    }
+    for campo in campos.iter().filter(|c| parsear_agregado(c).is_none()) {
+        if campo == "*" || !agrupadas.contains(&resolver_columna(&columnas, campo)?) {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                format!(
+                    "El campo {} debe ser una funcion de agregacion o aparecer en el GROUP BY",
+                    campo
+                ),
+            )
+            .en_token(campo));
+        }
+    }
+    Ok(Plan::Aggregate {
+        agrupar_por: agrupar_por.to_vec(),
+        agregados,
+        entrada: Box::new(plan),
+    })
+}
+
+fn plan_sort(plan: Plan, clausula_order: &[String]) -> Result<Plan, Errores> {
+    if let Some(separada) = clausula_order.iter().find(|t| t.contains(',')) {
+        return Err(Errores::nuevo(
+            TipoError::InvalidSyntax,
+            "ORDER BY solo admite una columna".to_string(),
+        )
+        .en_token(separada.trim_end_matches(',')));
+    }
+    let columna = nombre_de_campo(&clausula_order[0]);
+    resolver_columna(&plan.columnas()?, &columna)?;
+    let modo_ordenamiento = if clausula_order.len() >= 2 {
+        clausula_order[1]
+            .to_uppercase()
+            .trim_end_matches(";")
+            .to_string()
+    } else {
+        "ASC".to_string()
+    };
+    let descendente = match modo_ordenamiento.as_str() {
+        "ASC" => false,
+        "DESC" => true,
+        _ => {
+            return Err(Errores::nuevo(
+                TipoError::InvalidSyntax,
+                format!("Orden desconocido: {}", modo_ordenamiento),
+            )
+            .en_token(&modo_ordenamiento))
+        }
+    };
+    Ok(Plan::Sort {
+        columna,
+        descendente,
+        entrada: Box::new(plan),
+    })
+}